@@ -0,0 +1,105 @@
+//! Compares the ring buffer indicators with the way they used to be
+//! worked out: collecting the true ranges and re-averaging every window.
+//!
+//! Run with `cargo bench -p algorithms`
+
+use algorithms::{atr_percentile, Atr, IntoAtrIter, IntoEmaIter, SnapshotCandle, TrueRange};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const CANDLES: usize = 1_000_000;
+const PERIOD: usize = 14;
+const LOOKBACK: usize = 500;
+
+/// A wandering price, the same every run
+fn candles() -> Vec<SnapshotCandle> {
+    let mut close = 100.0;
+    (0..CANDLES)
+        .map(|i| {
+            let open = close;
+            close += (i as f32 * 0.37).sin() * 0.5;
+            let range = 0.2 + (i as f32 * 0.011).cos().abs();
+            SnapshotCandle {
+                open,
+                high: open.max(close) + range,
+                low: open.min(close) - range,
+                close,
+            }
+        })
+        .collect()
+}
+
+fn old_atrs(candles: &[SnapshotCandle], period: usize) -> Vec<f32> {
+    let true_ranges: Vec<f32> = candles.iter().true_range().collect();
+    true_ranges
+        .windows(period)
+        .map(|window| window.iter().sum::<f32>() / period as f32)
+        .collect()
+}
+
+fn old_atr_percentile(candles: &[SnapshotCandle], period: usize, lookback: usize) -> Option<f32> {
+    let atrs = old_atrs(candles, period);
+    let latest = *atrs.last()?;
+    let history = &atrs[atrs.len().saturating_sub(lookback)..];
+    let below = history.iter().filter(|&&atr| atr <= latest).count();
+    Some(below as f32 / history.len() as f32)
+}
+
+fn rolling_atr(c: &mut Criterion) {
+    let candles = candles();
+    let mut group = c.benchmark_group("rolling_atr");
+    group.sample_size(10);
+    group.bench_function(BenchmarkId::new("windows", CANDLES), |b| {
+        b.iter(|| old_atrs(black_box(&candles), PERIOD).last().copied())
+    });
+    group.bench_function(BenchmarkId::new("ring_buffer", CANDLES), |b| {
+        b.iter(|| black_box(&candles).iter().atrs(PERIOD).last())
+    });
+    group.finish();
+}
+
+fn percentile(c: &mut Criterion) {
+    let candles = candles();
+    let mut group = c.benchmark_group("atr_percentile");
+    group.sample_size(10);
+    group.bench_function(BenchmarkId::new("windows", CANDLES), |b| {
+        b.iter(|| old_atr_percentile(black_box(&candles), PERIOD, LOOKBACK))
+    });
+    group.bench_function(BenchmarkId::new("ring_buffer", CANDLES), |b| {
+        b.iter(|| atr_percentile(black_box(&candles).iter(), PERIOD, LOOKBACK))
+    });
+    group.finish();
+}
+
+fn ema(c: &mut Criterion) {
+    let candles = candles();
+    let closes: Vec<f32> = candles.iter().map(|candle| candle.close).collect();
+    let mut group = c.benchmark_group("ema");
+    group.sample_size(10);
+    // An EMA of every close, recomputing each one from the last few
+    // periods of closes
+    group.bench_function(BenchmarkId::new("windows", CANDLES), |b| {
+        b.iter(|| {
+            black_box(&closes)
+                .windows(PERIOD * 4)
+                .map(|window| {
+                    let alpha = 2.0 / (PERIOD as f32 + 1.0);
+                    window
+                        .iter()
+                        .fold(window[0], |ema, close| ema + alpha * (close - ema))
+                })
+                .for_each(|ema| {
+                    black_box(ema);
+                })
+        })
+    });
+    group.bench_function(BenchmarkId::new("streaming", CANDLES), |b| {
+        b.iter(|| black_box(&closes).iter().copied().emas(PERIOD).last())
+    });
+    group.bench_function(BenchmarkId::new("weighted_atr", CANDLES), |b| {
+        b.iter(|| black_box(&candles).iter().weighted_atr(PERIOD))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, rolling_atr, percentile, ema);
+criterion_main!(benches);
@@ -0,0 +1,246 @@
+//! Wilder's average directional index and the +DI and -DI lines it's made
+//! from. +DI and -DI say how much of the recent range was moves up and moves
+//! down; the ADX says how strong the trend is, whichever way it goes, from 0
+//! to 100. Readings over about 25 are usually taken to be a trend.
+//!
+//! The true range comes from [`TRCandle`]. The true range, directional
+//! movement and ADX are smoothed the way [`Rsi`](crate::Rsi) is: a simple
+//! average of the first `period` values, then each new one counts for
+//! `1 / period`.
+
+use crate::TRCandle;
+
+/// The directional movement as of one candle
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct AdxReading {
+    /// How strong the trend is, from 0 to 100
+    pub adx: f32,
+    /// The smoothed upward movement as a percent of the true range
+    pub plus_di: f32,
+    /// The smoothed downward movement as a percent of the true range
+    pub minus_di: f32,
+}
+
+/// A running ADX, fed one candle at a time
+#[derive(Debug, PartialEq, Clone)]
+pub struct Adx {
+    /// The high, low and close of the last candle
+    previous: Option<(f32, f32, f32)>,
+    true_range: Wilder,
+    plus_dm: Wilder,
+    minus_dm: Wilder,
+    adx: Wilder,
+}
+
+impl Adx {
+    /// The period Wilder used
+    pub const DEFAULT_PERIOD: usize = 14;
+
+    pub fn new(period: usize) -> Self {
+        Self {
+            previous: None,
+            true_range: Wilder::new(period),
+            plus_dm: Wilder::new(period),
+            minus_dm: Wilder::new(period),
+            adx: Wilder::new(period),
+        }
+    }
+
+    pub fn period(&self) -> usize {
+        self.adx.period
+    }
+
+    /// Adds a candle and returns the ADX. None until there have been
+    /// `period * 2` candles: `period + 1` for the first +DI and -DI, then
+    /// `period` of those for the first ADX. Always None if the period is 0
+    pub fn push<C: TRCandle>(&mut self, candle: &C) -> Option<AdxReading> {
+        if self.period() == 0 {
+            return None;
+        }
+        let (high, low, close) = (candle.high(), candle.low(), candle.close());
+        let (previous_high, previous_low, previous_close) =
+            self.previous.replace((high, low, close))?;
+        let up = high - previous_high;
+        let down = previous_low - low;
+        let plus_dm = if up > down && up > 0.0 { up } else { 0.0 };
+        let minus_dm = if down > up && down > 0.0 { down } else { 0.0 };
+        let true_range = self.true_range.push(candle.true_range(previous_close));
+        let plus_dm = self.plus_dm.push(plus_dm);
+        let minus_dm = self.minus_dm.push(minus_dm);
+        let (true_range, plus_dm, minus_dm) = (true_range?, plus_dm?, minus_dm?);
+        let percent_of_range = |movement: f32| {
+            if true_range == 0.0 {
+                0.0
+            } else {
+                100.0 * movement / true_range
+            }
+        };
+        let plus_di = percent_of_range(plus_dm);
+        let minus_di = percent_of_range(minus_dm);
+        let total = plus_di + minus_di;
+        let dx = if total == 0.0 {
+            0.0
+        } else {
+            100.0 * (plus_di - minus_di).abs() / total
+        };
+        Some(AdxReading {
+            adx: self.adx.push(dx)?,
+            plus_di,
+            minus_di,
+        })
+    }
+}
+
+/// Wilder's smoothing: the simple average of the first `period` values,
+/// then `(average * (period - 1) + value) / period`
+#[derive(Debug, PartialEq, Clone)]
+struct Wilder {
+    period: usize,
+    /// The values seen while warming up
+    count: usize,
+    average: f32,
+}
+
+impl Wilder {
+    fn new(period: usize) -> Self {
+        Self {
+            period,
+            count: 0,
+            average: 0.0,
+        }
+    }
+
+    /// None until `period` values are in
+    fn push(&mut self, value: f32) -> Option<f32> {
+        let period = self.period as f32;
+        if self.count < self.period {
+            self.count += 1;
+            self.average += value;
+            if self.count < self.period {
+                return None;
+            }
+            self.average /= period;
+        } else {
+            self.average = (self.average * (period - 1.0) + value) / period;
+        }
+        Some(self.average)
+    }
+}
+
+/// Turn an Iterator of candles into their ADX
+pub trait IntoAdxIterator: Iterator + Sized
+where
+    Self::Item: TRCandle,
+{
+    /// The ADX over `period` candles, usually [`Adx::DEFAULT_PERIOD`]. See
+    /// [`Adx::push`] for when it starts yielding. Yields nothing if
+    /// `period` is 0
+    fn adx(self, period: usize) -> AdxIterator<Self> {
+        AdxIterator {
+            candles: self,
+            adx: Adx::new(period),
+        }
+    }
+}
+
+impl<I> IntoAdxIterator for I
+where
+    I: Iterator,
+    I::Item: TRCandle,
+{
+}
+
+pub struct AdxIterator<I> {
+    candles: I,
+    adx: Adx,
+}
+
+impl<I> Iterator for AdxIterator<I>
+where
+    I: Iterator,
+    I::Item: TRCandle,
+{
+    type Item = AdxReading;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.adx.period() == 0 {
+            return None;
+        }
+        loop {
+            let candle = self.candles.next()?;
+            if let Some(reading) = self.adx.push(&candle) {
+                break Some(reading);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candle::test_data::Candle;
+    use pretty_assertions::assert_eq;
+
+    /// Candles a unit tall, each a unit above the last if `step` is 1
+    fn stairs(count: usize, step: f32) -> Vec<Candle> {
+        (0..count)
+            .map(|i| {
+                let low = i as f32 * step;
+                Candle::new(low + 1.0, low, low, low + 1.0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn strong_trend() {
+        let readings: Vec<_> = stairs(10, 1.0).into_iter().adx(3).collect();
+        // The first ADX comes on the 6th candle
+        assert_eq!(5, readings.len());
+        for reading in readings {
+            assert_eq!(
+                AdxReading {
+                    adx: 100.0,
+                    plus_di: 100.0,
+                    minus_di: 0.0
+                },
+                reading
+            );
+        }
+        let falling = stairs(10, -1.0).into_iter().adx(3).last().unwrap();
+        assert!(falling.minus_di > falling.plus_di, "{falling:?}");
+        assert_eq!(100.0, falling.adx);
+    }
+
+    #[test]
+    fn trend_fades() {
+        // A climb then a sideways chop; the ADX falls off once it stops
+        let mut candles = stairs(10, 1.0);
+        candles.extend((0..10).map(|i| {
+            let low = 9.0 + (i % 2) as f32;
+            Candle::new(low + 1.0, low, low, low + 1.0)
+        }));
+        let readings: Vec<_> = candles.iter().adx(3).collect();
+        let adx: Vec<f32> = readings.iter().map(|reading| reading.adx).collect();
+        assert_eq!(100.0, adx[0]);
+        assert!(adx.last().unwrap() < &50.0, "{adx:?}");
+    }
+
+    #[test]
+    fn warm_up() {
+        let candles = stairs(6, 1.0);
+        assert_eq!(0, candles[..5].iter().adx(3).count());
+        assert_eq!(1, candles.iter().adx(3).count());
+        assert_eq!(0, candles.iter().adx(0).count());
+        // A market that never moves has no trend
+        let flat = vec![Candle::new(1.0, 1.0, 1.0, 1.0); 6];
+        assert_eq!(
+            Some(AdxReading {
+                adx: 0.0,
+                plus_di: 0.0,
+                minus_di: 0.0
+            }),
+            flat.iter().adx(3).last()
+        );
+        assert_eq!(14, Adx::new(Adx::DEFAULT_PERIOD).period());
+    }
+}
@@ -0,0 +1,157 @@
+//! Measures the parts of a candle; its body and wicks, and where it closed
+//! within its range. Good for filters like "a strong bodied breakout candle"
+//! or "a long lower wick rejecting support".
+
+use crate::candle::{Close, High, Low, Open};
+
+/// Impl this trait for your data to get the anatomy of a candle.
+/// Everything with a high, low, open and close gets it for free.
+pub trait CandleAnatomy: High + Low + Open + Close {
+    /// The distance between the high and low
+    fn range(&self) -> f32 {
+        self.high() - self.low()
+    }
+
+    /// The distance between the open and close. Always positive
+    fn body(&self) -> f32 {
+        (self.close() - self.open()).abs()
+    }
+
+    /// The distance from the top of the body to the high
+    fn upper_wick(&self) -> f32 {
+        self.high() - self.open().max(self.close())
+    }
+
+    /// The distance from the bottom of the body to the low
+    fn lower_wick(&self) -> f32 {
+        self.open().min(self.close()) - self.low()
+    }
+
+    /// Returns the anatomy of this candle with all the ratios worked out
+    fn anatomy(&self) -> Anatomy {
+        let range = self.range();
+        // A candle with no range (all four prices the same) has no body or
+        // wicks to speak of, and closed right in the middle
+        let ratio = |part: f32| if range > 0.0 { part / range } else { 0.0 };
+        let close_position = if range > 0.0 {
+            (self.close() - self.low()) / range
+        } else {
+            0.5
+        };
+        Anatomy {
+            range,
+            body: self.body(),
+            upper_wick: self.upper_wick(),
+            lower_wick: self.lower_wick(),
+            body_ratio: ratio(self.body()),
+            upper_wick_ratio: ratio(self.upper_wick()),
+            lower_wick_ratio: ratio(self.lower_wick()),
+            close_position,
+        }
+    }
+}
+
+impl<T: High + Low + Open + Close> CandleAnatomy for T {}
+
+/// The parts of a candle, in price units and as fractions of its range
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Anatomy {
+    pub range: f32,
+    pub body: f32,
+    pub upper_wick: f32,
+    pub lower_wick: f32,
+    /// body / range
+    pub body_ratio: f32,
+    /// upper_wick / range
+    pub upper_wick_ratio: f32,
+    /// lower_wick / range
+    pub lower_wick_ratio: f32,
+    /// Where the close is within the range. 0 is at the low, 1 is at the high
+    pub close_position: f32,
+}
+
+/// Turn an Iterator of candles into an Iterator of their anatomies
+pub trait IntoAnatomyIter: Iterator + Sized
+where
+    Self::Item: CandleAnatomy,
+{
+    fn anatomy(self) -> AnatomyIter<Self> {
+        AnatomyIter { iter: self }
+    }
+}
+
+impl<I> IntoAnatomyIter for I
+where
+    I: Iterator,
+    I::Item: CandleAnatomy,
+{
+}
+
+pub struct AnatomyIter<I> {
+    iter: I,
+}
+
+impl<I> Iterator for AnatomyIter<I>
+where
+    I: Iterator,
+    I::Item: CandleAnatomy,
+{
+    type Item = Anatomy;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|candle| candle.anatomy())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candle::test_data::Candle;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn bullish_candle() {
+        // high, low, open, close
+        let candle = Candle::new(10.0, 0.0, 2.0, 9.0);
+        let expected = Anatomy {
+            range: 10.0,
+            body: 7.0,
+            upper_wick: 1.0,
+            lower_wick: 2.0,
+            body_ratio: 0.7,
+            upper_wick_ratio: 0.1,
+            lower_wick_ratio: 0.2,
+            close_position: 0.9,
+        };
+        assert_eq!(expected, candle.anatomy());
+    }
+
+    #[test]
+    fn bearish_candle() {
+        let candle = Candle::new(10.0, 0.0, 6.0, 4.0);
+        let got = candle.anatomy();
+        assert_eq!(2.0, got.body);
+        assert_eq!(4.0, got.upper_wick);
+        assert_eq!(4.0, got.lower_wick);
+        assert_eq!(0.4, got.close_position);
+    }
+
+    #[test]
+    fn flat_candle() {
+        let candle = Candle::new(5.0, 5.0, 5.0, 5.0);
+        let got = candle.anatomy();
+        assert_eq!(0.0, got.range);
+        assert_eq!(0.0, got.body_ratio);
+        assert_eq!(0.5, got.close_position);
+    }
+
+    #[test]
+    fn iterator() {
+        let candles = [
+            Candle::new(10.0, 0.0, 2.0, 9.0),
+            Candle::new(10.0, 0.0, 6.0, 4.0),
+        ];
+        let got: Vec<_> = candles.iter().anatomy().map(|a| a.body).collect();
+        assert_eq!(vec![7.0, 2.0], got);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{TRCandle, TrueRange};
+use crate::{Ema, EmaWarmup, RingBuffer, TRCandle, TRIter, TrueRange};
 
 /// All iterators over f32 get an average function
 pub trait Average {
@@ -19,9 +19,60 @@ where
     }
 }
 
+/// All iterators over f32 get an exponentially weighted average, where
+/// each value counts for `2 / (period + 1)` and older ones fade away
+pub trait ExponentialAverage {
+    fn exponential_average(self, period: usize) -> Option<f32>;
+}
+
+impl<I> ExponentialAverage for I
+where
+    I: Iterator<Item = f32>,
+{
+    fn exponential_average(self, period: usize) -> Option<f32> {
+        self.emas(period).last()
+    }
+}
+
+/// Turn an Iterator of f32 into its running exponentially weighted average
+pub trait IntoEmaIter: Iterator<Item = f32> + Sized {
+    /// The same as [`ExponentialAverage::exponential_average`] of every
+    /// value so far, one per value. An [`Ema`] started from the first value;
+    /// yields nothing if `period` is 0
+    fn emas(self, period: usize) -> EmaIter<Self> {
+        EmaIter {
+            values: self,
+            ema: Ema::new(period, EmaWarmup::FirstValue),
+        }
+    }
+}
+
+impl<I> IntoEmaIter for I where I: Iterator<Item = f32> {}
+
+pub struct EmaIter<I> {
+    values: I,
+    ema: Ema,
+}
+
+impl<I> Iterator for EmaIter<I>
+where
+    I: Iterator<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.ema.push(self.values.next()?)
+    }
+}
+
 /// Iterators over TRCandle get an `atr` function
 pub trait Atr {
     fn atr(self) -> Option<f32>;
+
+    /// The true ranges averaged with recent candles counting for more, so
+    /// one old outlier doesn't jump out of the window all at once. Give it
+    /// more than `period` candles; the oldest ones barely count
+    fn weighted_atr(self, period: usize) -> Option<f32>;
 }
 
 impl<I, C> Atr for I
@@ -32,6 +83,157 @@ where
     fn atr(self) -> Option<f32> {
         self.true_range().average()
     }
+
+    fn weighted_atr(self, period: usize) -> Option<f32> {
+        self.true_range().exponential_average(period)
+    }
+}
+
+/// Turn an Iterator of TRCandle into a running ATR, one per candle. Each
+/// step costs the same however long the period is
+pub trait IntoAtrIter: Iterator + Sized
+where
+    Self::Item: TRCandle,
+{
+    /// The average of the last `period` true ranges. Starts yielding once
+    /// there are `period` of them, and yields nothing if `period` is 0
+    fn atrs(self, period: usize) -> AtrIter<Self> {
+        AtrIter {
+            true_ranges: self.true_range(),
+            window: RingBuffer::new(period),
+        }
+    }
+
+    /// The same as [`Atr::weighted_atr`] of every candle so far, one per candle
+    fn weighted_atrs(self, period: usize) -> EmaIter<TRIter<Self>> {
+        self.true_range().emas(period)
+    }
+
+    /// One item per candle: None until `period` candles are in, then the
+    /// ATR with Wilder's smoothing. See [`RollingAtr`]. All None if
+    /// `period` is 0
+    fn rolling_atr(self, period: usize) -> RollingAtrIter<Self> {
+        RollingAtrIter {
+            candles: self,
+            atr: RollingAtr::new(period),
+        }
+    }
+}
+
+impl<I> IntoAtrIter for I
+where
+    I: Iterator,
+    I::Item: TRCandle,
+{
+}
+
+pub struct AtrIter<I> {
+    true_ranges: TRIter<I>,
+    window: RingBuffer,
+}
+
+impl<I> Iterator for AtrIter<I>
+where
+    I: Iterator,
+    I::Item: TRCandle,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.window.capacity() == 0 {
+            return None;
+        }
+        loop {
+            self.window.push(self.true_ranges.next()?);
+            if self.window.is_full() {
+                break self.window.mean();
+            }
+        }
+    }
+}
+
+/// A running ATR with Wilder's smoothing, fed one candle at a time, like
+/// charting packages show. The first value is the simple average of the
+/// first `period` true ranges; after that each new one counts for
+/// `1 / period`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RollingAtr {
+    period: usize,
+    previous_close: Option<f32>,
+    /// The true ranges seen while warming up
+    count: usize,
+    atr: f32,
+}
+
+impl RollingAtr {
+    /// The period Wilder used
+    pub const DEFAULT_PERIOD: usize = 14;
+
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            previous_close: None,
+            count: 0,
+            atr: 0.0,
+        }
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    /// Adds a candle and returns the ATR. The first candle's true range is
+    /// its high - low. None until there have been `period` candles, or
+    /// always if the period is 0
+    pub fn push<C: TRCandle>(&mut self, candle: &C) -> Option<f32> {
+        if self.period == 0 {
+            return None;
+        }
+        let true_range = match self.previous_close.replace(candle.close()) {
+            Some(previous_close) => candle.true_range(previous_close),
+            None => candle.high() - candle.low(),
+        };
+        let period = self.period as f32;
+        if self.count < self.period {
+            // Warming up; sum the true ranges then average them on the last one
+            self.count += 1;
+            self.atr += true_range;
+            if self.count < self.period {
+                return None;
+            }
+            self.atr /= period;
+        } else {
+            self.atr = (self.atr * (period - 1.0) + true_range) / period;
+        }
+        self.value()
+    }
+
+    /// The ATR so far. None until it's warmed up
+    pub fn value(&self) -> Option<f32> {
+        (self.period > 0 && self.count == self.period).then_some(self.atr)
+    }
+}
+
+pub struct RollingAtrIter<I> {
+    candles: I,
+    atr: RollingAtr,
+}
+
+impl<I> Iterator for RollingAtrIter<I>
+where
+    I: Iterator,
+    I::Item: TRCandle,
+{
+    type Item = Option<f32>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let candle = self.candles.next()?;
+        Some(self.atr.push(&candle))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.candles.size_hint()
+    }
 }
 
 #[cfg(test)]
@@ -86,4 +288,79 @@ mod tests {
         let candles: Vec<Candle> = vec![];
         assert_eq!(candles.into_iter().atr(), None);
     }
+
+    #[test]
+    fn exponential_average() {
+        // Each value counts for 2 / (3 + 1) = 0.5
+        let values = vec![4.0, 8.0, 2.0];
+        assert_eq!(Some(4.0), values.into_iter().exponential_average(3));
+        assert_eq!(None, std::iter::empty().exponential_average(3));
+    }
+
+    #[test]
+    fn weighted_atr() {
+        // A spike at the start fades; at the end it counts
+        let quiet = Candle::new(10.5, 9.5, 10.0, 10.0);
+        let spike = Candle::new(15.0, 5.0, 10.0, 10.0);
+        let mut candles = vec![spike.clone()];
+        candles.resize(10, quiet);
+        let old_spike = candles.iter().weighted_atr(3).unwrap();
+        assert!(old_spike < 1.05, "{old_spike}");
+        candles.push(spike);
+        let new_spike = candles.iter().weighted_atr(3).unwrap();
+        assert!(new_spike > 5.0, "{new_spike}");
+        // The flat average doesn't care when the spike was
+        assert!(candles.iter().atr().unwrap() < 3.0);
+    }
+
+    #[test]
+    fn emas() {
+        let values = [4.0, 8.0, 2.0];
+        assert_eq!(
+            vec![4.0, 6.0, 4.0],
+            values.into_iter().emas(3).collect::<Vec<_>>()
+        );
+        assert_eq!(0, values.into_iter().emas(0).count());
+    }
+
+    #[test]
+    fn atrs() {
+        let candles = test_data_2();
+        let true_ranges: Vec<f32> = candles.iter().true_range().collect();
+        let expected: Vec<f32> = true_ranges
+            .windows(3)
+            .flat_map(|window| window.iter().copied().average())
+            .collect();
+        let got: Vec<f32> = candles.iter().atrs(3).collect();
+        assert_eq!(expected.len(), got.len());
+        for (expected, got) in expected.into_iter().zip(got) {
+            assert!((expected - got).abs() < 0.0001, "{expected} {got}");
+        }
+        assert_eq!(0, candles.iter().atrs(0).count());
+        assert_eq!(
+            candles.iter().weighted_atr(3),
+            candles.iter().weighted_atrs(3).last()
+        );
+    }
+
+    #[test]
+    fn rolling_atr() {
+        // True ranges 10, 7, 6, 10, 8, 7, 7, 7, 7, 12
+        let candles = test_data_2();
+        let got: Vec<Option<f32>> = candles.iter().rolling_atr(3).collect();
+        assert_eq!(candles.len(), got.len());
+        assert_eq!(vec![None, None], got[..2]);
+        // The first is the simple average, then each true range counts for a third
+        let mut expected = vec![23.0 / 3.0];
+        for true_range in [10.0, 8.0, 7.0, 7.0, 7.0, 7.0, 12.0] {
+            let previous = expected[expected.len() - 1];
+            expected.push((previous * 2.0 + true_range) / 3.0);
+        }
+        for (expected, got) in expected.into_iter().zip(&got[2..]) {
+            let got = got.unwrap();
+            assert!((expected - got).abs() < 0.0001, "{expected} {got}");
+        }
+        assert!(candles.iter().rolling_atr(0).all(|atr| atr.is_none()));
+        assert_eq!(14, RollingAtr::new(RollingAtr::DEFAULT_PERIOD).period());
+    }
 }
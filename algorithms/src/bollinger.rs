@@ -0,0 +1,179 @@
+//! Bollinger bands: a simple moving average of closes with a band either
+//! side of it, some number of standard deviations away. The bands widen when
+//! the market gets volatile and squeeze together when it goes quiet.
+
+use crate::{Close, RingBuffer};
+
+/// The bands as of one close
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct BollingerBand {
+    /// The simple average of the last `period` closes
+    pub middle: f32,
+    /// `middle` plus `multiplier` standard deviations
+    pub upper: f32,
+    /// `middle` minus `multiplier` standard deviations
+    pub lower: f32,
+}
+
+impl BollingerBand {
+    /// The distance between the upper and lower bands
+    pub fn width(&self) -> f32 {
+        self.upper - self.lower
+    }
+}
+
+/// Running Bollinger bands over the last `period` values, fed one at a time
+#[derive(Debug, PartialEq, Clone)]
+pub struct BollingerBands {
+    window: RingBuffer,
+    multiplier: f32,
+}
+
+impl BollingerBands {
+    /// The period Bollinger used
+    pub const DEFAULT_PERIOD: usize = 20;
+    /// How many standard deviations Bollinger put the bands from the middle
+    pub const DEFAULT_MULTIPLIER: f32 = 2.0;
+
+    pub fn new(period: usize, multiplier: f32) -> Self {
+        Self {
+            window: RingBuffer::new(period),
+            multiplier,
+        }
+    }
+
+    pub fn period(&self) -> usize {
+        self.window.capacity()
+    }
+
+    pub fn multiplier(&self) -> f32 {
+        self.multiplier
+    }
+
+    /// Adds a value and returns the bands over the last `period`. None
+    /// until there have been `period` values, or always if the period is 0
+    pub fn push(&mut self, value: f32) -> Option<BollingerBand> {
+        self.window.push(value);
+        self.value()
+    }
+
+    /// The bands over the last `period` values, once there have been that
+    /// many. Uses the population standard deviation, like Bollinger did
+    pub fn value(&self) -> Option<BollingerBand> {
+        if !self.window.is_full() {
+            return None;
+        }
+        let middle = self.window.mean()?;
+        let variance = self
+            .window
+            .iter()
+            .map(|value| (value - middle).powi(2))
+            .sum::<f32>()
+            / self.period() as f32;
+        let offset = variance.sqrt() * self.multiplier;
+        Some(BollingerBand {
+            middle,
+            upper: middle + offset,
+            lower: middle - offset,
+        })
+    }
+}
+
+/// Turn an Iterator of candles into Bollinger bands around their closes
+pub trait IntoBollingerIterator: Iterator + Sized
+where
+    Self::Item: Close,
+{
+    /// One item per candle: None until `period` closes are in, then the
+    /// bands over the last `period`, `multiplier` standard deviations
+    /// either side of the average. All None if `period` is 0
+    fn bollinger(self, period: usize, multiplier: f32) -> BollingerIterator<Self> {
+        BollingerIterator {
+            candles: self,
+            bands: BollingerBands::new(period, multiplier),
+        }
+    }
+}
+
+impl<I> IntoBollingerIterator for I
+where
+    I: Iterator,
+    I::Item: Close,
+{
+}
+
+pub struct BollingerIterator<I> {
+    candles: I,
+    bands: BollingerBands,
+}
+
+impl<I> Iterator for BollingerIterator<I>
+where
+    I: Iterator,
+    I::Item: Close,
+{
+    type Item = Option<BollingerBand>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let close = self.candles.next()?.close();
+        Some(self.bands.push(close))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.candles.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candle::test_data::closes;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn bands() {
+        // The last four closes average 5 with a standard deviation of 2
+        let candles = closes([9.0, 3.0, 7.0, 3.0, 7.0]);
+        let bands: Vec<_> = candles.iter().bollinger(4, 1.5).collect();
+        assert_eq!(5, bands.len());
+        assert!(bands[..3].iter().all(Option::is_none));
+        let band = bands[4].unwrap();
+        assert_eq!(5.0, band.middle);
+        assert!((band.upper - 8.0).abs() < 0.0001, "{band:?}");
+        assert!((band.lower - 2.0).abs() < 0.0001, "{band:?}");
+        assert!((band.width() - 6.0).abs() < 0.0001, "{band:?}");
+    }
+
+    #[test]
+    fn flat_market_squeezes() {
+        let candles = closes([3.0; 5]);
+        let band = candles.iter().bollinger(3, 2.0).last().flatten();
+        assert_eq!(
+            Some(BollingerBand {
+                middle: 3.0,
+                upper: 3.0,
+                lower: 3.0
+            }),
+            band
+        );
+    }
+
+    #[test]
+    fn one_per_candle() {
+        let candles = closes([1.0, 2.0]);
+        assert_eq!(
+            vec![None, None],
+            candles.iter().bollinger(3, 2.0).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![None, None],
+            candles.iter().bollinger(0, 2.0).collect::<Vec<_>>()
+        );
+        let bands = BollingerBands::new(
+            BollingerBands::DEFAULT_PERIOD,
+            BollingerBands::DEFAULT_MULTIPLIER,
+        );
+        assert_eq!(20, bands.period());
+        assert_eq!(2.0, bands.multiplier());
+    }
+}
@@ -1,5 +1,6 @@
 //! This module defines four traits: High, Low, Open, and Close, which
-//! represent the four values of a candlestick chart. It also implements
+//! represent the four values of a candlestick chart, plus Volume for the
+//! amount traded while it was drawn. It also implements
 //! each of these traits for any type that implements Deref to a type
 //! that implements the corresponding trait. This allows the values of
 //! a candlestick chart to be used without needing to know the specific
@@ -26,6 +27,10 @@ pub trait Close {
     fn close(&self) -> f32;
 }
 
+pub trait Volume {
+    fn volume(&self) -> f32;
+}
+
 impl<T, H> High for T
 where
     T: Deref<Target = H>,
@@ -66,6 +71,16 @@ where
     }
 }
 
+impl<T, V> Volume for T
+where
+    T: Deref<Target = V>,
+    V: Volume,
+{
+    fn volume(&self) -> f32 {
+        self.deref().volume()
+    }
+}
+
 #[cfg(test)]
 pub mod test_data {
     use super::{Close, High, Low, Open};
@@ -113,6 +128,14 @@ pub mod test_data {
         }
     }
 
+    /// Flat candles, one per close, for indicators that only look at closes
+    pub fn closes(closes: impl IntoIterator<Item = f32>) -> Vec<Candle> {
+        closes
+            .into_iter()
+            .map(|close| Candle::new(close, close, close, close))
+            .collect()
+    }
+
     pub fn test_data_1() -> Vec<Candle> {
         // hl means the absolute difference between the high and the low
         // hpc is the absolute difference between the high and the previous close
@@ -0,0 +1,75 @@
+//! A proxy for buying and selling pressure when there's no real tick data.
+//!
+//! Each candle's volume is counted as buying if it closed above the last
+//! close, or selling if it closed below. A candle that closes where the
+//! last one did keeps the last direction (the "tick rule"). The running
+//! total of up volume minus down volume is the cumulative delta.
+
+/// Turn an Iterator of `(close, volume)` pairs into an Iterator of the
+/// cumulative delta after each one
+pub trait IntoCumulativeDeltaIter: Iterator<Item = (f32, f32)> + Sized {
+    fn cumulative_delta(self) -> CumulativeDeltaIter<Self> {
+        CumulativeDeltaIter {
+            iter: self,
+            previous_close: None,
+            direction: 0.0,
+            cumulative: 0.0,
+        }
+    }
+}
+
+impl<I> IntoCumulativeDeltaIter for I where I: Iterator<Item = (f32, f32)> {}
+
+pub struct CumulativeDeltaIter<I> {
+    iter: I,
+    previous_close: Option<f32>,
+    // 1 for buying, -1 for selling, 0 until we know
+    direction: f32,
+    cumulative: f32,
+}
+
+impl<I> Iterator for CumulativeDeltaIter<I>
+where
+    I: Iterator<Item = (f32, f32)>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (close, volume) = self.iter.next()?;
+        if let Some(previous_close) = self.previous_close.replace(close) {
+            if close > previous_close {
+                self.direction = 1.0;
+            } else if close < previous_close {
+                self.direction = -1.0;
+            }
+        }
+        // The first candle has nothing to compare with, so it counts for nothing
+        self.cumulative += self.direction * volume;
+        Some(self.cumulative)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn cumulative_delta() {
+        let input = [
+            (10.0, 100.0),
+            (11.0, 50.0), // up
+            (11.0, 20.0), // flat, still up
+            (10.5, 30.0), // down
+            (10.5, 10.0), // flat, still down
+            (12.0, 40.0), // up
+        ];
+        let got: Vec<_> = input.into_iter().cumulative_delta().collect();
+        assert_eq!(vec![0.0, 50.0, 70.0, 40.0, 30.0, 70.0], got);
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(None, std::iter::empty().cumulative_delta().next());
+    }
+}
@@ -0,0 +1,103 @@
+//! Ways of measuring the distance between two prices so that strategy
+//! thresholds mean the same thing whatever the instrument or volatility.
+
+use crate::{IntoAtrIter, RingBuffer, TRCandle};
+
+/// The size of one pip in price units given the instrument's pip location.
+/// eg. -4 gives 0.0001 for EUR/USD, and -2 gives 0.01 for USD/JPY
+pub fn pip_size(pip_location: i32) -> f32 {
+    10f32.powi(pip_location)
+}
+
+/// How many ATRs apart two prices are. Always positive
+pub fn distance_in_atr(price_a: f32, price_b: f32, atr: f32) -> f32 {
+    (price_a - price_b).abs() / atr
+}
+
+/// How many pips apart two prices are. Always positive
+pub fn distance_in_pips(price_a: f32, price_b: f32, pip_location: i32) -> f32 {
+    (price_a - price_b).abs() / pip_size(pip_location)
+}
+
+/// The fraction (0 to 1) of `history` that is less than or equal to `value`
+pub(crate) fn percentile_rank<'a>(
+    history: impl IntoIterator<Item = &'a f32>,
+    value: f32,
+) -> Option<f32> {
+    let (below, count) = history.into_iter().fold((0, 0), |(below, count), &n| {
+        (below + usize::from(n <= value), count + 1)
+    });
+    if count == 0 {
+        None
+    } else {
+        Some(below as f32 / count as f32)
+    }
+}
+
+/// Ranks the latest `period` ATR against the `lookback` ATRs before it
+/// (including itself), from 0 (the quietest) to 1 (the most volatile).
+///
+/// Returns None if there aren't enough candles for even one ATR.
+pub fn atr_percentile<I>(candles: I, period: usize, lookback: usize) -> Option<f32>
+where
+    I: IntoIterator,
+    I::Item: TRCandle,
+{
+    let mut atrs = RingBuffer::new(lookback);
+    for atr in candles.into_iter().atrs(period) {
+        atrs.push(atr);
+    }
+    let latest = atrs.latest()?;
+    percentile_rank(atrs.iter(), latest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candle::test_data::{test_data_1, Candle};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn atr_distance() {
+        assert_eq!(2.0, distance_in_atr(1.0, 1.5, 0.25));
+        assert_eq!(2.0, distance_in_atr(1.5, 1.0, 0.25));
+    }
+
+    #[test]
+    fn pip_distance() {
+        assert!((distance_in_pips(1.1000, 1.1050, -4) - 50.0).abs() < 0.01);
+        assert!((distance_in_pips(150.25, 149.75, -2) - 50.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn rank() {
+        assert_eq!(None, percentile_rank(&[], 1.0));
+        assert_eq!(Some(0.5), percentile_rank(&[1.0, 2.0, 3.0, 4.0], 2.0));
+        assert_eq!(Some(1.0), percentile_rank(&[1.0, 2.0, 3.0, 4.0], 4.0));
+    }
+
+    #[test]
+    fn atr_rank() {
+        // True ranges are 5, 6, 4, 4, 5, 5, 4, 5, 6
+        // 2 period ATRs are 5.5, 5, 4, 4.5, 5, 4.5, 4.5, 5.5
+        let candles = test_data_1();
+        assert_eq!(Some(1.0), atr_percentile(candles.iter(), 2, 100));
+        // The last 4 are 5, 4.5, 4.5, 5.5
+        assert_eq!(Some(1.0), atr_percentile(candles.iter(), 2, 4));
+        // The last 3 of the 3 period ATRs are 4.6666, 4.6666, 5
+        assert_eq!(Some(1.0), atr_percentile(candles.iter(), 3, 3));
+        // Adds a true range of 2, so the last 8 ATRs are 5, 4, 4.5, 5, 4.5, 4.5, 5.5, 4
+        let quiet_end = [Candle::new(10.0, 9.9, 10.0, 10.0)];
+        assert_eq!(
+            Some(0.25),
+            atr_percentile(candles.iter().chain(quiet_end.iter()), 2, 8)
+        );
+    }
+
+    #[test]
+    fn atr_rank_not_enough_candles() {
+        let candles = test_data_1();
+        assert_eq!(None, atr_percentile(candles.iter(), 10, 5));
+        assert_eq!(None, atr_percentile(candles.iter(), 0, 5));
+    }
+}
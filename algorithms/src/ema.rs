@@ -0,0 +1,179 @@
+//! Exponential moving average of closes. Each close counts for
+//! `2 / (period + 1)` of the average and older ones fade away, so it follows
+//! the price more closely than a simple average over the same period.
+
+use crate::Close;
+
+/// How an EMA gets going before it's seen `period` values
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum EmaWarmup {
+    /// Yield nothing for the first `period - 1` values, then start from the
+    /// simple average of the first `period`. What charting packages do
+    #[default]
+    SimpleAverage,
+    /// Start from the first value and yield one per value. Never skips a
+    /// candle, but the first few values lean heavily on the first close
+    FirstValue,
+}
+
+/// A running EMA, fed one value at a time
+#[derive(Debug, PartialEq, Clone)]
+pub struct Ema {
+    period: usize,
+    alpha: f32,
+    warmup: EmaWarmup,
+    average: Option<f32>,
+    // The sum and count of values seen while warming up
+    seed_sum: f32,
+    seen: usize,
+}
+
+impl Ema {
+    pub fn new(period: usize, warmup: EmaWarmup) -> Self {
+        Self {
+            period,
+            alpha: 2.0 / (period as f32 + 1.0),
+            warmup,
+            average: None,
+            seed_sum: 0.0,
+            seen: 0,
+        }
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    /// The average so far. None until it's warmed up
+    pub fn value(&self) -> Option<f32> {
+        self.average
+    }
+
+    /// Adds a value and returns the new average. None while warming up, or
+    /// always if the period is 0
+    pub fn push(&mut self, value: f32) -> Option<f32> {
+        if self.period == 0 {
+            return None;
+        }
+        let average = match (self.average, self.warmup) {
+            (Some(average), _) => average + self.alpha * (value - average),
+            (None, EmaWarmup::FirstValue) => value,
+            (None, EmaWarmup::SimpleAverage) => {
+                self.seed_sum += value;
+                self.seen += 1;
+                if self.seen < self.period {
+                    return None;
+                }
+                self.seed_sum / self.period as f32
+            }
+        };
+        self.average = Some(average);
+        self.average
+    }
+}
+
+/// Turn an Iterator of candles into the EMA of their closes
+pub trait IntoEmaIterator: Iterator + Sized
+where
+    Self::Item: Close,
+{
+    /// The EMA over `period` closes, warmed up with
+    /// [`EmaWarmup::SimpleAverage`]. Yields nothing if `period` is 0
+    fn ema(self, period: usize) -> EmaIterator<Self> {
+        self.ema_with_warmup(period, EmaWarmup::default())
+    }
+
+    /// The EMA over `period` closes. Yields nothing if `period` is 0
+    fn ema_with_warmup(self, period: usize, warmup: EmaWarmup) -> EmaIterator<Self> {
+        EmaIterator {
+            candles: self,
+            ema: Ema::new(period, warmup),
+        }
+    }
+}
+
+impl<I> IntoEmaIterator for I
+where
+    I: Iterator,
+    I::Item: Close,
+{
+}
+
+pub struct EmaIterator<I> {
+    candles: I,
+    ema: Ema,
+}
+
+impl<I> Iterator for EmaIterator<I>
+where
+    I: Iterator,
+    I::Item: Close,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.ema.period() == 0 {
+            return None;
+        }
+        loop {
+            let close = self.candles.next()?.close();
+            if let Some(average) = self.ema.push(close) {
+                break Some(average);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{candle::test_data::closes, IntoEmaIter};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn simple_average_warmup() {
+        // Starts from (2 + 4 + 6) / 3 = 4, then each close counts for a half
+        let candles = closes([2.0, 4.0, 6.0, 8.0, 2.0]);
+        assert_eq!(
+            vec![4.0, 6.0, 4.0],
+            candles.iter().ema(3).collect::<Vec<_>>()
+        );
+        assert_eq!(0, candles[..2].iter().ema(3).count());
+    }
+
+    #[test]
+    fn first_value_warmup() {
+        let candles = closes([4.0, 8.0, 2.0]);
+        let got: Vec<_> = candles
+            .iter()
+            .ema_with_warmup(3, EmaWarmup::FirstValue)
+            .collect();
+        assert_eq!(vec![4.0, 6.0, 4.0], got);
+        // The same as the running average of plain values
+        assert_eq!(got, [4.0, 8.0, 2.0].into_iter().emas(3).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn zero_period() {
+        let candles = closes([4.0, 8.0, 2.0]);
+        assert_eq!(0, candles.iter().ema(0).count());
+        assert_eq!(
+            0,
+            candles
+                .iter()
+                .ema_with_warmup(0, EmaWarmup::FirstValue)
+                .count()
+        );
+    }
+
+    #[test]
+    fn streaming() {
+        let mut ema = Ema::new(2, EmaWarmup::SimpleAverage);
+        assert_eq!(None, ema.push(1.0));
+        assert_eq!(None, ema.value());
+        assert_eq!(Some(2.0), ema.push(3.0));
+        // 2 / 3 of the way from 2 to 5
+        assert_eq!(Some(4.0), ema.push(5.0));
+        assert_eq!(Some(4.0), ema.value());
+    }
+}
@@ -0,0 +1,146 @@
+//! Moving average envelopes: a simple moving average of closes with a band
+//! a fixed percent above and below it. Unlike Bollinger bands they don't
+//! react to volatility, which keeps them steady on instruments whose
+//! volatility jumps around.
+
+use crate::{Close, Sma};
+
+/// The envelope as of one close
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Envelope {
+    /// The simple average of the last `period` closes
+    pub middle: f32,
+    /// `middle` plus `percent`
+    pub upper: f32,
+    /// `middle` minus `percent`
+    pub lower: f32,
+}
+
+impl Envelope {
+    /// The distance between the upper and lower bands
+    pub fn width(&self) -> f32 {
+        self.upper - self.lower
+    }
+}
+
+/// A running envelope over the last `period` values, fed one at a time
+#[derive(Debug, PartialEq, Clone)]
+pub struct Envelopes {
+    sma: Sma,
+    percent: f32,
+}
+
+impl Envelopes {
+    pub const DEFAULT_PERIOD: usize = 20;
+    pub const DEFAULT_PERCENT: f32 = 2.5;
+
+    /// Bands `percent` percent either side of the average of `period`
+    /// values, eg. 2.5 for 2.5%
+    pub fn new(period: usize, percent: f32) -> Self {
+        Self {
+            sma: Sma::new(period),
+            percent,
+        }
+    }
+
+    pub fn period(&self) -> usize {
+        self.sma.period()
+    }
+
+    pub fn percent(&self) -> f32 {
+        self.percent
+    }
+
+    /// Adds a value and returns the envelope over the last `period`. None
+    /// until there have been `period` values, or always if the period is 0
+    pub fn push(&mut self, value: f32) -> Option<Envelope> {
+        let middle = self.sma.push(value)?;
+        let offset = middle * self.percent / 100.0;
+        Some(Envelope {
+            middle,
+            upper: middle + offset,
+            lower: middle - offset,
+        })
+    }
+}
+
+/// Turn an Iterator of candles into envelopes around their closes
+pub trait IntoEnvelopeIterator: Iterator + Sized
+where
+    Self::Item: Close,
+{
+    /// One item per candle: None until `period` closes are in, then the
+    /// bands `percent` percent either side of the average of the last
+    /// `period`. All None if `period` is 0
+    fn envelope(self, period: usize, percent: f32) -> EnvelopeIterator<Self> {
+        EnvelopeIterator {
+            candles: self,
+            envelopes: Envelopes::new(period, percent),
+        }
+    }
+}
+
+impl<I> IntoEnvelopeIterator for I
+where
+    I: Iterator,
+    I::Item: Close,
+{
+}
+
+pub struct EnvelopeIterator<I> {
+    candles: I,
+    envelopes: Envelopes,
+}
+
+impl<I> Iterator for EnvelopeIterator<I>
+where
+    I: Iterator,
+    I::Item: Close,
+{
+    type Item = Option<Envelope>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let close = self.candles.next()?.close();
+        Some(self.envelopes.push(close))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.candles.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candle::test_data::closes;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn bands() {
+        let candles = closes([90.0, 110.0, 100.0, 300.0]);
+        let envelopes: Vec<_> = candles.iter().envelope(3, 5.0).collect();
+        assert_eq!(4, envelopes.len());
+        assert_eq!(None, envelopes[1]);
+        let expected = Envelope {
+            middle: 100.0,
+            upper: 105.0,
+            lower: 95.0,
+        };
+        assert_eq!(Some(expected), envelopes[2]);
+        // The same width however far the last close jumped
+        let last = envelopes[3].unwrap();
+        assert_eq!(170.0, last.middle);
+        assert!((last.width() - 17.0).abs() < 0.0001, "{last:?}");
+    }
+
+    #[test]
+    fn period_zero() {
+        let candles = closes([1.0, 2.0]);
+        assert_eq!(
+            vec![None, None],
+            candles.iter().envelope(0, 1.0).collect::<Vec<_>>()
+        );
+        let envelopes = Envelopes::new(Envelopes::DEFAULT_PERIOD, Envelopes::DEFAULT_PERCENT);
+        assert_eq!((20, 2.5), (envelopes.period(), envelopes.percent()));
+    }
+}
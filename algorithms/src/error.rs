@@ -0,0 +1,19 @@
+/// Things that can go wrong when running the algorithms on bad input
+#[derive(thiserror::Error, Debug, PartialEq, Eq, Clone)]
+pub enum Error {
+    #[error("Can't have a zero sized sliding window")]
+    ZeroWindow,
+    #[error("Window size {window_size} must be <= input length {input_len}")]
+    WindowTooBig {
+        window_size: usize,
+        input_len: usize,
+    },
+    #[error("The min window size {min} must be at least 1 and no more than the max {max}")]
+    WindowRange { min: usize, max: usize },
+    #[error("Values must be added in time order")]
+    OutOfOrder,
+    #[error("Invalid trade plan: {0}")]
+    TradePlan(&'static str),
+    #[error("Couldn't serialize or deserialize the snapshot: {0}")]
+    Snapshot(String),
+}
@@ -0,0 +1,172 @@
+//! Tracks the maximum favourable excursion (MFE) and maximum adverse
+//! excursion (MAE) of a position as candles come in after the entry.
+//!
+//! Both are measured in R multiples, where 1R is the distance from the
+//! entry to the stop loss.
+
+use crate::{
+    candle::{High, Low},
+    SignalOutcome,
+};
+
+/// Which way a position was opened
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TradeDirection {
+    Long,
+    Short,
+}
+
+/// The running excursions of a position, in R
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct Excursion {
+    /// The furthest price has gone in our favour
+    pub mfe: f32,
+    /// The furthest price has gone against us. Stored as a positive number
+    pub mae: f32,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ExcursionTracker {
+    direction: TradeDirection,
+    entry: f32,
+    // The distance from the entry to the stop loss; 1R
+    risk: f32,
+    excursion: Excursion,
+}
+
+impl ExcursionTracker {
+    /// Starts tracking a position. `stop_loss` must not be the same as `entry`
+    pub fn new(direction: TradeDirection, entry: f32, stop_loss: f32) -> Self {
+        Self {
+            direction,
+            entry,
+            risk: (entry - stop_loss).abs(),
+            excursion: Excursion::default(),
+        }
+    }
+
+    /// How many R we'd be up (or down if negative) if we closed at `price`
+    pub fn r(&self, price: f32) -> f32 {
+        match self.direction {
+            TradeDirection::Long => (price - self.entry) / self.risk,
+            TradeDirection::Short => (self.entry - price) / self.risk,
+        }
+    }
+
+    /// Takes the next candle after the entry into account and returns the
+    /// excursions so far
+    pub fn update(&mut self, candle: &(impl High + Low)) -> Excursion {
+        let (best, worst) = match self.direction {
+            TradeDirection::Long => (candle.high(), candle.low()),
+            TradeDirection::Short => (candle.low(), candle.high()),
+        };
+        self.excursion.mfe = self.excursion.mfe.max(self.r(best));
+        self.excursion.mae = self.excursion.mae.max(-self.r(worst));
+        self.excursion
+    }
+
+    /// The excursions so far
+    pub fn excursion(&self) -> Excursion {
+        self.excursion
+    }
+
+    /// Closes the position at `exit`, giving the outcome for the signal stats
+    pub fn outcome<K>(&self, setup: K, exit: f32) -> SignalOutcome<K> {
+        SignalOutcome {
+            setup,
+            r: self.r(exit),
+            mfe: self.excursion.mfe,
+            mae: self.excursion.mae,
+        }
+    }
+}
+
+/// Turn an Iterator of candles after an entry into an Iterator of the
+/// running excursions
+pub trait IntoExcursionIter: Iterator + Sized
+where
+    Self::Item: High + Low,
+{
+    fn excursions(self, tracker: ExcursionTracker) -> ExcursionIter<Self> {
+        ExcursionIter {
+            candles: self,
+            tracker,
+        }
+    }
+}
+
+impl<I> IntoExcursionIter for I
+where
+    I: Iterator,
+    I::Item: High + Low,
+{
+}
+
+pub struct ExcursionIter<I> {
+    candles: I,
+    tracker: ExcursionTracker,
+}
+
+impl<I> Iterator for ExcursionIter<I>
+where
+    I: Iterator,
+    I::Item: High + Low,
+{
+    type Item = Excursion;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let candle = self.candles.next()?;
+        Some(self.tracker.update(&candle))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candle::test_data::Candle;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn long() {
+        // Entry at 10, stop at 8 so 1R is 2
+        let tracker = ExcursionTracker::new(TradeDirection::Long, 10.0, 8.0);
+        let candles = [
+            Candle::new(11.0, 9.0, 10.0, 10.5),
+            Candle::new(14.0, 10.0, 10.5, 13.0),
+            Candle::new(13.0, 7.0, 13.0, 12.0),
+        ];
+        let got: Vec<_> = candles.iter().excursions(tracker).collect();
+        let expected = vec![
+            Excursion { mfe: 0.5, mae: 0.5 },
+            Excursion { mfe: 2.0, mae: 0.5 },
+            Excursion { mfe: 2.0, mae: 1.5 },
+        ];
+        assert_eq!(expected, got);
+    }
+
+    #[test]
+    fn short() {
+        // Entry at 10, stop at 11 so 1R is 1
+        let mut tracker = ExcursionTracker::new(TradeDirection::Short, 10.0, 11.0);
+        tracker.update(&Candle::new(10.5, 8.0, 10.0, 9.0));
+        assert_eq!(Excursion { mfe: 2.0, mae: 0.5 }, tracker.excursion());
+        let outcome = tracker.outcome("breakout", 9.0);
+        assert_eq!(
+            SignalOutcome {
+                setup: "breakout",
+                r: 1.0,
+                mfe: 2.0,
+                mae: 0.5
+            },
+            outcome
+        );
+    }
+
+    #[test]
+    fn never_negative() {
+        // Price only goes our way
+        let mut tracker = ExcursionTracker::new(TradeDirection::Long, 10.0, 9.0);
+        let got = tracker.update(&Candle::new(12.0, 11.0, 11.0, 12.0));
+        assert_eq!(Excursion { mfe: 2.0, mae: 0.0 }, got);
+    }
+}
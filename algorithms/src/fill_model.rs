@@ -0,0 +1,165 @@
+//! The price a backtest assumes an entry is filled at. Breakout strategies
+//! look far better filled at the close of the candle that broke out than
+//! they do filled at the next open, or at a stop order's trigger price, so
+//! the assumption is made explicit and can be compared.
+
+use crate::{
+    candle::{Close, High, Low, Open},
+    TradeDirection,
+};
+
+/// How an entry signalled at the close of a candle gets filled
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FillModel {
+    /// At the close of the signal candle. Optimistic; nobody can trade a
+    /// price that's already gone
+    SignalClose,
+    /// At the open of the candle after the signal
+    NextOpen,
+    /// A stop entry order at the trigger price, filled by the first later
+    /// candle whose high (for longs) or low (for shorts) reaches it. If the
+    /// candle gaps through the trigger it's filled at the open instead
+    StopTrigger,
+}
+
+/// Where an entry was filled
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Fill {
+    /// How many candles after the signal candle the fill happened in. 0 is
+    /// the signal candle itself
+    pub candles_after: usize,
+    pub price: f32,
+}
+
+impl FillModel {
+    /// Fills an entry signalled at the close of `signal`, given the candles
+    /// after it. `trigger` is only used by [`FillModel::StopTrigger`].
+    /// Returns None if the entry never fills in the candles given; use
+    /// `take` on them to limit how long a stop order waits
+    pub fn fill<C>(
+        &self,
+        direction: TradeDirection,
+        trigger: f32,
+        signal: &impl Close,
+        after: impl IntoIterator<Item = C>,
+    ) -> Option<Fill>
+    where
+        C: High + Low + Open,
+    {
+        match self {
+            FillModel::SignalClose => Some(Fill {
+                candles_after: 0,
+                price: signal.close(),
+            }),
+            FillModel::NextOpen => after.into_iter().next().map(|candle| Fill {
+                candles_after: 1,
+                price: candle.open(),
+            }),
+            FillModel::StopTrigger => after.into_iter().enumerate().find_map(|(index, candle)| {
+                stop_fill(direction, trigger, &candle).map(|price| Fill {
+                    candles_after: index + 1,
+                    price,
+                })
+            }),
+        }
+    }
+}
+
+/// The fill price of a stop entry order at `trigger` during `candle`, if it's reached
+fn stop_fill(
+    direction: TradeDirection,
+    trigger: f32,
+    candle: &(impl High + Low + Open),
+) -> Option<f32> {
+    match direction {
+        TradeDirection::Long if candle.high() >= trigger => Some(trigger.max(candle.open())),
+        TradeDirection::Short if candle.low() <= trigger => Some(trigger.min(candle.open())),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candle::test_data::Candle;
+    use pretty_assertions::assert_eq;
+
+    // Broke out above 10 and closed at 10.5
+    fn signal() -> Candle {
+        Candle::new(10.6, 9.8, 9.9, 10.5)
+    }
+
+    #[test]
+    fn signal_close_and_next_open() {
+        let after = [Candle::new(10.8, 10.3, 10.4, 10.7)];
+        let fill = FillModel::SignalClose.fill(TradeDirection::Long, 10.0, &signal(), &after);
+        assert_eq!(
+            Some(Fill {
+                candles_after: 0,
+                price: 10.5
+            }),
+            fill
+        );
+        let fill = FillModel::NextOpen.fill(TradeDirection::Long, 10.0, &signal(), &after);
+        assert_eq!(
+            Some(Fill {
+                candles_after: 1,
+                price: 10.4
+            }),
+            fill
+        );
+        let none: [Candle; 0] = [];
+        assert_eq!(
+            None,
+            FillModel::NextOpen.fill(TradeDirection::Long, 10.0, &signal(), &none)
+        );
+    }
+
+    #[test]
+    fn stop_trigger() {
+        let after = [
+            // Doesn't reach 10.7
+            Candle::new(10.6, 10.2, 10.4, 10.5),
+            Candle::new(10.9, 10.4, 10.5, 10.8),
+        ];
+        let fill = FillModel::StopTrigger.fill(TradeDirection::Long, 10.7, &signal(), &after);
+        assert_eq!(
+            Some(Fill {
+                candles_after: 2,
+                price: 10.7
+            }),
+            fill
+        );
+        // Limiting the wait to one candle means it never fills
+        let fill = FillModel::StopTrigger.fill(
+            TradeDirection::Long,
+            10.7,
+            &signal(),
+            after.iter().take(1),
+        );
+        assert_eq!(None, fill);
+    }
+
+    #[test]
+    fn stop_trigger_gap() {
+        // Opens past the trigger so the order fills at the open
+        let after = [Candle::new(11.2, 10.9, 11.0, 11.1)];
+        let fill = FillModel::StopTrigger.fill(TradeDirection::Long, 10.7, &signal(), &after);
+        assert_eq!(
+            Some(Fill {
+                candles_after: 1,
+                price: 11.0
+            }),
+            fill
+        );
+        let after = [Candle::new(9.4, 9.0, 9.3, 9.1)];
+        let fill = FillModel::StopTrigger.fill(TradeDirection::Short, 9.5, &signal(), &after);
+        assert_eq!(
+            Some(Fill {
+                candles_after: 1,
+                price: 9.3
+            }),
+            fill
+        );
+    }
+}
@@ -1,7 +1,11 @@
-use crate::Pivot;
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{pivot_high_low::find_pivot, High, Low, Pivot};
 
 /// Represents the four possible types of high-low swings in a series of pivots:
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 pub enum SwingType {
     /// A new higher resistance line has been created
     HigherHigh,
@@ -23,7 +27,7 @@ pub enum SwingType {
     Hold,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct SwingStatus {
     pub swing_type: SwingType,
     pub support: Option<f32>,
@@ -33,10 +37,7 @@ pub struct SwingStatus {
 /// Takes a list of high/low pivots and generates support and resistance lines from them
 pub struct SwingStatusIter<I> {
     input: I,
-    prev_high: Option<f32>,
-    prev_low: Option<f32>,
-    support: Option<f32>,
-    resistance: Option<f32>,
+    tracker: SwingTracker,
 }
 
 impl<I> SwingStatusIter<I>
@@ -47,10 +48,130 @@ where
     pub fn new(input: I) -> Self {
         SwingStatusIter {
             input,
-            prev_high: None,
-            prev_low: None,
-            support: None,
-            resistance: None,
+            tracker: SwingTracker::default(),
+        }
+    }
+}
+
+/// The push based version of [`SwingStatusIter`], for when the pivots or
+/// renko bricks arrive one at a time, eg. from a price stream
+#[derive(Debug, Default, Clone)]
+pub struct SwingTracker {
+    prev_high: Option<f32>,
+    prev_low: Option<f32>,
+    support: Option<f32>,
+    resistance: Option<f32>,
+    // The pivot window size used by `push_brick`
+    window_size: usize,
+    // The last `window_size` bricks pushed
+    bricks: VecDeque<Extent>,
+}
+
+/// Just the high and low of a brick, so we don't have to keep the brick
+#[derive(Debug, Clone, Copy)]
+struct Extent {
+    high: f32,
+    low: f32,
+}
+
+impl High for Extent {
+    fn high(&self) -> f32 {
+        self.high
+    }
+}
+
+impl Low for Extent {
+    fn low(&self) -> f32 {
+        self.low
+    }
+}
+
+impl SwingTracker {
+    /// A tracker that's fed pivots with [`Self::push`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A tracker that's fed renko bricks with [`Self::push_brick`], finding
+    /// pivots over a window of `window_size` bricks like [`crate::pivots`]
+    pub fn with_window(window_size: usize) -> Self {
+        Self {
+            window_size,
+            bricks: VecDeque::with_capacity(window_size),
+            ..Self::default()
+        }
+    }
+
+    /// The current support line, if we've seen enough lows to have one
+    pub fn support(&self) -> Option<f32> {
+        self.support
+    }
+
+    /// The current resistance line, if we've seen enough highs to have one
+    pub fn resistance(&self) -> Option<f32> {
+        self.resistance
+    }
+
+    /// Adds a new renko brick (or any candle) and returns the swing it
+    /// makes. Until there are `window_size` bricks, or if the window size
+    /// is 0, there's no pivot and it holds. Like [`crate::pivots`], a pivot
+    /// is reported when the brick that confirms it arrives
+    pub fn push_brick(&mut self, brick: &(impl High + Low)) -> SwingStatus {
+        if self.window_size == 0 {
+            return self.push(Pivot::NoChange);
+        }
+        if self.bricks.len() == self.window_size {
+            self.bricks.pop_front();
+        }
+        self.bricks.push_back(Extent {
+            high: brick.high(),
+            low: brick.low(),
+        });
+        let pivot = if self.bricks.len() == self.window_size {
+            find_pivot(self.bricks.make_contiguous(), self.window_size / 2)
+        } else {
+            Pivot::NoChange
+        };
+        self.push(pivot)
+    }
+
+    /// Adds the next pivot and returns the swing it makes
+    pub fn push(&mut self, input: Pivot) -> SwingStatus {
+        let swing_type = self
+            .check_hh(&input)
+            .or_else(|| self.check_lh(&input))
+            .or_else(|| self.check_hl(&input))
+            .or_else(|| self.check_ll(&input))
+            .or_else(|| self.check_hhhl(&input))
+            .or_else(|| self.check_hhll(&input))
+            .or_else(|| self.check_lhhl(&input))
+            .or_else(|| self.check_lhll(&input))
+            .unwrap_or(SwingType::Hold);
+
+        // Update our internal state
+        let high = input.high();
+        let low = input.low();
+        match (high, self.prev_high) {
+            (Some(high), None) => self.prev_high = Some(high),
+            (Some(high), Some(_prev)) => {
+                self.prev_high = Some(high);
+                self.resistance = Some(high);
+            }
+            _ => (),
+        };
+        match (low, self.prev_low) {
+            (Some(low), None) => self.prev_low = Some(low),
+            (Some(low), Some(_prev)) => {
+                self.prev_low = Some(low);
+                self.support = Some(low);
+            }
+            _ => (),
+        };
+
+        SwingStatus {
+            swing_type,
+            support: self.support,
+            resistance: self.resistance,
         }
     }
 
@@ -181,45 +302,7 @@ where
 
     fn next(&mut self) -> Option<Self::Item> {
         let input = self.input.next()?;
-
-        let swing_type = self
-            .check_hh(&input)
-            .or_else(|| self.check_lh(&input))
-            .or_else(|| self.check_hl(&input))
-            .or_else(|| self.check_ll(&input))
-            .or_else(|| self.check_hhhl(&input))
-            .or_else(|| self.check_hhll(&input))
-            .or_else(|| self.check_lhhl(&input))
-            .or_else(|| self.check_lhll(&input))
-            .unwrap_or(SwingType::Hold);
-
-        // Update our internal state
-        let high = input.high();
-        let low = input.low();
-        match (high, self.prev_high) {
-            (Some(high), None) => self.prev_high = Some(high),
-            (Some(high), Some(_prev)) => {
-                self.prev_high = Some(high);
-                self.resistance = Some(high);
-            }
-            _ => (),
-        };
-        match (low, self.prev_low) {
-            (Some(low), None) => self.prev_low = Some(low),
-            (Some(low), Some(_prev)) => {
-                self.prev_low = Some(low);
-                self.support = Some(low);
-            }
-            _ => (),
-        };
-
-        let support = self.support;
-        let resistance = self.resistance;
-        Some(SwingStatus {
-            swing_type,
-            support,
-            resistance,
-        })
+        Some(self.tracker.push(input))
     }
 }
 
@@ -267,12 +350,12 @@ mod tests {
         );
         assert_eq!(
             Some(10.0),
-            iter.prev_high,
+            iter.tracker.prev_high,
             "It should update its internal state"
         );
-        assert_eq!(None, iter.prev_low);
-        assert_eq!(None, iter.support);
-        assert_eq!(None, iter.resistance);
+        assert_eq!(None, iter.tracker.prev_low);
+        assert_eq!(None, iter.tracker.support);
+        assert_eq!(None, iter.tracker.resistance);
     }
 
     #[test]
@@ -769,13 +852,13 @@ mod tests {
         assert_eq!(expected, got);
     }
 
-    fn create_swing_status_iter() -> SwingStatusIter<std::iter::Empty<Pivot>> {
-        SwingStatusIter::new(std::iter::empty())
+    fn create_swing_tracker() -> SwingTracker {
+        SwingTracker::new()
     }
 
     #[test]
     fn check_hh_test() {
-        let mut ssi = create_swing_status_iter();
+        let mut ssi = create_swing_tracker();
         let pivot = Pivot::High(110.0);
         // With no previous it should return None
         assert_eq!(ssi.check_hh(&pivot), None);
@@ -797,7 +880,7 @@ mod tests {
 
     #[test]
     fn check_lh_test() {
-        let mut ssi = create_swing_status_iter();
+        let mut ssi = create_swing_tracker();
         let pivot = Pivot::High(90.0);
         // With no previous it should return None
         assert_eq!(ssi.check_lh(&pivot), None);
@@ -811,7 +894,7 @@ mod tests {
 
     #[test]
     fn check_ll_test() {
-        let mut ssi = create_swing_status_iter();
+        let mut ssi = create_swing_tracker();
         let pivot = Pivot::Low(80.0);
         // With no previous it should return None
         assert_eq!(ssi.check_ll(&pivot), None);
@@ -825,7 +908,7 @@ mod tests {
 
     #[test]
     fn check_hl_test() {
-        let mut ssi = create_swing_status_iter();
+        let mut ssi = create_swing_tracker();
         let pivot = Pivot::Low(100.0);
         // With no previous it should return None
         assert_eq!(ssi.check_hl(&pivot), None);
@@ -839,7 +922,7 @@ mod tests {
 
     #[test]
     fn check_hhhl_test() {
-        let mut ssi = create_swing_status_iter();
+        let mut ssi = create_swing_tracker();
         let pivot = Pivot::HighLow {
             high: 110.0,
             low: 95.0,
@@ -865,7 +948,7 @@ mod tests {
 
     #[test]
     fn check_lhhl_test() {
-        let mut ssi = create_swing_status_iter();
+        let mut ssi = create_swing_tracker();
         let pivot = Pivot::HighLow {
             high: 95.0,
             low: 95.0,
@@ -891,7 +974,7 @@ mod tests {
 
     #[test]
     fn check_lhll_test() {
-        let mut ssi = create_swing_status_iter();
+        let mut ssi = create_swing_tracker();
         let pivot = Pivot::HighLow {
             high: 95.0,
             low: 85.0,
@@ -917,7 +1000,7 @@ mod tests {
 
     #[test]
     fn check_hhll_test() {
-        let mut ssi = create_swing_status_iter();
+        let mut ssi = create_swing_tracker();
         // With all previous values at None, it should return None
         let pivot = Pivot::HighLow {
             high: 110.0,
@@ -944,4 +1027,42 @@ mod tests {
         };
         assert_eq!(ssi.check_hhll(&pivot), None);
     }
+
+    #[test]
+    fn tracker_matches_iter() {
+        let pivots = vec![
+            Pivot::High(2.0),
+            Pivot::Low(1.0),
+            Pivot::High(3.0),
+            Pivot::NoChange,
+            Pivot::Low(1.5),
+        ];
+        let expected: Vec<_> = pivots.clone().into_iter().high_low_swing().collect();
+        let mut tracker = SwingTracker::new();
+        let got: Vec<_> = pivots
+            .into_iter()
+            .map(|pivot| tracker.push(pivot))
+            .collect();
+        assert_eq!(expected, got);
+        assert_eq!(Some(1.5), tracker.support());
+        assert_eq!(Some(3.0), tracker.resistance());
+    }
+
+    #[test]
+    fn tracker_bricks() {
+        use crate::{candle::test_data::test_data_1, pivots};
+        let data = test_data_1();
+        let expected: Vec<_> = pivots(&data, 3).unwrap().high_low_swing().collect();
+        let mut tracker = SwingTracker::with_window(3);
+        let got: Vec<_> = data
+            .iter()
+            .map(|candle| tracker.push_brick(candle))
+            .collect();
+        assert_eq!(expected, got);
+        // Without a window it never finds a pivot
+        let mut tracker = SwingTracker::new();
+        assert!(data
+            .iter()
+            .all(|candle| tracker.push_brick(candle).swing_type == SwingType::Hold));
+    }
 }
@@ -0,0 +1,115 @@
+//! Hull moving average of closes. Twice a half period WMA, less a full
+//! period WMA, overshoots by about as much as the full WMA lags; smoothing
+//! that with a WMA over the square root of the period gives a line that
+//! keeps up with the price but is still smooth.
+
+use crate::{Close, Wma};
+
+/// A running Hull moving average of the last `period` values, fed one at a time
+#[derive(Debug, PartialEq, Clone)]
+pub struct Hma {
+    period: usize,
+    half: Wma,
+    full: Wma,
+    smooth: Wma,
+}
+
+impl Hma {
+    pub fn new(period: usize) -> Self {
+        let smooth = ((period as f32).sqrt().round() as usize).max(1);
+        Self {
+            period,
+            half: Wma::new((period / 2).max(1)),
+            full: Wma::new(period),
+            smooth: Wma::new(smooth),
+        }
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    /// Adds a value and returns the average. None until there have been
+    /// `period` values plus enough to smooth them, or always if the period
+    /// is 0
+    pub fn push(&mut self, value: f32) -> Option<f32> {
+        let half = self.half.push(value);
+        let full = self.full.push(value)?;
+        self.smooth.push(2.0 * half? - full)
+    }
+}
+
+/// Turn an Iterator of candles into the Hull moving average of their closes
+pub trait IntoHmaIterator: Iterator + Sized
+where
+    Self::Item: Close,
+{
+    /// The Hull average of the closes. Starts yielding once there are
+    /// `period` closes plus the square root of `period` to smooth over, and
+    /// yields nothing if `period` is 0
+    fn hma(self, period: usize) -> HmaIterator<Self> {
+        HmaIterator {
+            candles: self,
+            hma: Hma::new(period),
+        }
+    }
+}
+
+impl<I> IntoHmaIterator for I
+where
+    I: Iterator,
+    I::Item: Close,
+{
+}
+
+pub struct HmaIterator<I> {
+    candles: I,
+    hma: Hma,
+}
+
+impl<I> Iterator for HmaIterator<I>
+where
+    I: Iterator,
+    I::Item: Close,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.hma.period() == 0 {
+            return None;
+        }
+        loop {
+            let close = self.candles.next()?.close();
+            if let Some(average) = self.hma.push(close) {
+                break Some(average);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candle::test_data::closes;
+
+    #[test]
+    fn no_lag_on_a_trend() {
+        // Half period 2 and smoothing 2, so the first average is at the
+        // fifth close. A WMA lags a straight line; the Hull average doesn't
+        let candles = closes((1..=10).map(|close| close as f32));
+        let got: Vec<f32> = candles.iter().hma(4).collect();
+        assert_eq!(6, got.len());
+        for (expected, got) in (5..=10).zip(got) {
+            assert!((expected as f32 - got).abs() < 0.0001, "{expected} {got}");
+        }
+    }
+
+    #[test]
+    fn short() {
+        let candles = closes([1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(0, candles.iter().hma(4).count());
+        assert_eq!(0, candles.iter().hma(0).count());
+        assert_eq!(4, candles.iter().hma(1).count());
+        assert_eq!(9, Hma::new(9).period());
+    }
+}
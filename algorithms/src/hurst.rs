@@ -0,0 +1,199 @@
+//! The Hurst exponent of the log returns over a rolling window, by rescaled
+//! range analysis. Around 0.5 the returns are a random walk; above it moves
+//! tend to carry on, which suits breakouts, and below it they tend to be
+//! undone, so the price keeps reverting to its mean.
+//!
+//! The window is cut into chunks of the whole window, halves, quarters and
+//! so on down to [`Hurst::MIN_CHUNK`] returns. The exponent is the slope of
+//! the log of the average rescaled range against the log of the chunk size.
+
+use crate::{Close, ReturnKind, RingBuffer};
+
+/// A running Hurst exponent, fed one candle at a time
+#[derive(Debug, PartialEq, Clone)]
+pub struct Hurst {
+    returns: RingBuffer,
+    previous: Option<f32>,
+}
+
+impl Hurst {
+    pub const DEFAULT_PERIOD: usize = 100;
+    /// Fewer returns than this don't have a meaningful range
+    pub const MIN_CHUNK: usize = 8;
+
+    /// Works over the last `period` returns. It needs at least twice
+    /// [`MIN_CHUNK`](Self::MIN_CHUNK) to have two chunk sizes to compare
+    pub fn new(period: usize) -> Self {
+        Self {
+            returns: RingBuffer::new(period),
+            previous: None,
+        }
+    }
+
+    pub fn period(&self) -> usize {
+        self.returns.capacity()
+    }
+
+    /// Adds a candle and returns the exponent over the last `period`
+    /// returns, from 0 to 1. None until there have been `period + 1`
+    /// candles, if the period is too short, or if the price hasn't moved
+    pub fn push<C: Close>(&mut self, candle: &C) -> Option<f32> {
+        let close = candle.close();
+        let previous = self.previous.replace(close)?;
+        self.returns.push(ReturnKind::Log.between(previous, close));
+        if !self.returns.is_full() {
+            return None;
+        }
+        let returns: Vec<f32> = self.returns.iter().copied().collect();
+        hurst_exponent(&returns)
+    }
+}
+
+/// The Hurst exponent of `returns`, clamped to 0 to 1. None if there are
+/// fewer than two chunk sizes with any range in them
+pub fn hurst_exponent(returns: &[f32]) -> Option<f32> {
+    let mut points = Vec::new();
+    let mut size = returns.len();
+    while size >= Hurst::MIN_CHUNK {
+        let ranges: Vec<f32> = returns
+            .chunks_exact(size)
+            .filter_map(rescaled_range)
+            .collect();
+        if !ranges.is_empty() {
+            let mean = ranges.iter().sum::<f32>() / ranges.len() as f32;
+            points.push(((size as f32).ln(), mean.ln()));
+        }
+        size /= 2;
+    }
+    if points.len() < 2 {
+        return None;
+    }
+    let n = points.len() as f32;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f32>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f32>() / n;
+    let (sxy, sxx) = points.iter().fold((0.0, 0.0), |(sxy, sxx), (x, y)| {
+        let dx = x - mean_x;
+        (sxy + dx * (y - mean_y), sxx + dx * dx)
+    });
+    Some((sxy / sxx).clamp(0.0, 1.0))
+}
+
+/// The range of the running total of the deviations from the mean, over the
+/// standard deviation. None if the values are all the same
+fn rescaled_range(values: &[f32]) -> Option<f32> {
+    let n = values.len() as f32;
+    let mean = values.iter().sum::<f32>() / n;
+    let (mut total, mut highest, mut lowest) = (0.0f32, f32::MIN, f32::MAX);
+    for value in values {
+        total += value - mean;
+        highest = highest.max(total);
+        lowest = lowest.min(total);
+    }
+    let std_dev = (values
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f32>()
+        / n)
+        .sqrt();
+    (std_dev > 0.0).then(|| (highest - lowest) / std_dev)
+}
+
+/// Turn an Iterator of candles into their rolling Hurst exponent
+pub trait IntoHurstIterator: Iterator + Sized
+where
+    Self::Item: Close,
+{
+    /// One item per candle: None until `period + 1` candles are in, then
+    /// the exponent of the last `period` returns. See [`Hurst::push`]
+    fn hurst(self, period: usize) -> HurstIterator<Self> {
+        HurstIterator {
+            candles: self,
+            hurst: Hurst::new(period),
+        }
+    }
+}
+
+impl<I> IntoHurstIterator for I
+where
+    I: Iterator,
+    I::Item: Close,
+{
+}
+
+pub struct HurstIterator<I> {
+    candles: I,
+    hurst: Hurst,
+}
+
+impl<I> Iterator for HurstIterator<I>
+where
+    I: Iterator,
+    I::Item: Close,
+{
+    type Item = Option<f32>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let candle = self.candles.next()?;
+        Some(self.hurst.push(&candle))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.candles.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candle::test_data::Candle;
+    use pretty_assertions::assert_eq;
+
+    /// Candles whose closes move by each of `returns`, as log returns
+    fn candles(returns: impl Iterator<Item = f32>) -> Vec<Candle> {
+        let mut close = 100.0f32;
+        let mut candles = vec![Candle::new(close, close, close, close)];
+        for r in returns {
+            close *= r.exp();
+            candles.push(Candle::new(close, close, close, close));
+        }
+        candles
+    }
+
+    /// A return of about 1% that's a little bigger every second and third time
+    fn size(i: usize) -> f32 {
+        0.01 * (1.0 + 0.1 * (i % 3) as f32)
+    }
+
+    #[test]
+    fn mean_reverting() {
+        let returns = (0..64).map(|i| if i % 2 == 0 { size(i) } else { -size(i) });
+        let hurst = candles(returns).iter().hurst(64).last().flatten().unwrap();
+        assert!(hurst < 0.2, "{hurst}");
+    }
+
+    #[test]
+    fn persistent() {
+        // Sixteen up, sixteen down, and so on
+        let returns = (0..64).map(|i| if (i / 16) % 2 == 0 { size(i) } else { -size(i) });
+        let hurst = candles(returns).iter().hurst(64).last().flatten().unwrap();
+        assert!(hurst > 0.8, "{hurst}");
+    }
+
+    #[test]
+    fn warm_up() {
+        let flat = vec![Candle::new(1.0, 1.0, 1.0, 1.0); 40];
+        let got: Vec<_> = flat.iter().hurst(16).collect();
+        // No movement, so no range to measure
+        assert_eq!(vec![None; 40], got);
+        let returns = (0..20).map(|i| if i % 2 == 0 { size(i) } else { -size(i) });
+        let got: Vec<_> = candles(returns).iter().hurst(16).collect();
+        assert!(got[..16].iter().all(Option::is_none));
+        assert!(got[16..].iter().all(Option::is_some));
+        // Too short for two chunk sizes
+        assert_eq!(
+            None,
+            candles((0..20).map(size)).iter().hurst(15).last().flatten()
+        );
+        assert_eq!(100, Hurst::new(Hurst::DEFAULT_PERIOD).period());
+    }
+}
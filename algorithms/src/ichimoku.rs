@@ -0,0 +1,251 @@
+//! Ichimoku Kinko Hyo. Each line is the middle of the range over a period:
+//! the Tenkan (conversion) line over a short one, the Kijun (base) line over
+//! a longer one. The cloud between the Senkou (leading) spans is drawn
+//! ahead of the price, and the Chikou (lagging) span is the close drawn
+//! behind it, both by the displacement.
+//!
+//! Readings come out as they'd be drawn at each candle: the spans are the
+//! ones worked out `displacement` candles ago, and the Chikou is this
+//! candle's close, which belongs `displacement` candles back.
+
+use std::collections::VecDeque;
+
+use crate::{Close, High, Low, RingBuffer};
+
+/// The periods the lines are worked out over
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct IchimokuPeriods {
+    /// The Tenkan line's period
+    pub conversion: usize,
+    /// The Kijun line's period
+    pub base: usize,
+    /// The period of Senkou span B
+    pub span_b: usize,
+    /// How many candles the spans are drawn ahead, and the Chikou behind
+    pub displacement: usize,
+}
+
+impl Default for IchimokuPeriods {
+    /// The 9, 26, 52 and 26 Hosoda used
+    fn default() -> Self {
+        Self {
+            conversion: 9,
+            base: 26,
+            span_b: 52,
+            displacement: 26,
+        }
+    }
+}
+
+/// The lines at one candle. Each is None until there have been enough
+/// candles for it
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct IchimokuReading {
+    pub tenkan: Option<f32>,
+    pub kijun: Option<f32>,
+    /// The middle of the Tenkan and Kijun, from `displacement` candles ago
+    pub senkou_a: Option<f32>,
+    /// The middle of the range over the span B period, from
+    /// `displacement` candles ago
+    pub senkou_b: Option<f32>,
+    /// This candle's close, which is drawn `displacement` candles back
+    pub chikou: f32,
+}
+
+impl IchimokuReading {
+    /// The top and bottom of the cloud, once both spans are in
+    pub fn cloud(&self) -> Option<(f32, f32)> {
+        let (a, b) = (self.senkou_a?, self.senkou_b?);
+        Some((a.max(b), a.min(b)))
+    }
+}
+
+/// The middle of the highest high and lowest low of the last `period` candles
+#[derive(Debug, PartialEq, Clone)]
+struct Midpoint {
+    highs: RingBuffer,
+    lows: RingBuffer,
+}
+
+impl Midpoint {
+    fn new(period: usize) -> Self {
+        Self {
+            highs: RingBuffer::new(period),
+            lows: RingBuffer::new(period),
+        }
+    }
+
+    fn push(&mut self, high: f32, low: f32) -> Option<f32> {
+        self.highs.push(high);
+        self.lows.push(low);
+        if self.highs.is_empty() || !self.highs.is_full() {
+            return None;
+        }
+        let highest = self.highs.iter().copied().fold(f32::MIN, f32::max);
+        let lowest = self.lows.iter().copied().fold(f32::MAX, f32::min);
+        Some((highest + lowest) / 2.0)
+    }
+}
+
+/// A running Ichimoku, fed one candle at a time
+#[derive(Debug, PartialEq, Clone)]
+pub struct Ichimoku {
+    periods: IchimokuPeriods,
+    tenkan: Midpoint,
+    kijun: Midpoint,
+    span_b: Midpoint,
+    /// The spans worked out for the last `displacement` candles, oldest first
+    leading: VecDeque<(Option<f32>, Option<f32>)>,
+}
+
+impl Ichimoku {
+    pub fn new(periods: IchimokuPeriods) -> Self {
+        Self {
+            periods,
+            tenkan: Midpoint::new(periods.conversion),
+            kijun: Midpoint::new(periods.base),
+            span_b: Midpoint::new(periods.span_b),
+            leading: VecDeque::with_capacity(periods.displacement + 1),
+        }
+    }
+
+    pub fn periods(&self) -> IchimokuPeriods {
+        self.periods
+    }
+
+    /// Adds a candle and returns the lines as drawn at it
+    pub fn push<C: High + Low + Close>(&mut self, candle: &C) -> IchimokuReading {
+        let (high, low) = (candle.high(), candle.low());
+        let tenkan = self.tenkan.push(high, low);
+        let kijun = self.kijun.push(high, low);
+        let span_a = tenkan
+            .zip(kijun)
+            .map(|(tenkan, kijun)| (tenkan + kijun) / 2.0);
+        self.leading
+            .push_back((span_a, self.span_b.push(high, low)));
+        let (senkou_a, senkou_b) = if self.leading.len() > self.periods.displacement {
+            self.leading.pop_front().unwrap_or_default()
+        } else {
+            (None, None)
+        };
+        IchimokuReading {
+            tenkan,
+            kijun,
+            senkou_a,
+            senkou_b,
+            chikou: candle.close(),
+        }
+    }
+}
+
+/// Turn an Iterator of candles into their Ichimoku lines
+pub trait IntoIchimokuIterator: Iterator + Sized
+where
+    Self::Item: High + Low + Close,
+{
+    /// One reading per candle. See [`Ichimoku::push`]
+    fn ichimoku(self, periods: IchimokuPeriods) -> IchimokuIterator<Self> {
+        IchimokuIterator {
+            candles: self,
+            ichimoku: Ichimoku::new(periods),
+        }
+    }
+}
+
+impl<I> IntoIchimokuIterator for I
+where
+    I: Iterator,
+    I::Item: High + Low + Close,
+{
+}
+
+pub struct IchimokuIterator<I> {
+    candles: I,
+    ichimoku: Ichimoku,
+}
+
+impl<I> Iterator for IchimokuIterator<I>
+where
+    I: Iterator,
+    I::Item: High + Low + Close,
+{
+    type Item = IchimokuReading;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let candle = self.candles.next()?;
+        Some(self.ichimoku.push(&candle))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.candles.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candle::test_data::Candle;
+    use pretty_assertions::assert_eq;
+
+    const PERIODS: IchimokuPeriods = IchimokuPeriods {
+        conversion: 2,
+        base: 3,
+        span_b: 4,
+        displacement: 2,
+    };
+
+    /// Candles one wide, each one higher than the last
+    fn rising(count: usize) -> Vec<Candle> {
+        (0..count)
+            .map(|i| {
+                let low = i as f32;
+                Candle::new(low + 1.0, low, low, low + 0.5)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lines() {
+        let readings: Vec<_> = rising(7).iter().ichimoku(PERIODS).collect();
+        assert_eq!(7, readings.len());
+        let tenkans: Vec<_> = readings.iter().map(|reading| reading.tenkan).collect();
+        assert_eq!(
+            vec![
+                None,
+                Some(1.0),
+                Some(2.0),
+                Some(3.0),
+                Some(4.0),
+                Some(5.0),
+                Some(6.0)
+            ],
+            tenkans
+        );
+        assert_eq!(None, readings[1].kijun);
+        assert_eq!(Some(1.5), readings[2].kijun);
+        // Span A is first worked out at the third candle, and drawn two later
+        let span_a: Vec<_> = readings.iter().map(|reading| reading.senkou_a).collect();
+        assert_eq!(
+            vec![None, None, None, None, Some(1.75), Some(2.75), Some(3.75)],
+            span_a
+        );
+        // Span B is first worked out at the fourth candle
+        assert_eq!(None, readings[4].senkou_b);
+        assert_eq!(Some(2.0), readings[5].senkou_b);
+        assert_eq!(Some((2.75, 2.0)), readings[5].cloud());
+        assert_eq!(None, readings[4].cloud());
+        assert_eq!(6.5, readings[6].chikou);
+    }
+
+    #[test]
+    fn no_displacement() {
+        let periods = IchimokuPeriods {
+            displacement: 0,
+            ..PERIODS
+        };
+        let reading = rising(4).iter().ichimoku(periods).last().unwrap();
+        assert_eq!(Some(2.75), reading.senkou_a);
+        assert_eq!(Some(2.0), reading.senkou_b);
+        assert_eq!(26, Ichimoku::new(IchimokuPeriods::default()).periods().base);
+    }
+}
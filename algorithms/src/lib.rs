@@ -1,15 +1,109 @@
+mod adx;
+mod anatomy;
 mod atr;
+mod bollinger;
 mod candle;
+mod cumulative_delta;
+mod distance;
+mod ema;
+mod envelope;
+mod error;
+mod excursion;
+mod fill_model;
 mod higher_high_lower_low;
+mod hma;
+mod hurst;
+mod ichimoku;
+mod linear_regression;
+mod macd;
+mod obv;
+mod order_flow;
+mod pairs;
+mod parabolic_sar;
 mod pivot_high_low;
+mod pivot_zones;
 mod renko;
+mod returns;
+mod ring_buffer;
+mod roc;
+mod rolling;
+mod round_numbers;
+mod rsi;
+mod seasonality;
+mod series;
+mod signal_stats;
+mod sma;
+mod snapshot;
+mod stop_placement;
 mod support_resistance;
+mod swing_failure;
+mod swing_legs;
+mod timeframe;
+mod trade_plan;
 mod true_range;
+mod volatility_regime;
+mod watermark;
+mod williams_r;
+mod wma;
 
-pub use atr::Atr;
-pub use candle::{Close, High, Low, Open};
-pub use higher_high_lower_low::{IntoSwingStatusIter, SwingStatus};
-pub use pivot_high_low::{pivots, Pivot};
-pub use renko::{IntoRenkoIterator, RenkoCandle, RenkoDirection};
+pub use adx::{Adx, AdxIterator, AdxReading, IntoAdxIterator};
+pub use anatomy::{Anatomy, AnatomyIter, CandleAnatomy, IntoAnatomyIter};
+pub use atr::{Atr, AtrIter, EmaIter, IntoAtrIter, IntoEmaIter, RollingAtr, RollingAtrIter};
+pub use bollinger::{BollingerBand, BollingerBands, BollingerIterator, IntoBollingerIterator};
+pub use candle::{Close, High, Low, Open, Volume};
+pub use cumulative_delta::{CumulativeDeltaIter, IntoCumulativeDeltaIter};
+pub use distance::{atr_percentile, distance_in_atr, distance_in_pips, pip_size};
+pub use ema::{Ema, EmaIterator, EmaWarmup, IntoEmaIterator};
+pub use envelope::{Envelope, EnvelopeIterator, Envelopes, IntoEnvelopeIterator};
+pub use error::Error;
+pub use excursion::{
+    Excursion, ExcursionIter, ExcursionTracker, IntoExcursionIter, TradeDirection,
+};
+pub use fill_model::{Fill, FillModel};
+pub use higher_high_lower_low::{IntoSwingStatusIter, SwingStatus, SwingTracker, SwingType};
+pub use hma::{Hma, HmaIterator, IntoHmaIterator};
+pub use hurst::{hurst_exponent, Hurst, HurstIterator, IntoHurstIterator};
+pub use ichimoku::{
+    Ichimoku, IchimokuIterator, IchimokuPeriods, IchimokuReading, IntoIchimokuIterator,
+};
+pub use linear_regression::{IntoLinearRegression, LinearRegression, LinearRegressionIter};
+pub use macd::{IntoMacdIterator, Macd, MacdIterator, MacdPeriods, MacdReading};
+pub use obv::{IntoObvIterator, Obv, ObvIterator};
+pub use order_flow::{close_location, IntoOrderFlowImbalanceIter, OrderFlowImbalanceIter};
+pub use pairs::{hedge_ratio, spread, spread_z_scores, MeanReversion, PairSignal, SpreadKind};
+pub use parabolic_sar::{
+    IntoParabolicSarIterator, ParabolicSar, ParabolicSarIterator, SarAcceleration, SarReading,
+};
+pub use pivot_high_low::{
+    adaptive_pivots, confirmed_pivots, pivots, AdaptiveWindow, ConfirmedPivot, Pivot,
+    PivotConfirmation,
+};
+pub use pivot_zones::{pivot_zones, PivotZone, TimeframeLevels};
+pub use renko::{IntoRenkoIterator, RenkoCandle, RenkoDirection, RenkoReversal};
+pub use returns::{CumulativeReturnIterator, IntoReturnsIterator, ReturnKind, ReturnsIterator};
+pub use ring_buffer::RingBuffer;
+pub use roc::{IntoRocIterator, Roc, RocIterator};
+pub use rolling::{IntoRollingStats, PercentileRankIter, ZScoreIter};
+pub use round_numbers::{Confluence, RoundNumbers};
+pub use rsi::{IntoRsiIterator, Rsi, RsiIterator};
+pub use seasonality::{SeasonalityBucket, SeasonalityProfile};
+pub use series::Series;
+pub use signal_stats::{stats_by_setup, SignalOutcome, SignalStats};
+pub use sma::{IntoSmaIterator, Sma, SmaIterator};
+pub use snapshot::{AnalysisSnapshot, SnapshotCandle};
+pub use stop_placement::StopPlacement;
 pub use support_resistance::{IntoSupportAndResistance, SupportAndResistance};
+pub use swing_failure::{swing_failures, SwingFailure};
+pub use swing_legs::{IntoSwingLegIter, SwingLeg, SwingLegIter, SwingLegReading, SwingLegs};
+pub use timeframe::{Periods, Timeframe};
+pub use trade_plan::{TradeOutcome, TradePlan};
 pub use true_range::{TRCandle, TRIter, TrueRange};
+pub use volatility_regime::{
+    IntoVolatilityRegimeIter, RegimeChange, RegimeReading, RegimeThresholds, VolatilityRegime,
+    VolatilityRegimeIter,
+};
+pub use watermark::{
+    IntoWatermarkIter, RollingWatermark, UpdateWatermark, Watermark, WatermarkIter, WatermarkSide,
+};
+pub use williams_r::{IntoWilliamsRIterator, WilliamsR, WilliamsRIterator};
+pub use wma::{IntoWmaIterator, Wma, WmaIterator};
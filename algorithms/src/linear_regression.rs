@@ -0,0 +1,177 @@
+//! Rolling least squares linear regression over a window of prices.
+//!
+//! Gives you the slope (how steep the trend is, in price per candle) and
+//! a channel either side of the regression line that is `k` standard
+//! errors wide, for spotting regression channel breakouts.
+
+use std::collections::VecDeque;
+
+/// The regression line fitted to one window of prices
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct LinearRegression {
+    /// Change in price per candle
+    pub slope: f32,
+    /// The value of the line at the oldest price in the window
+    pub intercept: f32,
+    /// The value of the line at the newest price in the window
+    pub value: f32,
+    /// The standard error of the prices around the line
+    pub std_error: f32,
+    /// `value` + k * `std_error`
+    pub upper: f32,
+    /// `value` - k * `std_error`
+    pub lower: f32,
+}
+
+pub struct LinearRegressionIter<I> {
+    prices: I,
+    // Number of prices to fit the line to
+    period: usize,
+    // Width of the channel in standard errors
+    k: f32,
+    // The last `period` prices
+    window: VecDeque<f32>,
+}
+
+impl<I> LinearRegressionIter<I>
+where
+    I: Iterator<Item = f32>,
+{
+    fn new(prices: I, period: usize, k: f32) -> Self {
+        Self {
+            prices,
+            period,
+            k,
+            window: VecDeque::with_capacity(period),
+        }
+    }
+
+    /// Fits a line to the current window. x is the index within the window
+    fn fit(&self) -> LinearRegression {
+        let n = self.window.len() as f32;
+        let mean_x = (n - 1.0) / 2.0;
+        let mean_y = self.window.iter().sum::<f32>() / n;
+        let (sxy, sxx) = self
+            .window
+            .iter()
+            .enumerate()
+            .fold((0.0, 0.0), |(sxy, sxx), (x, y)| {
+                let dx = x as f32 - mean_x;
+                (sxy + dx * (y - mean_y), sxx + dx * dx)
+            });
+        let slope = sxy / sxx;
+        let intercept = mean_y - slope * mean_x;
+        let sse: f32 = self
+            .window
+            .iter()
+            .enumerate()
+            .map(|(x, y)| (y - (intercept + slope * x as f32)).powi(2))
+            .sum();
+        // Two degrees of freedom are used up by the slope and intercept
+        let std_error = if self.window.len() > 2 {
+            (sse / (n - 2.0)).sqrt()
+        } else {
+            0.0
+        };
+        let value = intercept + slope * (n - 1.0);
+        LinearRegression {
+            slope,
+            intercept,
+            value,
+            std_error,
+            upper: value + self.k * std_error,
+            lower: value - self.k * std_error,
+        }
+    }
+}
+
+impl<I> Iterator for LinearRegressionIter<I>
+where
+    I: Iterator<Item = f32>,
+{
+    type Item = LinearRegression;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // You need at least two points to draw a line
+        if self.period < 2 {
+            return None;
+        }
+        loop {
+            let price = self.prices.next()?;
+            if self.window.len() == self.period {
+                self.window.pop_front();
+            }
+            self.window.push_back(price);
+            if self.window.len() == self.period {
+                break Some(self.fit());
+            }
+        }
+    }
+}
+
+pub trait IntoLinearRegression<I> {
+    /// Fits a line to each `period` prices, yielding one regression per
+    /// price once the first window is full. The channel is `k` standard
+    /// errors either side of the line. A `period` under 2 yields nothing.
+    fn linear_regression(self, period: usize, k: f32) -> LinearRegressionIter<I>;
+}
+
+impl<I> IntoLinearRegression<I> for I
+where
+    I: Iterator<Item = f32>,
+{
+    fn linear_regression(self, period: usize, k: f32) -> LinearRegressionIter<Self> {
+        LinearRegressionIter::new(self, period, k)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 0.0001
+    }
+
+    #[test]
+    fn straight_line() {
+        let prices = (0..6).map(|x| 10.0 + 2.0 * x as f32);
+        let got: Vec<_> = prices.linear_regression(3, 2.0).collect();
+        assert_eq!(4, got.len());
+        for (n, regression) in got.iter().enumerate() {
+            assert_eq!(2.0, regression.slope);
+            assert_eq!(10.0 + 2.0 * n as f32, regression.intercept);
+            assert_eq!(14.0 + 2.0 * n as f32, regression.value);
+            assert_eq!(0.0, regression.std_error);
+            assert_eq!(regression.value, regression.upper);
+            assert_eq!(regression.value, regression.lower);
+        }
+    }
+
+    #[test]
+    fn channel() {
+        let prices = vec![1.0, 2.0, 4.0];
+        let got: Vec<_> = prices.into_iter().linear_regression(3, 2.0).collect();
+        assert_eq!(1, got.len());
+        let got = got[0];
+        assert!(close(got.slope, 1.5), "{got:?}");
+        assert!(close(got.intercept, 0.833333), "{got:?}");
+        assert!(close(got.value, 3.833333), "{got:?}");
+        assert!(close(got.std_error, 0.408248), "{got:?}");
+        assert!(close(got.upper, 4.64983), "{got:?}");
+        assert!(close(got.lower, 3.016837), "{got:?}");
+    }
+
+    #[test]
+    fn not_enough_prices() {
+        let prices = vec![1.0, 2.0];
+        assert_eq!(None, prices.into_iter().linear_regression(3, 2.0).next());
+    }
+
+    #[test]
+    fn period_too_small() {
+        let prices = vec![1.0, 2.0, 3.0];
+        assert_eq!(None, prices.into_iter().linear_regression(1, 2.0).next());
+    }
+}
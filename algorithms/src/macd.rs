@@ -0,0 +1,190 @@
+//! Moving average convergence divergence of closes: how far a fast EMA is
+//! above a slow one, and an EMA of that gap to compare it against. The
+//! histogram going from negative to positive is a sign of upward momentum.
+
+use crate::{Close, Ema, EmaWarmup};
+
+/// The periods of the three EMAs
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct MacdPeriods {
+    pub fast: usize,
+    pub slow: usize,
+    /// The EMA of the MACD line
+    pub signal: usize,
+}
+
+impl Default for MacdPeriods {
+    /// The usual 12, 26 and 9
+    fn default() -> Self {
+        Self {
+            fast: 12,
+            slow: 26,
+            signal: 9,
+        }
+    }
+}
+
+/// The MACD as of one close
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct MacdReading {
+    /// The fast EMA minus the slow one
+    pub macd: f32,
+    /// The EMA of the MACD line
+    pub signal: f32,
+    /// The MACD line minus the signal line
+    pub histogram: f32,
+}
+
+/// A running MACD, fed one close at a time
+#[derive(Debug, PartialEq, Clone)]
+pub struct Macd {
+    fast: Ema,
+    slow: Ema,
+    signal: Ema,
+}
+
+impl Macd {
+    pub fn new(periods: MacdPeriods) -> Self {
+        let ema = |period| Ema::new(period, EmaWarmup::SimpleAverage);
+        Self {
+            fast: ema(periods.fast),
+            slow: ema(periods.slow),
+            signal: ema(periods.signal),
+        }
+    }
+
+    /// Adds a close and returns the MACD. None until the slow EMA and then
+    /// the signal line have warmed up, ie. for the first
+    /// `max(fast, slow) + signal - 2` closes. Always None if any period is 0
+    pub fn push(&mut self, close: f32) -> Option<MacdReading> {
+        let fast = self.fast.push(close);
+        let slow = self.slow.push(close);
+        let macd = fast? - slow?;
+        let signal = self.signal.push(macd)?;
+        Some(MacdReading {
+            macd,
+            signal,
+            histogram: macd - signal,
+        })
+    }
+
+    /// True if a period is 0, so it'll never yield anything
+    fn is_empty(&self) -> bool {
+        [&self.fast, &self.slow, &self.signal]
+            .iter()
+            .any(|ema| ema.period() == 0)
+    }
+}
+
+/// Turn an Iterator of candles into the MACD of their closes
+pub trait IntoMacdIterator: Iterator + Sized
+where
+    Self::Item: Close,
+{
+    /// One reading per close once the EMAs have warmed up. See [`Macd::push`]
+    fn macd(self, periods: MacdPeriods) -> MacdIterator<Self> {
+        MacdIterator {
+            candles: self,
+            macd: Macd::new(periods),
+        }
+    }
+}
+
+impl<I> IntoMacdIterator for I
+where
+    I: Iterator,
+    I::Item: Close,
+{
+}
+
+pub struct MacdIterator<I> {
+    candles: I,
+    macd: Macd,
+}
+
+impl<I> Iterator for MacdIterator<I>
+where
+    I: Iterator,
+    I::Item: Close,
+{
+    type Item = MacdReading;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.macd.is_empty() {
+            return None;
+        }
+        loop {
+            let close = self.candles.next()?.close();
+            if let Some(reading) = self.macd.push(close) {
+                break Some(reading);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        candle::test_data::{closes, Candle},
+        IntoEmaIterator,
+    };
+    use pretty_assertions::assert_eq;
+
+    const PERIODS: MacdPeriods = MacdPeriods {
+        fast: 2,
+        slow: 3,
+        signal: 2,
+    };
+
+    #[test]
+    fn lines() {
+        let candles = closes([1.0, 2.0, 4.0, 3.0, 5.0, 6.0]);
+        let readings: Vec<_> = candles.iter().macd(PERIODS).collect();
+        // The slow EMA starts on the 3rd close, the signal on the 4th
+        assert_eq!(3, readings.len());
+        let fast: Vec<f32> = candles.iter().ema(2).skip(1).collect();
+        let slow: Vec<f32> = candles.iter().ema(3).collect();
+        let macd: Vec<f32> = fast.iter().zip(&slow).map(|(f, s)| f - s).collect();
+        for (reading, macd) in readings.iter().zip(&macd[1..]) {
+            assert!((reading.macd - macd).abs() < 0.0001, "{reading:?} {macd}");
+            assert_eq!(reading.macd - reading.signal, reading.histogram);
+        }
+        // The signal line starts at the average of the first two MACDs
+        assert!((readings[0].signal - (macd[0] + macd[1]) / 2.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn momentum() {
+        // Falling then rising; the MACD goes from negative to positive, and
+        // the histogram turns first
+        let candles = closes((0..20).map(|i| (i as f32 - 10.0).abs()));
+        let readings: Vec<_> = candles.iter().macd(PERIODS).collect();
+        assert!(readings[0].macd < 0.0);
+        assert!(readings.last().unwrap().macd > 0.0);
+        assert!(readings
+            .iter()
+            .any(|reading| reading.macd < 0.0 && reading.histogram > 0.0));
+    }
+
+    #[test]
+    fn not_enough_closes() {
+        let candles = closes([1.0, 2.0, 3.0]);
+        assert_eq!(None, candles.iter().macd(PERIODS).next());
+        let zero = MacdPeriods {
+            signal: 0,
+            ..PERIODS
+        };
+        assert_eq!(None, closes([1.0; 10]).iter().macd(zero).next());
+        // Doesn't wait forever for a reading that can't come
+        assert_eq!(None, std::iter::repeat(Candle::default()).macd(zero).next());
+        assert_eq!(
+            MacdPeriods {
+                fast: 12,
+                slow: 26,
+                signal: 9
+            },
+            MacdPeriods::default()
+        );
+    }
+}
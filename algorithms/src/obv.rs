@@ -0,0 +1,134 @@
+//! On-Balance Volume: a running total of volume, added when the close is
+//! up on the last close and taken away when it's down. Volume running
+//! ahead of price is read as buying or selling before the move shows up.
+
+use crate::{Close, Volume};
+
+/// A running OBV, fed one candle at a time
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Obv {
+    previous_close: Option<f32>,
+    total: f32,
+}
+
+impl Obv {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a candle and returns the total so far. The first candle has
+    /// nothing to compare with so it starts the total at 0, as does a close
+    /// that's the same as the last one
+    pub fn push<C: Close + Volume>(&mut self, candle: &C) -> f32 {
+        let close = candle.close();
+        if let Some(previous) = self.previous_close {
+            if close > previous {
+                self.total += candle.volume();
+            } else if close < previous {
+                self.total -= candle.volume();
+            }
+        }
+        self.previous_close = Some(close);
+        self.total
+    }
+}
+
+/// Turn an Iterator of candles into their On-Balance Volume
+pub trait IntoObvIterator: Iterator + Sized
+where
+    Self::Item: Close + Volume,
+{
+    /// One item per candle, starting at 0
+    fn obv(self) -> ObvIterator<Self> {
+        ObvIterator {
+            candles: self,
+            obv: Obv::new(),
+        }
+    }
+}
+
+impl<I> IntoObvIterator for I
+where
+    I: Iterator,
+    I::Item: Close + Volume,
+{
+}
+
+pub struct ObvIterator<I> {
+    candles: I,
+    obv: Obv,
+}
+
+impl<I> Iterator for ObvIterator<I>
+where
+    I: Iterator,
+    I::Item: Close + Volume,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let candle = self.candles.next()?;
+        Some(self.obv.push(&candle))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.candles.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    struct Candle {
+        close: f32,
+        volume: f32,
+    }
+
+    impl Close for Candle {
+        fn close(&self) -> f32 {
+            self.close
+        }
+    }
+
+    impl Volume for Candle {
+        fn volume(&self) -> f32 {
+            self.volume
+        }
+    }
+
+    fn candles(closes_and_volumes: &[(f32, f32)]) -> Vec<Candle> {
+        closes_and_volumes
+            .iter()
+            .map(|&(close, volume)| Candle { close, volume })
+            .collect()
+    }
+
+    #[test]
+    fn up_down_and_flat() {
+        let candles = candles(&[
+            (10.0, 100.0),
+            // Up
+            (11.0, 50.0),
+            // Down
+            (10.5, 30.0),
+            // Flat
+            (10.5, 80.0),
+            (12.0, 20.0),
+        ]);
+        assert_eq!(
+            vec![0.0, 50.0, 20.0, 20.0, 40.0],
+            candles.iter().obv().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn starts_at_zero() {
+        assert_eq!(
+            vec![0.0],
+            candles(&[(5.0, 1000.0)]).iter().obv().collect::<Vec<_>>()
+        );
+        assert_eq!(0, Vec::<Candle>::new().iter().obv().count());
+    }
+}
@@ -0,0 +1,121 @@
+//! A proxy for order-flow imbalance when there's no real order book or tick
+//! data.
+//!
+//! Where a candle closes within its range says who won it: a close on the
+//! high means buyers were in control, a close on the low means sellers
+//! were. Weighting that by volume and averaging over a window gives the
+//! buying or selling pressure, from -1 (all selling) to 1 (all buying).
+
+use std::collections::VecDeque;
+
+use crate::candle::{Close, High, Low};
+
+/// Where `candle` closed within its range, from -1 (on the low) to 1 (on
+/// the high). 0 for a candle with no range
+pub fn close_location(candle: &(impl High + Low + Close)) -> f32 {
+    let range = candle.high() - candle.low();
+    if range > 0.0 {
+        ((candle.close() - candle.low()) - (candle.high() - candle.close())) / range
+    } else {
+        0.0
+    }
+}
+
+/// Turn an Iterator of `(candle, volume)` pairs into an Iterator of the
+/// buying or selling pressure over the last `period` of them
+pub trait IntoOrderFlowImbalanceIter<C>: Iterator<Item = (C, f32)> + Sized
+where
+    C: High + Low + Close,
+{
+    /// The volume weighted average [`close_location`] of the last `period`
+    /// candles, from -1 to 1. 0 if they had no volume. Starts yielding once
+    /// there have been `period` candles; yields nothing for a `period` of 0
+    fn order_flow_imbalance(self, period: usize) -> OrderFlowImbalanceIter<Self> {
+        OrderFlowImbalanceIter {
+            iter: self,
+            period,
+            window: VecDeque::with_capacity(period),
+        }
+    }
+}
+
+impl<I, C> IntoOrderFlowImbalanceIter<C> for I
+where
+    I: Iterator<Item = (C, f32)>,
+    C: High + Low + Close,
+{
+}
+
+pub struct OrderFlowImbalanceIter<I> {
+    iter: I,
+    period: usize,
+    // (close location * volume, volume) for the last `period` candles
+    window: VecDeque<(f32, f32)>,
+}
+
+impl<I, C> Iterator for OrderFlowImbalanceIter<I>
+where
+    I: Iterator<Item = (C, f32)>,
+    C: High + Low + Close,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.period == 0 {
+            return None;
+        }
+        loop {
+            let (candle, volume) = self.iter.next()?;
+            if self.window.len() == self.period {
+                self.window.pop_front();
+            }
+            self.window
+                .push_back((close_location(&candle) * volume, volume));
+            if self.window.len() == self.period {
+                let (pressure, volume) = self
+                    .window
+                    .iter()
+                    .fold((0.0, 0.0), |(p, v), (pressure, volume)| {
+                        (p + pressure, v + volume)
+                    });
+                break Some(if volume > 0.0 { pressure / volume } else { 0.0 });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candle::test_data::Candle;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn location() {
+        assert_eq!(1.0, close_location(&Candle::new(12.0, 10.0, 10.0, 12.0)));
+        assert_eq!(-1.0, close_location(&Candle::new(12.0, 10.0, 12.0, 10.0)));
+        assert_eq!(0.0, close_location(&Candle::new(12.0, 10.0, 10.0, 11.0)));
+        assert_eq!(0.0, close_location(&Candle::new(10.0, 10.0, 10.0, 10.0)));
+    }
+
+    #[test]
+    fn imbalance() {
+        let input = [
+            (Candle::new(12.0, 10.0, 10.0, 12.0), 300.0), // on the high
+            (Candle::new(12.0, 10.0, 12.0, 10.0), 100.0), // on the low
+            (Candle::new(12.0, 10.0, 10.0, 11.5), 0.0),   // no volume
+            (Candle::new(12.0, 10.0, 12.0, 10.0), 100.0), // on the low
+        ];
+        let got: Vec<_> = input.into_iter().order_flow_imbalance(2).collect();
+        // (300 - 100) / 400, then just the selling, then just the selling again
+        assert_eq!(vec![0.5, -1.0, -1.0], got);
+    }
+
+    #[test]
+    fn no_volume() {
+        let input = [(Candle::new(12.0, 10.0, 10.0, 12.0), 0.0)];
+        let got: Vec<_> = input.clone().into_iter().order_flow_imbalance(1).collect();
+        assert_eq!(vec![0.0], got);
+        assert_eq!(None, input.into_iter().order_flow_imbalance(0).next());
+    }
+}
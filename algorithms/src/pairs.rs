@@ -0,0 +1,188 @@
+//! Pairs trading: two correlated instruments (eg. EUR_USD and GBP_USD)
+//! drift apart and come back together. The spread between them is traded
+//! rather than either one: when it's stretched, sell the rich leg and buy
+//! the cheap one, and close both when the spread gets back to normal.
+//!
+//! Everything works on [`Series`] so the legs are lined up by time.
+
+use crate::{IntoRollingStats, Series};
+
+/// How the two legs are combined into one number
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SpreadKind {
+    /// `a - hedge_ratio * b`. See [`hedge_ratio`]
+    Difference { hedge_ratio: f32 },
+    /// `a / b`
+    Ratio,
+}
+
+impl SpreadKind {
+    fn apply(&self, a: f32, b: f32) -> f32 {
+        match *self {
+            SpreadKind::Difference { hedge_ratio } => a - hedge_ratio * b,
+            SpreadKind::Ratio => a / b,
+        }
+    }
+}
+
+/// The spread between `a` and `b` at every time either has a price,
+/// forward filling the other. Starts once both have prices. Times where a
+/// ratio would divide by zero are left out
+pub fn spread(a: &Series<f32>, b: &Series<f32>, kind: SpreadKind) -> Series<f32> {
+    a.join(b)
+        .iter()
+        .map(|(time, (a, b))| (time, kind.apply(*a, *b)))
+        .filter(|(_, spread)| spread.is_finite())
+        .collect()
+}
+
+/// How many units of `b` hedge one unit of `a`; the least squares slope of
+/// `a` against `b`. None if there are fewer than two prices or `b` never moves
+pub fn hedge_ratio(a: &Series<f32>, b: &Series<f32>) -> Option<f32> {
+    let pairs: Vec<(f32, f32)> = a.align(b).values().copied().collect();
+    if pairs.len() < 2 {
+        return None;
+    }
+    let n = pairs.len() as f32;
+    let mean_a = pairs.iter().map(|(a, _)| a).sum::<f32>() / n;
+    let mean_b = pairs.iter().map(|(_, b)| b).sum::<f32>() / n;
+    let (sab, sbb) = pairs.iter().fold((0.0, 0.0), |(sab, sbb), (a, b)| {
+        let db = b - mean_b;
+        (sab + db * (a - mean_a), sbb + db * db)
+    });
+    (sbb > 0.0).then(|| sab / sbb)
+}
+
+/// The rolling z-score of the spread over `period` values. The first
+/// `period - 1` times are dropped while it warms up
+pub fn spread_z_scores(spread: &Series<f32>, period: usize) -> Series<f32> {
+    let z_scores = spread.values().copied().rolling_z_score(period);
+    spread
+        .times()
+        .skip(period.saturating_sub(1))
+        .zip(z_scores)
+        .collect()
+}
+
+/// What to do with the pair
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PairSignal {
+    /// The spread is unusually low: buy `a` and sell `b`
+    EnterLong,
+    /// The spread is unusually high: sell `a` and buy `b`
+    EnterShort,
+    /// The spread is back to normal: close both legs
+    Exit,
+}
+
+/// Trades the spread back to its mean. Enter when the z-score gets beyond
+/// `entry` either side, exit once it's back within `exit`
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct MeanReversion {
+    pub entry: f32,
+    pub exit: f32,
+}
+
+impl Default for MeanReversion {
+    /// Enter at 2 standard deviations, exit at half of one
+    fn default() -> Self {
+        Self {
+            entry: 2.0,
+            exit: 0.5,
+        }
+    }
+}
+
+impl MeanReversion {
+    /// The signals from a series of z-scores, only at the times something
+    /// should be done. Holds at most one position at a time
+    pub fn signals(&self, z_scores: &Series<f32>) -> Series<PairSignal> {
+        let mut position = None;
+        z_scores
+            .iter()
+            .filter_map(|(time, &z)| {
+                let signal = match position {
+                    None if z >= self.entry => PairSignal::EnterShort,
+                    None if z <= -self.entry => PairSignal::EnterLong,
+                    Some(_) if z.abs() <= self.exit => PairSignal::Exit,
+                    _ => return None,
+                };
+                position = match signal {
+                    PairSignal::Exit => None,
+                    entry => Some(entry),
+                };
+                Some((time, signal))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, TimeZone, Utc};
+    use pretty_assertions::assert_eq;
+
+    fn time(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2023, 3, 1, hour, 0, 0).unwrap()
+    }
+
+    fn series(values: &[f32]) -> Series<f32> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(hour, value)| (time(hour as u32), *value))
+            .collect()
+    }
+
+    #[test]
+    fn spread_and_ratio() {
+        let a = series(&[2.0, 4.0, 6.0]);
+        let b = series(&[1.0, 2.0, 2.0]);
+        let got: Vec<_> = spread(&a, &b, SpreadKind::Ratio)
+            .values()
+            .copied()
+            .collect();
+        assert_eq!(vec![2.0, 2.0, 3.0], got);
+        let difference = SpreadKind::Difference { hedge_ratio: 2.0 };
+        let got: Vec<_> = spread(&a, &b, difference).values().copied().collect();
+        assert_eq!(vec![0.0, 0.0, 2.0], got);
+        // Ratios to zero are skipped
+        let zero = series(&[0.0, 2.0, 2.0]);
+        assert_eq!(2, spread(&a, &zero, SpreadKind::Ratio).len());
+    }
+
+    #[test]
+    fn hedge() {
+        // a moves twice as much as b
+        let a = series(&[10.0, 12.0, 11.0, 16.0]);
+        let b = series(&[5.0, 6.0, 5.5, 8.0]);
+        assert_eq!(Some(2.0), hedge_ratio(&a, &b));
+        assert_eq!(None, hedge_ratio(&a, &series(&[1.0; 4])));
+        assert_eq!(None, hedge_ratio(&series(&[1.0]), &series(&[1.0])));
+    }
+
+    #[test]
+    fn z_scores_keep_times() {
+        let spread = series(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+        let got = spread_z_scores(&spread, 8);
+        assert_eq!(vec![(time(7), &2.0)], got.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn mean_reversion() {
+        let z = series(&[0.0, 2.5, 3.0, 1.0, 0.2, -2.1, -0.4, 0.0]);
+        let got: Vec<_> = MeanReversion::default()
+            .signals(&z)
+            .iter()
+            .map(|(time, signal)| (time, *signal))
+            .collect();
+        let expected = vec![
+            (time(1), PairSignal::EnterShort),
+            (time(4), PairSignal::Exit),
+            (time(5), PairSignal::EnterLong),
+            (time(6), PairSignal::Exit),
+        ];
+        assert_eq!(expected, got);
+    }
+}
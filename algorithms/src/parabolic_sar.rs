@@ -0,0 +1,296 @@
+//! Wilder's parabolic stop and reverse. The SAR trails below the lows in an
+//! uptrend and above the highs in a downtrend, closing in on the price
+//! faster the longer the trend makes new extremes. When the price crosses
+//! it the trend flips, and the SAR jumps to the other side, which makes it
+//! a handy trailing stop.
+
+use crate::{High, Low, TradeDirection};
+
+/// How fast the SAR closes in on the price
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SarAcceleration {
+    /// The acceleration factor at the start of each trend
+    pub start: f32,
+    /// How much the factor goes up each time the trend makes a new extreme
+    pub step: f32,
+    /// The most the factor can go up to
+    pub max: f32,
+}
+
+impl Default for SarAcceleration {
+    /// The 0.02, 0.02 and 0.2 Wilder used
+    fn default() -> Self {
+        Self {
+            start: 0.02,
+            step: 0.02,
+            max: 0.2,
+        }
+    }
+}
+
+/// The SAR as of one candle
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SarReading {
+    /// The stop for this candle: below it in a long trend, above it in a short one
+    pub sar: f32,
+    /// Which way the trend is going
+    pub direction: TradeDirection,
+}
+
+/// The trend being followed
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Trend {
+    direction: TradeDirection,
+    sar: f32,
+    /// The highest high of a long trend, or lowest low of a short one
+    extreme: f32,
+    factor: f32,
+}
+
+/// A running parabolic SAR, fed one candle at a time
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParabolicSar {
+    acceleration: SarAcceleration,
+    /// The high and low of the last two candles, newest first
+    previous: Option<(f32, f32)>,
+    before_previous: Option<(f32, f32)>,
+    trend: Option<Trend>,
+}
+
+impl ParabolicSar {
+    pub fn new(acceleration: SarAcceleration) -> Self {
+        Self {
+            acceleration,
+            previous: None,
+            before_previous: None,
+            trend: None,
+        }
+    }
+
+    /// Adds a candle and returns the SAR for it. None for the first candle,
+    /// which there's nothing to compare to. The second candle starts a long
+    /// trend if its high is higher than the first's, otherwise a short one
+    pub fn push<C: High + Low>(&mut self, candle: &C) -> Option<SarReading> {
+        let (high, low) = (candle.high(), candle.low());
+        let previous = self.previous.replace((high, low));
+        let before_previous = std::mem::replace(&mut self.before_previous, previous);
+        let (previous_high, previous_low) = previous?;
+        let trend = match self.trend {
+            None if high > previous_high => Trend {
+                direction: TradeDirection::Long,
+                sar: previous_low,
+                extreme: high.max(previous_high),
+                factor: self.acceleration.start,
+            },
+            None => Trend {
+                direction: TradeDirection::Short,
+                sar: previous_high,
+                extreme: low.min(previous_low),
+                factor: self.acceleration.start,
+            },
+            Some(trend) => {
+                let (before_high, before_low) =
+                    before_previous.unwrap_or((previous_high, previous_low));
+                self.follow(
+                    trend,
+                    high,
+                    low,
+                    previous_high.max(before_high),
+                    previous_low.min(before_low),
+                )
+            }
+        };
+        self.trend = Some(trend);
+        Some(SarReading {
+            sar: trend.sar,
+            direction: trend.direction,
+        })
+    }
+
+    /// Moves `trend` on to a candle with `high` and `low`, where
+    /// `recent_high` and `recent_low` are the extremes of the two candles
+    /// before it, which the SAR can't go past
+    fn follow(
+        &self,
+        trend: Trend,
+        high: f32,
+        low: f32,
+        recent_high: f32,
+        recent_low: f32,
+    ) -> Trend {
+        let SarAcceleration { start, step, max } = self.acceleration;
+        let sar = trend.sar + trend.factor * (trend.extreme - trend.sar);
+        let factor = (trend.factor + step).min(max);
+        match trend.direction {
+            TradeDirection::Long => {
+                let sar = sar.min(recent_low);
+                if low < sar {
+                    Trend {
+                        direction: TradeDirection::Short,
+                        sar: trend.extreme,
+                        extreme: low,
+                        factor: start,
+                    }
+                } else if high > trend.extreme {
+                    Trend {
+                        sar,
+                        extreme: high,
+                        factor,
+                        ..trend
+                    }
+                } else {
+                    Trend { sar, ..trend }
+                }
+            }
+            TradeDirection::Short => {
+                let sar = sar.max(recent_high);
+                if high > sar {
+                    Trend {
+                        direction: TradeDirection::Long,
+                        sar: trend.extreme,
+                        extreme: high,
+                        factor: start,
+                    }
+                } else if low < trend.extreme {
+                    Trend {
+                        sar,
+                        extreme: low,
+                        factor,
+                        ..trend
+                    }
+                } else {
+                    Trend { sar, ..trend }
+                }
+            }
+        }
+    }
+}
+
+/// Turn an Iterator of candles into their parabolic SAR
+pub trait IntoParabolicSarIterator: Iterator + Sized
+where
+    Self::Item: High + Low,
+{
+    /// One item per candle: None for the first, then the SAR and which
+    /// way the trend is going. See [`ParabolicSar::push`]
+    fn parabolic_sar(self, acceleration: SarAcceleration) -> ParabolicSarIterator<Self> {
+        ParabolicSarIterator {
+            candles: self,
+            sar: ParabolicSar::new(acceleration),
+        }
+    }
+}
+
+impl<I> IntoParabolicSarIterator for I
+where
+    I: Iterator,
+    I::Item: High + Low,
+{
+}
+
+pub struct ParabolicSarIterator<I> {
+    candles: I,
+    sar: ParabolicSar,
+}
+
+impl<I> Iterator for ParabolicSarIterator<I>
+where
+    I: Iterator,
+    I::Item: High + Low,
+{
+    type Item = Option<SarReading>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let candle = self.candles.next()?;
+        Some(self.sar.push(&candle))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.candles.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candle::test_data::Candle;
+    use pretty_assertions::assert_eq;
+
+    fn candle(high: f32, low: f32) -> Candle {
+        Candle::new(high, low, low, high)
+    }
+
+    #[test]
+    fn trails_then_reverses() {
+        let candles = [
+            candle(10.0, 9.0),
+            candle(11.0, 10.0),
+            candle(12.0, 11.0),
+            candle(13.0, 12.0),
+            candle(8.0, 7.0),
+        ];
+        let got: Vec<_> = candles
+            .iter()
+            .parabolic_sar(SarAcceleration::default())
+            .collect();
+        assert_eq!(None, got[0]);
+        let sars: Vec<f32> = got[1..]
+            .iter()
+            .map(|reading| reading.unwrap().sar)
+            .collect();
+        // Starts at the first low; the second step is held back by it too
+        let expected = [9.0, 9.0, 9.12, 13.0];
+        for (expected, got) in expected.into_iter().zip(&sars) {
+            assert!((expected - got).abs() < 0.0001, "{sars:?}");
+        }
+        let directions: Vec<_> = got[1..]
+            .iter()
+            .map(|reading| reading.unwrap().direction)
+            .collect();
+        assert_eq!(
+            vec![
+                TradeDirection::Long,
+                TradeDirection::Long,
+                TradeDirection::Long,
+                // Flipped to the highest high when the low went under the SAR
+                TradeDirection::Short
+            ],
+            directions
+        );
+    }
+
+    #[test]
+    fn short_trend() {
+        let candles: Vec<_> = (0..30)
+            .map(|i| candle(100.0 - i as f32, 99.0 - i as f32))
+            .collect();
+        let readings: Vec<_> = candles
+            .iter()
+            .parabolic_sar(SarAcceleration::default())
+            .flatten()
+            .collect();
+        assert_eq!(29, readings.len());
+        for (reading, candle) in readings.iter().zip(&candles[1..]) {
+            assert_eq!(TradeDirection::Short, reading.direction);
+            assert!(reading.sar >= candle.high, "{reading:?} {candle:?}");
+        }
+        // It closes in as the trend goes on
+        let gap = |i: usize| readings[i].sar - candles[i + 1].high;
+        assert!(gap(28) < gap(5));
+    }
+
+    #[test]
+    fn acceleration_is_capped() {
+        let acceleration = SarAcceleration {
+            start: 0.1,
+            step: 0.1,
+            max: 0.2,
+        };
+        let mut sar = ParabolicSar::new(acceleration);
+        for i in 0..10 {
+            sar.push(&candle(i as f32 + 1.0, i as f32));
+        }
+        assert_eq!(Some(0.2), sar.trend.map(|trend| trend.factor));
+        assert_eq!(SarAcceleration::default().max, 0.2);
+    }
+}
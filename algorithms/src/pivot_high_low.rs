@@ -1,7 +1,11 @@
-use crate::candle::{High, Low};
+use crate::{
+    candle::{Close, High, Low},
+    Error,
+};
+use serde::{Deserialize, Serialize};
 use std::fmt::Debug as Dbg;
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum Pivot {
     High(f32),
     Low(f32),
@@ -52,6 +56,32 @@ impl Pivot {
     }
 }
 
+/// Controls where in the output a pivot is reported.
+///
+/// A pivot can only be known once the candles to the right of it have
+/// completed, so reporting it at its own index leaks the future into
+/// anything that consumes the output candle by candle (eg. a backtest).
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum PivotConfirmation {
+    /// Report the pivot at the index of the candle that made it. This
+    /// looks ahead by the right hand side of the window, so it's only
+    /// good for drawing charts.
+    Centered,
+    /// Report the pivot at the index of the last candle in the window,
+    /// ie. as soon as it could really have been known.
+    #[default]
+    Causal,
+}
+
+/// A pivot along with how late it was reported
+#[derive(Debug, PartialEq, Clone)]
+pub struct ConfirmedPivot {
+    pub pivot: Pivot,
+    /// The number of candles between the candle that made the pivot
+    /// and the candle at which it was reported
+    pub delay: usize,
+}
+
 /// Finds pivot points in a slice of types implementing `High` and `Low`.
 /// A pivot is defined as a point in the slice where a certain number of
 /// candles before and after the middle candle are lower or higher than
@@ -60,61 +90,293 @@ impl Pivot {
 /// This takes a slice rather than an iterator because it's more efficient
 /// to get at the Windows that we need
 ///
+/// Pivots are reported at the last candle of the window that confirms
+/// them. See [`confirmed_pivots`] to choose the placement and find out
+/// the delay.
+///
 /// # Arguments
 ///
 /// * `input` - A reference to a slice of types implementing `High` and `Low`.
 /// * `window_size` - The size of the window around each candle to consider.
 ///
+/// # Errors
+///
+/// Returns [`Error::ZeroWindow`] if `window_size` is 0 and
+/// [`Error::WindowTooBig`] if there are fewer candles than `window_size`,
+/// in which case you probably want to go and get more candles.
 pub fn pivots(
     input: &[impl High + Low + Dbg],
     window_size: usize,
-) -> impl Iterator<Item = Pivot> + Clone + Dbg + '_ {
-    // TODO: Make this a compile time check
-    assert!(window_size != 0, "Can't have a zero sized sliding window");
-    // TODO: Make this an Error instead of a panic?
-    assert!(
-        window_size <= input.len(),
-        "Window size must be <= input length"
-    );
+) -> Result<impl Iterator<Item = Pivot> + Clone + Dbg + '_, Error> {
+    if window_size == 0 {
+        return Err(Error::ZeroWindow);
+    }
+    if window_size > input.len() {
+        return Err(Error::WindowTooBig {
+            window_size,
+            input_len: input.len(),
+        });
+    }
     let mid_index = window_size / 2;
     let start = std::iter::repeat(Pivot::NoChange).take(window_size - 1);
-    let rest = input.windows(window_size).map(move |window| {
-        let mid = &window[mid_index];
-        let mid_high = mid.high();
-        let mid_low = mid.low();
-        let left = window[..mid_index].iter();
-        let right = window[mid_index..].iter().skip(1);
-        // If the middle candle's high is higher than all the other candles, this is a pivot high
-        let is_high = left.clone().all(|candle| mid_high > candle.high())
-            && right.clone().all(|candle| mid_high > candle.high());
-        // If the middle candle's low is lower than all the other candles, this is a pivot low
-        let is_low = left.clone().all(|candle| mid_low < candle.low())
-            && right.clone().all(|candle| mid_low < candle.low());
-        match (is_high, is_low) {
-            (true, true) => Pivot::HighLow {
-                high: mid_high,
-                low: mid_low,
-            },
-            (true, false) => Pivot::High(mid_high),
-            (false, true) => Pivot::Low(mid_low),
-            (false, false) => Pivot::NoChange,
+    let rest = input
+        .windows(window_size)
+        .map(move |window| find_pivot(window, mid_index));
+    Ok(start.chain(rest))
+}
+
+/// Like [`pivots`] but lets you choose where each pivot is reported and
+/// records how many candles late it was.
+///
+/// There is always exactly one output per input candle. With
+/// [`PivotConfirmation::Centered`] the candles at the end that can't be
+/// confirmed yet are reported as [`Pivot::NoChange`].
+///
+/// # Errors
+///
+/// The same as [`pivots`]
+pub fn confirmed_pivots(
+    input: &[impl High + Low + Dbg],
+    window_size: usize,
+    confirmation: PivotConfirmation,
+) -> Result<impl Iterator<Item = ConfirmedPivot> + Clone + Dbg + '_, Error> {
+    let mid_index = window_size / 2;
+    // The number of candles to the right of the middle one
+    let right = window_size.saturating_sub(mid_index + 1);
+    let (leading, trailing, delay) = match confirmation {
+        PivotConfirmation::Centered => (mid_index, right, 0),
+        PivotConfirmation::Causal => (window_size.saturating_sub(1), 0, right),
+    };
+    let found = pivots(input, window_size)?.skip(window_size - 1);
+    Ok(std::iter::repeat_n(Pivot::NoChange, leading)
+        .chain(found)
+        .chain(std::iter::repeat_n(Pivot::NoChange, trailing))
+        .map(move |pivot| ConfirmedPivot { pivot, delay }))
+}
+
+/// A pivot window that grows when the market is choppy and shrinks when
+/// it's trending, so we don't pick up every wiggle in a range but still
+/// react quickly in a trend.
+///
+/// Choppiness is measured with Kaufman's efficiency ratio over the last
+/// `lookback` closes: the net move divided by the sum of all the moves.
+/// A ratio of 1 (a straight line) gives the `min` window and 0 (going
+/// nowhere) gives the `max`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct AdaptiveWindow {
+    min: usize,
+    max: usize,
+    // The number of price changes to measure the efficiency ratio over
+    lookback: usize,
+}
+
+impl AdaptiveWindow {
+    /// Windows from `min` to `max` candles, picked by the efficiency ratio
+    /// over the last `lookback` price changes
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::WindowRange`] if `min` is 0 or more than `max`
+    pub fn new(min: usize, max: usize, lookback: usize) -> Result<Self, Error> {
+        if min == 0 || min > max {
+            return Err(Error::WindowRange { min, max });
         }
+        Ok(Self { min, max, lookback })
+    }
+
+    pub fn min(&self) -> usize {
+        self.min
+    }
+
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    pub fn lookback(&self) -> usize {
+        self.lookback
+    }
+
+    /// The window size to use for an efficiency ratio between 0 and 1
+    pub fn window_size(&self, efficiency_ratio: f32) -> usize {
+        let choppiness = 1.0 - efficiency_ratio.clamp(0.0, 1.0);
+        self.min + ((self.max - self.min) as f32 * choppiness).round() as usize
+    }
+}
+
+/// Kaufman's efficiency ratio of some closes; the net move divided by the
+/// sum of the absolute moves. None if there aren't at least two closes
+pub(crate) fn efficiency_ratio(closes: impl IntoIterator<Item = f32>) -> Option<f32> {
+    let mut closes = closes.into_iter();
+    let first = closes.next()?;
+    let (last, path) = closes.fold((None, 0.0), |(last, path): (Option<f32>, f32), close| {
+        (Some(close), path + (close - last.unwrap_or(first)).abs())
     });
-    start.chain(rest)
+    let last = last?;
+    if path == 0.0 {
+        // The price hasn't moved at all; about as choppy as it gets
+        Some(0.0)
+    } else {
+        Some((last - first).abs() / path)
+    }
+}
+
+/// Like [`pivots`], but the window size for each candle comes from the
+/// recent choppiness. See [`AdaptiveWindow`].
+///
+/// Yields one [`Pivot`] per input candle, reported at the last candle of
+/// its window the same as [`pivots`]. Until there are `lookback` price
+/// changes to measure, the `max` window is used.
+///
+/// # Errors
+///
+/// Returns [`Error::WindowTooBig`] if there are fewer candles than
+/// `window.min`.
+pub fn adaptive_pivots<C>(
+    input: &[C],
+    window: AdaptiveWindow,
+) -> Result<impl Iterator<Item = Pivot> + '_, Error>
+where
+    C: High + Low + Close + Dbg,
+{
+    if window.min > input.len() {
+        return Err(Error::WindowTooBig {
+            window_size: window.min,
+            input_len: input.len(),
+        });
+    }
+    Ok((0..input.len()).map(move |index| {
+        let window_size = index
+            .checked_sub(window.lookback)
+            .filter(|_| window.lookback > 0)
+            .and_then(|start| efficiency_ratio(input[start..=index].iter().map(Close::close)))
+            .map_or(window.max, |ratio| window.window_size(ratio));
+        match (index + 1).checked_sub(window_size) {
+            Some(start) => find_pivot(&input[start..=index], window_size / 2),
+            // Not enough candles for this window yet
+            None => Pivot::NoChange,
+        }
+    }))
+}
+
+/// Works out whether the middle candle of `window` is a pivot
+pub(crate) fn find_pivot(window: &[impl High + Low], mid_index: usize) -> Pivot {
+    let mid = &window[mid_index];
+    let mid_high = mid.high();
+    let mid_low = mid.low();
+    let left = window[..mid_index].iter();
+    let right = window[mid_index..].iter().skip(1);
+    // If the middle candle's high is higher than all the other candles, this is a pivot high
+    let is_high = left.clone().all(|candle| mid_high > candle.high())
+        && right.clone().all(|candle| mid_high > candle.high());
+    // If the middle candle's low is lower than all the other candles, this is a pivot low
+    let is_low = left.clone().all(|candle| mid_low < candle.low())
+        && right.clone().all(|candle| mid_low < candle.low());
+    match (is_high, is_low) {
+        (true, true) => Pivot::HighLow {
+            high: mid_high,
+            low: mid_low,
+        },
+        (true, false) => Pivot::High(mid_high),
+        (false, true) => Pivot::Low(mid_low),
+        (false, false) => Pivot::NoChange,
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use super::{pivots, Pivot};
+    use super::{
+        adaptive_pivots, confirmed_pivots, efficiency_ratio, pivots, AdaptiveWindow,
+        ConfirmedPivot, Pivot, PivotConfirmation,
+    };
     use crate::{
         candle::test_data::{test_data_1, test_data_2, Candle},
-        Close, High, Low, Open, RenkoCandle, RenkoDirection,
+        Close, Error, High, Low, Open, RenkoCandle, RenkoDirection,
     };
 
+    #[test]
+    fn zero_window() {
+        let data = test_data_1();
+        assert_eq!(Some(Error::ZeroWindow), pivots(data.as_slice(), 0).err());
+    }
+
+    #[test]
+    fn window_too_big() {
+        let data = test_data_1();
+        assert_eq!(
+            Some(Error::WindowTooBig {
+                window_size: 10,
+                input_len: 9
+            }),
+            pivots(data.as_slice(), 10).err()
+        );
+        assert!(confirmed_pivots(data.as_slice(), 10, PivotConfirmation::Causal).is_err());
+    }
+
+    #[test]
+    fn efficiency() {
+        assert_eq!(None, efficiency_ratio([1.0]));
+        assert_eq!(Some(1.0), efficiency_ratio([1.0, 2.0, 3.0]));
+        assert_eq!(Some(0.0), efficiency_ratio([1.0, 2.0, 1.0]));
+        assert_eq!(Some(0.0), efficiency_ratio([1.0, 1.0, 1.0]));
+        assert_eq!(Some(0.5), efficiency_ratio([1.0, 3.0, 2.0, 3.0]));
+    }
+
+    #[test]
+    fn adaptive_window_size() {
+        let window = AdaptiveWindow::new(3, 7, 4).unwrap();
+        assert_eq!(3, window.window_size(1.0));
+        assert_eq!(5, window.window_size(0.5));
+        assert_eq!(7, window.window_size(0.0));
+    }
+
+    #[test]
+    fn adaptive_fixed_matches_pivots() {
+        let data = test_data_2();
+        let got: Vec<_> = adaptive_pivots(data.as_slice(), AdaptiveWindow::new(3, 3, 4).unwrap())
+            .unwrap()
+            .collect();
+        let expected: Vec<_> = pivots(data.as_slice(), 3).unwrap().collect();
+        assert_eq!(expected, got);
+    }
+
+    #[test]
+    fn adaptive_grows_in_chop() {
+        // The closes of test_data_3 go nowhere, so the big window is used
+        // and the small wiggles aren't pivots
+        let data = crate::candle::test_data::test_data_3();
+        let small: Vec<_> = pivots(data.as_slice(), 3).unwrap().collect();
+        assert!(small.iter().any(|pivot| !pivot.is_no_change()));
+        let got: Vec<_> = adaptive_pivots(data.as_slice(), AdaptiveWindow::new(3, 9, 4).unwrap())
+            .unwrap()
+            .collect();
+        assert_eq!(vec![Pivot::NoChange; data.len()], got);
+    }
+
+    #[test]
+    fn adaptive_errors() {
+        let data = test_data_1();
+        assert_eq!(
+            Err(Error::WindowRange { min: 0, max: 3 }),
+            AdaptiveWindow::new(0, 3, 4)
+        );
+        assert_eq!(
+            Err(Error::WindowRange { min: 5, max: 3 }),
+            AdaptiveWindow::new(5, 3, 4)
+        );
+        let window = AdaptiveWindow::new(10, 12, 4).unwrap();
+        assert_eq!(
+            Some(Error::WindowTooBig {
+                window_size: 10,
+                input_len: 9
+            }),
+            adaptive_pivots(data.as_slice(), window).err()
+        );
+    }
+
     #[test]
     fn test_1_odd_number() {
         let data = test_data_1();
-        let pivots = pivots(data.as_slice(), 5);
+        let pivots = pivots(data.as_slice(), 5).unwrap();
         let expected = vec![
             Pivot::NoChange,
             Pivot::NoChange,
@@ -132,7 +394,7 @@ mod test {
     #[test]
     fn test_1_even_window() {
         let data = test_data_1();
-        let pivots = pivots(data.as_slice(), 4);
+        let pivots = pivots(data.as_slice(), 4).unwrap();
         let expected = vec![
             Pivot::NoChange,
             Pivot::NoChange,
@@ -147,10 +409,73 @@ mod test {
         assert_eq!(expected, pivots.collect::<Vec<_>>());
     }
 
+    #[test]
+    fn test_1_causal_records_delay() {
+        let data = test_data_1();
+        let got: Vec<_> = confirmed_pivots(data.as_slice(), 5, PivotConfirmation::Causal)
+            .unwrap()
+            .collect();
+        let expected: Vec<_> = pivots(data.as_slice(), 5)
+            .unwrap()
+            .map(|pivot| ConfirmedPivot { pivot, delay: 2 })
+            .collect();
+        assert_eq!(expected, got);
+    }
+
+    #[test]
+    fn test_1_centered() {
+        let data = test_data_1();
+        let got: Vec<_> = confirmed_pivots(data.as_slice(), 5, PivotConfirmation::Centered)
+            .unwrap()
+            .map(|confirmed| {
+                assert_eq!(confirmed.delay, 0);
+                confirmed.pivot
+            })
+            .collect();
+        let expected = vec![
+            Pivot::NoChange,
+            Pivot::NoChange,
+            Pivot::Low(4.0),
+            Pivot::NoChange,
+            Pivot::High(11.0),
+            Pivot::Low(3.0),
+            Pivot::NoChange,
+            Pivot::NoChange,
+            Pivot::NoChange,
+        ];
+        assert_eq!(expected, got);
+    }
+
+    #[test]
+    fn test_1_centered_even_window() {
+        let data = test_data_1();
+        let got: Vec<_> = confirmed_pivots(data.as_slice(), 4, PivotConfirmation::Centered)
+            .unwrap()
+            .map(|confirmed| confirmed.pivot)
+            .collect();
+        let expected = vec![
+            Pivot::NoChange,
+            Pivot::NoChange,
+            Pivot::Low(4.0),
+            Pivot::NoChange,
+            Pivot::High(11.0),
+            Pivot::Low(3.0),
+            Pivot::NoChange,
+            Pivot::NoChange,
+            Pivot::NoChange,
+        ];
+        assert_eq!(expected, got);
+        let delays: Vec<_> = confirmed_pivots(data.as_slice(), 4, PivotConfirmation::Causal)
+            .unwrap()
+            .map(|confirmed| confirmed.delay)
+            .collect();
+        assert_eq!(vec![1; 9], delays);
+    }
+
     #[test]
     fn test_2_large() {
         let data = test_data_2();
-        let pivots = pivots(data.as_slice(), 5);
+        let pivots = pivots(data.as_slice(), 5).unwrap();
         let expected = vec![
             Pivot::NoChange,
             Pivot::NoChange,
@@ -169,7 +494,7 @@ mod test {
     #[test]
     fn test_2_small() {
         let data = test_data_2();
-        let pivots = pivots(data.as_slice(), 3);
+        let pivots = pivots(data.as_slice(), 3).unwrap();
         let expected = vec![
             Pivot::NoChange,
             Pivot::NoChange,
@@ -194,7 +519,7 @@ mod test {
             Candle::new(18.0, 11.0, 14.0, 13.0),
         ];
 
-        let pivots = pivots(data.as_slice(), 3);
+        let pivots = pivots(data.as_slice(), 3).unwrap();
         let expected = vec![
             Pivot::NoChange,
             Pivot::NoChange,
@@ -485,7 +810,7 @@ mod test {
                 candle.low()
             );
         }
-        let pivots: Vec<_> = pivots(candles.as_slice(), 5).collect();
+        let pivots: Vec<_> = pivots(candles.as_slice(), 5).unwrap().collect();
         println!("pivots: {pivots:#?}");
 
         create_candlestick_chart(&candles);
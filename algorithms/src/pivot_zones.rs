@@ -0,0 +1,148 @@
+//! Merges pivot levels from several timeframes into zones. A level that
+//! shows up on the M15 and the H4 chart is watched by more traders, and so
+//! is more likely to hold, than one only the M15 chart shows.
+
+use crate::Pivot;
+
+/// The pivot levels found on one timeframe
+#[derive(Debug, PartialEq, Clone)]
+pub struct TimeframeLevels<K> {
+    /// Which timeframe, eg. a granularity
+    pub timeframe: K,
+    /// How much a level on this timeframe counts for. Higher timeframes
+    /// usually get more
+    pub weight: f32,
+    pub levels: Vec<f32>,
+}
+
+impl<K> TimeframeLevels<K> {
+    /// The highs and lows of `pivots` as levels
+    pub fn from_pivots(timeframe: K, weight: f32, pivots: impl IntoIterator<Item = Pivot>) -> Self {
+        let levels = pivots
+            .into_iter()
+            .flat_map(|pivot| pivot.high().into_iter().chain(pivot.low()))
+            .collect();
+        Self {
+            timeframe,
+            weight,
+            levels,
+        }
+    }
+}
+
+/// A band of prices where pivot levels cluster
+#[derive(Debug, PartialEq, Clone)]
+pub struct PivotZone<K> {
+    /// The lowest level in the zone
+    pub low: f32,
+    /// The highest level in the zone
+    pub high: f32,
+    /// The weighted average of the levels in the zone
+    pub level: f32,
+    /// The total weight of the levels in the zone
+    pub strength: f32,
+    /// Each timeframe with a level in the zone, once
+    pub timeframes: Vec<K>,
+}
+
+impl<K> PivotZone<K> {
+    /// Whether levels from more than one timeframe are in the zone
+    pub fn is_multi_timeframe(&self) -> bool {
+        self.timeframes.len() > 1
+    }
+}
+
+/// Groups the levels of every timeframe into zones at most `tolerance` (in
+/// price units) wide. The strongest zones come first
+pub fn pivot_zones<K>(timeframes: &[TimeframeLevels<K>], tolerance: f32) -> Vec<PivotZone<K>>
+where
+    K: Clone + PartialEq,
+{
+    let mut levels: Vec<(f32, f32, &K)> = timeframes
+        .iter()
+        .flat_map(|timeframe| {
+            timeframe
+                .levels
+                .iter()
+                .map(move |level| (*level, timeframe.weight, &timeframe.timeframe))
+        })
+        .collect();
+    levels.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut zones: Vec<PivotZone<K>> = Vec::new();
+    for (level, weight, timeframe) in levels {
+        match zones.last_mut() {
+            Some(zone) if level - zone.low <= tolerance => {
+                zone.level =
+                    (zone.level * zone.strength + level * weight) / (zone.strength + weight);
+                zone.high = level;
+                zone.strength += weight;
+                if !zone.timeframes.contains(timeframe) {
+                    zone.timeframes.push(timeframe.clone());
+                }
+            }
+            _ => zones.push(PivotZone {
+                low: level,
+                high: level,
+                level,
+                strength: weight,
+                timeframes: vec![timeframe.clone()],
+            }),
+        }
+    }
+    zones.sort_by(|a, b| b.strength.total_cmp(&a.strength));
+    zones
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn from_pivots() {
+        let pivots = [
+            Pivot::High(1.2),
+            Pivot::NoChange,
+            Pivot::HighLow {
+                high: 1.3,
+                low: 1.0,
+            },
+        ];
+        let got = TimeframeLevels::from_pivots("M15", 1.0, pivots);
+        assert_eq!(vec![1.2, 1.3, 1.0], got.levels);
+    }
+
+    #[test]
+    fn zones() {
+        let timeframes = [
+            TimeframeLevels {
+                timeframe: "M15",
+                weight: 1.0,
+                levels: vec![1.1000, 1.1050, 1.1200],
+            },
+            TimeframeLevels {
+                timeframe: "H4",
+                weight: 3.0,
+                levels: vec![1.1004, 1.1300],
+            },
+        ];
+        let zones = pivot_zones(&timeframes, 0.0010);
+        assert_eq!(4, zones.len());
+        // The M15 and H4 levels around 1.1000 make the strongest zone
+        let strongest = &zones[0];
+        assert_eq!(vec!["M15", "H4"], strongest.timeframes);
+        assert!(strongest.is_multi_timeframe());
+        assert_eq!(4.0, strongest.strength);
+        assert_eq!((1.1000, 1.1004), (strongest.low, strongest.high));
+        assert!((strongest.level - 1.1003).abs() < 0.00001);
+        // Then the lone H4 level
+        assert_eq!(vec!["H4"], zones[1].timeframes);
+        assert!(!zones[1].is_multi_timeframe());
+    }
+
+    #[test]
+    fn no_levels() {
+        let timeframes: [TimeframeLevels<&str>; 0] = [];
+        assert!(pivot_zones(&timeframes, 0.001).is_empty());
+    }
+}
@@ -1,6 +1,8 @@
+use serde::{Deserialize, Serialize};
+
 use crate::{Close, High, Low, Open};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct RenkoCandle {
     // The floor of the open price divided by size
     pub level: i32,
@@ -41,12 +43,38 @@ impl Low for RenkoCandle {
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
 pub enum RenkoDirection {
     Up,
     Down,
 }
 
+/// How many bricks in a row must go the new way before a change of
+/// direction is believed and the bricks start coming out again
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum RenkoReversal {
+    /// Emit every brick, even a single brick against the trend
+    EveryBrick,
+    /// Swallow bricks after a change of direction until this many in a row
+    /// have gone the new way, then emit from that one on
+    Confirm(usize),
+    /// Swallow the first brick after a change of direction, and emit from
+    /// the second one on. The same as `Confirm(2)`
+    #[default]
+    Classic,
+}
+
+impl RenkoReversal {
+    /// The number of bricks in a row needed to confirm a new direction
+    pub fn bricks(&self) -> usize {
+        match self {
+            RenkoReversal::EveryBrick => 1,
+            RenkoReversal::Confirm(bricks) => (*bricks).max(1),
+            RenkoReversal::Classic => 2,
+        }
+    }
+}
+
 pub struct RenkoIterator<I> {
     // Incoming prices of candle closes
     prices: I,
@@ -58,22 +86,30 @@ pub struct RenkoIterator<I> {
     // Made from the last incoming price or the close of the last renko released
     // It is the (price / size).floor().
     start_level: Option<i32>,
-    // The direction of the last renko candle
-    // If a candle changes direction, we don't emit it
+    // The direction of the last renko candle, whether we emitted it or not
     last_direction: Option<RenkoDirection>,
+    // How many candles in a row have gone in `last_direction`
+    run: usize,
+    // Whether `last_direction` has been confirmed, so we can emit candles going that way
+    confirmed: bool,
+    // How many candles in a row it takes to confirm a change of direction
+    reversal: RenkoReversal,
 }
 
 impl<I> RenkoIterator<I>
 where
     I: Iterator<Item = f32>,
 {
-    fn new(prices: I, size: f32) -> Self {
+    fn new(prices: I, size: f32, reversal: RenkoReversal) -> Self {
         Self {
             prices,
             size,
             last_level: None,
             start_level: None,
             last_direction: None,
+            run: 0,
+            confirmed: false,
+            reversal,
         }
     }
     /// Consumes the incoming iteator and returns the next
@@ -140,16 +176,26 @@ where
                     // Store the new last_direction
                     let last_direction = self.last_direction;
                     self.last_direction = Some(candle.direction);
-                    match (last_direction, candle.direction) {
+                    match last_direction {
                         // If we didn't have a last direction before, release this candle
-                        (None, _) => break candle,
-                        // If the candle is going the same way as the last candle, release the candle
-                        (Some(last_direction), _) if last_direction == candle.direction => {
-                            break candle
+                        None => {
+                            self.run = 1;
+                            self.confirmed = true;
+                        }
+                        // If the candle is going the same way as the last candle, it adds to the run
+                        Some(last_direction) if last_direction == candle.direction => {
+                            self.run += 1;
                         }
                         // If we get an up, down, up, down, up, down don't release anything except the first up
-                        // until we get two in a row in the same direction
-                        _ => (),
+                        // until we get enough in a row in the same direction
+                        Some(_) => {
+                            self.run = 1;
+                            self.confirmed = false;
+                        }
+                    }
+                    self.confirmed |= self.run >= self.reversal.bricks();
+                    if self.confirmed {
+                        break candle;
                     }
                 }
                 // Sequential candles are the same, get a new last_level candle
@@ -162,7 +208,12 @@ where
 }
 
 pub trait IntoRenkoIterator<I> {
+    /// Turns prices into renko candles of `size`, using the
+    /// [`RenkoReversal::Classic`] filter on changes of direction
     fn renko(self, size: f32) -> RenkoIterator<I>;
+    /// Turns prices into renko candles of `size`, choosing how changes of
+    /// direction are filtered
+    fn renko_with_reversal(self, size: f32, reversal: RenkoReversal) -> RenkoIterator<I>;
 }
 
 impl<I> IntoRenkoIterator<I> for I
@@ -170,7 +221,11 @@ where
     I: Iterator<Item = f32>,
 {
     fn renko(self, size: f32) -> RenkoIterator<Self> {
-        RenkoIterator::new(self, size)
+        RenkoIterator::new(self, size, RenkoReversal::default())
+    }
+
+    fn renko_with_reversal(self, size: f32, reversal: RenkoReversal) -> RenkoIterator<Self> {
+        RenkoIterator::new(self, size, reversal)
     }
 }
 
@@ -275,4 +330,55 @@ mod tests {
         let got: Vec<RenkoCandle> = prices.into_iter().renko(2.0).collect();
         assert_eq!(expected, got);
     }
+
+    /// Turns a list of (level, direction) into candles of size 2
+    fn bricks(bricks: &[(i32, RenkoDirection)]) -> Vec<RenkoCandle> {
+        bricks
+            .iter()
+            .map(|&(level, direction)| RenkoCandle {
+                level,
+                size: 2.0,
+                direction,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_classic_is_confirm_2() {
+        let prices = [
+            10.0, 15.0, 12.0, 17.0, 13.0, 13.5, 13.999, 12.0, 12.1, 11.0, 10.0, 11.999, 11.2,
+        ];
+        let classic: Vec<_> = prices.into_iter().renko(2.0).collect();
+        let confirm: Vec<_> = prices
+            .into_iter()
+            .renko_with_reversal(2.0, RenkoReversal::Confirm(2))
+            .collect();
+        assert_eq!(classic, confirm);
+    }
+
+    #[test]
+    fn test_every_brick() {
+        use RenkoDirection::{Down, Up};
+        let prices = [10.0, 15.0, 12.0, 17.0];
+        let got: Vec<_> = prices
+            .into_iter()
+            .renko_with_reversal(2.0, RenkoReversal::EveryBrick)
+            .collect();
+        let expected = bricks(&[(5, Up), (6, Up), (7, Down), (6, Up), (7, Up)]);
+        assert_eq!(expected, got);
+    }
+
+    #[test]
+    fn test_confirm_3() {
+        use RenkoDirection::{Down, Up};
+        // Up 3, down 2 (swallowed), up 1 (swallowed), down 3
+        let prices = [10.0, 16.0, 12.0, 14.0, 8.0];
+        let got: Vec<_> = prices
+            .into_iter()
+            .renko_with_reversal(2.0, RenkoReversal::Confirm(3))
+            .collect();
+        // The trend we start with doesn't need confirming
+        let expected = bricks(&[(5, Up), (6, Up), (7, Up), (5, Down)]);
+        assert_eq!(expected, got);
+    }
 }
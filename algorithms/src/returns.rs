@@ -0,0 +1,168 @@
+//! Returns from one close to the next, and since the first. These are what
+//! statistics, correlations and volatility are worked out on, rather than
+//! the prices themselves. Closes are expected to be more than zero.
+
+use crate::Close;
+
+/// How a change in price is measured
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ReturnKind {
+    /// The change as a fraction of the first price: 0.01 is up 1%
+    Simple,
+    /// The natural log of the ratio of the prices. These add up over time,
+    /// where simple returns compound
+    Log,
+}
+
+impl ReturnKind {
+    /// The return from the price `from` to the price `to`
+    pub fn between(self, from: f32, to: f32) -> f32 {
+        match self {
+            ReturnKind::Simple => to / from - 1.0,
+            ReturnKind::Log => (to / from).ln(),
+        }
+    }
+}
+
+/// Turn an Iterator of candles into their returns
+pub trait IntoReturnsIterator: Iterator + Sized
+where
+    Self::Item: Close,
+{
+    /// The return from each close to the next; one fewer item than there
+    /// are candles
+    fn returns(self, kind: ReturnKind) -> ReturnsIterator<Self> {
+        ReturnsIterator {
+            candles: self,
+            kind,
+            previous: None,
+        }
+    }
+
+    /// [`returns`](Self::returns) as fractions
+    fn simple_returns(self) -> ReturnsIterator<Self> {
+        self.returns(ReturnKind::Simple)
+    }
+
+    /// [`returns`](Self::returns) as natural logs
+    fn log_returns(self) -> ReturnsIterator<Self> {
+        self.returns(ReturnKind::Log)
+    }
+
+    /// The simple return from the first close to each close, the same as
+    /// compounding the simple returns. One item per candle, starting at 0
+    fn cumulative_return(self) -> CumulativeReturnIterator<Self> {
+        CumulativeReturnIterator {
+            candles: self,
+            first: None,
+        }
+    }
+}
+
+impl<I> IntoReturnsIterator for I
+where
+    I: Iterator,
+    I::Item: Close,
+{
+}
+
+pub struct ReturnsIterator<I> {
+    candles: I,
+    kind: ReturnKind,
+    previous: Option<f32>,
+}
+
+impl<I> Iterator for ReturnsIterator<I>
+where
+    I: Iterator,
+    I::Item: Close,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let close = self.candles.next()?.close();
+            if let Some(previous) = self.previous.replace(close) {
+                return Some(self.kind.between(previous, close));
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (low, high) = self.candles.size_hint();
+        match self.previous {
+            Some(_) => (low, high),
+            None => (
+                low.saturating_sub(1),
+                high.map(|high| high.saturating_sub(1)),
+            ),
+        }
+    }
+}
+
+pub struct CumulativeReturnIterator<I> {
+    candles: I,
+    first: Option<f32>,
+}
+
+impl<I> Iterator for CumulativeReturnIterator<I>
+where
+    I: Iterator,
+    I::Item: Close,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let close = self.candles.next()?.close();
+        let first = *self.first.get_or_insert(close);
+        Some(ReturnKind::Simple.between(first, close))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.candles.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candle::test_data::closes;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn simple_and_log() {
+        let candles = closes([100.0, 110.0, 99.0, 99.0]);
+        let simple: Vec<_> = candles.iter().simple_returns().collect();
+        let expected = [0.1, -0.1, 0.0];
+        assert_eq!(3, simple.len());
+        for (expected, got) in expected.into_iter().zip(&simple) {
+            assert!((expected - got).abs() < 0.0001, "{simple:?}");
+        }
+        // Log returns add up to the log of the whole move
+        let total: f32 = candles.iter().log_returns().sum();
+        assert!((total - (99.0f32 / 100.0).ln()).abs() < 0.0001);
+        assert_eq!(3, candles.iter().log_returns().size_hint().0);
+    }
+
+    #[test]
+    fn cumulative() {
+        let candles = closes([100.0, 110.0, 99.0]);
+        let got: Vec<_> = candles.iter().cumulative_return().collect();
+        assert_eq!(3, got.len());
+        assert_eq!(0.0, got[0]);
+        // Compounding the simple returns gets the same answer
+        let compounded = candles
+            .iter()
+            .simple_returns()
+            .fold(1.0, |total, r| total * (1.0 + r))
+            - 1.0;
+        assert!((got[2] - compounded).abs() < 0.0001, "{got:?}");
+        assert!((got[2] + 0.01).abs() < 0.0001, "{got:?}");
+    }
+
+    #[test]
+    fn too_few_candles() {
+        assert_eq!(None, closes([1.0]).iter().simple_returns().next());
+        assert_eq!(None, closes([]).iter().cumulative_return().next());
+    }
+}
@@ -0,0 +1,136 @@
+//! A fixed size window over the latest values of a stream. It never
+//! allocates after it's made and keeps a running sum, so rolling averages
+//! cost the same however long the window is.
+
+/// The latest `capacity` values pushed, oldest first
+#[derive(Debug, Clone, PartialEq)]
+pub struct RingBuffer {
+    values: Box<[f32]>,
+    // Where the next value goes, which is also the oldest value once full
+    next: usize,
+    len: usize,
+    // Kept in f64 so adding and removing millions of values doesn't drift
+    sum: f64,
+}
+
+impl RingBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            values: vec![0.0; capacity].into_boxed_slice(),
+            next: 0,
+            len: 0,
+            sum: 0.0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity()
+    }
+
+    /// Adds `value`, returning the value it pushed out if we were full. With
+    /// no capacity, `value` is pushed straight back out
+    pub fn push(&mut self, value: f32) -> Option<f32> {
+        if self.capacity() == 0 {
+            return Some(value);
+        }
+        let evicted = if self.is_full() {
+            let old = self.values[self.next];
+            self.sum -= old as f64;
+            Some(old)
+        } else {
+            self.len += 1;
+            None
+        };
+        self.values[self.next] = value;
+        self.sum += value as f64;
+        self.next = (self.next + 1) % self.capacity();
+        evicted
+    }
+
+    pub fn sum(&self) -> f32 {
+        self.sum as f32
+    }
+
+    /// The average of the values in the buffer. None if it's empty
+    pub fn mean(&self) -> Option<f32> {
+        if self.is_empty() {
+            None
+        } else {
+            Some((self.sum / self.len as f64) as f32)
+        }
+    }
+
+    /// The newest value
+    pub fn latest(&self) -> Option<f32> {
+        if self.is_empty() {
+            None
+        } else {
+            let index = (self.next + self.capacity() - 1) % self.capacity();
+            Some(self.values[index])
+        }
+    }
+
+    /// The values, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = &f32> + '_ {
+        let start = (self.next + self.capacity() - self.len) % self.capacity().max(1);
+        let (wrapped, first) = self.values.split_at(start);
+        first.iter().chain(wrapped).take(self.len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn push() {
+        let mut buffer = RingBuffer::new(3);
+        assert_eq!(None, buffer.mean());
+        assert_eq!(None, buffer.push(1.0));
+        assert_eq!(None, buffer.push(2.0));
+        assert_eq!(Some(1.5), buffer.mean());
+        assert!(!buffer.is_full());
+        assert_eq!(None, buffer.push(3.0));
+        assert!(buffer.is_full());
+        assert_eq!(Some(1.0), buffer.push(4.0));
+        assert_eq!(Some(2.0), buffer.push(5.0));
+        assert_eq!(
+            vec![3.0, 4.0, 5.0],
+            buffer.iter().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(Some(5.0), buffer.latest());
+        assert_eq!(12.0, buffer.sum());
+        assert_eq!(Some(4.0), buffer.mean());
+    }
+
+    #[test]
+    fn partly_full() {
+        let mut buffer = RingBuffer::new(4);
+        buffer.push(1.0);
+        buffer.push(2.0);
+        assert_eq!(vec![1.0, 2.0], buffer.iter().copied().collect::<Vec<_>>());
+        assert_eq!(Some(2.0), buffer.latest());
+    }
+
+    #[test]
+    fn no_capacity() {
+        let mut buffer = RingBuffer::new(0);
+        assert_eq!(Some(1.0), buffer.push(1.0));
+        assert!(buffer.is_empty());
+        assert_eq!(None, buffer.latest());
+        assert_eq!(0, buffer.iter().count());
+    }
+}
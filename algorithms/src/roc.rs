@@ -0,0 +1,108 @@
+//! Rate of change: the percentage the close has moved over the last
+//! `period` candles. Above 0 the market has been going up, below 0 down,
+//! and how far from 0 says how hard. Closes are expected to be more than
+//! zero.
+
+use crate::{Close, RingBuffer};
+
+/// A running rate of change, fed one close at a time
+#[derive(Debug, PartialEq, Clone)]
+pub struct Roc {
+    // The last `period` closes; the one pushed out is `period` candles ago
+    closes: RingBuffer,
+}
+
+impl Roc {
+    pub fn new(period: usize) -> Self {
+        Self {
+            closes: RingBuffer::new(period),
+        }
+    }
+
+    pub fn period(&self) -> usize {
+        self.closes.capacity()
+    }
+
+    /// Adds a close and returns its percentage change from the close
+    /// `period` candles before it. None until there is one, or always if
+    /// the period is 0
+    pub fn push(&mut self, close: f32) -> Option<f32> {
+        if self.period() == 0 {
+            return None;
+        }
+        let then = self.closes.push(close)?;
+        Some((close - then) / then * 100.0)
+    }
+}
+
+/// Turn an Iterator of candles into the rate of change of their closes
+pub trait IntoRocIterator: Iterator + Sized
+where
+    Self::Item: Close,
+{
+    /// One item per candle: None for the first `period`, then the
+    /// percentage change from the close `period` candles back. All None if
+    /// `period` is 0
+    fn roc(self, period: usize) -> RocIterator<Self> {
+        RocIterator {
+            candles: self,
+            roc: Roc::new(period),
+        }
+    }
+}
+
+impl<I> IntoRocIterator for I
+where
+    I: Iterator,
+    I::Item: Close,
+{
+}
+
+pub struct RocIterator<I> {
+    candles: I,
+    roc: Roc,
+}
+
+impl<I> Iterator for RocIterator<I>
+where
+    I: Iterator,
+    I::Item: Close,
+{
+    type Item = Option<f32>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let close = self.candles.next()?.close();
+        Some(self.roc.push(close))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.candles.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candle::test_data::closes;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn percentage_change() {
+        let candles = closes([10.0, 12.0, 11.0, 15.0, 8.25]);
+        assert_eq!(
+            vec![None, None, Some(10.0), Some(25.0), Some(-25.0)],
+            candles.iter().roc(2).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn one_per_candle() {
+        let candles = closes([1.0, 2.0]);
+        assert_eq!(vec![None, None], candles.iter().roc(2).collect::<Vec<_>>());
+        assert_eq!(vec![None, None], candles.iter().roc(0).collect::<Vec<_>>());
+        assert_eq!(
+            vec![None, Some(100.0)],
+            candles.iter().roc(1).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,144 @@
+//! Rolling statistics over any stream of numbers, so conditions like "the
+//! ATR is in its 90th percentile" or "price is 2 standard deviations above
+//! its mean" are one-liners.
+//!
+//! Both adapters only start yielding once they've seen `period` values.
+
+use std::collections::VecDeque;
+
+use crate::distance::percentile_rank;
+
+/// Turn an Iterator of f32 into rolling statistics over the last `period` values
+pub trait IntoRollingStats: Iterator<Item = f32> + Sized {
+    /// The fraction (0 to 1) of the last `period` values (including the
+    /// latest) that are less than or equal to the latest value
+    fn rolling_percentile(self, period: usize) -> PercentileRankIter<Self> {
+        PercentileRankIter {
+            window: Window::new(self, period),
+        }
+    }
+
+    /// How many standard deviations the latest value is from the mean of the
+    /// last `period` values (including the latest). 0 if they're all the same
+    fn rolling_z_score(self, period: usize) -> ZScoreIter<Self> {
+        ZScoreIter {
+            window: Window::new(self, period),
+        }
+    }
+}
+
+impl<I> IntoRollingStats for I where I: Iterator<Item = f32> {}
+
+/// The last `period` values from `iter`
+struct Window<I> {
+    iter: I,
+    period: usize,
+    values: VecDeque<f32>,
+}
+
+impl<I> Window<I>
+where
+    I: Iterator<Item = f32>,
+{
+    fn new(iter: I, period: usize) -> Self {
+        Self {
+            iter,
+            period,
+            values: VecDeque::with_capacity(period),
+        }
+    }
+
+    /// Slides the window along until it's full, and returns the latest value
+    fn advance(&mut self) -> Option<f32> {
+        if self.period == 0 {
+            return None;
+        }
+        loop {
+            let value = self.iter.next()?;
+            if self.values.len() == self.period {
+                self.values.pop_front();
+            }
+            self.values.push_back(value);
+            if self.values.len() == self.period {
+                break Some(value);
+            }
+        }
+    }
+}
+
+pub struct PercentileRankIter<I> {
+    window: Window<I>,
+}
+
+impl<I> Iterator for PercentileRankIter<I>
+where
+    I: Iterator<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let latest = self.window.advance()?;
+        percentile_rank(&self.window.values, latest)
+    }
+}
+
+pub struct ZScoreIter<I> {
+    window: Window<I>,
+}
+
+impl<I> Iterator for ZScoreIter<I>
+where
+    I: Iterator<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let latest = self.window.advance()?;
+        let values = &self.window.values;
+        let n = values.len() as f32;
+        let mean = values.iter().sum::<f32>() / n;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / n;
+        let std_dev = variance.sqrt();
+        if std_dev > 0.0 {
+            Some((latest - mean) / std_dev)
+        } else {
+            Some(0.0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn percentile() {
+        let input = [1.0, 2.0, 3.0, 4.0, 0.5, 3.5];
+        let got: Vec<_> = input.into_iter().rolling_percentile(4).collect();
+        // [1, 2, 3, 4] -> 4 is the top
+        // [2, 3, 4, 0.5] -> 0.5 is the bottom
+        // [3, 4, 0.5, 3.5] -> 3.5 beats 3 and 0.5
+        assert_eq!(vec![1.0, 0.25, 0.75], got);
+    }
+
+    #[test]
+    fn z_score() {
+        let input = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+        // The mean is 5 and the standard deviation is 2
+        let got: Vec<_> = input.into_iter().rolling_z_score(8).collect();
+        assert_eq!(vec![2.0], got);
+    }
+
+    #[test]
+    fn flat_z_score() {
+        let got: Vec<_> = [3.0; 4].into_iter().rolling_z_score(2).collect();
+        assert_eq!(vec![0.0; 3], got);
+    }
+
+    #[test]
+    fn zero_period() {
+        assert_eq!(None, [1.0, 2.0].into_iter().rolling_percentile(0).next());
+        assert_eq!(None, [1.0, 2.0].into_iter().rolling_z_score(0).next());
+    }
+}
@@ -0,0 +1,122 @@
+//! Round number (psychological) price levels, like 1.1000 or 1.1050 on
+//! EUR/USD. Lots of orders sit on round numbers, so a support or
+//! resistance level that lines up with one is stronger.
+
+use crate::distance::pip_size;
+
+/// Generates round number levels every `step_pips` pips
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RoundNumbers {
+    /// The location of the pip, eg. -4 for EUR/USD where a pip is 0.0001.
+    /// The same as the oanda instrument's `pip_location`.
+    pub pip_location: i32,
+    /// The gap between levels in pips. eg. 50 or 100
+    pub step_pips: u32,
+}
+
+/// A level that lines up with a round number
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Confluence {
+    /// The level we were given, eg. a support or resistance line
+    pub level: f32,
+    /// The round number it's close to
+    pub round_number: f32,
+}
+
+impl RoundNumbers {
+    pub fn new(pip_location: i32, step_pips: u32) -> Self {
+        Self {
+            pip_location,
+            step_pips,
+        }
+    }
+
+    /// The size of one pip in price units
+    pub fn pip_size(&self) -> f32 {
+        pip_size(self.pip_location)
+    }
+
+    /// The gap between levels in price units
+    pub fn step(&self) -> f32 {
+        self.pip_size() * self.step_pips as f32
+    }
+
+    /// The round number closest to `price`
+    pub fn nearest(&self, price: f32) -> f32 {
+        let step = self.step();
+        (price / step).round() * step
+    }
+
+    /// All the round numbers from `low` to `high` inclusive, lowest first
+    pub fn levels_between(&self, low: f32, high: f32) -> impl Iterator<Item = f32> {
+        let step = self.step();
+        let first = (low / step).ceil() as i64;
+        let last = (high / step).floor() as i64;
+        (first..=last).map(move |n| n as f32 * step)
+    }
+
+    /// Returns the levels that are within `tolerance` (in price units) of a
+    /// round number, along with the round number they line up with.
+    pub fn confluence(
+        &self,
+        levels: impl IntoIterator<Item = f32>,
+        tolerance: f32,
+    ) -> impl Iterator<Item = Confluence> {
+        let round_numbers = *self;
+        levels.into_iter().filter_map(move |level| {
+            let round_number = round_numbers.nearest(level);
+            ((level - round_number).abs() <= tolerance).then_some(Confluence {
+                level,
+                round_number,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 0.00001
+    }
+
+    #[test]
+    fn levels_between() {
+        let round_numbers = RoundNumbers::new(-4, 50);
+        let got: Vec<_> = round_numbers.levels_between(1.0912, 1.1061).collect();
+        assert_eq!(3, got.len(), "{got:?}");
+        for (got, expected) in got.into_iter().zip([1.095, 1.1, 1.105]) {
+            assert!(close(got, expected), "{got} != {expected}");
+        }
+    }
+
+    #[test]
+    fn yen_pairs() {
+        // USD/JPY has a pip location of -2
+        let round_numbers = RoundNumbers::new(-2, 100);
+        let got: Vec<_> = round_numbers.levels_between(148.3, 150.2).collect();
+        assert_eq!(vec![149.0, 150.0], got);
+    }
+
+    #[test]
+    fn nearest() {
+        let round_numbers = RoundNumbers::new(-4, 100);
+        assert!(close(round_numbers.nearest(1.0949), 1.09));
+        assert!(close(round_numbers.nearest(1.0951), 1.10));
+    }
+
+    #[test]
+    fn confluence() {
+        let round_numbers = RoundNumbers::new(-4, 50);
+        // Support and resistance lines
+        let levels = [1.0948, 1.0975, 1.1003];
+        let got: Vec<_> = round_numbers.confluence(levels, 0.0005).collect();
+        assert_eq!(2, got.len(), "{got:?}");
+        assert_eq!(1.0948, got[0].level);
+        assert!(close(got[0].round_number, 1.095));
+        assert_eq!(1.1003, got[1].level);
+        assert!(close(got[1].round_number, 1.1));
+    }
+}
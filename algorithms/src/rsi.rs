@@ -0,0 +1,171 @@
+//! The relative strength index of closes, from 0 to 100, with Wilder's
+//! smoothing. Over 70 is usually read as overbought and under 30 as oversold.
+//!
+//! The first average gain and loss are the simple averages of the first
+//! `period` changes. After that each new change counts for `1 / period`,
+//! which is what Wilder did and what charting packages show.
+
+use crate::Close;
+
+/// A running RSI, fed one close at a time
+#[derive(Debug, PartialEq, Clone)]
+pub struct Rsi {
+    period: usize,
+    previous_close: Option<f32>,
+    /// The changes seen while warming up
+    changes: usize,
+    average_gain: f32,
+    average_loss: f32,
+}
+
+impl Rsi {
+    /// The period Wilder used
+    pub const DEFAULT_PERIOD: usize = 14;
+
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            previous_close: None,
+            changes: 0,
+            average_gain: 0.0,
+            average_loss: 0.0,
+        }
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    /// Adds a close and returns the RSI. None until there have been
+    /// `period + 1` closes, or always if the period is 0
+    pub fn push(&mut self, close: f32) -> Option<f32> {
+        if self.period == 0 {
+            return None;
+        }
+        let previous_close = self.previous_close.replace(close)?;
+        let change = close - previous_close;
+        let (gain, loss) = (change.max(0.0), (-change).max(0.0));
+        let period = self.period as f32;
+        if self.changes < self.period {
+            // Warming up; sum the changes then average them on the last one
+            self.changes += 1;
+            self.average_gain += gain;
+            self.average_loss += loss;
+            if self.changes < self.period {
+                return None;
+            }
+            self.average_gain /= period;
+            self.average_loss /= period;
+        } else {
+            self.average_gain = (self.average_gain * (period - 1.0) + gain) / period;
+            self.average_loss = (self.average_loss * (period - 1.0) + loss) / period;
+        }
+        self.value()
+    }
+
+    /// The RSI so far. None until it's warmed up. A market that hasn't
+    /// moved at all is 50
+    pub fn value(&self) -> Option<f32> {
+        if self.period == 0 || self.changes < self.period {
+            return None;
+        }
+        let total = self.average_gain + self.average_loss;
+        if total == 0.0 {
+            Some(50.0)
+        } else {
+            // The same as 100 - 100 / (1 + RS), without dividing by a zero loss
+            Some(100.0 * self.average_gain / total)
+        }
+    }
+}
+
+/// Turn an Iterator of candles into the RSI of their closes
+pub trait IntoRsiIterator: Iterator + Sized
+where
+    Self::Item: Close,
+{
+    /// The RSI over `period` closes, usually [`Rsi::DEFAULT_PERIOD`].
+    /// Starts yielding on close `period + 1`, one per close after that.
+    /// Yields nothing if `period` is 0
+    fn rsi(self, period: usize) -> RsiIterator<Self> {
+        RsiIterator {
+            candles: self,
+            rsi: Rsi::new(period),
+        }
+    }
+}
+
+impl<I> IntoRsiIterator for I
+where
+    I: Iterator,
+    I::Item: Close,
+{
+}
+
+pub struct RsiIterator<I> {
+    candles: I,
+    rsi: Rsi,
+}
+
+impl<I> Iterator for RsiIterator<I>
+where
+    I: Iterator,
+    I::Item: Close,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rsi.period() == 0 {
+            return None;
+        }
+        loop {
+            let close = self.candles.next()?.close();
+            if let Some(rsi) = self.rsi.push(close) {
+                break Some(rsi);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candle::test_data::closes;
+
+    #[test]
+    fn wilder_smoothing() {
+        // Changes: +2, -1, +1 then -2
+        let candles = closes([10.0, 12.0, 11.0, 12.0, 10.0]);
+        let got: Vec<f32> = candles.iter().rsi(3).collect();
+        // The first averages are simple: gain 3 / 3 = 1, loss 1 / 3
+        // Then smoothed: gain (1 * 2 + 0) / 3 = 2 / 3, loss (1 / 3 * 2 + 2) / 3 = 8 / 9
+        let expected = [100.0 * 1.0 / (1.0 + 1.0 / 3.0), 100.0 * 6.0 / 14.0];
+        assert_eq!(expected.len(), got.len());
+        for (expected, got) in expected.into_iter().zip(got) {
+            assert!((expected - got).abs() < 0.0001, "{expected} {got}");
+        }
+    }
+
+    #[test]
+    fn extremes() {
+        let rising = closes([1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(Some(100.0), rising.iter().rsi(3).last());
+        let falling = closes([4.0, 3.0, 2.0, 1.0]);
+        assert_eq!(Some(0.0), falling.iter().rsi(3).last());
+        let flat = closes([1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(Some(50.0), flat.iter().rsi(3).last());
+    }
+
+    #[test]
+    fn warm_up() {
+        let candles = closes([1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert_eq!(0, candles[..3].iter().rsi(3).count());
+        assert_eq!(2, candles.iter().rsi(3).count());
+        assert_eq!(0, candles.iter().rsi(0).count());
+        let mut rsi = Rsi::new(Rsi::DEFAULT_PERIOD);
+        assert!(candles
+            .iter()
+            .all(|candle| rsi.push(candle.close).is_none()));
+        assert_eq!(None, rsi.value());
+    }
+}
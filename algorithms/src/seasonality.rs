@@ -0,0 +1,150 @@
+//! Time of day and day of week statistics. Markets are much quieter at
+//! some hours than others; this builds a profile from historical candles
+//! so the trader can stay out of the dead hours.
+//!
+//! All times are in UTC.
+
+use chrono::{DateTime, Datelike, Timelike, Utc, Weekday};
+
+use crate::candle::{Close, High, Low, Open};
+
+/// The averages for all the candles that fell in one hour or weekday
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct SeasonalityBucket {
+    /// The number of candles
+    pub count: usize,
+    /// The average high - low
+    pub average_range: f32,
+    /// The average (close - open) / open
+    pub average_return: f32,
+}
+
+impl SeasonalityBucket {
+    fn add(&mut self, range: f32, r#return: f32) {
+        // Keep a running average so we don't have to store the sums
+        self.count += 1;
+        let count = self.count as f32;
+        self.average_range += (range - self.average_range) / count;
+        self.average_return += (r#return - self.average_return) / count;
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct SeasonalityProfile {
+    /// Indexed by hour of the day, 0 to 23
+    by_hour: [SeasonalityBucket; 24],
+    /// Indexed by days from Monday
+    by_weekday: [SeasonalityBucket; 7],
+    overall: SeasonalityBucket,
+}
+
+impl SeasonalityProfile {
+    /// Builds the profile from candles and their open times
+    pub fn new<C>(candles: impl IntoIterator<Item = (DateTime<Utc>, C)>) -> Self
+    where
+        C: High + Low + Open + Close,
+    {
+        let mut profile = Self::default();
+        for (time, candle) in candles {
+            let range = candle.high() - candle.low();
+            let r#return = (candle.close() - candle.open()) / candle.open();
+            profile.by_hour[time.hour() as usize].add(range, r#return);
+            profile.by_weekday[time.weekday().num_days_from_monday() as usize].add(range, r#return);
+            profile.overall.add(range, r#return);
+        }
+        profile
+    }
+
+    /// The stats for an hour of the day (0 to 23). None if we've never seen it
+    pub fn hour(&self, hour: u32) -> Option<&SeasonalityBucket> {
+        self.by_hour
+            .get(hour as usize)
+            .filter(|bucket| bucket.count > 0)
+    }
+
+    /// The stats for a day of the week. None if we've never seen it
+    pub fn weekday(&self, weekday: Weekday) -> Option<&SeasonalityBucket> {
+        Some(&self.by_weekday[weekday.num_days_from_monday() as usize])
+            .filter(|bucket| bucket.count > 0)
+    }
+
+    /// The stats over every candle
+    pub fn overall(&self) -> &SeasonalityBucket {
+        &self.overall
+    }
+
+    /// The hours whose average range is less than `fraction` of the overall
+    /// average range. Hours we've never seen are left out.
+    pub fn dead_hours(&self, fraction: f32) -> Vec<u32> {
+        (0..24)
+            .filter(|&hour| self.is_dead_hour(hour, fraction))
+            .collect()
+    }
+
+    /// True if the hour's average range is less than `fraction` of the
+    /// overall average range
+    pub fn is_dead_hour(&self, hour: u32, fraction: f32) -> bool {
+        self.hour(hour)
+            .is_some_and(|bucket| bucket.average_range < self.overall.average_range * fraction)
+    }
+
+    /// True if `time` falls in a dead hour. See [`Self::dead_hours`]
+    pub fn is_dead_time(&self, time: DateTime<Utc>, fraction: f32) -> bool {
+        self.is_dead_hour(time.hour(), fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candle::test_data::Candle;
+    use chrono::{Duration, TimeZone};
+    use pretty_assertions::assert_eq;
+
+    /// Two days of hourly candles. Hours 0 to 5 are quiet
+    fn candles() -> Vec<(DateTime<Utc>, Candle)> {
+        // 2023-03-06 is a Monday
+        let start = Utc.with_ymd_and_hms(2023, 3, 6, 0, 0, 0).unwrap();
+        (0..48)
+            .map(|n| {
+                let time = start + Duration::hours(n);
+                let candle = if time.hour() < 6 {
+                    Candle::new(10.1, 10.0, 10.0, 10.0)
+                } else {
+                    Candle::new(11.0, 9.0, 10.0, 10.5)
+                };
+                (time, candle)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn by_hour() {
+        let profile = SeasonalityProfile::new(candles());
+        let quiet = profile.hour(3).unwrap();
+        assert_eq!(2, quiet.count);
+        assert!((quiet.average_range - 0.1).abs() < 0.0001);
+        assert_eq!(0.0, quiet.average_return);
+        let busy = profile.hour(14).unwrap();
+        assert_eq!(2.0, busy.average_range);
+        assert_eq!(0.05, busy.average_return);
+        assert_eq!(None, profile.hour(24));
+    }
+
+    #[test]
+    fn by_weekday() {
+        let profile = SeasonalityProfile::new(candles());
+        assert_eq!(24, profile.weekday(Weekday::Mon).unwrap().count);
+        assert_eq!(24, profile.weekday(Weekday::Tue).unwrap().count);
+        assert_eq!(None, profile.weekday(Weekday::Sat));
+        assert_eq!(48, profile.overall().count);
+    }
+
+    #[test]
+    fn dead_hours() {
+        let profile = SeasonalityProfile::new(candles());
+        assert_eq!(vec![0, 1, 2, 3, 4, 5], profile.dead_hours(0.5));
+        let time = Utc.with_ymd_and_hms(2023, 3, 10, 4, 30, 0).unwrap();
+        assert!(profile.is_dead_time(time, 0.5));
+    }
+}
@@ -0,0 +1,226 @@
+//! A time series: values paired with the time they happened.
+//!
+//! Indicators warm up over different numbers of candles, and two
+//! instruments don't always have candles at the same times, so lining
+//! things up by index is error prone. A [`Series`] lines things up by
+//! time instead, carrying the last known value forward over any gaps.
+
+use chrono::{DateTime, Utc};
+
+use crate::Error;
+
+/// Values in time order
+#[derive(Debug, PartialEq, Clone)]
+pub struct Series<T> {
+    points: Vec<(DateTime<Utc>, T)>,
+}
+
+impl<T> Default for Series<T> {
+    fn default() -> Self {
+        Self { points: Vec::new() }
+    }
+}
+
+impl<T> Series<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a value to the end of the series
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::OutOfOrder`] if `time` isn't after the last time in the series
+    pub fn push(&mut self, time: DateTime<Utc>, value: T) -> Result<(), Error> {
+        match self.points.last() {
+            Some((last, _)) if *last >= time => Err(Error::OutOfOrder),
+            _ => {
+                self.points.push((time, value));
+                Ok(())
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// The time and value pairs, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = (DateTime<Utc>, &T)> + '_ {
+        self.points.iter().map(|(time, value)| (*time, value))
+    }
+
+    pub fn times(&self) -> impl Iterator<Item = DateTime<Utc>> + '_ {
+        self.points.iter().map(|(time, _)| *time)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> + '_ {
+        self.points.iter().map(|(_, value)| value)
+    }
+
+    pub fn first(&self) -> Option<(DateTime<Utc>, &T)> {
+        self.points.first().map(|(time, value)| (*time, value))
+    }
+
+    pub fn last(&self) -> Option<(DateTime<Utc>, &T)> {
+        self.points.last().map(|(time, value)| (*time, value))
+    }
+
+    /// The value at exactly `time`, if there is one
+    pub fn get(&self, time: DateTime<Utc>) -> Option<&T> {
+        self.points
+            .binary_search_by_key(&time, |(time, _)| *time)
+            .ok()
+            .map(|index| &self.points[index].1)
+    }
+
+    /// The last value at or before `time`; ie. forward filled.
+    /// Returns None if `time` is before the start of the series.
+    pub fn value_at(&self, time: DateTime<Utc>) -> Option<&T> {
+        let after = self.points.partition_point(|(point, _)| *point <= time);
+        after.checked_sub(1).map(|index| &self.points[index].1)
+    }
+
+    /// Pairs every value in this series with the value from `other` at the
+    /// same time, forward filling `other`. Times before `other` has any
+    /// values (eg. while it's warming up) are dropped.
+    pub fn align<U>(&self, other: &Series<U>) -> Series<(T, U)>
+    where
+        T: Clone,
+        U: Clone,
+    {
+        self.points
+            .iter()
+            .filter_map(|(time, value)| {
+                other
+                    .value_at(*time)
+                    .map(|other| (*time, (value.clone(), other.clone())))
+            })
+            .collect()
+    }
+
+    /// Pairs the values from both series at every time that's in either of
+    /// them, forward filling whichever one doesn't have a value at that
+    /// time. Starts once both series have a value.
+    pub fn join<U>(&self, other: &Series<U>) -> Series<(T, U)>
+    where
+        T: Clone,
+        U: Clone,
+    {
+        let mut times: Vec<_> = self.times().chain(other.times()).collect();
+        times.sort();
+        times.dedup();
+        times
+            .into_iter()
+            .filter_map(|time| {
+                self.value_at(time)
+                    .zip(other.value_at(time))
+                    .map(|(a, b)| (time, (a.clone(), b.clone())))
+            })
+            .collect()
+    }
+
+    /// Applies `f` to every value, keeping the times
+    pub fn map<U>(&self, mut f: impl FnMut(&T) -> U) -> Series<U> {
+        Series {
+            points: self
+                .points
+                .iter()
+                .map(|(time, value)| (*time, f(value)))
+                .collect(),
+        }
+    }
+}
+
+/// Collects `(time, value)` pairs in any order. They're sorted by time,
+/// and if a time appears more than once the last value for it wins.
+impl<T> FromIterator<(DateTime<Utc>, T)> for Series<T> {
+    fn from_iter<I: IntoIterator<Item = (DateTime<Utc>, T)>>(iter: I) -> Self {
+        let mut points: Vec<_> = iter.into_iter().collect();
+        points.sort_by_key(|(time, _)| *time);
+        // dedup_by keeps the first of each run, so do it backwards to keep the last
+        points.reverse();
+        points.dedup_by_key(|(time, _)| *time);
+        points.reverse();
+        Self { points }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use pretty_assertions::assert_eq;
+
+    fn time(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2023, 3, 1, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn push_in_order() {
+        let mut series = Series::new();
+        series.push(time(1), 1.0).unwrap();
+        series.push(time(2), 2.0).unwrap();
+        assert_eq!(Err(Error::OutOfOrder), series.push(time(2), 3.0));
+        assert_eq!(Err(Error::OutOfOrder), series.push(time(0), 3.0));
+        assert_eq!(2, series.len());
+    }
+
+    #[test]
+    fn from_iter_sorts() {
+        let series: Series<_> = [(time(3), 'c'), (time(1), 'a'), (time(3), 'd')]
+            .into_iter()
+            .collect();
+        assert_eq!(vec![time(1), time(3)], series.times().collect::<Vec<_>>());
+        assert_eq!(Some(&'d'), series.get(time(3)));
+    }
+
+    #[test]
+    fn value_at_forward_fills() {
+        let series: Series<_> = [(time(1), 1), (time(3), 3)].into_iter().collect();
+        assert_eq!(None, series.value_at(time(0)));
+        assert_eq!(Some(&1), series.value_at(time(1)));
+        assert_eq!(Some(&1), series.value_at(time(2)));
+        assert_eq!(None, series.get(time(2)));
+        assert_eq!(Some(&3), series.value_at(time(4)));
+    }
+
+    #[test]
+    fn align() {
+        let price: Series<_> = (1..=5).map(|hour| (time(hour), hour as f32)).collect();
+        // An indicator that took two candles to warm up and missed hour 4
+        let indicator: Series<_> = [(time(3), 30.0), (time(5), 50.0)].into_iter().collect();
+        let got: Vec<_> = price
+            .align(&indicator)
+            .iter()
+            .map(|(time, value)| (time, *value))
+            .collect();
+        let expected = vec![
+            (time(3), (3.0, 30.0)),
+            (time(4), (4.0, 30.0)),
+            (time(5), (5.0, 50.0)),
+        ];
+        assert_eq!(expected, got);
+    }
+
+    #[test]
+    fn join() {
+        let a: Series<_> = [(time(1), 'a'), (time(3), 'b')].into_iter().collect();
+        let b: Series<_> = [(time(2), 1), (time(4), 2)].into_iter().collect();
+        let got: Vec<_> = a
+            .join(&b)
+            .iter()
+            .map(|(time, value)| (time, *value))
+            .collect();
+        let expected = vec![
+            (time(2), ('a', 1)),
+            (time(3), ('b', 1)),
+            (time(4), ('b', 2)),
+        ];
+        assert_eq!(expected, got);
+    }
+}
@@ -0,0 +1,154 @@
+//! Statistics over how past signals turned out, so different setups (eg.
+//! each [`SwingType`](crate::SwingType)) can be compared by the numbers.
+//!
+//! Everything is measured in R multiples, where 1R is the amount risked
+//! on the signal (the distance from entry to the stop loss).
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::atr::Average;
+
+/// How one signal turned out
+#[derive(Debug, PartialEq, Clone)]
+pub struct SignalOutcome<K> {
+    /// The kind of setup that produced the signal
+    pub setup: K,
+    /// The result when the trade was closed, in R
+    pub r: f32,
+    /// Maximum favourable excursion; the furthest price went our way, in R
+    pub mfe: f32,
+    /// Maximum adverse excursion; the furthest price went against us, in R.
+    /// Stored as a positive number
+    pub mae: f32,
+}
+
+/// Summary statistics for a group of signal outcomes
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SignalStats {
+    /// The number of signals
+    pub count: usize,
+    /// The fraction of signals that made money, from 0 to 1
+    pub hit_rate: f32,
+    /// The average R of the signals that made money. 0 if there weren't any
+    pub average_win: f32,
+    /// The average R of the signals that lost money. This is negative, or 0
+    /// if there weren't any
+    pub average_loss: f32,
+    /// The R we expect to make per signal:
+    /// hit_rate * average_win + loss_rate * average_loss
+    pub expectancy: f32,
+    pub average_mfe: f32,
+    pub average_mae: f32,
+}
+
+impl SignalStats {
+    /// Works out the stats for some outcomes. Returns None if there aren't any
+    pub fn new<'a, K: 'a>(
+        outcomes: impl IntoIterator<Item = &'a SignalOutcome<K>>,
+    ) -> Option<Self> {
+        let outcomes: Vec<_> = outcomes.into_iter().collect();
+        let count = outcomes.len();
+        if count == 0 {
+            return None;
+        }
+        let wins: Vec<f32> = outcomes.iter().map(|o| o.r).filter(|&r| r > 0.0).collect();
+        let losses: Vec<f32> = outcomes.iter().map(|o| o.r).filter(|&r| r < 0.0).collect();
+        let hit_rate = wins.len() as f32 / count as f32;
+        let loss_rate = losses.len() as f32 / count as f32;
+        let average_win = wins.into_iter().average().unwrap_or_default();
+        let average_loss = losses.into_iter().average().unwrap_or_default();
+        Some(Self {
+            count,
+            hit_rate,
+            average_win,
+            average_loss,
+            expectancy: hit_rate * average_win + loss_rate * average_loss,
+            average_mfe: outcomes.iter().map(|o| o.mfe).average()?,
+            average_mae: outcomes.iter().map(|o| o.mae).average()?,
+        })
+    }
+}
+
+/// Groups the outcomes by their setup and works out the stats for each
+pub fn stats_by_setup<K>(outcomes: &[SignalOutcome<K>]) -> HashMap<K, SignalStats>
+where
+    K: Hash + Eq + Clone,
+{
+    let mut groups: HashMap<K, Vec<&SignalOutcome<K>>> = HashMap::new();
+    for outcome in outcomes {
+        groups
+            .entry(outcome.setup.clone())
+            .or_default()
+            .push(outcome);
+    }
+    groups
+        .into_iter()
+        .flat_map(|(setup, outcomes)| SignalStats::new(outcomes).map(|stats| (setup, stats)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SwingType;
+    use pretty_assertions::assert_eq;
+
+    fn outcome(setup: SwingType, r: f32) -> SignalOutcome<SwingType> {
+        SignalOutcome {
+            setup,
+            r,
+            mfe: r.max(0.0) + 0.5,
+            mae: (-r).max(0.0) + 0.5,
+        }
+    }
+
+    #[test]
+    fn stats() {
+        let outcomes = [
+            outcome(SwingType::HigherLow, 2.0),
+            outcome(SwingType::HigherLow, -1.0),
+            outcome(SwingType::HigherLow, 3.0),
+            outcome(SwingType::HigherLow, -1.0),
+        ];
+        let expected = SignalStats {
+            count: 4,
+            hit_rate: 0.5,
+            average_win: 2.5,
+            average_loss: -1.0,
+            expectancy: 0.75,
+            average_mfe: 1.75,
+            average_mae: 1.0,
+        };
+        assert_eq!(Some(expected), SignalStats::new(&outcomes));
+    }
+
+    #[test]
+    fn no_outcomes() {
+        let outcomes: [SignalOutcome<SwingType>; 0] = [];
+        assert_eq!(None, SignalStats::new(&outcomes));
+    }
+
+    #[test]
+    fn break_even_is_neither_win_nor_loss() {
+        let outcomes = [outcome(SwingType::Hold, 0.0), outcome(SwingType::Hold, 1.0)];
+        let stats = SignalStats::new(&outcomes).unwrap();
+        assert_eq!(0.5, stats.hit_rate);
+        assert_eq!(0.0, stats.average_loss);
+        assert_eq!(0.5, stats.expectancy);
+    }
+
+    #[test]
+    fn by_setup() {
+        let outcomes = [
+            outcome(SwingType::HigherLow, 2.0),
+            outcome(SwingType::LowerHigh, -1.0),
+            outcome(SwingType::HigherLow, -1.0),
+        ];
+        let stats = stats_by_setup(&outcomes);
+        assert_eq!(2, stats.len());
+        assert_eq!(2, stats[&SwingType::HigherLow].count);
+        assert_eq!(0.5, stats[&SwingType::HigherLow].expectancy);
+        assert_eq!(0.0, stats[&SwingType::LowerHigh].hit_rate);
+    }
+}
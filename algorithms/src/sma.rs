@@ -0,0 +1,108 @@
+//! Simple moving average of closes over a rolling window.
+
+use crate::{Close, RingBuffer};
+
+/// A running simple average of the last `period` values, fed one at a time
+#[derive(Debug, PartialEq, Clone)]
+pub struct Sma {
+    window: RingBuffer,
+}
+
+impl Sma {
+    pub fn new(period: usize) -> Self {
+        Self {
+            window: RingBuffer::new(period),
+        }
+    }
+
+    pub fn period(&self) -> usize {
+        self.window.capacity()
+    }
+
+    /// Adds a value and returns the average of the last `period`. None
+    /// until there have been `period` values, or always if the period is 0
+    pub fn push(&mut self, value: f32) -> Option<f32> {
+        self.window.push(value);
+        self.value()
+    }
+
+    /// The average of the last `period` values, once there have been that many
+    pub fn value(&self) -> Option<f32> {
+        if self.window.is_full() {
+            self.window.mean()
+        } else {
+            None
+        }
+    }
+}
+
+/// Turn an Iterator of candles into the simple moving average of their closes
+pub trait IntoSmaIterator: Iterator + Sized
+where
+    Self::Item: Close,
+{
+    /// One item per candle: None until `period` closes are in, then the
+    /// average of the last `period`. All None if `period` is 0
+    fn sma(self, period: usize) -> SmaIterator<Self> {
+        SmaIterator {
+            candles: self,
+            sma: Sma::new(period),
+        }
+    }
+}
+
+impl<I> IntoSmaIterator for I
+where
+    I: Iterator,
+    I::Item: Close,
+{
+}
+
+pub struct SmaIterator<I> {
+    candles: I,
+    sma: Sma,
+}
+
+impl<I> Iterator for SmaIterator<I>
+where
+    I: Iterator,
+    I::Item: Close,
+{
+    type Item = Option<f32>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let close = self.candles.next()?.close();
+        Some(self.sma.push(close))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.candles.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candle::test_data::closes;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn rolling_average() {
+        let candles = closes([1.0, 2.0, 3.0, 4.0, 8.0]);
+        assert_eq!(
+            vec![None, None, Some(2.0), Some(3.0), Some(5.0)],
+            candles.iter().sma(3).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn one_per_candle() {
+        let candles = closes([1.0, 2.0]);
+        assert_eq!(vec![None, None], candles.iter().sma(3).collect::<Vec<_>>());
+        assert_eq!(vec![None, None], candles.iter().sma(0).collect::<Vec<_>>());
+        assert_eq!(
+            vec![Some(1.0), Some(2.0)],
+            candles.iter().sma(1).collect::<Vec<_>>()
+        );
+    }
+}
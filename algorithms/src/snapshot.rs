@@ -0,0 +1,187 @@
+//! Everything that went into finding a pair of levels, in one value that
+//! can be saved as JSON or MessagePack. Archiving one with each trading
+//! decision lets us re-render the chart, or re-run the analysis, later.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    pivots, Close, Error, High, IntoRenkoIterator, IntoSwingStatusIter, Low, Open, Pivot,
+    RenkoCandle, RenkoReversal, SwingStatus,
+};
+
+/// The prices of one of the candles a snapshot was made from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotCandle {
+    pub open: f32,
+    pub high: f32,
+    pub low: f32,
+    pub close: f32,
+}
+
+impl SnapshotCandle {
+    pub fn new(candle: &(impl Open + High + Low + Close)) -> Self {
+        Self {
+            open: candle.open(),
+            high: candle.high(),
+            low: candle.low(),
+            close: candle.close(),
+        }
+    }
+}
+
+impl Open for SnapshotCandle {
+    fn open(&self) -> f32 {
+        self.open
+    }
+}
+
+impl High for SnapshotCandle {
+    fn high(&self) -> f32 {
+        self.high
+    }
+}
+
+impl Low for SnapshotCandle {
+    fn low(&self) -> f32 {
+        self.low
+    }
+}
+
+impl Close for SnapshotCandle {
+    fn close(&self) -> f32 {
+        self.close
+    }
+}
+
+/// The candles, renko bricks, pivots, swings and resulting levels of one
+/// run of the analysis
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisSnapshot {
+    pub candles: Vec<SnapshotCandle>,
+    pub brick_size: f32,
+    pub reversal: RenkoReversal,
+    /// The pivot window size, in bricks
+    pub window_size: usize,
+    /// The bricks made from the candle closes
+    pub bricks: Vec<RenkoCandle>,
+    /// One per brick
+    pub pivots: Vec<Pivot>,
+    /// One per pivot
+    pub swings: Vec<SwingStatus>,
+    pub support: Option<f32>,
+    pub resistance: Option<f32>,
+}
+
+impl AnalysisSnapshot {
+    /// Runs the analysis on `candles`: renko bricks of `brick_size` made
+    /// from the closes, pivots over windows of `window_size` bricks, then
+    /// the high/low swings of the pivots
+    ///
+    /// # Errors
+    ///
+    /// The same as [`pivots`], eg. if there aren't enough bricks to fill a
+    /// window
+    pub fn capture(
+        candles: &[impl Open + High + Low + Close],
+        brick_size: f32,
+        reversal: RenkoReversal,
+        window_size: usize,
+    ) -> Result<Self, Error> {
+        let bricks: Vec<RenkoCandle> = candles
+            .iter()
+            .map(Close::close)
+            .renko_with_reversal(brick_size, reversal)
+            .collect();
+        let pivots: Vec<Pivot> = pivots(&bricks, window_size)?.collect();
+        let swings: Vec<SwingStatus> = pivots.iter().cloned().high_low_swing().collect();
+        let (support, resistance) = swings
+            .last()
+            .map(|swing| (swing.support, swing.resistance))
+            .unwrap_or_default();
+        Ok(Self {
+            candles: candles.iter().map(SnapshotCandle::new).collect(),
+            brick_size,
+            reversal,
+            window_size,
+            bricks,
+            pivots,
+            swings,
+            support,
+            resistance,
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String, Error> {
+        serde_json::to_string(self).map_err(|err| Error::Snapshot(err.to_string()))
+    }
+
+    pub fn from_json(input: &str) -> Result<Self, Error> {
+        serde_json::from_str(input).map_err(|err| Error::Snapshot(err.to_string()))
+    }
+
+    /// The snapshot as MessagePack, which is a lot smaller than JSON
+    pub fn to_msgpack(&self) -> Result<Vec<u8>, Error> {
+        rmp_serde::to_vec_named(self).map_err(|err| Error::Snapshot(err.to_string()))
+    }
+
+    pub fn from_msgpack(input: &[u8]) -> Result<Self, Error> {
+        rmp_serde::from_slice(input).map_err(|err| Error::Snapshot(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candle::test_data::Candle;
+    use pretty_assertions::assert_eq;
+
+    fn snapshot() -> AnalysisSnapshot {
+        let closes = [1.0, 3.0, 5.0, 7.0, 5.0, 2.0, 4.0, 6.0, 9.0, 12.0, 8.0, 4.0];
+        let candles: Vec<_> = closes
+            .iter()
+            .map(|close| Candle::new(close + 0.5, close - 0.5, *close, *close))
+            .collect();
+        AnalysisSnapshot::capture(&candles, 1.0, RenkoReversal::Classic, 3).unwrap()
+    }
+
+    #[test]
+    fn capture() {
+        let snapshot = snapshot();
+        assert_eq!(12, snapshot.candles.len());
+        assert_eq!(snapshot.bricks.len(), snapshot.pivots.len());
+        assert_eq!(snapshot.pivots.len(), snapshot.swings.len());
+        let last = snapshot.swings.last().unwrap();
+        assert_eq!(
+            (last.support, last.resistance),
+            (snapshot.support, snapshot.resistance)
+        );
+    }
+
+    #[test]
+    fn json() {
+        let snapshot = snapshot();
+        let json = snapshot.to_json().unwrap();
+        assert_eq!(snapshot, AnalysisSnapshot::from_json(&json).unwrap());
+    }
+
+    #[test]
+    fn msgpack() {
+        let snapshot = snapshot();
+        let msgpack = snapshot.to_msgpack().unwrap();
+        assert_eq!(snapshot, AnalysisSnapshot::from_msgpack(&msgpack).unwrap());
+        assert!(msgpack.len() < snapshot.to_json().unwrap().len());
+    }
+
+    #[test]
+    fn not_enough_bricks() {
+        let candles = [Candle::new(1.0, 1.0, 1.0, 1.0)];
+        assert!(matches!(
+            AnalysisSnapshot::capture(&candles, 1.0, RenkoReversal::Classic, 3),
+            Err(Error::WindowTooBig { .. })
+        ));
+        assert!(matches!(
+            AnalysisSnapshot::from_json("{}"),
+            Err(Error::Snapshot(_))
+        ));
+    }
+}
@@ -0,0 +1,174 @@
+//! Different ways to pick a stop loss price, behind one enum so the
+//! trader can switch between them with configuration.
+
+use std::fmt::Debug as Dbg;
+
+use crate::{
+    candle::{Close, High, Low},
+    pivots, Atr, IntoSwingStatusIter, SwingType, TradeDirection,
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StopPlacement {
+    /// Just past the last swing in our favour; the last higher low for a
+    /// long, or the last lower high for a short
+    Swing {
+        /// The window size to find pivots with
+        window_size: usize,
+    },
+    /// `multiplier` ATRs away from the entry
+    Atr {
+        /// The number of candles to average the true range over
+        period: usize,
+        multiplier: f32,
+    },
+    /// The other side of the Donchian channel; the lowest low of the last
+    /// `period` candles for a long, or the highest high for a short
+    Donchian { period: usize },
+}
+
+impl StopPlacement {
+    /// Suggests a stop loss price for a trade entered at `entry` after
+    /// `candles` (oldest first).
+    ///
+    /// Returns None if there aren't enough candles, or the stop would end
+    /// up on the wrong side of the entry.
+    pub fn stop_loss<C>(&self, candles: &[C], direction: TradeDirection, entry: f32) -> Option<f32>
+    where
+        C: High + Low + Close + Dbg,
+    {
+        let stop = match *self {
+            StopPlacement::Swing { window_size } => swing_stop(candles, window_size, direction)?,
+            StopPlacement::Atr { period, multiplier } => {
+                if period == 0 || period > candles.len() {
+                    return None;
+                }
+                let atr = candles[(candles.len() - period)..].iter().atr()?;
+                match direction {
+                    TradeDirection::Long => entry - atr * multiplier,
+                    TradeDirection::Short => entry + atr * multiplier,
+                }
+            }
+            StopPlacement::Donchian { period } => {
+                if period == 0 || period > candles.len() {
+                    return None;
+                }
+                let recent = candles[(candles.len() - period)..].iter();
+                match direction {
+                    TradeDirection::Long => recent.map(Low::low).reduce(f32::min)?,
+                    TradeDirection::Short => recent.map(High::high).reduce(f32::max)?,
+                }
+            }
+        };
+        let right_side = match direction {
+            TradeDirection::Long => stop < entry,
+            TradeDirection::Short => stop > entry,
+        };
+        right_side.then_some(stop)
+    }
+}
+
+/// The last higher low (for longs) or lower high (for shorts)
+fn swing_stop<C>(candles: &[C], window_size: usize, direction: TradeDirection) -> Option<f32>
+where
+    C: High + Low + Dbg,
+{
+    let swings = pivots(candles, window_size).ok()?.high_low_swing();
+    match direction {
+        TradeDirection::Long => {
+            swings
+                .filter(|status| status.swing_type == SwingType::HigherLow)
+                .last()?
+                .support
+        }
+        TradeDirection::Short => {
+            swings
+                .filter(|status| status.swing_type == SwingType::LowerHigh)
+                .last()?
+                .resistance
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candle::test_data::{test_data_1, Candle};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn atr() {
+        let candles = test_data_1();
+        // The last 2 true ranges are 5 and 6
+        let placement = StopPlacement::Atr {
+            period: 2,
+            multiplier: 2.0,
+        };
+        assert_eq!(
+            Some(9.0),
+            placement.stop_loss(&candles, TradeDirection::Long, 20.0)
+        );
+        assert_eq!(
+            Some(31.0),
+            placement.stop_loss(&candles, TradeDirection::Short, 20.0)
+        );
+    }
+
+    #[test]
+    fn donchian() {
+        let candles = test_data_1();
+        let placement = StopPlacement::Donchian { period: 3 };
+        assert_eq!(
+            Some(4.0),
+            placement.stop_loss(&candles, TradeDirection::Long, 8.0)
+        );
+        assert_eq!(
+            Some(12.0),
+            placement.stop_loss(&candles, TradeDirection::Short, 8.0)
+        );
+        // A long entered under the channel has no sensible stop
+        assert_eq!(
+            None,
+            placement.stop_loss(&candles, TradeDirection::Long, 3.0)
+        );
+    }
+
+    #[test]
+    fn swing() {
+        // Lows make pivots at 5, then a higher low at 6
+        let candles = [
+            Candle::new(10.0, 7.0, 8.0, 8.0),
+            Candle::new(9.0, 5.0, 8.0, 8.0),
+            Candle::new(10.0, 7.0, 8.0, 8.0),
+            Candle::new(11.0, 8.0, 8.0, 8.0),
+            Candle::new(10.0, 6.0, 8.0, 8.0),
+            Candle::new(12.0, 8.0, 8.0, 8.0),
+            Candle::new(13.0, 9.0, 8.0, 8.0),
+        ];
+        let placement = StopPlacement::Swing { window_size: 3 };
+        assert_eq!(
+            Some(6.0),
+            placement.stop_loss(&candles, TradeDirection::Long, 12.0)
+        );
+        // There's no lower high to put a short's stop over
+        assert_eq!(
+            None,
+            placement.stop_loss(&candles, TradeDirection::Short, 12.0)
+        );
+    }
+
+    #[test]
+    fn not_enough_candles() {
+        let candles = test_data_1();
+        let placement = StopPlacement::Donchian { period: 10 };
+        assert_eq!(
+            None,
+            placement.stop_loss(&candles, TradeDirection::Long, 8.0)
+        );
+        let placement = StopPlacement::Swing { window_size: 10 };
+        assert_eq!(
+            None,
+            placement.stop_loss(&candles, TradeDirection::Long, 8.0)
+        );
+    }
+}
@@ -0,0 +1,156 @@
+//! Swing failure patterns: price wicks beyond the last pivot high or low
+//! but closes back inside it. Those stops got run and the breakout failed,
+//! which is a common reversal trigger around support and resistance.
+
+use crate::{
+    candle::{Close, High, Low},
+    pivots, Error,
+};
+use std::fmt::Debug as Dbg;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum SwingFailure {
+    /// Price wicked above the last pivot high but closed back below it
+    Bearish {
+        /// The pivot high that was swept
+        level: f32,
+        /// The high of the candle that swept it
+        wick: f32,
+    },
+    /// Price wicked below the last pivot low but closed back above it
+    Bullish {
+        /// The pivot low that was swept
+        level: f32,
+        /// The low of the candle that swept it
+        wick: f32,
+    },
+    /// No swing failure on this candle. A tall candle that fails both ways
+    /// at once doesn't tell us a direction so it's reported as this too.
+    NoChange,
+}
+
+impl SwingFailure {
+    pub fn is_bearish(&self) -> bool {
+        matches!(self, SwingFailure::Bearish { .. })
+    }
+
+    pub fn is_bullish(&self) -> bool {
+        matches!(self, SwingFailure::Bullish { .. })
+    }
+
+    pub fn is_no_change(&self) -> bool {
+        matches!(self, SwingFailure::NoChange)
+    }
+}
+
+/// Finds swing failures against the pivots found by [`pivots`] with
+/// `window_size`. Yields one [`SwingFailure`] per input candle.
+///
+/// A pivot is only used from the candle after it was confirmed, so
+/// there's no lookahead. A pivot level stops being used once a candle
+/// closes beyond it, as that's a real breakout, not a failed one.
+///
+/// # Errors
+///
+/// The same as [`pivots`]
+pub fn swing_failures<C>(
+    input: &[C],
+    window_size: usize,
+) -> Result<impl Iterator<Item = SwingFailure> + '_, Error>
+where
+    C: High + Low + Close + Dbg,
+{
+    let pivots = pivots(input, window_size)?;
+    let levels: (Option<f32>, Option<f32>) = (None, None);
+    Ok(input
+        .iter()
+        .zip(pivots)
+        .scan(levels, |(high_level, low_level), (candle, pivot)| {
+            let close = candle.close();
+            let bearish = high_level
+                .filter(|&level| candle.high() > level && close < level)
+                .map(|level| SwingFailure::Bearish {
+                    level,
+                    wick: candle.high(),
+                });
+            let bullish = low_level
+                .filter(|&level| candle.low() < level && close > level)
+                .map(|level| SwingFailure::Bullish {
+                    level,
+                    wick: candle.low(),
+                });
+            // Closing beyond a level breaks it
+            if high_level.is_some_and(|level| close > level) {
+                *high_level = None;
+            }
+            if low_level.is_some_and(|level| close < level) {
+                *low_level = None;
+            }
+            // This candle confirmed the pivot, so it can be used from the next candle on
+            if let Some(high) = pivot.high() {
+                *high_level = Some(high);
+            }
+            if let Some(low) = pivot.low() {
+                *low_level = Some(low);
+            }
+            Some(match (bearish, bullish) {
+                (Some(bearish), None) => bearish,
+                (None, Some(bullish)) => bullish,
+                _ => SwingFailure::NoChange,
+            })
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candle::test_data::Candle;
+    use pretty_assertions::assert_eq;
+
+    fn candles() -> Vec<Candle> {
+        vec![
+            Candle::new(10.0, 5.0, 8.0, 7.0),
+            Candle::new(12.0, 6.0, 9.0, 8.0),   // pivot high
+            Candle::new(9.0, 5.0, 8.0, 7.0),    // pivot low
+            Candle::new(13.0, 8.0, 9.0, 11.0),  // sweeps 12, closes under it
+            Candle::new(14.0, 9.0, 12.0, 13.0), // closes over 12, breaking it
+            Candle::new(13.0, 4.0, 10.0, 6.0),  // sweeps 5, closes over it
+        ]
+    }
+
+    #[test]
+    fn bearish_and_bullish() {
+        let candles = candles();
+        let got: Vec<_> = swing_failures(candles.as_slice(), 3).unwrap().collect();
+        let expected = vec![
+            SwingFailure::NoChange,
+            SwingFailure::NoChange,
+            SwingFailure::NoChange,
+            SwingFailure::Bearish {
+                level: 12.0,
+                wick: 13.0,
+            },
+            SwingFailure::NoChange,
+            SwingFailure::Bullish {
+                level: 5.0,
+                wick: 4.0,
+            },
+        ];
+        assert_eq!(expected, got);
+    }
+
+    #[test]
+    fn broken_level_is_forgotten() {
+        let mut candles = candles();
+        // Sweep 12 again after it was broken
+        candles[5] = Candle::new(12.5, 9.0, 12.0, 11.0);
+        let got: Vec<_> = swing_failures(candles.as_slice(), 3).unwrap().collect();
+        assert!(got[5].is_no_change(), "{got:?}");
+    }
+
+    #[test]
+    fn window_too_big() {
+        let candles = candles();
+        assert!(swing_failures(candles.as_slice(), 7).is_err());
+    }
+}
@@ -0,0 +1,275 @@
+//! The legs between swing highs and lows: how far and how long each one
+//! ran, and how big they've been on average lately, so targets can be set
+//! relative to the market's usual swing.
+//!
+//! Feed it one [`Pivot`] per candle, eg. from [`pivots`](crate::pivots).
+//! Highs and lows take turns; a higher high after a high (or a lower low
+//! after a low) moves the end of the leg rather than starting a new one, so
+//! a leg is only done once the pivot the other way comes along.
+
+use std::collections::VecDeque;
+
+use crate::Pivot;
+
+/// A move from one swing to the next
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SwingLeg {
+    /// The index of the candle the leg started at
+    pub start: usize,
+    /// The index of the candle the leg ended at
+    pub end: usize,
+    pub from: f32,
+    pub to: f32,
+}
+
+impl SwingLeg {
+    /// How far the price went
+    pub fn size(&self) -> f32 {
+        (self.to - self.from).abs()
+    }
+
+    /// How many candles it took
+    pub fn duration(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_up(&self) -> bool {
+        self.to > self.from
+    }
+}
+
+/// A finished leg with the averages of the legs up to and including it
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SwingLegReading {
+    pub leg: SwingLeg,
+    /// The average size of the last `period` legs
+    pub average_size: f32,
+    /// The average number of candles the last `period` legs took
+    pub average_duration: f32,
+}
+
+/// A swing high or low, and the candle it was at
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Extreme {
+    index: usize,
+    price: f32,
+    is_high: bool,
+}
+
+/// Running swing legs, fed one pivot per candle
+#[derive(Debug, PartialEq, Clone)]
+pub struct SwingLegs {
+    period: usize,
+    index: usize,
+    /// Where the leg being made started
+    start: Option<Extreme>,
+    /// The furthest the leg being made has got
+    end: Option<Extreme>,
+    legs: VecDeque<SwingLeg>,
+}
+
+impl SwingLegs {
+    /// Averages over the last `period` legs
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            index: 0,
+            start: None,
+            end: None,
+            legs: VecDeque::with_capacity(period),
+        }
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    /// Adds the next candle's pivot. Returns the leg it finished, if it's
+    /// the first pivot the other way, with the averages including it. A
+    /// candle that makes a high and a low continues whichever way is next
+    pub fn push(&mut self, pivot: &Pivot) -> Option<SwingLegReading> {
+        let index = self.index;
+        self.index += 1;
+        let next_is_high = self.end.map(|end| !end.is_high);
+        let (price, is_high) = match (pivot, next_is_high) {
+            (Pivot::High(high), _) => (*high, true),
+            (Pivot::Low(low), _) => (*low, false),
+            (Pivot::HighLow { high, .. }, Some(true)) => (*high, true),
+            (Pivot::HighLow { low, .. }, Some(false)) => (*low, false),
+            (Pivot::HighLow { .. } | Pivot::NoChange, _) => return None,
+        };
+        let extreme = Extreme {
+            index,
+            price,
+            is_high,
+        };
+        match self.end {
+            Some(end) if end.is_high == is_high => {
+                let further = if is_high {
+                    price > end.price
+                } else {
+                    price < end.price
+                };
+                if further {
+                    self.end = Some(extreme);
+                }
+                None
+            }
+            Some(end) => {
+                let finished = self.start.replace(end).map(|start| SwingLeg {
+                    start: start.index,
+                    end: end.index,
+                    from: start.price,
+                    to: end.price,
+                });
+                self.end = Some(extreme);
+                finished.and_then(|leg| self.record(leg))
+            }
+            None => {
+                self.end = Some(extreme);
+                None
+            }
+        }
+    }
+
+    /// Adds a finished leg to the averages. None if the period is 0
+    fn record(&mut self, leg: SwingLeg) -> Option<SwingLegReading> {
+        if self.period == 0 {
+            return None;
+        }
+        if self.legs.len() == self.period {
+            self.legs.pop_front();
+        }
+        self.legs.push_back(leg);
+        let count = self.legs.len() as f32;
+        Some(SwingLegReading {
+            leg,
+            average_size: self.legs.iter().map(SwingLeg::size).sum::<f32>() / count,
+            average_duration: self
+                .legs
+                .iter()
+                .map(|leg| leg.duration() as f32)
+                .sum::<f32>()
+                / count,
+        })
+    }
+}
+
+/// Turn an Iterator of pivots, one per candle, into the swing legs between them
+pub trait IntoSwingLegIter: Iterator<Item = Pivot> + Sized {
+    /// One item per finished leg, with the averages over the last `period`
+    /// legs. Nothing if `period` is 0. See [`SwingLegs::push`]
+    fn swing_legs(self, period: usize) -> SwingLegIter<Self> {
+        SwingLegIter {
+            pivots: self,
+            legs: SwingLegs::new(period),
+        }
+    }
+}
+
+impl<I> IntoSwingLegIter for I where I: Iterator<Item = Pivot> {}
+
+pub struct SwingLegIter<I> {
+    pivots: I,
+    legs: SwingLegs,
+}
+
+impl<I> Iterator for SwingLegIter<I>
+where
+    I: Iterator<Item = Pivot>,
+{
+    type Item = SwingLegReading;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let pivot = self.pivots.next()?;
+            if let Some(reading) = self.legs.push(&pivot) {
+                return Some(reading);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn legs() {
+        let pivots = vec![
+            Pivot::Low(10.0),
+            Pivot::NoChange,
+            Pivot::High(14.0),
+            // A higher high carries the leg on
+            Pivot::High(16.0),
+            Pivot::NoChange,
+            Pivot::Low(12.0),
+            Pivot::High(15.0),
+            Pivot::Low(13.0),
+        ];
+        let readings: Vec<_> = pivots.into_iter().swing_legs(2).collect();
+        let legs: Vec<_> = readings.iter().map(|reading| reading.leg).collect();
+        assert_eq!(
+            vec![
+                SwingLeg {
+                    start: 0,
+                    end: 3,
+                    from: 10.0,
+                    to: 16.0
+                },
+                SwingLeg {
+                    start: 3,
+                    end: 5,
+                    from: 16.0,
+                    to: 12.0
+                },
+                SwingLeg {
+                    start: 5,
+                    end: 6,
+                    from: 12.0,
+                    to: 15.0
+                },
+            ],
+            legs
+        );
+        assert!(legs[0].is_up() && !legs[1].is_up());
+        assert_eq!((6.0, 3), (legs[0].size(), legs[0].duration()));
+        // The averages only go back two legs
+        assert_eq!(5.0, readings[1].average_size);
+        assert_eq!(2.5, readings[1].average_duration);
+        assert_eq!(3.5, readings[2].average_size);
+        assert_eq!(1.5, readings[2].average_duration);
+    }
+
+    #[test]
+    fn high_low_candles() {
+        let mut legs = SwingLegs::new(5);
+        // Nothing to say which way it's going yet
+        assert_eq!(
+            None,
+            legs.push(&Pivot::HighLow {
+                high: 9.0,
+                low: 1.0
+            })
+        );
+        assert_eq!(None, legs.push(&Pivot::High(5.0)));
+        assert_eq!(
+            None,
+            legs.push(&Pivot::HighLow {
+                high: 8.0,
+                low: 2.0
+            })
+        );
+        let reading = legs.push(&Pivot::High(6.0)).unwrap();
+        assert_eq!((5.0, 2.0), (reading.leg.from, reading.leg.to));
+        assert_eq!(5, legs.period());
+        assert_eq!(
+            0,
+            vec![Pivot::Low(1.0), Pivot::High(2.0), Pivot::Low(1.0)]
+                .into_iter()
+                .swing_legs(0)
+                .count()
+        );
+    }
+}
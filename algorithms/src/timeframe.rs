@@ -0,0 +1,102 @@
+//! How long things take in candles. Indicators warm up over a number of
+//! candles, but that only means something with a candle size attached:
+//! 14 periods of M15 is three and a half hours, or 4 H1 candles.
+//!
+//! This crate doesn't know about any broker's granularities; convert them
+//! into a [`Timeframe`] at the edge.
+
+use chrono::Duration;
+
+/// A fixed candle size
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct Timeframe {
+    seconds: i64,
+}
+
+impl Timeframe {
+    /// None unless `seconds` is positive
+    pub fn from_seconds(seconds: i64) -> Option<Self> {
+        (seconds > 0).then_some(Self { seconds })
+    }
+
+    pub fn minutes(minutes: i64) -> Option<Self> {
+        Self::from_seconds(minutes * 60)
+    }
+
+    pub fn hours(hours: i64) -> Option<Self> {
+        Self::minutes(hours * 60)
+    }
+
+    pub fn seconds(&self) -> i64 {
+        self.seconds
+    }
+
+    /// How long each candle covers
+    pub fn duration(&self) -> Duration {
+        Duration::seconds(self.seconds)
+    }
+
+    /// `count` candles of this size
+    pub fn periods(self, count: usize) -> Periods {
+        Periods {
+            count,
+            timeframe: self,
+        }
+    }
+
+    /// How many whole candles of this size it takes to cover `span`,
+    /// rounding up. 0 for an empty or negative span
+    pub fn candles_in(&self, span: Duration) -> usize {
+        let seconds = span.num_seconds();
+        if seconds <= 0 {
+            return 0;
+        }
+        ((seconds + self.seconds - 1) / self.seconds) as usize
+    }
+}
+
+/// A number of candles of a given size, eg. 14 periods of M15
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Periods {
+    pub count: usize,
+    pub timeframe: Timeframe,
+}
+
+impl Periods {
+    /// The time the candles cover
+    pub fn span(&self) -> Duration {
+        Duration::seconds(self.timeframe.seconds * self.count as i64)
+    }
+
+    /// How many candles of `timeframe` it takes to cover the same time,
+    /// rounding up so a warm-up is never cut short
+    pub fn candles_of(&self, timeframe: Timeframe) -> usize {
+        timeframe.candles_in(self.span())
+    }
+
+    /// The same span expressed in candles of `timeframe`
+    pub fn to(&self, timeframe: Timeframe) -> Periods {
+        timeframe.periods(self.candles_of(timeframe))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn conversions() {
+        let m15 = Timeframe::minutes(15).unwrap();
+        let h1 = Timeframe::hours(1).unwrap();
+        let warmup = m15.periods(14);
+        assert_eq!(Duration::minutes(210), warmup.span());
+        // Three and a half hours needs 4 hourly candles
+        assert_eq!(4, warmup.candles_of(h1));
+        assert_eq!(h1.periods(4), warmup.to(h1));
+        assert_eq!(56, h1.periods(14).candles_of(m15));
+        assert_eq!(0, m15.candles_in(Duration::zero()));
+        assert_eq!(None, Timeframe::from_seconds(0));
+        assert!(m15 < h1);
+    }
+}
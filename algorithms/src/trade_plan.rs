@@ -0,0 +1,183 @@
+//! A planned trade and how it turned out, measured in R multiples where 1R
+//! is the distance from the entry to the stop loss.
+
+use crate::{Error, ExcursionTracker, TradeDirection};
+
+/// Where we plan to get in, where we're wrong, and where we take profits
+#[derive(Debug, PartialEq, Clone)]
+pub struct TradePlan {
+    direction: TradeDirection,
+    entry: f32,
+    stop: f32,
+    targets: Vec<f32>,
+}
+
+impl TradePlan {
+    /// The direction comes from which side of the entry the stop is on.
+    /// Targets are sorted nearest first
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TradePlan`] if the stop is at the entry, or a
+    /// target isn't on the profitable side of the entry
+    pub fn new(entry: f32, stop: f32, mut targets: Vec<f32>) -> Result<Self, Error> {
+        let direction = if stop < entry {
+            TradeDirection::Long
+        } else if stop > entry {
+            TradeDirection::Short
+        } else {
+            return Err(Error::TradePlan("the stop can't be at the entry"));
+        };
+        let plan = Self {
+            direction,
+            entry,
+            stop,
+            targets: Vec::new(),
+        };
+        if targets.iter().any(|&target| plan.r_multiple(target) <= 0.0) {
+            return Err(Error::TradePlan(
+                "targets must be on the profitable side of the entry",
+            ));
+        }
+        targets.sort_by(|a, b| plan.r_multiple(*a).total_cmp(&plan.r_multiple(*b)));
+        Ok(Self { targets, ..plan })
+    }
+
+    pub fn direction(&self) -> TradeDirection {
+        self.direction
+    }
+
+    pub fn entry(&self) -> f32 {
+        self.entry
+    }
+
+    pub fn stop(&self) -> f32 {
+        self.stop
+    }
+
+    /// The take profit prices, nearest first
+    pub fn targets(&self) -> &[f32] {
+        &self.targets
+    }
+
+    /// 1R; the distance from the entry to the stop. Always positive
+    pub fn risk(&self) -> f32 {
+        (self.entry - self.stop).abs()
+    }
+
+    /// How many R we'd be up (or down if negative) if we closed at `price`
+    pub fn r_multiple(&self, price: f32) -> f32 {
+        match self.direction {
+            TradeDirection::Long => (price - self.entry) / self.risk(),
+            TradeDirection::Short => (self.entry - price) / self.risk(),
+        }
+    }
+
+    /// The price we'd be `r` R up at
+    pub fn price_at(&self, r: f32) -> f32 {
+        match self.direction {
+            TradeDirection::Long => self.entry + r * self.risk(),
+            TradeDirection::Short => self.entry - r * self.risk(),
+        }
+    }
+
+    /// The reward to risk of each target, nearest first
+    pub fn reward_risk(&self) -> impl Iterator<Item = f32> + '_ {
+        self.targets.iter().map(|&target| self.r_multiple(target))
+    }
+
+    /// The price we'd have to close at to break even after paying `costs`
+    /// (eg. the spread and commission, in price units)
+    pub fn breakeven(&self, costs: f32) -> f32 {
+        match self.direction {
+            TradeDirection::Long => self.entry + costs,
+            TradeDirection::Short => self.entry - costs,
+        }
+    }
+
+    /// Tracks the excursions of the trade once it's entered
+    pub fn excursion_tracker(&self) -> ExcursionTracker {
+        ExcursionTracker::new(self.direction, self.entry, self.stop)
+    }
+
+    /// How the trade turned out when closed at `exit`
+    pub fn close(&self, exit: f32) -> TradeOutcome {
+        TradeOutcome {
+            direction: self.direction,
+            entry: self.entry,
+            exit,
+            r: self.r_multiple(exit),
+        }
+    }
+}
+
+/// A closed trade
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TradeOutcome {
+    pub direction: TradeDirection,
+    pub entry: f32,
+    pub exit: f32,
+    /// The result in R
+    pub r: f32,
+}
+
+impl TradeOutcome {
+    pub fn is_win(&self) -> bool {
+        self.r > 0.0
+    }
+
+    /// The profit (or loss if negative) per unit traded, in price units
+    pub fn profit_per_unit(&self) -> f32 {
+        match self.direction {
+            TradeDirection::Long => self.exit - self.entry,
+            TradeDirection::Short => self.entry - self.exit,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn long() {
+        let plan = TradePlan::new(10.0, 8.0, vec![16.0, 12.0]).unwrap();
+        assert_eq!(TradeDirection::Long, plan.direction());
+        assert_eq!(2.0, plan.risk());
+        assert_eq!(&[12.0, 16.0], plan.targets());
+        assert_eq!(vec![1.0, 3.0], plan.reward_risk().collect::<Vec<_>>());
+        assert_eq!(-1.0, plan.r_multiple(8.0));
+        assert_eq!(14.0, plan.price_at(2.0));
+        assert_eq!(10.5, plan.breakeven(0.5));
+        let outcome = plan.close(13.0);
+        assert_eq!(1.5, outcome.r);
+        assert_eq!(3.0, outcome.profit_per_unit());
+        assert!(outcome.is_win());
+    }
+
+    #[test]
+    fn short() {
+        let plan = TradePlan::new(10.0, 11.0, vec![7.0, 8.0]).unwrap();
+        assert_eq!(TradeDirection::Short, plan.direction());
+        assert_eq!(&[8.0, 7.0], plan.targets());
+        assert_eq!(8.0, plan.price_at(2.0));
+        assert_eq!(9.5, plan.breakeven(0.5));
+        let outcome = plan.close(11.5);
+        assert_eq!(-1.5, outcome.r);
+        assert!(!outcome.is_win());
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(matches!(
+            TradePlan::new(10.0, 10.0, vec![]),
+            Err(Error::TradePlan(_))
+        ));
+        // A long with a target under the entry
+        assert!(matches!(
+            TradePlan::new(10.0, 8.0, vec![9.0]),
+            Err(Error::TradePlan(_))
+        ));
+    }
+}
@@ -0,0 +1,262 @@
+//! Labels the market as quiet, normal or wild, so a strategy can switch
+//! settings (or switch off) when the regime changes.
+//!
+//! Two measures of volatility are ranked against their own recent history:
+//! the ATR, which includes gaps and wicks, and the realized volatility (the
+//! standard deviation of log returns), which only sees closes. The regime
+//! comes from the average of the two percentiles.
+
+use std::collections::VecDeque;
+
+use crate::{candle::Close, true_range::TRCandle, ReturnKind, RingBuffer};
+
+/// How volatile the market is compared to its recent history
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum VolatilityRegime {
+    Low,
+    Normal,
+    High,
+}
+
+/// The regime went from `from` to `to`
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RegimeChange {
+    pub from: VolatilityRegime,
+    pub to: VolatilityRegime,
+}
+
+/// The regime as of one candle
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RegimeReading {
+    pub regime: VolatilityRegime,
+    /// Where the ATR ranks in its recent history, from 0 to 1
+    pub atr_percentile: f32,
+    /// Where the realized volatility ranks in its recent history, from 0 to 1
+    pub realized_volatility_percentile: f32,
+    /// Set on the candle the regime changed. Not set on the first reading
+    pub change: Option<RegimeChange>,
+}
+
+/// Where the regimes start and end
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RegimeThresholds {
+    /// The average percentile at or under which volatility is low
+    pub low: f32,
+    /// The average percentile at or over which volatility is high
+    pub high: f32,
+}
+
+impl Default for RegimeThresholds {
+    /// The bottom and top quarters
+    fn default() -> Self {
+        Self {
+            low: 0.25,
+            high: 0.75,
+        }
+    }
+}
+
+impl RegimeThresholds {
+    fn classify(&self, percentile: f32) -> VolatilityRegime {
+        if percentile <= self.low {
+            VolatilityRegime::Low
+        } else if percentile >= self.high {
+            VolatilityRegime::High
+        } else {
+            VolatilityRegime::Normal
+        }
+    }
+}
+
+/// Turn an Iterator of candles into an Iterator of volatility regimes
+pub trait IntoVolatilityRegimeIter: Iterator + Sized
+where
+    Self::Item: TRCandle,
+{
+    /// Measures volatility over `period` candles and ranks it against the
+    /// last `lookback` measurements. Starts yielding once there have been
+    /// `period + 1` candles, one per candle after that. Yields nothing if
+    /// either is 0
+    fn volatility_regimes(
+        self,
+        period: usize,
+        lookback: usize,
+        thresholds: RegimeThresholds,
+    ) -> VolatilityRegimeIter<Self> {
+        VolatilityRegimeIter {
+            candles: self,
+            period,
+            lookback,
+            thresholds,
+            previous_close: None,
+            true_ranges: RingBuffer::new(period),
+            returns: VecDeque::with_capacity(period),
+            atrs: VecDeque::with_capacity(lookback),
+            volatilities: VecDeque::with_capacity(lookback),
+            regime: None,
+        }
+    }
+}
+
+impl<I> IntoVolatilityRegimeIter for I
+where
+    I: Iterator,
+    I::Item: TRCandle,
+{
+}
+
+pub struct VolatilityRegimeIter<I> {
+    candles: I,
+    period: usize,
+    lookback: usize,
+    thresholds: RegimeThresholds,
+    previous_close: Option<f32>,
+    true_ranges: RingBuffer,
+    returns: VecDeque<f32>,
+    atrs: VecDeque<f32>,
+    volatilities: VecDeque<f32>,
+    regime: Option<VolatilityRegime>,
+}
+
+impl<I> VolatilityRegimeIter<I> {
+    /// Only keeps the regime changes, for strategies that just want to know
+    /// when to switch
+    pub fn changes(self) -> impl Iterator<Item = RegimeChange>
+    where
+        Self: Iterator<Item = RegimeReading>,
+    {
+        self.filter_map(|reading| reading.change)
+    }
+}
+
+/// Pushes `value` onto the back of `window`, keeping it at most `len` long
+fn push(window: &mut VecDeque<f32>, len: usize, value: f32) {
+    if window.len() == len {
+        window.pop_front();
+    }
+    window.push_back(value);
+}
+
+/// The fraction (0 to 1) of `history` below `value`, counting ties as half
+/// below so a flat history ranks in the middle rather than at the top
+fn mid_rank(history: &VecDeque<f32>, value: f32) -> f32 {
+    let below = history.iter().filter(|&&n| n < value).count() as f32;
+    let equal = history.iter().filter(|&&n| n == value).count() as f32;
+    (below + equal / 2.0) / history.len() as f32
+}
+
+fn mean(values: &VecDeque<f32>) -> f32 {
+    values.iter().sum::<f32>() / values.len() as f32
+}
+
+fn std_dev(values: &VecDeque<f32>) -> f32 {
+    let mean = mean(values);
+    (values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / values.len() as f32).sqrt()
+}
+
+impl<I> Iterator for VolatilityRegimeIter<I>
+where
+    I: Iterator,
+    I::Item: TRCandle,
+{
+    type Item = RegimeReading;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.period == 0 || self.lookback == 0 {
+            return None;
+        }
+        loop {
+            let candle = self.candles.next()?;
+            let close = candle.close();
+            let Some(previous_close) = self.previous_close.replace(close) else {
+                continue;
+            };
+            self.true_ranges.push(candle.true_range(previous_close));
+            push(
+                &mut self.returns,
+                self.period,
+                ReturnKind::Log.between(previous_close, close),
+            );
+            if self.returns.len() < self.period {
+                continue;
+            }
+            let atr = self.true_ranges.mean()?;
+            let volatility = std_dev(&self.returns);
+            push(&mut self.atrs, self.lookback, atr);
+            push(&mut self.volatilities, self.lookback, volatility);
+            let atr_percentile = mid_rank(&self.atrs, atr);
+            let realized_volatility_percentile = mid_rank(&self.volatilities, volatility);
+            let regime = self
+                .thresholds
+                .classify((atr_percentile + realized_volatility_percentile) / 2.0);
+            let change = match self.regime.replace(regime) {
+                Some(from) if from != regime => Some(RegimeChange { from, to: regime }),
+                _ => None,
+            };
+            break Some(RegimeReading {
+                regime,
+                atr_percentile,
+                realized_volatility_percentile,
+                change,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candle::test_data::Candle;
+    use pretty_assertions::assert_eq;
+
+    /// Candles closing alternately up and down by `range`
+    fn candles(count: usize, range: f32) -> impl Iterator<Item = Candle> {
+        (0..count).map(move |i| {
+            let close = if i % 2 == 0 { 100.0 } else { 100.0 + range };
+            Candle::new(close + range / 2.0, close - range / 2.0, close, close)
+        })
+    }
+
+    fn quiet_then_wild() -> impl Iterator<Item = Candle> {
+        candles(10, 0.1)
+            .chain(candles(10, 1.0))
+            .chain(candles(10, 0.1))
+    }
+
+    #[test]
+    fn regimes() {
+        let readings: Vec<_> = quiet_then_wild()
+            .volatility_regimes(3, 10, RegimeThresholds::default())
+            .collect();
+        // The first reading is on the 4th candle
+        assert_eq!(27, readings.len());
+        // A steady market ranks in the middle
+        assert_eq!(VolatilityRegime::Normal, readings[0].regime);
+        assert_eq!(0.5, readings[0].atr_percentile);
+        // The wide candles top the rankings
+        assert_eq!(VolatilityRegime::High, readings[8].regime);
+        // Until they're normal, then the narrow ones are at the bottom
+        assert_eq!(VolatilityRegime::Normal, readings[15].regime);
+        assert_eq!(VolatilityRegime::Low, readings[18].regime);
+    }
+
+    #[test]
+    fn changes() {
+        use VolatilityRegime::*;
+        let changes: Vec<_> = quiet_then_wild()
+            .volatility_regimes(3, 10, RegimeThresholds::default())
+            .changes()
+            .map(|change| (change.from, change.to))
+            .collect();
+        let expected = vec![(Normal, High), (High, Normal), (Normal, Low), (Low, Normal)];
+        assert_eq!(expected, changes);
+    }
+
+    #[test]
+    fn empty_settings() {
+        let mut regimes = candles(10, 1.0).volatility_regimes(0, 10, RegimeThresholds::default());
+        assert_eq!(None, regimes.next());
+        let mut regimes = candles(10, 1.0).volatility_regimes(3, 0, RegimeThresholds::default());
+        assert_eq!(None, regimes.next());
+    }
+}
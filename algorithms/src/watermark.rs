@@ -0,0 +1,221 @@
+//! The highest (or lowest) value seen so far, for trailing stops, Donchian
+//! channels and drawdowns. Live position management can update one a price
+//! at a time; a backtest can run one over a whole series of prices.
+
+use std::collections::VecDeque;
+
+/// Which extreme a watermark follows
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum WatermarkSide {
+    High,
+    Low,
+}
+
+impl WatermarkSide {
+    /// Whether `a` is further out than `b` on this side
+    fn beyond(&self, a: f32, b: f32) -> bool {
+        match self {
+            WatermarkSide::High => a >= b,
+            WatermarkSide::Low => a <= b,
+        }
+    }
+}
+
+/// Anything that can be updated with a value and give back its watermark
+pub trait UpdateWatermark {
+    /// Takes `value` into account and returns the watermark
+    fn update(&mut self, value: f32) -> f32;
+}
+
+/// The extreme of every value seen. With a decay, the watermark also moves
+/// that fraction of the way back towards each new value, so old extremes
+/// fade instead of holding forever
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Watermark {
+    side: WatermarkSide,
+    decay: f32,
+    mark: Option<f32>,
+}
+
+impl Watermark {
+    pub fn high() -> Self {
+        Self::new(WatermarkSide::High)
+    }
+
+    pub fn low() -> Self {
+        Self::new(WatermarkSide::Low)
+    }
+
+    pub fn new(side: WatermarkSide) -> Self {
+        Self {
+            side,
+            decay: 0.0,
+            mark: None,
+        }
+    }
+
+    /// `decay` from 0 (never fades) to 1 (just follows the values)
+    pub fn with_decay(mut self, decay: f32) -> Self {
+        self.decay = decay.clamp(0.0, 1.0);
+        self
+    }
+
+    /// The watermark, once there's been a value
+    pub fn value(&self) -> Option<f32> {
+        self.mark
+    }
+}
+
+impl UpdateWatermark for Watermark {
+    fn update(&mut self, value: f32) -> f32 {
+        let mark = match self.mark {
+            Some(mark) if !self.side.beyond(value, mark) => mark + (value - mark) * self.decay,
+            _ => value,
+        };
+        self.mark = Some(mark);
+        mark
+    }
+}
+
+/// The extreme of the last `period` values; eg. one side of a Donchian
+/// channel. A `period` of 0 is treated as 1
+#[derive(Debug, PartialEq, Clone)]
+pub struct RollingWatermark {
+    side: WatermarkSide,
+    period: usize,
+    /// How many values have been seen
+    count: usize,
+    /// (index, value) of the values that could still become the extreme,
+    /// the current extreme first
+    candidates: VecDeque<(usize, f32)>,
+}
+
+impl RollingWatermark {
+    pub fn high(period: usize) -> Self {
+        Self::new(WatermarkSide::High, period)
+    }
+
+    pub fn low(period: usize) -> Self {
+        Self::new(WatermarkSide::Low, period)
+    }
+
+    pub fn new(side: WatermarkSide, period: usize) -> Self {
+        Self {
+            side,
+            period: period.max(1),
+            count: 0,
+            candidates: VecDeque::new(),
+        }
+    }
+
+    /// The watermark, once there's been a value
+    pub fn value(&self) -> Option<f32> {
+        self.candidates.front().map(|(_, value)| *value)
+    }
+}
+
+impl UpdateWatermark for RollingWatermark {
+    fn update(&mut self, value: f32) -> f32 {
+        // Anything the new value beats can never be the extreme again
+        while let Some(&(_, last)) = self.candidates.back() {
+            if self.side.beyond(value, last) {
+                self.candidates.pop_back();
+            } else {
+                break;
+            }
+        }
+        self.candidates.push_back((self.count, value));
+        self.count += 1;
+        while let Some(&(index, _)) = self.candidates.front() {
+            if index + self.period < self.count {
+                self.candidates.pop_front();
+            } else {
+                break;
+            }
+        }
+        self.value().unwrap_or(value)
+    }
+}
+
+/// Turn an Iterator of f32 into an Iterator of watermarks
+pub trait IntoWatermarkIter: Iterator<Item = f32> + Sized {
+    /// Runs the values through `watermark`, yielding it after each one
+    fn watermarks<W: UpdateWatermark>(self, watermark: W) -> WatermarkIter<Self, W> {
+        WatermarkIter {
+            iter: self,
+            watermark,
+        }
+    }
+}
+
+impl<I> IntoWatermarkIter for I where I: Iterator<Item = f32> {}
+
+pub struct WatermarkIter<I, W> {
+    iter: I,
+    watermark: W,
+}
+
+impl<I, W> Iterator for WatermarkIter<I, W>
+where
+    I: Iterator<Item = f32>,
+    W: UpdateWatermark,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.iter.next()?;
+        Some(self.watermark.update(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const PRICES: [f32; 6] = [3.0, 5.0, 4.0, 2.0, 3.0, 6.0];
+
+    #[test]
+    fn running() {
+        let got: Vec<_> = PRICES.into_iter().watermarks(Watermark::high()).collect();
+        assert_eq!(vec![3.0, 5.0, 5.0, 5.0, 5.0, 6.0], got);
+        let got: Vec<_> = PRICES.into_iter().watermarks(Watermark::low()).collect();
+        assert_eq!(vec![3.0, 3.0, 3.0, 2.0, 2.0, 2.0], got);
+    }
+
+    #[test]
+    fn decay() {
+        let high = Watermark::high().with_decay(0.5);
+        let got: Vec<_> = PRICES.into_iter().watermarks(high).collect();
+        // 5 fades half way to 4, then half way to 2, then to 3
+        assert_eq!(vec![3.0, 5.0, 4.5, 3.25, 3.125, 6.0], got);
+    }
+
+    #[test]
+    fn rolling() {
+        let got: Vec<_> = PRICES
+            .into_iter()
+            .watermarks(RollingWatermark::high(3))
+            .collect();
+        assert_eq!(vec![3.0, 5.0, 5.0, 5.0, 4.0, 6.0], got);
+        let got: Vec<_> = PRICES
+            .into_iter()
+            .watermarks(RollingWatermark::low(2))
+            .collect();
+        assert_eq!(vec![3.0, 3.0, 4.0, 2.0, 2.0, 3.0], got);
+        let got: Vec<_> = PRICES
+            .into_iter()
+            .watermarks(RollingWatermark::high(0))
+            .collect();
+        assert_eq!(PRICES.to_vec(), got);
+    }
+
+    #[test]
+    fn live() {
+        let mut high = Watermark::high();
+        assert_eq!(None, high.value());
+        assert_eq!(1.1, high.update(1.1));
+        assert_eq!(1.1, high.update(1.05));
+        assert_eq!(Some(1.1), high.value());
+    }
+}
@@ -0,0 +1,134 @@
+//! Williams %R: where the close is in the range of the last `period`
+//! candles, from -100 at the lowest low to 0 at the highest high. Above -20
+//! is usually read as overbought and under -80 as oversold.
+
+use crate::{Close, High, Low, RingBuffer};
+
+/// A running Williams %R, fed one candle at a time
+#[derive(Debug, PartialEq, Clone)]
+pub struct WilliamsR {
+    highs: RingBuffer,
+    lows: RingBuffer,
+}
+
+impl WilliamsR {
+    /// The period Williams used
+    pub const DEFAULT_PERIOD: usize = 14;
+
+    pub fn new(period: usize) -> Self {
+        Self {
+            highs: RingBuffer::new(period),
+            lows: RingBuffer::new(period),
+        }
+    }
+
+    pub fn period(&self) -> usize {
+        self.highs.capacity()
+    }
+
+    /// Adds a candle and returns its %R against the last `period` candles,
+    /// itself included. None until there have been `period` candles, or
+    /// always if the period is 0. A range with no height is -50
+    pub fn push<C: High + Low + Close>(&mut self, candle: &C) -> Option<f32> {
+        self.highs.push(candle.high());
+        self.lows.push(candle.low());
+        if self.highs.is_empty() || !self.highs.is_full() {
+            return None;
+        }
+        let highest = self.highs.iter().copied().fold(f32::MIN, f32::max);
+        let lowest = self.lows.iter().copied().fold(f32::MAX, f32::min);
+        let range = highest - lowest;
+        if range == 0.0 {
+            Some(-50.0)
+        } else {
+            Some(-100.0 * (highest - candle.close()) / range)
+        }
+    }
+}
+
+/// Turn an Iterator of candles into their Williams %R
+pub trait IntoWilliamsRIterator: Iterator + Sized
+where
+    Self::Item: High + Low + Close,
+{
+    /// One item per candle: None until `period` candles are in, then the
+    /// %R over the last `period`, usually [`WilliamsR::DEFAULT_PERIOD`].
+    /// All None if `period` is 0
+    fn williams_r(self, period: usize) -> WilliamsRIterator<Self> {
+        WilliamsRIterator {
+            candles: self,
+            williams_r: WilliamsR::new(period),
+        }
+    }
+}
+
+impl<I> IntoWilliamsRIterator for I
+where
+    I: Iterator,
+    I::Item: High + Low + Close,
+{
+}
+
+pub struct WilliamsRIterator<I> {
+    candles: I,
+    williams_r: WilliamsR,
+}
+
+impl<I> Iterator for WilliamsRIterator<I>
+where
+    I: Iterator,
+    I::Item: High + Low + Close,
+{
+    type Item = Option<f32>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let candle = self.candles.next()?;
+        Some(self.williams_r.push(&candle))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.candles.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candle::test_data::Candle;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn range() {
+        let candles = [
+            Candle::new(10.0, 7.0, 8.0, 8.0),
+            Candle::new(12.0, 8.0, 8.0, 11.0),
+            Candle::new(11.0, 7.0, 11.0, 7.0),
+            Candle::new(9.0, 4.0, 7.0, 9.0),
+        ];
+        let got: Vec<_> = candles.iter().williams_r(3).collect();
+        // The windows of three go from 7 to 12, then 4 to 12
+        assert_eq!(vec![None, None, Some(-100.0), Some(-37.5)], got);
+    }
+
+    #[test]
+    fn extremes() {
+        let at_high = [
+            Candle::new(2.0, 1.0, 1.0, 1.0),
+            Candle::new(3.0, 2.0, 2.0, 3.0),
+        ];
+        assert_eq!(Some(Some(0.0)), at_high.iter().williams_r(2).last());
+        let flat = vec![Candle::new(1.0, 1.0, 1.0, 1.0); 2];
+        assert_eq!(Some(Some(-50.0)), flat.iter().williams_r(2).last());
+    }
+
+    #[test]
+    fn one_per_candle() {
+        let candles = vec![Candle::new(2.0, 1.0, 1.0, 1.5); 3];
+        assert_eq!(
+            vec![None, None, None],
+            candles.iter().williams_r(0).collect::<Vec<_>>()
+        );
+        assert_eq!(3, candles.iter().williams_r(4).count());
+        assert_eq!(14, WilliamsR::new(WilliamsR::DEFAULT_PERIOD).period());
+    }
+}
@@ -0,0 +1,143 @@
+//! Linearly weighted moving average of closes. Over a period of `n` the
+//! newest close counts `n` times, the one before `n - 1` times, down to the
+//! oldest which counts once, so it turns faster than a simple average.
+
+use crate::{Close, RingBuffer};
+
+/// A running weighted average of the last `period` values, fed one at a time
+#[derive(Debug, PartialEq, Clone)]
+pub struct Wma {
+    window: RingBuffer,
+}
+
+impl Wma {
+    pub fn new(period: usize) -> Self {
+        Self {
+            window: RingBuffer::new(period),
+        }
+    }
+
+    pub fn period(&self) -> usize {
+        self.window.capacity()
+    }
+
+    /// Adds a value and returns the weighted average of the last `period`.
+    /// None until there have been `period` values, or always if the period
+    /// is 0
+    pub fn push(&mut self, value: f32) -> Option<f32> {
+        self.window.push(value);
+        self.value()
+    }
+
+    /// The weighted average of the last `period` values, once there have
+    /// been that many
+    pub fn value(&self) -> Option<f32> {
+        if self.window.is_empty() || !self.window.is_full() {
+            return None;
+        }
+        let period = self.period() as f64;
+        let weighted: f64 = (1..)
+            .zip(self.window.iter())
+            .map(|(weight, &value)| weight as f64 * value as f64)
+            .sum();
+        Some((weighted / (period * (period + 1.0) / 2.0)) as f32)
+    }
+}
+
+/// Turn an Iterator of candles into the weighted moving average of their closes
+pub trait IntoWmaIterator: Iterator + Sized
+where
+    Self::Item: Close,
+{
+    /// The weighted average of the last `period` closes. Starts yielding
+    /// once there are `period` of them, and yields nothing if `period` is 0
+    fn wma(self, period: usize) -> WmaIterator<Self> {
+        WmaIterator {
+            candles: self,
+            wma: Wma::new(period),
+        }
+    }
+}
+
+impl<I> IntoWmaIterator for I
+where
+    I: Iterator,
+    I::Item: Close,
+{
+}
+
+pub struct WmaIterator<I> {
+    candles: I,
+    wma: Wma,
+}
+
+impl<I> Iterator for WmaIterator<I>
+where
+    I: Iterator,
+    I::Item: Close,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.wma.period() == 0 {
+            return None;
+        }
+        loop {
+            let close = self.candles.next()?.close();
+            if let Some(average) = self.wma.push(close) {
+                break Some(average);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candle::test_data::{test_data_1, test_data_2, Candle};
+
+    #[test]
+    fn test_wma_1() {
+        let candles = test_data_1();
+        let closes: Vec<f32> = candles.iter().map(Close::close).collect();
+        let expected: Vec<f32> = closes
+            .windows(3)
+            .map(|window| (window[0] + window[1] * 2.0 + window[2] * 3.0) / 6.0)
+            .collect();
+        let got: Vec<f32> = candles.iter().wma(3).collect();
+        assert_eq!(expected.len(), got.len());
+        for (expected, got) in expected.into_iter().zip(got) {
+            assert!((expected - got).abs() < 0.0001, "{expected} {got}");
+        }
+    }
+
+    #[test]
+    fn test_wma_2() {
+        // The newest close counts for the most
+        let candles = test_data_2();
+        let wma = candles.iter().wma(5).last().unwrap();
+        let closes: Vec<f32> = candles.iter().map(Close::close).collect();
+        let last = &closes[closes.len() - 5..];
+        let expected = (1..=5)
+            .zip(last)
+            .map(|(weight, close)| weight as f32 * close)
+            .sum::<f32>()
+            / 15.0;
+        assert!((expected - wma).abs() < 0.0001, "{expected} {wma}");
+    }
+
+    #[test]
+    fn test_wma_short() {
+        let candles = test_data_1();
+        assert_eq!(0, candles[..2].iter().wma(3).count());
+        assert_eq!(0, candles.iter().wma(0).count());
+        let closes: Vec<f32> = candles.iter().map(Close::close).collect();
+        assert_eq!(closes, candles.iter().wma(1).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_wma_empty() {
+        let candles: Vec<Candle> = vec![];
+        assert_eq!(None, candles.into_iter().wma(3).next());
+    }
+}
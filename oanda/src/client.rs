@@ -1,26 +1,49 @@
 pub mod account;
+pub mod cache;
 pub mod instrument;
 pub mod order;
+pub mod position;
+pub mod pricing;
+pub mod recorder;
+mod shared;
+pub mod snapshot;
+mod span;
 pub mod trade;
+pub mod transaction;
 
 use std::borrow::ToOwned;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use error_stack::{report, IntoReport, ResultExt};
-use reqwest::RequestBuilder;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Method, RequestBuilder, StatusCode, Url};
 use serde::de::DeserializeOwned;
+use tracing::{debug, debug_span, field, Instrument as _, Span};
 
 use crate::{error::Error, host::Host};
 
-use self::account::Accounts;
+use self::account::{Account, Accounts};
+use self::cache::ResponseCache;
 use self::instrument::Instrument;
 use self::order::Order;
+use self::position::Positions;
+use self::pricing::Pricing;
+use self::recorder::{Fixture, Recorder};
+pub use self::shared::RequestCounts;
+use self::shared::{RateLimiter, Shared};
+use self::span::Endpoint;
 use self::trade::Trade;
+use self::transaction::Transactions;
 
+/// Cheap to clone. All the clones share one rate limit, cache, recorder and
+/// set of request counts, so clone it into each task that needs it
 #[derive(Debug, Clone)]
 pub struct Client {
     token: String,
     pub(crate) host: Host,
     rest_client: reqwest::Client,
+    shared: Arc<Shared>,
 }
 
 impl Client {
@@ -40,8 +63,40 @@ impl Client {
             token,
             host,
             rest_client,
+            shared: Arc::default(),
         }
     }
+    /// Caches GET responses on disk, and checks with the server whether
+    /// they're still current instead of downloading them again.
+    /// See [`ResponseCache`]
+    pub fn with_cache(self, cache: ResponseCache) -> Client {
+        self.with_shared(|shared| shared.cache = Some(cache))
+    }
+    /// Writes every request and its response to disk as a sanitized JSON
+    /// fixture. See [`Recorder`]
+    pub fn with_recorder(self, recorder: Recorder) -> Client {
+        self.with_shared(|shared| shared.recorder = Some(recorder))
+    }
+    /// Sends at most `per_second` requests a second, across all the clones.
+    /// [default=100]
+    pub fn with_rate_limit(self, per_second: u32) -> Client {
+        self.with_shared(|shared| shared.limiter = RateLimiter::new(per_second))
+    }
+    /// Changes the shared state. Clones made before this keep the old state
+    /// between them, so set the client up before cloning it
+    fn with_shared(mut self, change: impl FnOnce(&mut Shared)) -> Client {
+        if Arc::get_mut(&mut self.shared).is_none() {
+            self.shared = Arc::new(self.shared.detach());
+        }
+        if let Some(shared) = Arc::get_mut(&mut self.shared) {
+            change(shared);
+        }
+        self
+    }
+    /// How many requests this client and its clones have sent
+    pub fn request_counts(&self) -> RequestCounts {
+        self.shared.metrics.counts()
+    }
     /// Given a URL path, inserts the part before it
     pub fn url(&self, path: &str) -> String {
         self.host.rest_url(path)
@@ -49,20 +104,126 @@ impl Client {
     /// Given a URL path, creates a Get request builder with the correct
     /// host and authentication token
     pub fn start_get(&self, url: &str) -> RequestBuilder {
+        self.start(Method::GET, url)
+    }
+    /// Given a URL path, creates a Put request builder with the correct
+    /// host and authentication token. Add the body with `.json()`
+    pub fn start_put(&self, url: &str) -> RequestBuilder {
+        self.start(Method::PUT, url)
+    }
+    fn start(&self, method: Method, url: &str) -> RequestBuilder {
         use reqwest::header::{ACCEPT, AUTHORIZATION};
         self.rest_client
-            .get(url)
+            .request(method, url)
             .header(AUTHORIZATION, format!("Bearer {}", &self.token))
             .header(ACCEPT, "application/json")
     }
+    /// Checks the token works against our host with a cheap authenticated
+    /// call. Call it at startup to catch misconfiguration early.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::Network`] if we can't reach the host
+    /// * [`Error::WrongEnvironment`] if the token is for the other host (eg.
+    ///   a practice token used on the live host)
+    /// * [`Error::InvalidToken`] if neither host accepts the token
+    /// * [`Error::Status`] for any other unexpected reply
+    pub async fn verify(&self) -> error_stack::Result<(), Error> {
+        let status = self
+            .probe(self.host)
+            .await
+            .map_err(Error::from)
+            .into_report()
+            .change_context(Error::Network)
+            .attach_printable_lazy(|| format!("Host: {}", self.host.rest()))?;
+        match status {
+            status if status.is_success() => Ok(()),
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                let other = self.host.other();
+                match self.probe(other).await {
+                    Ok(status) if status.is_success() => Err(report!(Error::WrongEnvironment {
+                        token_host: other,
+                        client_host: self.host,
+                    })),
+                    _ => Err(report!(Error::InvalidToken)),
+                }
+            }
+            status => Err(report!(Error::Status(status)))
+                .attach_printable("While verifying the API token"),
+        }
+    }
+    /// The status of listing accounts on `host` with our token
+    async fn probe(&self, host: Host) -> Result<StatusCode, reqwest::Error> {
+        self.start_get(&host.rest_url("/v3/accounts"))
+            .send()
+            .await
+            .map(|response| response.status())
+    }
     /// Makes an authenticated get request to a path in the rest api
     pub async fn get<T: DeserializeOwned>(
         &self,
         request: RequestBuilder,
+    ) -> error_stack::Result<T, Error> {
+        self.send(request).await
+    }
+    /// Sends a request made with [`Self::start_get`] or [`Self::start_put`]
+    /// and parses the response
+    ///
+    /// The request runs in an `oanda_request` tracing span recording the
+    /// method, endpoint, instrument, hashed account id, HTTP status and duration
+    pub async fn send<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
     ) -> error_stack::Result<T, Error> {
         let request = request.build().map_err(Error::from).into_report()?;
+        let endpoint = Endpoint::new(request.url());
+        let span = debug_span!(
+            "oanda_request",
+            method = %request.method(),
+            endpoint = %endpoint.name,
+            instrument = endpoint.instrument,
+            account = endpoint.account,
+            status = field::Empty,
+            duration_ms = field::Empty,
+        );
+        let start = Instant::now();
+        self.shared.metrics.request();
+        let result = self.execute(request).instrument(span.clone()).await;
+        if result.is_err() {
+            self.shared.metrics.failure();
+        }
+        let duration_ms = start.elapsed().as_millis() as u64;
+        span.record("duration_ms", duration_ms);
+        span.in_scope(|| debug!(duration_ms, ok = result.is_ok(), "Request finished"));
+        result
+    }
+
+    async fn execute<T: DeserializeOwned>(
+        &self,
+        mut request: reqwest::Request,
+    ) -> error_stack::Result<T, Error> {
         let url = request.url().to_owned();
+        let method = request.method().clone();
+        let request_body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|bytes| String::from_utf8_lossy(bytes).into_owned());
+
+        // Only GETs are safe to answer from the cache
+        let cache = self
+            .shared
+            .cache
+            .as_ref()
+            .filter(|_| request.method() == Method::GET);
+        let cached = match cache {
+            Some(cache) => cache.load(url.as_str()).await,
+            None => None,
+        };
+        if let Some(cached) = &cached {
+            request.headers_mut().extend(cached.conditional_headers());
+        }
 
+        self.shared.limiter.wait().await;
         let response = self
             .rest_client
             .execute(request)
@@ -72,7 +233,13 @@ impl Client {
             .attach_printable_lazy(|| format!("URL: {url}"))?;
 
         let status = response.status();
-        if status.is_success() {
+        Span::current().record("status", status.as_u16());
+        if let (StatusCode::NOT_MODIFIED, Some(cached)) = (status, cached) {
+            // What we have in the cache is still current
+            self.shared.metrics.not_modified();
+            Self::parse(&cached.body).attach_printable_lazy(|| format!("url: {url} (cached)"))
+        } else if status.is_success() {
+            let headers = response.headers().clone();
             let body: String = response
                 .text()
                 .await
@@ -80,17 +247,30 @@ impl Client {
                 .into_report()
                 .attach_printable_lazy(|| format!("URL: {url}"))
                 .attach_printable_lazy(|| format!("HTTP status code: {status}"))?;
-            serde_json::from_str(&body)
-                .map_err(|err| Error::JsonParse {
-                    err,
-                    input: body.to_owned(),
-                })
-                .into_report()
-                .attach_printable_lazy(|| format!("url: {url}"))
+            if let Some(cache) = cache {
+                cache.store(url.as_str(), &headers, &body).await;
+            }
+            self.record(&method, &url, request_body.as_deref(), status, &body)
+                .await;
+            Self::parse(&body).attach_printable_lazy(|| format!("url: {url}"))
+        } else if status == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = retry_after(response.headers());
+            self.shared
+                .limiter
+                .cool_down(retry_after.unwrap_or(Self::DEFAULT_COOL_DOWN))
+                .await;
+            let body = response.text().await.unwrap_or_default();
+            Err(report!(Error::RateLimited { retry_after }))
+                .attach_printable(format!("URL: {url}"))
+                .attach_printable(format!("Body: {body}"))
         } else {
             // If we get a bad http status
             // try to get and add the body for more context
             let body = response.text().await.map_err(Error::from);
+            if let Ok(body) = &body {
+                self.record(&method, &url, request_body.as_deref(), status, body)
+                    .await;
+            }
             let mut err = report!(Error::Status(status)).attach_printable(format!("URL: {url}"));
             Err(match body {
                 Ok(body) => err.attach_printable(format!("Body: {body}")),
@@ -102,11 +282,42 @@ impl Client {
         }
     }
 
+    async fn record(
+        &self,
+        method: &Method,
+        url: &Url,
+        request_body: Option<&str>,
+        status: StatusCode,
+        response_body: &str,
+    ) {
+        if let Some(recorder) = &self.shared.recorder {
+            let fixture = Fixture::new(method, url, request_body, status, response_body);
+            recorder.record(fixture).await;
+        }
+    }
+
+    /// How long to hold requests back after a 429 that doesn't say
+    const DEFAULT_COOL_DOWN: Duration = Duration::from_secs(1);
+
+    fn parse<T: DeserializeOwned>(body: &str) -> error_stack::Result<T, Error> {
+        serde_json::from_str(body)
+            .map_err(|err| Error::JsonParse {
+                err,
+                input: body.to_owned(),
+            })
+            .into_report()
+    }
+
     /// Rest API for anything account related
     pub fn accounts(&self) -> Accounts {
         Accounts { client: self }
     }
 
+    /// Operations on one account, like closing everything in it
+    pub fn account(&self, account_id: impl ToString) -> Account<'_> {
+        Account::new(self, account_id.to_string())
+    }
+
     /// Rest API for anything instrument related
     pub fn instrument(&self, instrument: impl ToString) -> Instrument {
         Instrument {
@@ -124,6 +335,117 @@ impl Client {
     pub fn order(&self, account_id: impl ToString) -> Order {
         Order::new(self, account_id.to_string())
     }
+
+    /// Rest API for the current prices of instruments
+    pub fn pricing(&self, account_id: impl ToString) -> Pricing<'_> {
+        Pricing::new(self, account_id.to_string())
+    }
+
+    /// Rest API for the account's open positions
+    pub fn positions(&self, account_id: impl ToString) -> Positions<'_> {
+        Positions::new(self, account_id.to_string())
+    }
+
+    /// Rest API for the account's transaction history
+    pub fn transactions(&self, account_id: impl ToString) -> Transactions<'_> {
+        Transactions::new(self, account_id.to_string())
+    }
+}
+
+/// The seconds in a `Retry-After` header. The HTTP date form isn't handled
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let seconds = headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+#[cfg(test)]
+mod api_tests {
+    use crate::{host::Host, Client, Error};
+    use std::env::var;
+
+    #[tokio::test]
+    async fn verify() {
+        let api_key =
+            var("OANDA_TOKEN").expect("expected OANDA_TOKEN environment variable to be set");
+        Client::new(api_key.clone(), Host::Dev)
+            .verify()
+            .await
+            .unwrap();
+        let err = Client::new(api_key, Host::Live).verify().await.unwrap_err();
+        assert!(matches!(
+            err.current_context(),
+            Error::WrongEnvironment {
+                token_host: Host::Dev,
+                client_host: Host::Live
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn verify_invalid_token() {
+        let client = Client::new("not-a-token".to_string(), Host::Dev);
+        let err = client.verify().await.unwrap_err();
+        assert!(matches!(err.current_context(), Error::InvalidToken));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{host::Host, Client};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn clones_share_state() {
+        let client = Client::new("no-token-needed".to_string(), Host::Dev);
+        let tasks: Vec<_> = (0..10)
+            .map(|_| {
+                let client = client.clone();
+                // Nothing listens on the discard port, so these fail straight away
+                tokio::spawn(async move {
+                    let request = client.start_get("http://127.0.0.1:9/v3/accounts");
+                    client.send::<serde_json::Value>(request).await
+                })
+            })
+            .collect();
+        for task in tasks {
+            assert!(task.await.unwrap().is_err());
+        }
+        let counts = client.request_counts();
+        assert_eq!(10, counts.requests);
+        assert_eq!(10, counts.failures);
+        assert!(Arc::ptr_eq(&client.shared, &client.clone().shared));
+    }
+
+    #[test]
+    fn retry_after_header() {
+        use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+        use std::time::Duration;
+        let mut headers = HeaderMap::new();
+        assert_eq!(None, super::retry_after(&headers));
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("3"));
+        assert_eq!(Some(Duration::from_secs(3)), super::retry_after(&headers));
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(None, super::retry_after(&headers));
+    }
+
+    #[test]
+    fn setting_up_after_cloning_detaches() {
+        let client = Client::new("no-token-needed".to_string(), Host::Dev);
+        let clone = client.clone();
+        let limited = client.with_rate_limit(5);
+        assert!(!Arc::ptr_eq(&limited.shared, &clone.shared));
+        assert_eq!(5, limited.shared.limiter.per_second());
+        assert_eq!(100, clone.shared.limiter.per_second());
+    }
 }
 
 #[cfg(test)]
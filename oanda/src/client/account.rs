@@ -1,14 +1,49 @@
 use error_stack::{Result, ResultExt};
 use serde::Serialize;
 
+/// The account models live in [`crate::model`]; this keeps the old
+/// `client::account::model` path working
 pub use crate::model;
 
 use crate::{client::Client, error::Error};
 
+mod availability;
+mod equity;
+mod flatten;
+pub use availability::{watch_instruments, AvailabilityChange, AvailabilityTracker};
+pub use equity::{poll_equity, EquitySnapshot};
+pub use flatten::{FlattenSummary, FLATTEN_CONCURRENCY};
+
 pub struct Accounts<'a> {
     pub(crate) client: &'a Client,
 }
 
+/// Operations on a single account
+#[derive(Debug)]
+pub struct Account<'a> {
+    pub(crate) client: &'a Client,
+    account_id: String,
+}
+
+impl<'a> Account<'a> {
+    pub fn new(client: &'a Client, account_id: String) -> Self {
+        Self { client, account_id }
+    }
+
+    /// Whether the account nets or hedges positions in the same instrument
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the http request fails or the Json deseralization fails
+    pub async fn mode(&self) -> Result<model::account::AccountMode, Error> {
+        self.client
+            .accounts()
+            .summary(&self.account_id)
+            .await
+            .map(|summary| summary.mode())
+    }
+}
+
 impl Accounts<'_> {
     /// Returns the list of accounts associated with this Oanda account.
     ///
@@ -24,6 +59,27 @@ impl Accounts<'_> {
             .map(|accounts: model::Accounts| accounts.accounts)
             .attach_printable("While listing accounts")
     }
+    /// Returns a summary of an account; its balance, NAV, margin and so on,
+    /// without the list of trades, positions and orders.
+    ///
+    /// See [the docs](https://developer.oanda.com/rest-live-v20/account-ep/)
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the http request fails or the Json deseralization fails
+    pub async fn summary(&self, account_id: &str) -> Result<model::AccountSummary, Error> {
+        let url = self
+            .client
+            .url(&format!("/v3/accounts/{account_id}/summary"));
+        let request = self.client.start_get(&url);
+        self.client
+            .get(request)
+            .await
+            .map(|response: model::account::AccountSummaryResponse| response.account)
+            .attach_printable_lazy(|| {
+                format!("While getting the summary of account_id {account_id}")
+            })
+    }
     /// Returns the list of instruments ( things to trade like EUR/USD) available to an account
     ///
     /// See [the docs](https://developer.oanda.com/rest-live-v20/account-ep/)
@@ -162,6 +218,15 @@ mod tests {
         account_id
     }
 
+    #[tokio::test]
+    async fn account_summary() {
+        let client = client();
+        let account_id = account_id(&client).await;
+        let summary = client.accounts().summary(&account_id).await.unwrap();
+        assert_eq!(account_id, summary.id);
+        dbg!(summary);
+    }
+
     #[tokio::test]
     async fn list_instruments_simple() {
         let client = client();
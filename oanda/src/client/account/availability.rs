@@ -0,0 +1,193 @@
+//! Watches the account's instrument list in the background and reports when
+//! the instruments we trade change on the broker's side: a margin rate goes
+//! up or down, or an instrument is taken off or put back on the account.
+use std::{collections::BTreeMap, time::Duration};
+
+use tokio::sync::mpsc;
+use tracing::warn;
+
+use crate::{model::Instrument, Client};
+
+/// How many changes can wait in the channel before the watcher waits for
+/// the receiver to catch up
+const CHANNEL_SIZE: usize = 32;
+
+/// A broker side change to one of the instruments being watched
+#[derive(Debug, PartialEq, Clone)]
+pub enum AvailabilityChange {
+    /// The account's margin rate for the instrument changed
+    MarginRate {
+        instrument: String,
+        from: f32,
+        to: f32,
+    },
+    /// The instrument is no longer in the account's instrument list, so it
+    /// can't be traded
+    Unavailable { instrument: String },
+    /// The instrument is back in the account's instrument list
+    Available {
+        instrument: String,
+        margin_rate: f32,
+    },
+}
+
+/// Remembers the last seen state of the instruments being watched. Feed it
+/// the account's instrument list each time it's fetched
+#[derive(Debug, Clone)]
+pub struct AvailabilityTracker {
+    /// The margin rate of each watched instrument, None if it's unavailable.
+    /// Instruments we haven't seen a list for yet aren't in here
+    margin_rates: BTreeMap<String, Option<f32>>,
+    watched: Vec<String>,
+}
+
+impl AvailabilityTracker {
+    pub fn new(watched: impl IntoIterator<Item = impl ToString>) -> Self {
+        Self {
+            margin_rates: BTreeMap::new(),
+            watched: watched.into_iter().map(|name| name.to_string()).collect(),
+        }
+    }
+
+    /// Takes a fresh instrument list into account and returns what changed
+    /// for the watched instruments. The first list only reports the watched
+    /// instruments that are missing from it
+    pub fn update(&mut self, instruments: &[Instrument]) -> Vec<AvailabilityChange> {
+        let mut changes = Vec::new();
+        for name in &self.watched {
+            let now = instruments
+                .iter()
+                .find(|instrument| &instrument.name == name)
+                .map(|instrument| instrument.margin_rate);
+            let instrument = name.clone();
+            match (self.margin_rates.insert(name.clone(), now), now) {
+                (None | Some(Some(_)), None) => {
+                    changes.push(AvailabilityChange::Unavailable { instrument })
+                }
+                (Some(None), Some(margin_rate)) => changes.push(AvailabilityChange::Available {
+                    instrument,
+                    margin_rate,
+                }),
+                (Some(Some(from)), Some(to)) if from != to => {
+                    changes.push(AvailabilityChange::MarginRate {
+                        instrument,
+                        from,
+                        to,
+                    })
+                }
+                _ => (),
+            }
+        }
+        changes
+    }
+
+    /// True if the instrument was in the last list. False if it wasn't, or
+    /// there hasn't been a list yet
+    pub fn is_available(&self, instrument: &str) -> bool {
+        matches!(self.margin_rates.get(instrument), Some(Some(_)))
+    }
+}
+
+/// Spawns a task that fetches the account's instrument list every
+/// `interval` and sends any changes to `instruments` down the returned
+/// channel.
+///
+/// Failed fetches are logged and skipped. The task stops once the receiver
+/// has been dropped.
+pub fn watch_instruments(
+    client: Client,
+    account_id: String,
+    instruments: Vec<String>,
+    interval: Duration,
+) -> mpsc::Receiver<AvailabilityChange> {
+    let (sender, receiver) = mpsc::channel(CHANNEL_SIZE);
+    tokio::spawn(async move {
+        let mut tracker = AvailabilityTracker::new(instruments);
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        while !sender.is_closed() {
+            ticker.tick().await;
+            let accounts = client.accounts();
+            match accounts.list_instruments(&account_id).send().await {
+                Ok(list) => {
+                    for change in tracker.update(&list) {
+                        if sender.send(change).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+                Err(err) => warn!("Couldn't refresh the instrument list: {err:?}"),
+            }
+        }
+    });
+    receiver
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn instrument(name: &str, margin_rate: f32) -> Instrument {
+        let input = format!(
+            r#"{{
+                "name": "{name}",
+                "type": "CURRENCY",
+                "displayName": "{name}",
+                "pipLocation": -4,
+                "displayPrecision": 5,
+                "tradeUnitsPrecision": 0,
+                "minimumTradeSize": "1",
+                "maximumTrailingStopDistance": "1.00000",
+                "minimumTrailingStopDistance": "0.00050",
+                "maximumPositionSize": "0",
+                "maximumOrderUnits": "100000000",
+                "marginRate": "{margin_rate}",
+                "commission": {{ "commission": "0", "unitsTraded": "1", "minimumCommission": "0" }},
+                "guaranteedStopLossOrderMode": "DISABLED",
+                "financing": {{ "longRate": "0", "shortRate": "0", "financingDaysOfWeek": [] }},
+                "tags": []
+            }}"#
+        );
+        serde_json::from_str(&input).unwrap()
+    }
+
+    #[test]
+    fn changes() {
+        let mut tracker = AvailabilityTracker::new(["EUR_USD", "XAU_USD"]);
+        // The first list only reports what's missing
+        assert_eq!(
+            vec![AvailabilityChange::Unavailable {
+                instrument: "XAU_USD".to_string()
+            }],
+            tracker.update(&[instrument("EUR_USD", 0.0333), instrument("GBP_USD", 0.05)])
+        );
+        assert!(tracker.is_available("EUR_USD"));
+        assert!(!tracker.is_available("XAU_USD"));
+        // Nothing changed
+        assert_eq!(
+            Vec::<AvailabilityChange>::new(),
+            tracker.update(&[instrument("EUR_USD", 0.0333)])
+        );
+        assert_eq!(
+            vec![
+                AvailabilityChange::MarginRate {
+                    instrument: "EUR_USD".to_string(),
+                    from: 0.0333,
+                    to: 0.05
+                },
+                AvailabilityChange::Available {
+                    instrument: "XAU_USD".to_string(),
+                    margin_rate: 0.05
+                }
+            ],
+            tracker.update(&[instrument("EUR_USD", 0.05), instrument("XAU_USD", 0.05)])
+        );
+        assert_eq!(
+            vec![AvailabilityChange::Unavailable {
+                instrument: "EUR_USD".to_string()
+            }],
+            tracker.update(&[instrument("XAU_USD", 0.05)])
+        );
+    }
+}
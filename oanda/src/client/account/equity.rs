@@ -0,0 +1,75 @@
+//! Keeps an up to date copy of an account's equity and margin in the
+//! background, so risk checks in the trading loop can read it without
+//! waiting on the network.
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::watch;
+use tracing::warn;
+
+use crate::{model::AccountSummary, Client};
+
+/// The parts of an account summary that matter for risk checks, and when we got them
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct EquitySnapshot {
+    /// When the summary was fetched
+    pub time: DateTime<Utc>,
+    pub balance: f32,
+    pub nav: f32,
+    pub unrealized_pl: f32,
+    pub margin_used: f32,
+    pub margin_available: f32,
+    /// 1.0 or above means the account is being margin closed out
+    pub margin_closeout_percent: f32,
+}
+
+impl EquitySnapshot {
+    pub fn new(summary: &AccountSummary, time: DateTime<Utc>) -> Self {
+        Self {
+            time,
+            balance: summary.balance,
+            nav: summary.nav,
+            unrealized_pl: summary.unrealized_pl,
+            margin_used: summary.margin_used,
+            margin_available: summary.margin_available,
+            margin_closeout_percent: summary.margin_closeout_percent,
+        }
+    }
+
+    /// How long ago the snapshot was taken
+    pub fn age(&self) -> chrono::Duration {
+        Utc::now() - self.time
+    }
+}
+
+/// Spawns a task that fetches the account summary every `interval` and
+/// publishes it on the returned channel. The channel holds None until the
+/// first successful fetch.
+///
+/// Failed fetches are logged and the last good snapshot is kept; check
+/// [`EquitySnapshot::age`] if stale numbers matter. The task stops once
+/// every receiver has been dropped.
+pub fn poll_equity(
+    client: Client,
+    account_id: String,
+    interval: Duration,
+) -> watch::Receiver<Option<EquitySnapshot>> {
+    let (sender, receiver) = watch::channel(None);
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        while !sender.is_closed() {
+            ticker.tick().await;
+            match client.accounts().summary(&account_id).await {
+                Ok(summary) => {
+                    let snapshot = EquitySnapshot::new(&summary, Utc::now());
+                    if sender.send(Some(snapshot)).is_err() {
+                        break;
+                    }
+                }
+                Err(err) => warn!("Couldn't poll the account summary: {err:?}"),
+            }
+        }
+    });
+    receiver
+}
@@ -0,0 +1,45 @@
+//! Closing everything in an account at once; what the kill switch and the
+//! weekend flatten need.
+use std::collections::BTreeMap;
+
+use error_stack::Result;
+use futures::{stream, StreamExt};
+
+use super::Account;
+use crate::{
+    client::position::Positions,
+    model::position::{ClosePositionRequest, ClosePositionResponse},
+    Error,
+};
+
+/// How many positions we close at the same time
+pub const FLATTEN_CONCURRENCY: usize = 4;
+
+/// How closing each instrument's position went, keyed by instrument
+pub type FlattenSummary = BTreeMap<String, Result<ClosePositionResponse, Error>>;
+
+impl Account<'_> {
+    /// Closes every open position in the account at market price, up to
+    /// [`FLATTEN_CONCURRENCY`] at a time.
+    ///
+    /// One position failing to close doesn't stop the others; check each
+    /// entry in the summary.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if we can't get the list of open positions
+    pub async fn flatten(&self) -> Result<FlattenSummary, Error> {
+        let positions = Positions::new(self.client, self.account_id.clone());
+        let open = positions.open_positions().await?;
+        let positions = &positions;
+        Ok(stream::iter(open)
+            .map(|position| async move {
+                let request = ClosePositionRequest::all(&position);
+                let result = positions.close(&position.instrument, &request).await;
+                (position.instrument, result)
+            })
+            .buffer_unordered(FLATTEN_CONCURRENCY)
+            .collect()
+            .await)
+    }
+}
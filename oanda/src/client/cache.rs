@@ -0,0 +1,144 @@
+//! An on disk cache of GET responses, so restarting the robot doesn't
+//! download the same instrument lists and candles again.
+//!
+//! When we have a cached response with an `ETag` or `Last-Modified` header,
+//! the next request for the same URL sends `If-None-Match` /
+//! `If-Modified-Since`, and a `304 Not Modified` reply is answered from the
+//! cache. Responses without either header are never cached.
+//!
+//! The cache is best effort; if it can't be read or written we just go to
+//! the network.
+use std::path::PathBuf;
+
+use reqwest::header::{HeaderMap, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+}
+
+/// A response we've seen before
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub(crate) struct CachedResponse {
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub body: String,
+}
+
+impl CachedResponse {
+    /// The headers that ask the server to only reply if this response is out of date
+    pub fn conditional_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let pairs = [
+            (IF_NONE_MATCH, &self.etag),
+            (IF_MODIFIED_SINCE, &self.last_modified),
+        ];
+        for (name, value) in pairs {
+            if let Some(value) = value.as_deref().and_then(|value| value.parse().ok()) {
+                headers.insert(name, value);
+            }
+        }
+        headers
+    }
+}
+
+impl ResponseCache {
+    /// Stores responses as files in `dir`, which is created if needed
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, url: &str) -> PathBuf {
+        self.dir
+            .join(format!("{:016x}.json", fnv1a(url.as_bytes())))
+    }
+
+    pub(crate) async fn load(&self, url: &str) -> Option<CachedResponse> {
+        let contents = tokio::fs::read(self.path(url)).await.ok()?;
+        serde_json::from_slice::<CachedResponse>(&contents)
+            .ok()
+            // Guard against two urls hashing the same
+            .filter(|cached| cached.url == url)
+    }
+
+    /// Remembers `body` if the response `headers` let us check it's up to date later
+    pub(crate) async fn store(&self, url: &str, headers: &HeaderMap, body: &str) {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value: &reqwest::header::HeaderValue| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let cached = CachedResponse {
+            url: url.to_string(),
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+            body: body.to_string(),
+        };
+        if cached.etag.is_none() && cached.last_modified.is_none() {
+            return;
+        }
+        let result = async {
+            tokio::fs::create_dir_all(&self.dir).await?;
+            let contents = serde_json::to_vec(&cached)?;
+            tokio::fs::write(self.path(url), contents).await
+        }
+        .await;
+        if let Err(err) = result {
+            debug!("Couldn't cache the response from {url}: {err}");
+        }
+    }
+}
+
+/// A hash that doesn't change between rust versions, for naming cache files
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn cache(name: &str) -> ResponseCache {
+        let dir = std::env::temp_dir().join(format!("oanda-cache-test-{name}"));
+        let _ = std::fs::remove_dir_all(&dir);
+        ResponseCache::new(dir)
+    }
+
+    #[tokio::test]
+    async fn round_trip() {
+        let cache = cache("round-trip");
+        let url = "https://example.com/v3/instruments";
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, "\"abc\"".parse().unwrap());
+        cache.store(url, &headers, "{}").await;
+        let cached = cache.load(url).await.unwrap();
+        assert_eq!(
+            CachedResponse {
+                url: url.to_string(),
+                etag: Some("\"abc\"".to_string()),
+                last_modified: None,
+                body: "{}".to_string(),
+            },
+            cached
+        );
+        let conditional = cached.conditional_headers();
+        assert_eq!("\"abc\"", conditional[IF_NONE_MATCH]);
+        assert!(!conditional.contains_key(IF_MODIFIED_SINCE));
+        assert_eq!(None, cache.load("https://example.com/other").await);
+    }
+
+    #[tokio::test]
+    async fn uncacheable() {
+        let cache = cache("uncacheable");
+        let url = "https://example.com/v3/accounts";
+        cache.store(url, &HeaderMap::new(), "{}").await;
+        assert_eq!(None, cache.load(url).await);
+    }
+}
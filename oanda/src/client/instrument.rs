@@ -1,14 +1,21 @@
 pub use crate::model;
-use crate::{client::Client, error::Error};
-use chrono::{DateTime, Utc};
+use crate::{
+    client::{
+        snapshot::{RequestSnapshot, Snapshot},
+        Client,
+    },
+    error::Error,
+};
+use chrono::{DateTime, Duration, Utc};
 use error_stack::{Result, ResultExt};
+use futures::{stream, Stream, TryStreamExt};
+use reqwest::Method;
 use serde::Serialize;
 use std::fmt;
-use tracing::debug;
 use typed_builder::TypedBuilder;
 
 use self::model::{
-    candle::CandlestickGranularity,
+    candle::{CandleAlignment, CandlestickGranularity, HourlySpreads},
     date_time::DateTimeFormat,
     instrument::{DayOfWeek, PricingComponent},
 };
@@ -28,6 +35,111 @@ impl<'a> Instrument<'a> {
     {
         CandleStickRequest::builder().instruments(self)
     }
+
+    /// Pages forward through the candles from `from` until now. Handy for
+    /// backfilling. See [`CandlesFrom::pages`]
+    pub fn candles_from(&self, from: DateTime<Utc>) -> CandlesFrom<'_> {
+        CandlesFrom {
+            instrument: self,
+            from,
+            granularity: None,
+            price: None,
+            page_size: CandlesFrom::MAX_PAGE_SIZE,
+        }
+    }
+
+    /// Downloads bid and ask candles of `granularity` covering the last
+    /// `window`, and works out the spread at each hour of the day
+    pub async fn spread_stats(
+        &self,
+        window: Duration,
+        granularity: CandlestickGranularity,
+    ) -> Result<HourlySpreads, Error> {
+        let pages: Vec<Vec<model::Candle>> = self
+            .candles_from(Utc::now() - window)
+            .granularity(granularity)
+            .price(PricingComponent::default().bid().ask())
+            .pages()
+            .try_collect()
+            .await
+            .attach_printable_lazy(|| format!("Sampling spreads for {}", self.instrument))?;
+        Ok(HourlySpreads::from_candles(pages.iter().flatten()))
+    }
+}
+
+/// Downloads candles a page at a time, walking forward from a start time
+/// using OANDA's `from` + `count` parameters
+pub struct CandlesFrom<'a> {
+    instrument: &'a Instrument<'a>,
+    from: DateTime<Utc>,
+    granularity: Option<CandlestickGranularity>,
+    price: Option<PricingComponent>,
+    page_size: u32,
+}
+
+impl<'a> CandlesFrom<'a> {
+    /// The most candles OANDA will return in one request
+    pub const MAX_PAGE_SIZE: u32 = 5000;
+
+    /// [default=S5]
+    pub fn granularity(mut self, granularity: CandlestickGranularity) -> Self {
+        self.granularity = Some(granularity);
+        self
+    }
+
+    /// [default=M]
+    pub fn price(mut self, price: PricingComponent) -> Self {
+        self.price = Some(price);
+        self
+    }
+
+    /// The number of candles to ask for in each request. Clamped to 1 to
+    /// [`Self::MAX_PAGE_SIZE`]. [default=5000]
+    pub fn page_size(mut self, page_size: u32) -> Self {
+        self.page_size = page_size.clamp(1, Self::MAX_PAGE_SIZE);
+        self
+    }
+
+    /// Yields a page of candles at a time, oldest first, until we catch up
+    /// to now. The last page may end with the current incomplete candle.
+    /// Stops after the first error.
+    pub fn pages(self) -> impl Stream<Item = Result<Vec<model::Candle>, Error>> + 'a {
+        // The state is the time of the last candle we got, and whether we've reached now
+        stream::unfold((None, false), move |(last_time, done)| {
+            let request = CandleStickRequest {
+                instruments: self.instrument,
+                accept_datetime_format: None,
+                price: self.price.clone(),
+                granularity: self.granularity,
+                count: Some(self.page_size),
+                from: Some(last_time.unwrap_or(self.from)),
+                to: None,
+                smooth: None,
+                // Don't get the last candle of the previous page again
+                include_first: last_time.map(|_| false),
+                daily_alignment: None,
+                alignment_timezone: None,
+                weekly_alignment: None,
+            };
+            async move {
+                if done {
+                    return None;
+                }
+                match request.send().await {
+                    Err(err) => Some((Err(err), (last_time, true))),
+                    Ok(response) if response.candles.is_empty() => None,
+                    Ok(response) => {
+                        let candles = response.candles;
+                        let reached_now = candles.len()
+                            < request.count.unwrap_or_default() as usize
+                            || candles.iter().any(|candle| !candle.complete);
+                        let last_time = candles.last().map(|candle| candle.time);
+                        Some((Ok(candles), (last_time, reached_now)))
+                    }
+                }
+            }
+        })
+    }
 }
 
 #[derive(TypedBuilder, Serialize)]
@@ -38,6 +150,7 @@ pub struct CandleStickRequest<'a> {
     #[builder(setter(!strip_option))]
     instruments: &'a Instrument<'a>,
     /// Format of DateTime fields in the request and response.
+    #[serde(skip)] // Sent in the headers, not the url params
     #[builder(default, setter(strip_option))]
     accept_datetime_format: Option<DateTimeFormat>,
     /// The Price component(s) to get candlestick data for. [default=M]
@@ -95,11 +208,38 @@ pub struct CandleStickRequest<'a> {
 }
 
 impl<'a> CandleStickRequest<'a> {
+    /// Sets the daily and weekly alignment together, replacing any set in the builder
+    pub fn alignment(mut self, alignment: CandleAlignment) -> Self {
+        self.daily_alignment = alignment.daily_alignment;
+        self.alignment_timezone = alignment.alignment_timezone;
+        self.weekly_alignment = alignment.weekly_alignment;
+        self
+    }
+
+    /// Checks the alignment parameters make sense with the granularity.
+    /// See [`CandleAlignment::validate`]
+    pub fn validate(&self) -> Result<(), Error> {
+        let alignment = CandleAlignment {
+            daily_alignment: self.daily_alignment,
+            alignment_timezone: self.alignment_timezone.clone(),
+            weekly_alignment: self.weekly_alignment,
+        };
+        alignment.validate(self.granularity.unwrap_or(CandlestickGranularity::S5))
+    }
+
+    /// # Errors
+    ///
+    /// Fails without making a request if [`Self::validate`] does
     pub async fn send(&self) -> Result<model::candle::CandleResponse, Error> {
-        let path = format!("/v3/instruments/{}/candles", self.instruments.instrument);
-        let url = self.instruments.client.url(&path);
-        let request = self.instruments.client.start_get(&url).query(self);
-        debug!("Get candles request: {request:#?}");
+        self.validate()?;
+        let url = self.instruments.client.url(&self.path());
+        let format = self.accept_datetime_format.unwrap_or_default();
+        let request = self
+            .instruments
+            .client
+            .start_get(&url)
+            .header(format.header_name(), format.header_value())
+            .query(self);
         self.instruments
             .client
             .get(request)
@@ -108,6 +248,20 @@ impl<'a> CandleStickRequest<'a> {
     }
 }
 
+impl<'a> CandleStickRequest<'a> {
+    fn path(&self) -> String {
+        format!("/v3/instruments/{}/candles", self.instruments.instrument)
+    }
+}
+
+impl<'a> Snapshot for CandleStickRequest<'a> {
+    fn snapshot(&self) -> RequestSnapshot {
+        let format = self.accept_datetime_format.unwrap_or_default();
+        RequestSnapshot::new(&Method::GET, self.path(), self)
+            .header(format.header_name(), format.header_value())
+    }
+}
+
 impl<'a> fmt::Debug for CandleStickRequest<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("CandleStickRequest")
@@ -133,6 +287,25 @@ mod test {
 
     use crate::{client::Client, model::candle::CandlestickGranularity};
 
+    #[test]
+    fn snapshot() {
+        use crate::client::snapshot::Snapshot;
+        let client = Client::new("secret-token".to_string(), crate::host::Host::Dev);
+        let eur_usd = client.instrument("EUR_USD");
+        let request = eur_usd
+            .candles()
+            .granularity(CandlestickGranularity::H1)
+            .from(Utc.with_ymd_and_hms(2023, 1, 2, 0, 0, 0).unwrap())
+            .count(10)
+            .build();
+        let json = request.snapshot().to_json();
+        assert_eq!(
+            r#"{"method":"GET","path":"/v3/instruments/EUR_USD/candles","params":{"count":10,"from":"2023-01-02T00:00:00Z","granularity":"H1"},"headers":{"Accept-Datetime-Format":"RFC3339"}}"#,
+            json
+        );
+        assert!(!json.contains("secret-token"));
+    }
+
     #[tokio::test]
     async fn candles() {
         let api_key =
@@ -160,6 +333,60 @@ mod test {
         assert_eq!(candles.granularity, CandlestickGranularity::H1);
     }
 
+    #[tokio::test]
+    async fn candles_from_pages() {
+        use futures::TryStreamExt;
+        let api_key =
+            var("OANDA_TOKEN").expect("expected OANDA_TOKEN environment variable to be set");
+        let client = Client::new(api_key, crate::host::Host::Dev);
+        let eur_usd = client.instrument("EUR_USD");
+        let start_date = Utc::now() - chrono::Duration::days(3);
+        let pages: Vec<_> = eur_usd
+            .candles_from(start_date)
+            .granularity(CandlestickGranularity::M5)
+            .page_size(100)
+            .pages()
+            .try_collect()
+            .await
+            .unwrap();
+        assert!(pages.len() > 1);
+        let candles: Vec<_> = pages.into_iter().flatten().collect();
+        // No gaps or repeats between pages
+        assert!(candles.windows(2).all(|pair| pair[0].time < pair[1].time));
+        assert!(candles.first().unwrap().time >= start_date);
+    }
+
+    #[tokio::test]
+    async fn candles_spread_stats() {
+        let api_key =
+            var("OANDA_TOKEN").expect("expected OANDA_TOKEN environment variable to be set");
+        let client = Client::new(api_key, crate::host::Host::Dev);
+        let eur_usd = client.instrument("EUR_USD");
+        let spreads = eur_usd
+            .spread_stats(chrono::Duration::days(7), CandlestickGranularity::H1)
+            .await
+            .unwrap();
+        assert!(!spreads.is_empty());
+        assert!(spreads.iter().all(|stats| stats.median >= 0.0));
+    }
+
+    #[tokio::test]
+    async fn alignment_rejected_before_sending() {
+        use crate::model::{candle::CandleAlignment, instrument::DayOfWeek};
+        use crate::Error;
+        let client = Client::new("no-token-needed".to_string(), crate::host::Host::Dev);
+        let eur_usd = client.instrument("EUR_USD");
+        let err = eur_usd
+            .candles()
+            .granularity(CandlestickGranularity::H1)
+            .build()
+            .alignment(CandleAlignment::new().weekly(DayOfWeek::Monday))
+            .send()
+            .await
+            .unwrap_err();
+        assert!(matches!(err.current_context(), Error::CandleAlignment(_)));
+    }
+
     #[tokio::test]
     async fn candles_date_range() {
         let api_key =
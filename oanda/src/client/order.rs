@@ -1,5 +1,7 @@
 //! Anything order related. See <https://developer.oanda.com/rest-live-v20/order-ep/>
-use crate::client::Client;
+use error_stack::{Result, ResultExt};
+
+use crate::{client::Client, model::order::CancelOrderResponse, Error};
 
 use self::order_request::MarketOrderRequest;
 mod order_request;
@@ -25,6 +27,22 @@ impl<'a> Order<'a> {
     pub fn market_order(&self) -> MarketOrderRequestBuilder {
         MarketOrderRequest::builder().order_endpoint(self)
     }
+
+    /// Cancels a pending order, by OANDA's id or by our client id prefixed
+    /// with `@`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the http request fails or the Json deseralization fails
+    pub async fn cancel(&self, order: &str) -> Result<CancelOrderResponse, Error> {
+        let path = format!("/v3/accounts/{}/orders/{order}/cancel", self.account_id);
+        let request = self.client.start_put(&self.client.url(&path));
+        self.client
+            .send(request)
+            .await
+            .change_context(Error::CancelOrder)
+            .attach_printable_lazy(|| format!("Order: {order}"))
+    }
 }
 
 // pub struct OrderRequest<'a> {  }
@@ -0,0 +1,106 @@
+//! Open positions and closing them. See <https://developer.oanda.com/rest-live-v20/position-ep/>
+use error_stack::{Result, ResultExt};
+
+use crate::{
+    client::Client,
+    error::Error,
+    model::position::{
+        ClosePositionRequest, ClosePositionResponse, OpenPositionsPl, Position, PositionsResponse,
+    },
+};
+
+#[derive(Debug)]
+pub struct Positions<'a> {
+    client: &'a Client,
+    account_id: String,
+}
+
+impl<'a> Positions<'a> {
+    pub fn new(client: &'a Client, account_id: String) -> Self {
+        Self { client, account_id }
+    }
+
+    /// Every position in the account that has an open trade
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the http request fails or the Json deseralization fails
+    pub async fn open_positions(&self) -> Result<Vec<Position>, Error> {
+        let url = self
+            .client
+            .url(&format!("/v3/accounts/{}/openPositions", self.account_id));
+        self.client
+            .get(self.client.start_get(&url))
+            .await
+            .map(|response: PositionsResponse| response.positions)
+            .change_context(Error::ListPositions)
+    }
+
+    /// The open positions with their unrealized P&L in the home currency and
+    /// as a percent of NAV. Fetches the positions and the account summary
+    /// at the same time
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if either http request fails or the Json deseralization fails
+    pub async fn open_positions_pl(&self) -> Result<OpenPositionsPl, Error> {
+        let accounts = self.client.accounts();
+        let (positions, summary) =
+            futures::try_join!(self.open_positions(), accounts.summary(&self.account_id))?;
+        Ok(OpenPositionsPl::new(&summary, positions))
+    }
+
+    /// Closes out the sides of `instrument`'s position given by `request` at market price
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the http request fails or the Json deseralization fails
+    pub async fn close(
+        &self,
+        instrument: &str,
+        request: &ClosePositionRequest,
+    ) -> Result<ClosePositionResponse, Error> {
+        let url = self.client.url(&format!(
+            "/v3/accounts/{}/positions/{instrument}/close",
+            self.account_id
+        ));
+        self.client
+            .send(self.client.start_put(&url).json(request))
+            .await
+            .change_context(Error::ClosePosition)
+            .attach_printable_lazy(|| format!("Closing {instrument} with {request:?}"))
+    }
+}
+
+#[cfg(test)]
+mod api_tests {
+    use crate::client::test_utils::get_account_id;
+    use crate::Client;
+    use std::env::var;
+
+    #[tokio::test]
+    async fn open_positions() {
+        let api_key =
+            var("OANDA_TOKEN").expect("expected OANDA_TOKEN environment variable to be set");
+        let client = Client::new(api_key, crate::host::Host::Dev);
+        let account_id = get_account_id(&client).await.unwrap();
+        let positions = client.positions(account_id).open_positions().await.unwrap();
+        assert!(positions
+            .iter()
+            .all(|position| position.long.is_open() || position.short.is_open()));
+    }
+
+    #[tokio::test]
+    async fn open_positions_pl() {
+        let api_key =
+            var("OANDA_TOKEN").expect("expected OANDA_TOKEN environment variable to be set");
+        let client = Client::new(api_key, crate::host::Host::Dev);
+        let account_id = get_account_id(&client).await.unwrap();
+        let report = client
+            .positions(account_id)
+            .open_positions_pl()
+            .await
+            .unwrap();
+        assert!(report.nav > 0.0);
+    }
+}
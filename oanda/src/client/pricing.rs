@@ -0,0 +1,157 @@
+//! Current prices for an account. See <https://developer.oanda.com/rest-live-v20/pricing-ep/>
+use error_stack::{report, Result, ResultExt};
+
+mod candles;
+mod conflate;
+mod halts;
+mod stream;
+pub use candles::{CandleBuilder, IntoLiveCandles, LiveCandle, LiveCandles};
+pub use conflate::{ConflatePrices, Conflated};
+pub use halts::{HaltChange, HaltTracker};
+
+use crate::{
+    client::Client,
+    error::Error,
+    model::{
+        currency::{Currency, HomeConversionFactors},
+        pricing::{ClientPrice, PricesResponse},
+    },
+};
+
+#[derive(Debug)]
+pub struct Pricing<'a> {
+    client: &'a Client,
+    account_id: String,
+}
+
+impl<'a> Pricing<'a> {
+    pub fn new(client: &'a Client, account_id: String) -> Self {
+        Self { client, account_id }
+    }
+
+    /// The latest price of each of `instruments`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the http request fails or the Json deseralization fails
+    pub async fn prices<T: ToString>(
+        &self,
+        instruments: impl IntoIterator<Item = T>,
+    ) -> Result<Vec<ClientPrice>, Error> {
+        let instruments: Vec<String> = instruments.into_iter().map(|i| i.to_string()).collect();
+        let instruments = instruments.join(",");
+        let url = self
+            .client
+            .url(&format!("/v3/accounts/{}/pricing", self.account_id));
+        let request = self
+            .client
+            .start_get(&url)
+            .query(&[("instruments", &instruments)]);
+        self.client
+            .get(request)
+            .await
+            .map(|response: PricesResponse| response.prices)
+            .attach_printable_lazy(|| format!("While getting prices for {instruments}"))
+    }
+
+    /// The factors to convert P&L and pip values on the currency pair
+    /// `instrument` into the account's home currency
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the http request fails,
+    /// `instrument` isn't a currency pair, or OANDA doesn't send a conversion
+    /// for one of its currencies
+    pub async fn home_conversion_factors(
+        &self,
+        instrument: &str,
+    ) -> Result<HomeConversionFactors, Error> {
+        let (base, quote) = Currency::split_instrument(instrument)
+            .ok_or_else(|| report!(Error::InvalidCurrency(instrument.to_string())))
+            .attach_printable("Home conversions are only worked out for currency pairs")?;
+        let url = self
+            .client
+            .url(&format!("/v3/accounts/{}/pricing", self.account_id));
+        let request = self.client.start_get(&url).query(&[
+            ("instruments", instrument),
+            ("includeHomeConversions", "true"),
+        ]);
+        let response: PricesResponse =
+            self.client.get(request).await.attach_printable_lazy(|| {
+                format!("While getting home conversions for {instrument}")
+            })?;
+        let conversion = |currency: &Currency| {
+            response
+                .home_conversions
+                .iter()
+                .find(|conversion| &conversion.currency == currency)
+                .ok_or_else(|| report!(Error::Other))
+                .attach_printable_lazy(|| format!("No home conversion came back for {currency}"))
+        };
+        Ok(HomeConversionFactors::new(
+            conversion(&base)?,
+            conversion(&quote)?,
+        ))
+    }
+
+    /// Whether `instrument` can be traded right now, according to OANDA
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the http request fails or OANDA
+    /// doesn't send a price for the instrument
+    pub async fn is_tradeable(&self, instrument: &str) -> Result<bool, Error> {
+        Ok(self.price(instrument).await?.tradeable)
+    }
+
+    /// The current price of `instrument`, eg. to feed a [`HaltTracker`]
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the http request fails or OANDA
+    /// doesn't send a price for the instrument
+    pub async fn price(&self, instrument: &str) -> Result<ClientPrice, Error> {
+        self.prices([instrument])
+            .await?
+            .into_iter()
+            .find(|price| price.instrument == instrument)
+            .ok_or_else(|| report!(Error::Other))
+            .attach_printable_lazy(|| format!("No price came back for {instrument}"))
+    }
+}
+
+#[cfg(test)]
+mod api_tests {
+    use crate::client::test_utils::get_account_id;
+    use crate::Client;
+    use std::env::var;
+
+    #[tokio::test]
+    async fn prices() {
+        let api_key =
+            var("OANDA_TOKEN").expect("expected OANDA_TOKEN environment variable to be set");
+        let client = Client::new(api_key, crate::host::Host::Dev);
+        let account_id = get_account_id(&client).await.unwrap();
+        let prices = client
+            .pricing(account_id)
+            .prices(["EUR_USD", "USD_JPY"])
+            .await
+            .unwrap();
+        assert_eq!(2, prices.len());
+        dbg!(prices);
+    }
+
+    #[tokio::test]
+    async fn home_conversion_factors() {
+        let api_key =
+            var("OANDA_TOKEN").expect("expected OANDA_TOKEN environment variable to be set");
+        let client = Client::new(api_key, crate::host::Host::Dev);
+        let account_id = get_account_id(&client).await.unwrap();
+        let factors = client
+            .pricing(account_id)
+            .home_conversion_factors("EUR_JPY")
+            .await
+            .unwrap();
+        assert!(factors.pip_value(10_000.0, -2) > 0.0);
+    }
+}
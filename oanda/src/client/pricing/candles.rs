@@ -0,0 +1,294 @@
+//! Builds candles from a price stream as the prices come in, so a strategy
+//! can react part way through a candle without asking OANDA for it.
+use std::{
+    collections::BTreeMap,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use chrono::{DateTime, Duration, Utc};
+use error_stack::Result;
+use futures::Stream;
+
+use crate::{
+    error::Error,
+    model::{
+        candle::{CandlestickData, CandlestickGranularity},
+        price::from_f32,
+        pricing::ClientPrice,
+        Candle,
+    },
+};
+
+/// A candle built from the price stream
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiveCandle {
+    pub instrument: String,
+    /// Incomplete until a price after its end arrives, or it's closed with
+    /// [`CandleBuilder::close_before`]. `volume` is the number of prices
+    pub candle: Candle,
+}
+
+/// Keeps the current candle of each instrument up to date from prices.
+/// Candles start on multiples of the granularity from midnight UTC, the
+/// same as OANDA's candles of an hour or less
+#[derive(Debug, Clone)]
+pub struct CandleBuilder {
+    length: Duration,
+    /// The current candle of each instrument
+    candles: BTreeMap<String, Candle>,
+}
+
+impl CandleBuilder {
+    /// None for granularities longer than an hour, whose candles are lined
+    /// up with the daily alignment rather than midnight UTC
+    pub fn new(granularity: CandlestickGranularity) -> Option<Self> {
+        let length = granularity
+            .duration()
+            .filter(|length| *length <= Duration::hours(1))?;
+        Some(Self {
+            length,
+            candles: BTreeMap::new(),
+        })
+    }
+
+    /// The start time of the candle `time` is in
+    fn start(&self, time: DateTime<Utc>) -> DateTime<Utc> {
+        let length = self.length.num_milliseconds();
+        let millis = time.timestamp_millis();
+        let start = millis - millis.rem_euclid(length);
+        DateTime::from_timestamp_millis(start).unwrap_or(time)
+    }
+
+    /// The current candle of `instrument`, if we've had a price for it
+    pub fn current(&self, instrument: &str) -> Option<&Candle> {
+        self.candles.get(instrument)
+    }
+
+    /// Adds a price. Returns the instrument's previous candle, now
+    /// complete, if the price is in a later candle, followed by the updated
+    /// current candle. Prices without a bid and an ask are ignored, as are
+    /// prices older than the current candle
+    pub fn update(&mut self, price: &ClientPrice) -> Vec<LiveCandle> {
+        let Some((bid, ask)) = price.best_bid().zip(price.best_ask()) else {
+            return Vec::new();
+        };
+        let start = self.start(price.time);
+        let mut out = Vec::new();
+        match self.candles.get_mut(&price.instrument) {
+            Some(candle) if candle.time == start => {
+                for (data, price) in [
+                    (&mut candle.bid, bid),
+                    (&mut candle.ask, ask),
+                    (&mut candle.mid, (bid + ask) / 2.0),
+                ] {
+                    if let Some(data) = data {
+                        let price = from_f32(price);
+                        data.h = data.h.max(price);
+                        data.l = data.l.min(price);
+                        data.c = price;
+                    }
+                }
+                candle.volume += 1;
+            }
+            Some(candle) if candle.time > start => return Vec::new(),
+            _ => {
+                let new = Candle {
+                    time: start,
+                    bid: Some(flat(bid)),
+                    ask: Some(flat(ask)),
+                    mid: Some(flat((bid + ask) / 2.0)),
+                    volume: 1,
+                    complete: false,
+                };
+                if let Some(mut previous) = self.candles.insert(price.instrument.clone(), new) {
+                    previous.complete = true;
+                    out.push(LiveCandle {
+                        instrument: price.instrument.clone(),
+                        candle: previous,
+                    });
+                }
+            }
+        }
+        out.extend(self.current(&price.instrument).map(|candle| LiveCandle {
+            instrument: price.instrument.clone(),
+            candle: candle.clone(),
+        }));
+        out
+    }
+
+    /// Completes and removes the candles that ended at or before `now`, for
+    /// when an instrument goes quiet. Call it on a timer
+    pub fn close_before(&mut self, now: DateTime<Utc>) -> Vec<LiveCandle> {
+        let ended: Vec<String> = self
+            .candles
+            .iter()
+            .filter(|(_, candle)| candle.time + self.length <= now)
+            .map(|(instrument, _)| instrument.clone())
+            .collect();
+        ended
+            .into_iter()
+            .flat_map(|instrument| {
+                let mut candle = self.candles.remove(&instrument)?;
+                candle.complete = true;
+                Some(LiveCandle { instrument, candle })
+            })
+            .collect()
+    }
+}
+
+/// A candle that opened, and so far closed, at `price`
+fn flat(price: f32) -> CandlestickData {
+    let price = from_f32(price);
+    CandlestickData {
+        o: price,
+        h: price,
+        l: price,
+        c: price,
+    }
+}
+
+/// Build candles from a stream of prices, like the one from [`Pricing::stream`](super::Pricing::stream)
+pub trait IntoLiveCandles: Stream<Item = Result<ClientPrice, Error>> + Sized {
+    /// Yields the instrument's updated candle for every price, preceded by
+    /// the previous candle, complete, when a price starts a new one. Errors
+    /// are passed on. None for granularities [`CandleBuilder`] can't build
+    fn live_candles(self, granularity: CandlestickGranularity) -> Option<LiveCandles<Self>> {
+        Some(LiveCandles {
+            prices: Box::pin(self),
+            builder: CandleBuilder::new(granularity)?,
+            ready: Vec::new(),
+        })
+    }
+}
+
+impl<S> IntoLiveCandles for S where S: Stream<Item = Result<ClientPrice, Error>> {}
+
+pub struct LiveCandles<S> {
+    prices: Pin<Box<S>>,
+    builder: CandleBuilder,
+    /// Candles due to be yielded, last first
+    ready: Vec<LiveCandle>,
+}
+
+impl<S> Stream for LiveCandles<S>
+where
+    S: Stream<Item = Result<ClientPrice, Error>>,
+{
+    type Item = Result<LiveCandle, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(candle) = this.ready.pop() {
+                return Poll::Ready(Some(Ok(candle)));
+            }
+            match this.prices.as_mut().poll_next(cx) {
+                Poll::Ready(Some(Ok(price))) => {
+                    this.ready = this.builder.update(&price);
+                    this.ready.reverse();
+                }
+                Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err))),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::{price::to_f32, PriceBucket};
+    use chrono::TimeZone;
+    use futures::{stream, StreamExt};
+    use pretty_assertions::assert_eq;
+
+    fn price(instrument: &str, second: u32, bid: f32) -> ClientPrice {
+        let bucket = |price: f32| PriceBucket {
+            price: from_f32(price),
+            liquidity: 1_000_000,
+        };
+        ClientPrice {
+            instrument: instrument.to_string(),
+            time: Utc.with_ymd_and_hms(2023, 5, 2, 10, 0, second).unwrap(),
+            tradeable: true,
+            bids: vec![bucket(bid)],
+            asks: vec![bucket(bid + 0.0002)],
+            closeout_bid: from_f32(bid),
+            closeout_ask: from_f32(bid + 0.0002),
+        }
+    }
+
+    #[test]
+    fn too_long() {
+        assert!(CandleBuilder::new(CandlestickGranularity::H4).is_none());
+        assert!(CandleBuilder::new(CandlestickGranularity::M15).is_some());
+    }
+
+    #[test]
+    fn builds_candles() {
+        let mut builder = CandleBuilder::new(CandlestickGranularity::S5).unwrap();
+        builder.update(&price("EUR_USD", 1, 1.1000));
+        builder.update(&price("EUR_USD", 2, 1.1010));
+        let got = builder.update(&price("EUR_USD", 4, 1.0990));
+        assert_eq!(1, got.len());
+        let candle = &got[0].candle;
+        assert!(!candle.complete);
+        assert_eq!(3, candle.volume);
+        assert_eq!(
+            Utc.with_ymd_and_hms(2023, 5, 2, 10, 0, 0).unwrap(),
+            candle.time
+        );
+        let bid = candle.bid.as_ref().unwrap();
+        assert_eq!(
+            (1.1, 1.101, 1.099, 1.099),
+            (to_f32(bid.o), to_f32(bid.h), to_f32(bid.l), to_f32(bid.c))
+        );
+        // The next candle completes this one
+        let got = builder.update(&price("EUR_USD", 6, 1.0995));
+        assert_eq!(2, got.len());
+        assert!(got[0].candle.complete);
+        assert_eq!(3, got[0].candle.volume);
+        assert!(!got[1].candle.complete);
+        assert_eq!(
+            Utc.with_ymd_and_hms(2023, 5, 2, 10, 0, 5).unwrap(),
+            got[1].candle.time
+        );
+        // A late price for the old candle is ignored
+        assert!(builder.update(&price("EUR_USD", 3, 1.2)).is_empty());
+    }
+
+    #[test]
+    fn close_quiet_instruments() {
+        let mut builder = CandleBuilder::new(CandlestickGranularity::S5).unwrap();
+        builder.update(&price("EUR_USD", 1, 1.1000));
+        builder.update(&price("USD_JPY", 7, 130.0));
+        let now = Utc.with_ymd_and_hms(2023, 5, 2, 10, 0, 8).unwrap();
+        let closed = builder.close_before(now);
+        assert_eq!(1, closed.len());
+        assert_eq!("EUR_USD", closed[0].instrument);
+        assert!(closed[0].candle.complete);
+        assert!(builder.current("EUR_USD").is_none());
+        assert!(builder.current("USD_JPY").is_some());
+    }
+
+    #[tokio::test]
+    async fn stream() {
+        let prices = [
+            price("EUR_USD", 1, 1.1000),
+            price("EUR_USD", 2, 1.1010),
+            price("EUR_USD", 6, 1.1020),
+        ];
+        let got: Vec<_> = stream::iter(prices.map(Ok))
+            .live_candles(CandlestickGranularity::S5)
+            .unwrap()
+            .map(|candle| {
+                let candle = candle.unwrap().candle;
+                (candle.volume, candle.complete)
+            })
+            .collect()
+            .await;
+        assert_eq!(vec![(1, false), (2, false), (2, true), (1, false)], got);
+    }
+}
@@ -0,0 +1,151 @@
+//! Slows a price stream down to the pace a strategy can use it.
+use std::{
+    collections::BTreeMap,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use error_stack::Result;
+use futures::Stream;
+use tokio::time::{interval, Interval, MissedTickBehavior};
+
+use crate::{error::Error, model::pricing::ClientPrice};
+
+/// Conflate a stream of prices, like the one from [`Pricing::stream`](super::Pricing::stream)
+pub trait ConflatePrices: Stream<Item = Result<ClientPrice, Error>> + Sized {
+    /// Yields at most one price per instrument every `period`; the latest
+    /// one received in that period. Instruments whose price didn't change
+    /// in a period are skipped. Errors are passed on straight away.
+    ///
+    /// When the underlying stream ends, the prices still waiting are yielded
+    /// and the stream ends.
+    fn conflate(self, period: Duration) -> Conflated<Self> {
+        Conflated {
+            prices: Box::pin(self),
+            period,
+            ticker: None,
+            latest: BTreeMap::new(),
+            ready: Vec::new(),
+            done: false,
+        }
+    }
+}
+
+impl<S> ConflatePrices for S where S: Stream<Item = Result<ClientPrice, Error>> {}
+
+pub struct Conflated<S> {
+    prices: Pin<Box<S>>,
+    period: Duration,
+    /// Made on the first poll, so the stream can be built outside a runtime
+    ticker: Option<Interval>,
+    /// The latest price of each instrument since the last tick
+    latest: BTreeMap<String, ClientPrice>,
+    /// Prices due to be yielded, last first
+    ready: Vec<ClientPrice>,
+    done: bool,
+}
+
+impl<S> Conflated<S> {
+    fn flush(&mut self) {
+        self.ready = std::mem::take(&mut self.latest)
+            .into_values()
+            .rev()
+            .collect();
+    }
+}
+
+impl<S> Stream for Conflated<S>
+where
+    S: Stream<Item = Result<ClientPrice, Error>>,
+{
+    type Item = Result<ClientPrice, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(price) = this.ready.pop() {
+                return Poll::Ready(Some(Ok(price)));
+            }
+            if this.done {
+                return Poll::Ready(None);
+            }
+            let period = this.period;
+            let ticker = this.ticker.get_or_insert_with(|| {
+                let mut ticker = interval(period);
+                ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                ticker
+            });
+            if ticker.poll_tick(cx).is_ready() {
+                this.flush();
+                continue;
+            }
+            match this.prices.as_mut().poll_next(cx) {
+                Poll::Ready(Some(Ok(price))) => {
+                    this.latest.insert(price.instrument.clone(), price);
+                }
+                Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err))),
+                Poll::Ready(None) => {
+                    this.done = true;
+                    this.flush();
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::price::{from_f32, to_f32};
+    use chrono::Utc;
+    use error_stack::report;
+    use futures::{stream, StreamExt};
+    use pretty_assertions::assert_eq;
+
+    fn price(instrument: &str, bid: f32) -> ClientPrice {
+        ClientPrice {
+            instrument: instrument.to_string(),
+            time: Utc::now(),
+            tradeable: true,
+            bids: vec![],
+            asks: vec![],
+            closeout_bid: from_f32(bid),
+            closeout_ask: from_f32(bid),
+        }
+    }
+
+    #[tokio::test]
+    async fn keeps_the_latest_per_instrument() {
+        let prices = [
+            price("USD_JPY", 130.0),
+            price("EUR_USD", 1.0),
+            price("EUR_USD", 1.1),
+            price("USD_JPY", 131.0),
+            price("EUR_USD", 1.2),
+        ];
+        let got: Vec<_> = stream::iter(prices.map(Ok))
+            .conflate(Duration::from_secs(60))
+            .map(|price| {
+                let price = price.unwrap();
+                (price.instrument, to_f32(price.closeout_bid))
+            })
+            .collect()
+            .await;
+        assert_eq!(
+            vec![("EUR_USD".to_string(), 1.2), ("USD_JPY".to_string(), 131.0)],
+            got
+        );
+    }
+
+    #[tokio::test]
+    async fn passes_errors_on() {
+        let prices = vec![Ok(price("EUR_USD", 1.0)), Err(report!(Error::Other))];
+        let mut got = stream::iter(prices).conflate(Duration::from_secs(60));
+        assert!(got.next().await.unwrap().is_err());
+        let price = got.next().await.unwrap().unwrap();
+        assert_eq!(1.0, to_f32(price.closeout_bid));
+        assert!(got.next().await.is_none());
+    }
+}
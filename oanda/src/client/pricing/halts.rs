@@ -0,0 +1,118 @@
+//! Notices when instruments stop and start trading, eg. for a news halt,
+//! from the `tradeable` flag on their prices. Anything managing positions
+//! should pause while an instrument is halted rather than send orders that
+//! will be rejected.
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::model::pricing::ClientPrice;
+
+/// A change in whether an instrument can be traded
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum HaltChange {
+    /// The instrument stopped trading at `since`
+    Halted {
+        instrument: String,
+        since: DateTime<Utc>,
+    },
+    /// The instrument is trading again after `halted_for`
+    Resumed {
+        instrument: String,
+        halted_for: Duration,
+    },
+}
+
+/// Remembers which instruments are halted. Feed it every price, eg. from
+/// [`Pricing::stream`](super::Pricing::stream). It serializes as a map of
+/// instrument to when it stopped trading, so something that only looks now
+/// and then can keep it between runs
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct HaltTracker {
+    halted_since: BTreeMap<String, DateTime<Utc>>,
+}
+
+impl HaltTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes a price into account. Returns the change if the instrument was
+    /// just halted or just resumed, so it's only reported once
+    pub fn update(&mut self, price: &ClientPrice) -> Option<HaltChange> {
+        let instrument = &price.instrument;
+        match (price.tradeable, self.halted_since.get(instrument)) {
+            (false, None) => {
+                self.halted_since.insert(instrument.clone(), price.time);
+                Some(HaltChange::Halted {
+                    instrument: instrument.clone(),
+                    since: price.time,
+                })
+            }
+            (true, Some(&since)) => {
+                self.halted_since.remove(instrument);
+                Some(HaltChange::Resumed {
+                    instrument: instrument.clone(),
+                    halted_for: price.time - since,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    pub fn is_halted(&self, instrument: &str) -> bool {
+        self.halted_since.contains_key(instrument)
+    }
+
+    /// When `instrument` stopped trading, if it's halted
+    pub fn halted_since(&self, instrument: &str) -> Option<DateTime<Utc>> {
+        self.halted_since.get(instrument).copied()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::price::from_f32;
+    use chrono::TimeZone;
+    use pretty_assertions::assert_eq;
+
+    fn price(minute: u32, tradeable: bool) -> ClientPrice {
+        ClientPrice {
+            instrument: "XAU_USD".to_string(),
+            time: Utc.with_ymd_and_hms(2023, 5, 5, 12, minute, 0).unwrap(),
+            tradeable,
+            bids: vec![],
+            asks: vec![],
+            closeout_bid: from_f32(2000.0),
+            closeout_ask: from_f32(2000.5),
+        }
+    }
+
+    #[test]
+    fn halt_and_resume() {
+        let mut halts = HaltTracker::new();
+        assert_eq!(None, halts.update(&price(0, true)));
+        let halted = HaltChange::Halted {
+            instrument: "XAU_USD".to_string(),
+            since: price(1, false).time,
+        };
+        assert_eq!(Some(halted), halts.update(&price(1, false)));
+        // Only reported once
+        assert_eq!(None, halts.update(&price(2, false)));
+        assert!(halts.is_halted("XAU_USD"));
+        assert!(!halts.is_halted("EUR_USD"));
+        // Kept between runs
+        let saved = serde_json::to_string(&halts).unwrap();
+        assert_eq!(r#"{"XAU_USD":"2023-05-05T12:01:00Z"}"#, saved);
+        assert_eq!(halts, serde_json::from_str(&saved).unwrap());
+        let resumed = HaltChange::Resumed {
+            instrument: "XAU_USD".to_string(),
+            halted_for: Duration::minutes(4),
+        };
+        assert_eq!(Some(resumed), halts.update(&price(5, true)));
+        assert_eq!(None, halts.halted_since("XAU_USD"));
+    }
+}
@@ -0,0 +1,131 @@
+//! A live stream of prices. See <https://developer.oanda.com/rest-live-v20/pricing-ep/>
+use error_stack::{report, IntoReport, Result, ResultExt};
+use futures::{stream, Stream, StreamExt};
+
+use super::Pricing;
+use crate::{
+    error::Error,
+    model::pricing::{ClientPrice, PricingStreamMessage},
+};
+
+impl Pricing<'_> {
+    /// Streams the prices of `instruments` as they change. Heartbeats are
+    /// dropped. The stream ends when OANDA closes the connection.
+    ///
+    /// This can be thousands of prices a minute; see
+    /// [`ConflatePrices`](super::ConflatePrices) if you only need the latest.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the stream can't be opened.
+    /// Errors after that, like a line that isn't a price, are yielded by the
+    /// stream.
+    pub async fn stream<T: ToString>(
+        &self,
+        instruments: impl IntoIterator<Item = T>,
+    ) -> Result<impl Stream<Item = Result<ClientPrice, Error>>, Error> {
+        let instruments: Vec<String> = instruments.into_iter().map(|i| i.to_string()).collect();
+        let instruments = instruments.join(",");
+        let url = self
+            .client
+            .host
+            .streaming_url(format!("/v3/accounts/{}/pricing/stream", self.account_id));
+        let response = self
+            .client
+            .start_get(&url)
+            .query(&[("instruments", &instruments)])
+            .send()
+            .await
+            .map_err(Error::from)
+            .into_report()
+            .attach_printable_lazy(|| format!("While streaming prices for {instruments}"))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(report!(Error::Status(status)))
+                .attach_printable(format!("While streaming prices for {instruments}"));
+        }
+        Ok(
+            lines(response.bytes_stream()).filter_map(|line| async move {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(err) => return Some(Err(err)),
+                };
+                match serde_json::from_slice(&line) {
+                    Ok(PricingStreamMessage::Price(price)) => Some(Ok(price)),
+                    Ok(PricingStreamMessage::Heartbeat { .. }) => None,
+                    Err(err) => Some(
+                        Err(Error::JsonParse {
+                            err,
+                            input: String::from_utf8_lossy(&line).into_owned(),
+                        })
+                        .into_report(),
+                    ),
+                }
+            }),
+        )
+    }
+}
+
+/// Splits a stream of bytes into non empty lines. Stops after the first error
+fn lines<B, C>(bytes: B) -> impl Stream<Item = Result<Vec<u8>, Error>>
+where
+    B: Stream<Item = reqwest::Result<C>>,
+    C: AsRef<[u8]>,
+{
+    let bytes = Box::pin(bytes);
+    stream::unfold(Some((bytes, Vec::new())), |state| async move {
+        let (mut bytes, mut buffer) = state?;
+        loop {
+            if let Some(end) = buffer.iter().position(|byte| *byte == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                if line.iter().all(u8::is_ascii_whitespace) {
+                    continue;
+                }
+                return Some((Ok(line), Some((bytes, buffer))));
+            }
+            match bytes.next().await {
+                Some(Ok(chunk)) => buffer.extend_from_slice(chunk.as_ref()),
+                Some(Err(err)) => return Some((Err(report!(Error::from(err))), None)),
+                // Whatever's left without a newline is the last line
+                None if buffer.iter().all(u8::is_ascii_whitespace) => return None,
+                None => return Some((Ok(std::mem::take(&mut buffer)), Some((bytes, buffer)))),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn split_lines() {
+        let chunks = ["{\"a\":", "1}\n\n{\"b\"", ":2}\n{\"c\":3}"].map(Ok::<_, reqwest::Error>);
+        let got: Vec<_> = lines(stream::iter(chunks))
+            .map(|line| String::from_utf8(line.unwrap()).unwrap())
+            .collect()
+            .await;
+        assert_eq!(vec!["{\"a\":1}\n", "{\"b\":2}\n", "{\"c\":3}"], got);
+    }
+}
+
+#[cfg(test)]
+mod api_tests {
+    use crate::client::test_utils::get_account_id;
+    use crate::Client;
+    use futures::StreamExt;
+    use std::env::var;
+
+    #[tokio::test]
+    async fn stream_prices() {
+        let api_key =
+            var("OANDA_TOKEN").expect("expected OANDA_TOKEN environment variable to be set");
+        let client = Client::new(api_key, crate::host::Host::Dev);
+        let account_id = get_account_id(&client).await.unwrap();
+        let pricing = client.pricing(account_id);
+        let prices = pricing.stream(["EUR_USD"]).await.unwrap();
+        let price = Box::pin(prices).next().await.unwrap().unwrap();
+        assert_eq!("EUR_USD", price.instrument);
+    }
+}
@@ -0,0 +1,195 @@
+//! Records real request/response pairs as JSON fixtures, so tests against a
+//! mock server can replay real traffic.
+//!
+//! Fixtures are sanitized: the host and headers (including the API token)
+//! aren't recorded, and anything shaped like an account id is replaced with
+//! [`ACCOUNT_ID_PLACEHOLDER`] everywhere it appears, so the ids in an
+//! accounts list are hidden as well as the one in the path.
+//!
+//! Recording is best effort; a fixture that can't be written is logged and
+//! the request carries on.
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use chrono::Utc;
+use lazy_static::lazy_static;
+use regex::Regex;
+use reqwest::{Method, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::debug;
+
+/// What account ids are replaced with in fixtures
+pub const ACCOUNT_ID_PLACEHOLDER: &str = "000-000-0000000-000";
+
+lazy_static! {
+    /// OANDA account ids look like `101-011-1234567-001`
+    static ref ACCOUNT_ID: Regex = Regex::new(r"\d{3}-\d{3}-\d+-\d{3}").unwrap();
+}
+
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    dir: PathBuf,
+    /// Keeps fixtures from the same millisecond apart, and in order
+    count: Arc<AtomicUsize>,
+}
+
+/// One recorded request and the response to it
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Fixture {
+    pub method: String,
+    /// The path and query, without the host
+    pub path: String,
+    pub request_body: Option<Value>,
+    pub status: u16,
+    pub response_body: Value,
+}
+
+impl Fixture {
+    pub(crate) fn new(
+        method: &Method,
+        url: &Url,
+        request_body: Option<&str>,
+        status: StatusCode,
+        response_body: &str,
+    ) -> Self {
+        let sanitize = |text: &str| {
+            ACCOUNT_ID
+                .replace_all(text, ACCOUNT_ID_PLACEHOLDER)
+                .into_owned()
+        };
+        let path = match url.query() {
+            Some(query) => format!("{}?{query}", url.path()),
+            None => url.path().to_string(),
+        };
+        Self {
+            method: method.to_string(),
+            path: sanitize(&path),
+            request_body: request_body.map(|body| to_json(&sanitize(body))),
+            status: status.as_u16(),
+            response_body: to_json(&sanitize(response_body)),
+        }
+    }
+
+    /// A file name that sorts in recording order and says what the fixture is for
+    fn file_name(&self, count: usize) -> String {
+        let path = self.path.split('?').next().unwrap_or_default();
+        let slug: String = path
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        format!(
+            "{}-{count:04}-{}{slug}.json",
+            Utc::now().timestamp_millis(),
+            self.method
+        )
+    }
+}
+
+impl Recorder {
+    /// Writes fixtures as files in `dir`, which is created if needed
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            count: Arc::default(),
+        }
+    }
+
+    pub(crate) async fn record(&self, fixture: Fixture) {
+        let count = self.count.fetch_add(1, Ordering::Relaxed);
+        let path = self.dir.join(fixture.file_name(count));
+        let result = async {
+            tokio::fs::create_dir_all(&self.dir).await?;
+            let contents = serde_json::to_vec_pretty(&fixture)?;
+            tokio::fs::write(&path, contents).await
+        }
+        .await;
+        if let Err(err) = result {
+            debug!("Couldn't record a fixture to {}: {err}", path.display());
+        }
+    }
+}
+
+/// Bodies are JSON, but keep anything else as a string rather than lose it
+fn to_json(body: &str) -> Value {
+    serde_json::from_str(body).unwrap_or_else(|_| Value::String(body.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn sanitized() {
+        let url = "https://api-fxpractice.oanda.com/v3/accounts/101-011-1234567-001/positions/EUR_USD/close?x=1"
+            .parse()
+            .unwrap();
+        let got = Fixture::new(
+            &Method::PUT,
+            &url,
+            Some(r#"{"longUnits":"ALL"}"#),
+            StatusCode::OK,
+            r#"{"accountID":"101-011-1234567-001","lastTransactionID":"6"}"#,
+        );
+        assert_eq!(
+            Fixture {
+                method: "PUT".to_string(),
+                path: "/v3/accounts/000-000-0000000-000/positions/EUR_USD/close?x=1".to_string(),
+                request_body: Some(serde_json::json!({"longUnits": "ALL"})),
+                status: 200,
+                response_body: serde_json::json!({
+                    "accountID": ACCOUNT_ID_PLACEHOLDER,
+                    "lastTransactionID": "6"
+                }),
+            },
+            got
+        );
+        assert!(got
+            .file_name(3)
+            .ends_with("-0003-PUT_v3_accounts_000_000_0000000_000_positions_EUR_USD_close.json"));
+    }
+
+    #[test]
+    fn sanitized_accounts_list() {
+        let url = "https://api-fxpractice.oanda.com/v3/accounts"
+            .parse()
+            .unwrap();
+        let got = Fixture::new(
+            &Method::GET,
+            &url,
+            None,
+            StatusCode::OK,
+            r#"{"accounts":[{"id":"101-011-1234567-001","tags":[]},{"id":"101-011-1234567-002","tags":[]}]}"#,
+        );
+        assert_eq!("/v3/accounts", got.path);
+        assert_eq!(
+            serde_json::json!({"accounts": [
+                {"id": ACCOUNT_ID_PLACEHOLDER, "tags": []},
+                {"id": ACCOUNT_ID_PLACEHOLDER, "tags": []},
+            ]}),
+            got.response_body
+        );
+    }
+
+    #[tokio::test]
+    async fn record() {
+        let dir = std::env::temp_dir().join("oanda-recorder-test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let recorder = Recorder::new(&dir);
+        let url = "https://example.com/v3/accounts".parse().unwrap();
+        let fixture = || Fixture::new(&Method::GET, &url, None, StatusCode::UNAUTHORIZED, "nope");
+        recorder.record(fixture()).await;
+        let files: Vec<_> = std::fs::read_dir(&dir).unwrap().collect();
+        assert_eq!(1, files.len());
+        let contents = std::fs::read(files[0].as_ref().unwrap().path()).unwrap();
+        let got: Fixture = serde_json::from_slice(&contents).unwrap();
+        assert_eq!(fixture(), got);
+        assert_eq!(Value::String("nope".to_string()), got.response_body);
+    }
+}
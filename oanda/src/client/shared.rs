@@ -0,0 +1,168 @@
+//! The state every clone of a [`Client`](super::Client) shares: the rate
+//! limiter, the response cache, the recorder and the request counts. A
+//! clone per spawned task then still keeps to one rate limit between them.
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use tokio::{
+    sync::Mutex,
+    time::{sleep_until, Instant},
+};
+
+use super::{cache::ResponseCache, recorder::Recorder};
+
+/// OANDA allows 120 requests a second per connection. We keep under it
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    interval: Duration,
+    /// The earliest the next request may go
+    next: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub(crate) const DEFAULT_PER_SECOND: u32 = 100;
+
+    pub(crate) fn new(per_second: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(1) / per_second.max(1),
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    pub(crate) fn per_second(&self) -> u32 {
+        (Duration::from_secs(1).as_nanos() / self.interval.as_nanos()) as u32
+    }
+
+    /// Waits until it's our turn to send a request. Callers go in the
+    /// order they started waiting
+    pub(crate) async fn wait(&self) {
+        let mut next = self.next.lock().await;
+        let now = Instant::now();
+        if *next > now {
+            sleep_until(*next).await;
+        }
+        *next = (*next).max(now) + self.interval;
+    }
+
+    /// Holds every request back for `pause`, eg. after OANDA says we've
+    /// sent too many
+    pub(crate) async fn cool_down(&self, pause: Duration) {
+        let mut next = self.next.lock().await;
+        *next = (*next).max(Instant::now() + pause);
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_PER_SECOND)
+    }
+}
+
+/// How many requests the client (and all its clones) have sent
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RequestCounts {
+    pub requests: u64,
+    /// Requests that failed for any reason, including bad statuses
+    pub failures: u64,
+    /// Requests answered from the response cache
+    pub not_modified: u64,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Metrics {
+    requests: AtomicU64,
+    failures: AtomicU64,
+    not_modified: AtomicU64,
+}
+
+impl Metrics {
+    pub(crate) fn request(&self) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn failure(&self) {
+        self.failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn not_modified(&self) {
+        self.not_modified.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn counts(&self) -> RequestCounts {
+        RequestCounts {
+            requests: self.requests.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            not_modified: self.not_modified.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Shared {
+    pub(crate) cache: Option<ResponseCache>,
+    pub(crate) recorder: Option<Recorder>,
+    pub(crate) limiter: RateLimiter,
+    pub(crate) metrics: Metrics,
+}
+
+impl Shared {
+    /// The same settings, with a rate limit and counts of its own
+    pub(crate) fn detach(&self) -> Self {
+        Self {
+            cache: self.cache.clone(),
+            recorder: self.recorder.clone(),
+            limiter: RateLimiter::new(self.limiter.per_second()),
+            metrics: Metrics::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn spaces_requests() {
+        let limiter = RateLimiter::new(10);
+        let start = Instant::now();
+        for _ in 0..5 {
+            limiter.wait().await;
+        }
+        // The first goes straight away, then one every 100ms
+        assert_eq!(Duration::from_millis(400), start.elapsed());
+        assert_eq!(10, limiter.per_second());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cools_down() {
+        let limiter = RateLimiter::new(10);
+        let start = Instant::now();
+        limiter.wait().await;
+        limiter.cool_down(Duration::from_secs(2)).await;
+        limiter.wait().await;
+        assert_eq!(Duration::from_secs(2), start.elapsed());
+        // A shorter cool down doesn't bring the next request forward
+        limiter.cool_down(Duration::from_millis(1)).await;
+        limiter.wait().await;
+        assert_eq!(Duration::from_millis(2100), start.elapsed());
+    }
+
+    #[test]
+    fn counts() {
+        let metrics = Metrics::default();
+        metrics.request();
+        metrics.request();
+        metrics.failure();
+        metrics.not_modified();
+        assert_eq!(
+            RequestCounts {
+                requests: 2,
+                failures: 1,
+                not_modified: 1
+            },
+            metrics.counts()
+        );
+    }
+}
@@ -0,0 +1,105 @@
+//! Canonical JSON descriptions of requests, so the journal can record the
+//! exact parameters behind every call to the broker.
+//!
+//! A snapshot has the method, path and parameters of a request, with the
+//! parameters' keys sorted and unset ones left out, so the same request
+//! always gives the same JSON. Headers that change the response, like
+//! `Accept-Datetime-Format`, are kept too. The API token goes in a header but
+//! is never part of a snapshot.
+use std::collections::BTreeMap;
+
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// What a request will send. See the [module docs](self)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RequestSnapshot {
+    pub method: String,
+    /// The path, without the host
+    pub path: String,
+    /// The query parameters, or the body for requests that have one
+    pub params: Value,
+    /// The headers sent besides the API token, by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+}
+
+impl RequestSnapshot {
+    pub fn new(method: &Method, path: String, params: &impl Serialize) -> Self {
+        let params = serde_json::to_value(params).unwrap_or_default();
+        Self {
+            method: method.to_string(),
+            path,
+            params: canonical(params),
+            headers: BTreeMap::new(),
+        }
+    }
+
+    /// The snapshot with the header `name` set to `value`
+    pub fn header(mut self, name: &str, value: impl ToString) -> Self {
+        self.headers.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// The snapshot as a single line of JSON
+    pub fn to_json(&self) -> String {
+        // Only fails on maps with non string keys, and a Value can't have them
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// Requests that can describe themselves for the journal
+pub trait Snapshot {
+    fn snapshot(&self) -> RequestSnapshot;
+}
+
+/// Sorts object keys and drops null fields, at every level
+fn canonical(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut fields: Vec<_> = map
+                .into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .collect();
+            fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(key, value)| (key, canonical(value)))
+                    .collect::<Map<_, _>>(),
+            )
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(canonical).collect()),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Params {
+        to: Option<u32>,
+        count: Option<u32>,
+        nested: Vec<Value>,
+    }
+
+    #[test]
+    fn canonical_params() {
+        let params = Params {
+            to: None,
+            count: Some(5),
+            nested: vec![json!({"b": 1, "a": null, "c": [{"z": 1, "y": 2}]})],
+        };
+        let snapshot = RequestSnapshot::new(&Method::GET, "/v3/things".to_string(), &params);
+        assert_eq!(
+            r#"{"method":"GET","path":"/v3/things","params":{"count":5,"nested":[{"b":1,"c":[{"y":2,"z":1}]}]}}"#,
+            snapshot.to_json()
+        );
+    }
+}
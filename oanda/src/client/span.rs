@@ -0,0 +1,114 @@
+//! Names for the tracing span every request runs in, so slow broker calls
+//! show up in the logs by endpoint rather than by full URL.
+use reqwest::Url;
+
+use super::cache::fnv1a;
+
+/// What a request URL is for
+#[derive(Debug, PartialEq)]
+pub(crate) struct Endpoint {
+    /// The path with the account and instrument replaced by placeholders,
+    /// eg. `/v3/instruments/{instrument}/candles`
+    pub name: String,
+    pub instrument: Option<String>,
+    /// A hash of the account id; enough to tell accounts apart in the logs
+    /// without writing the id out
+    pub account: Option<String>,
+}
+
+impl Endpoint {
+    pub fn new(url: &Url) -> Self {
+        let mut name = String::new();
+        let mut instrument = None;
+        let mut account = None;
+        let mut segments = url.path_segments().into_iter().flatten().peekable();
+        while let Some(segment) = segments.next() {
+            name.push('/');
+            name.push_str(segment);
+            let placeholder = match (segment, segments.peek()) {
+                ("accounts", Some(id)) => {
+                    account = Some(hash_account_id(id));
+                    "{accountID}"
+                }
+                // `/v3/accounts/{accountID}/instruments` on its own is the list
+                ("instruments" | "positions", Some(symbol)) => {
+                    instrument = Some(symbol.to_string());
+                    "{instrument}"
+                }
+                // Trade ids and client ids
+                ("trades", Some(_)) => "{tradeSpecifier}",
+                _ => continue,
+            };
+            segments.next();
+            name.push('/');
+            name.push_str(placeholder);
+        }
+        Self {
+            name,
+            instrument,
+            account,
+        }
+    }
+}
+
+fn hash_account_id(id: &str) -> String {
+    format!("{:08x}", fnv1a(id.as_bytes()) as u32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn endpoint(url: &str) -> Endpoint {
+        Endpoint::new(&url.parse().unwrap())
+    }
+
+    #[test]
+    fn candles() {
+        let got = endpoint("https://example.com/v3/instruments/EUR_USD/candles?count=5");
+        assert_eq!(
+            Endpoint {
+                name: "/v3/instruments/{instrument}/candles".to_string(),
+                instrument: Some("EUR_USD".to_string()),
+                account: None,
+            },
+            got
+        );
+    }
+
+    #[test]
+    fn account() {
+        let got = endpoint("https://example.com/v3/accounts/101-011-1234567-001/instruments");
+        assert_eq!("/v3/accounts/{accountID}/instruments", got.name);
+        assert_eq!(None, got.instrument);
+        let account = got.account.unwrap();
+        assert_eq!(8, account.len());
+        assert!(!account.contains("1234567"));
+    }
+
+    #[test]
+    fn close_position() {
+        let got =
+            endpoint("https://example.com/v3/accounts/101-011-1234567-001/positions/EUR_USD/close");
+        assert_eq!(
+            "/v3/accounts/{accountID}/positions/{instrument}/close",
+            got.name
+        );
+        assert_eq!(Some("EUR_USD".to_string()), got.instrument);
+    }
+
+    #[test]
+    fn trade_by_client_id() {
+        let got = endpoint("https://example.com/v3/accounts/101-011-1234567-001/trades/@robot-1");
+        assert_eq!("/v3/accounts/{accountID}/trades/{tradeSpecifier}", got.name);
+        assert_eq!(None, got.instrument);
+    }
+
+    #[test]
+    fn accounts_list() {
+        let got = endpoint("https://example.com/v3/accounts");
+        assert_eq!("/v3/accounts", got.name);
+        assert_eq!(None, got.account);
+    }
+}
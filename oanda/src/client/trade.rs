@@ -4,9 +4,26 @@ mod open_trades_request;
 pub use open_trades_request::OpenTradesRequest;
 mod trades_request;
 
-use crate::client::Client;
+use chrono::{DateTime, Utc};
+use error_stack::{Report, Result, ResultExt};
+use futures::{stream, Stream, StreamExt};
 
-use self::trades_request::TradesRequest;
+use crate::{
+    client::Client,
+    model::trade::{
+        self as model, CloseTradeRequest, CloseTradeResponse, TradeResponse, TradeSpecifier,
+    },
+    Error,
+};
+
+use self::trades_request::{TradeStateFilter, TradesRequest};
+
+/// How far back through the pages a [`Trade::trades_history`] is
+enum HistoryPage {
+    Newest,
+    Before(u32),
+    Done,
+}
 
 #[derive(Debug)]
 pub struct Trade<'a> {
@@ -15,6 +32,9 @@ pub struct Trade<'a> {
 }
 
 impl<'a> Trade<'a> {
+    /// The most trades OANDA will put in one page
+    pub const MAX_PAGE_SIZE: usize = 500;
+
     pub fn new(client: &'a Client, account_id: String) -> Self {
         Self { client, account_id }
     }
@@ -31,4 +51,110 @@ impl<'a> Trade<'a> {
     ) -> trades_request::TradesRequestBuilder<((&Trade,), (), (), (), (), (), ())> {
         TradesRequest::builder().trade_endpoint(self)
     }
+
+    /// Every trade opened at or after `since`, open or closed, newest
+    /// first. Pages back through the history with `before_id` as it's
+    /// read, so it only fetches as far back as it needs to. Stops after the
+    /// first error
+    pub fn trades_history(
+        &'a self,
+        since: DateTime<Utc>,
+    ) -> impl Stream<Item = Result<model::Trade, Error>> + 'a {
+        stream::unfold(HistoryPage::Newest, move |page| async move {
+            let request = self
+                .trades()
+                .state(TradeStateFilter::All)
+                .count(Self::MAX_PAGE_SIZE);
+            let response = match page {
+                HistoryPage::Newest => request.build().send().await,
+                HistoryPage::Before(id) => request.before_id(id).build().send().await,
+                HistoryPage::Done => return None,
+            };
+            let trades = match response {
+                Ok(response) => response.trades,
+                Err(err) => return Some((vec![Err(err)], HistoryPage::Done)),
+            };
+            let oldest_id = trades.last().map(|trade| trade.id.clone());
+            let mut page: Vec<_> = trades
+                .into_iter()
+                .take_while(|trade| trade.open_time >= since)
+                .map(Ok)
+                .collect();
+            // A short page is the end of the history, or of what we were asked for
+            if page.len() < Self::MAX_PAGE_SIZE {
+                return Some((page, HistoryPage::Done));
+            }
+            let next = match oldest_id.as_deref().map(str::parse::<u32>) {
+                Some(Ok(id)) if id > 1 => HistoryPage::Before(id - 1),
+                Some(Err(_)) => {
+                    page.push(Err(Report::new(Error::ListTrades).attach_printable(
+                        format!("Trade id isn't a number: {oldest_id:?}"),
+                    )));
+                    HistoryPage::Done
+                }
+                _ => HistoryPage::Done,
+            };
+            Some((page, next))
+        })
+        .flat_map(stream::iter)
+    }
+
+    /// One trade, by OANDA's id or by the client id we gave it. See [`TradeSpecifier`]
+    pub async fn get(&self, trade: &TradeSpecifier) -> Result<model::Trade, Error> {
+        let path = format!("/v3/accounts/{}/trades/{trade}", self.account_id);
+        let request = self.client.start_get(&self.client.url(&path));
+        self.client
+            .get(request)
+            .await
+            .map(|response: TradeResponse| response.trade)
+            .change_context(Error::GetTrade)
+            .attach_printable_lazy(|| format!("Trade: {trade}"))
+    }
+
+    /// Closes all of `trade` at market price
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the http request fails or the Json deseralization fails
+    pub async fn close(&self, trade: &TradeSpecifier) -> Result<CloseTradeResponse, Error> {
+        let path = format!("/v3/accounts/{}/trades/{trade}/close", self.account_id);
+        let request = self
+            .client
+            .start_put(&self.client.url(&path))
+            .json(&CloseTradeRequest::all());
+        self.client
+            .send(request)
+            .await
+            .change_context(Error::CloseTrade)
+            .attach_printable_lazy(|| format!("Trade: {trade}"))
+    }
+}
+
+#[cfg(test)]
+mod api_tests {
+    use crate::client::test_utils::get_account_id;
+    use crate::Client;
+    use chrono::Utc;
+    use futures::TryStreamExt;
+    use std::env::var;
+
+    #[tokio::test]
+    async fn trades_history() {
+        let api_key =
+            var("OANDA_TOKEN").expect("expected OANDA_TOKEN environment variable to be set");
+        let client = Client::new(api_key, crate::host::Host::Dev);
+        let account_id = get_account_id(&client).await.unwrap();
+        let since = Utc::now() - chrono::Duration::days(30);
+        let got: Vec<_> = client
+            .trade(account_id)
+            .trades_history(since)
+            .try_collect()
+            .await
+            .unwrap();
+        assert!(got.iter().all(|trade| trade.open_time >= since));
+        // Newest first, across pages too
+        assert!(got
+            .windows(2)
+            .all(|pair| pair[0].open_time >= pair[1].open_time));
+    }
 }
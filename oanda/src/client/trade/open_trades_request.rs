@@ -1,8 +1,12 @@
 use self::model::{date_time::DateTimeFormat, trade::TradesResponse};
 use super::Trade;
-use crate::Error;
+use crate::{
+    client::snapshot::{RequestSnapshot, Snapshot},
+    Error,
+};
 use error_stack::{Result, ResultExt};
-use tracing::debug;
+use reqwest::Method;
+use serde_json::Map;
 use typed_builder::TypedBuilder;
 
 pub use crate::model;
@@ -16,10 +20,7 @@ pub struct OpenTradesRequest<'a> {
 
 impl<'a> OpenTradesRequest<'a> {
     pub async fn send(&self) -> Result<TradesResponse, Error> {
-        let path = format!("/v3/accounts/{}/openTrades", self.trade_endpoint.account_id);
-        let url = self.trade_endpoint.client.url(&path);
-        let request = self.trade_endpoint.client.start_get(&url);
-        debug!("Get open trades request: {request:#?}");
+        let url = self.trade_endpoint.client.url(&self.path());
         let request = self.trade_endpoint.client.start_get(&url).header(
             self.accept_date_time_format.header_name(),
             self.accept_date_time_format.header_value(),
@@ -30,6 +31,37 @@ impl<'a> OpenTradesRequest<'a> {
             .await
             .change_context(Error::ListOpenTrades)
     }
+
+    fn path(&self) -> String {
+        format!("/v3/accounts/{}/openTrades", self.trade_endpoint.account_id)
+    }
+}
+
+impl<'a> Snapshot for OpenTradesRequest<'a> {
+    /// There are no parameters, so `params` is empty
+    fn snapshot(&self) -> RequestSnapshot {
+        let format = self.accept_date_time_format;
+        RequestSnapshot::new(&Method::GET, self.path(), &Map::new())
+            .header(format.header_name(), format.header_value())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Client;
+
+    #[test]
+    fn snapshot() {
+        use crate::client::snapshot::Snapshot;
+        let client = Client::new("secret-token".to_string(), crate::host::Host::Dev);
+        let trade = client.trade("101-011-1234567-001".to_string());
+        let json = trade.open_trades().build().snapshot().to_json();
+        assert_eq!(
+            r#"{"method":"GET","path":"/v3/accounts/101-011-1234567-001/openTrades","params":{},"headers":{"Accept-Datetime-Format":"RFC3339"}}"#,
+            json
+        );
+        assert!(!json.contains("secret-token"));
+    }
 }
 
 #[cfg(test)]
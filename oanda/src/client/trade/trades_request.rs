@@ -1,9 +1,11 @@
 use error_stack::{Result, ResultExt};
+use reqwest::Method;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use typed_builder::TypedBuilder;
 
 use crate::{
+    client::snapshot::{RequestSnapshot, Snapshot},
     model::{date_time::DateTimeFormat, trade::TradesResponse},
     Error,
 };
@@ -47,8 +49,7 @@ pub struct TradesRequest<'a> {
 
 impl<'a> TradesRequest<'a> {
     pub async fn send(&self) -> Result<TradesResponse, Error> {
-        let path = format!("/v3/accounts/{}/trades", self.trade_endpoint.account_id);
-        let url = self.trade_endpoint.client.url(&path);
+        let url = self.trade_endpoint.client.url(&self.path());
         let request = self
             .trade_endpoint
             .client
@@ -64,6 +65,18 @@ impl<'a> TradesRequest<'a> {
             .await
             .change_context(Error::ListTrades)
     }
+
+    fn path(&self) -> String {
+        format!("/v3/accounts/{}/trades", self.trade_endpoint.account_id)
+    }
+}
+
+impl<'a> Snapshot for TradesRequest<'a> {
+    fn snapshot(&self) -> RequestSnapshot {
+        let format = self.accept_date_time_format;
+        RequestSnapshot::new(&Method::GET, self.path(), self)
+            .header(format.header_name(), format.header_value())
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -75,6 +88,27 @@ pub enum TradeStateFilter {
     All,
 }
 
+#[cfg(test)]
+mod test {
+    use crate::{model::date_time::DateTimeFormat, Client};
+
+    #[test]
+    fn snapshot() {
+        use crate::client::snapshot::Snapshot;
+        let client = Client::new("secret-token".to_string(), crate::host::Host::Dev);
+        let trade = client.trade("101-011-1234567-001".to_string());
+        let request = trade
+            .trades()
+            .accept_date_time_format(DateTimeFormat::Unix)
+            .count(5)
+            .build();
+        assert_eq!(
+            r#"{"method":"GET","path":"/v3/accounts/101-011-1234567-001/trades","params":{"count":5},"headers":{"Accept-Datetime-Format":"UNIX"}}"#,
+            request.snapshot().to_json()
+        );
+    }
+}
+
 #[cfg(test)]
 mod api_tests {
     use crate::client::test_utils::get_account_id;
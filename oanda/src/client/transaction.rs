@@ -0,0 +1,154 @@
+//! The account's transaction history. See <https://developer.oanda.com/rest-live-v20/transaction-ep/>
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Utc};
+use error_stack::{Result, ResultExt};
+use futures::{stream, Stream, StreamExt};
+use serde::Serialize;
+
+use crate::{
+    client::Client,
+    error::Error,
+    model::transaction::{Transaction, TransactionPagesResponse, TransactionsResponse},
+};
+
+#[derive(Debug)]
+pub struct Transactions<'a> {
+    client: &'a Client,
+    account_id: String,
+}
+
+impl<'a> Transactions<'a> {
+    pub fn new(client: &'a Client, account_id: String) -> Self {
+        Self { client, account_id }
+    }
+
+    /// Lists the account's transactions, oldest first.
+    /// See [`TransactionList::stream`]
+    pub fn list(&self) -> TransactionList<'_> {
+        TransactionList {
+            transactions: self,
+            from: None,
+            to: None,
+            page_size: None,
+        }
+    }
+}
+
+/// A query over the transaction history. OANDA answers it with a list of
+/// page URLs; [`TransactionList::stream`] follows them for you.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionList<'a> {
+    #[serde(skip)]
+    transactions: &'a Transactions<'a>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    page_size: Option<u32>,
+}
+
+/// How far through the pages a [`TransactionList::stream`] is
+enum Pages {
+    NotFetched,
+    Remaining(VecDeque<String>),
+    Failed,
+}
+
+impl<'a> TransactionList<'a> {
+    /// The most transactions OANDA will put in one page
+    pub const MAX_PAGE_SIZE: u32 = 1000;
+
+    /// The earliest transaction time to include. [default=Account Creation Time]
+    pub fn from(mut self, from: DateTime<Utc>) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    /// The latest transaction time to include. [default=Request Time]
+    pub fn to(mut self, to: DateTime<Utc>) -> Self {
+        self.to = Some(to);
+        self
+    }
+
+    /// The number of transactions in each page. Clamped to 1 to
+    /// [`Self::MAX_PAGE_SIZE`]. [default=100]
+    pub fn page_size(mut self, page_size: u32) -> Self {
+        self.page_size = Some(page_size.clamp(1, Self::MAX_PAGE_SIZE));
+        self
+    }
+
+    /// The page URLs and totals for this query, without fetching the pages
+    pub async fn send(&self) -> Result<TransactionPagesResponse, Error> {
+        let client = self.transactions.client;
+        let path = format!("/v3/accounts/{}/transactions", self.transactions.account_id);
+        let request = client.start_get(&client.url(&path)).query(self);
+        client
+            .get(request)
+            .await
+            .change_context(Error::ListTransactions)
+            .attach_printable_lazy(|| format!("With these params: {:?}", self))
+    }
+
+    async fn page(&self, url: &str) -> Result<Vec<Transaction>, Error> {
+        let client = self.transactions.client;
+        client
+            .get(client.start_get(url))
+            .await
+            .map(|response: TransactionsResponse| response.transactions)
+            .change_context(Error::ListTransactions)
+    }
+
+    /// Yields every transaction matching the query, fetching the pages one
+    /// at a time as they're needed. Stops after the first error.
+    pub fn stream(self) -> impl Stream<Item = Result<Transaction, Error>> + 'a {
+        stream::unfold(Pages::NotFetched, move |pages| async move {
+            let mut remaining = match pages {
+                Pages::NotFetched => match self.send().await {
+                    Ok(response) => VecDeque::from(response.pages),
+                    Err(err) => return Some((Err(err), Pages::Failed)),
+                },
+                Pages::Remaining(remaining) => remaining,
+                Pages::Failed => return None,
+            };
+            let url = remaining.pop_front()?;
+            match self.page(&url).await {
+                Ok(transactions) => Some((Ok(transactions), Pages::Remaining(remaining))),
+                Err(err) => Some((Err(err), Pages::Failed)),
+            }
+        })
+        .flat_map(|page| {
+            let transactions: Vec<_> = match page {
+                Ok(transactions) => transactions.into_iter().map(Ok).collect(),
+                Err(err) => vec![Err(err)],
+            };
+            stream::iter(transactions)
+        })
+    }
+}
+
+#[cfg(test)]
+mod api_tests {
+    use crate::client::test_utils::get_account_id;
+    use crate::Client;
+    use chrono::Utc;
+    use futures::TryStreamExt;
+    use std::env::var;
+
+    #[tokio::test]
+    async fn list_transactions() {
+        let api_key =
+            var("OANDA_TOKEN").expect("expected OANDA_TOKEN environment variable to be set");
+        let client = Client::new(api_key, crate::host::Host::Dev);
+        let account_id = get_account_id(&client).await.unwrap();
+        let transactions = client.transactions(account_id);
+        let list = transactions
+            .list()
+            .from(Utc::now() - chrono::Duration::days(30))
+            .page_size(10);
+        let expected = list.send().await.unwrap().count;
+        let got: Vec<_> = list.stream().try_collect().await.unwrap();
+        assert_eq!(expected, got.len());
+        // Pages come back in order
+        assert!(got.windows(2).all(|pair| pair[0].time <= pair[1].time));
+    }
+}
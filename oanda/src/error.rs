@@ -1,13 +1,30 @@
-use std::num::{ParseFloatError, ParseIntError};
+use std::{
+    num::{ParseFloatError, ParseIntError},
+    time::Duration,
+};
 
 use reqwest::StatusCode;
 
+use crate::host::Host;
+
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error("Get a list of open trades")]
     ListOpenTrades,
     #[error("Get a list of trades")]
     ListTrades,
+    #[error("Get a trade")]
+    GetTrade,
+    #[error("Get a list of transactions")]
+    ListTransactions,
+    #[error("Get a list of open positions")]
+    ListPositions,
+    #[error("Close a position")]
+    ClosePosition,
+    #[error("Close a trade")]
+    CloseTrade,
+    #[error("Cancel an order")]
+    CancelOrder,
     // #[error("Conversion Error: {err:? }: {r#struct}.{field}: {value} ")]
     // Conversion {
     //     r#struct: String,
@@ -19,6 +36,16 @@ pub enum Error {
     Request(#[from] reqwest::Error),
     #[error("https status code error: {0}")]
     Status(StatusCode),
+    /// OANDA answered 429. The client's rate limiter waits `retry_after`
+    /// (or a second if OANDA didn't say) before sending anything else
+    #[error("Rate limited by OANDA; retry after {retry_after:?}")]
+    RateLimited { retry_after: Option<Duration> },
+    #[error("The API token was rejected")]
+    InvalidToken,
+    #[error("The API token is for the {token_host} host but the client is using {client_host}")]
+    WrongEnvironment { token_host: Host, client_host: Host },
+    #[error("Couldn't reach the OANDA API")]
+    Network,
     #[error("Error parsing Json: {err:?}. Input: {input}")]
     JsonParse {
         err: serde_json::Error,
@@ -30,6 +57,19 @@ pub enum Error {
     FloatConversion(#[from] ParseFloatError),
     #[error("Json conversion error")]
     JsonConversion,
+    #[error("Order would be rejected: {0}")]
+    OrderViolation(crate::model::instrument::OrderViolation),
+    #[error("Client extension {field} {reason}")]
+    ClientExtensions {
+        field: &'static str,
+        reason: &'static str,
+    },
+    #[error("Invalid candle alignment: {0}")]
+    CandleAlignment(&'static str),
+    #[error("Not a currency: {0}")]
+    InvalidCurrency(String),
+    #[error("Not a number of units: {0}")]
+    InvalidUnits(String),
     #[error("Other")]
     Other,
 }
@@ -25,8 +25,19 @@ impl Host {
             Host::Live => "stream-fxtrade.oanda.com",
         }
     }
+    /// Live if we're Dev, Dev if we're Live
+    pub fn other(&self) -> Host {
+        match self {
+            Host::Dev => Host::Live,
+            Host::Live => Host::Dev,
+        }
+    }
     /// Generates a URL using the current host, `https` and your `path`
     pub fn rest_url(&self, path: impl std::fmt::Display) -> String {
         format!("https://{}{path}", self.rest())
     }
+    /// Generates a URL using the current streaming host, `https` and your `path`
+    pub fn streaming_url(&self, path: impl std::fmt::Display) -> String {
+        format!("https://{}{path}", self.streaming())
+    }
 }
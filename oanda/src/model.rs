@@ -1,11 +1,22 @@
 pub mod account;
 pub mod candle;
+pub mod currency;
 pub mod date_time;
 pub mod instrument;
+pub mod market_hours;
 pub mod order;
+pub mod position;
+pub mod price;
+pub mod pricing;
 pub mod trade;
 pub mod transaction;
+pub mod units;
 
-pub use account::{Account, Accounts};
+pub use account::{Account, AccountMode, AccountSummary, Accounts};
 pub use candle::Candle;
+pub use currency::Currency;
 pub use instrument::{Instrument, Instruments};
+pub use position::Position;
+pub use price::Price;
+pub use pricing::{ClientPrice, PriceBucket};
+pub use units::Units;
@@ -1,4 +1,8 @@
+use parse_display::Display;
 use serde::Deserialize;
+use serde_with::{serde_as, DisplayFromStr};
+
+use super::{currency::Currency, order::OrderPositionFill};
 
 /// See <https://developer.oanda.com/rest-live-v20/account-ep/>
 #[derive(Debug, Deserialize)]
@@ -12,3 +16,138 @@ pub struct Account {
     pub id: String,
     pub tags: Vec<String>,
 }
+
+/// See <https://developer.oanda.com/rest-live-v20/account-ep/>
+#[derive(Debug, Deserialize)]
+pub struct AccountSummaryResponse {
+    pub account: AccountSummary,
+    #[serde(rename = "lastTransactionID")]
+    pub last_transaction_id: String,
+}
+
+/// A summary representation of a client’s Account. It doesn't include the
+/// open trades, positions or pending orders.
+/// See <https://developer.oanda.com/rest-live-v20/account-df/#AccountSummary>
+#[serde_as]
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountSummary {
+    /// The Account’s identifier
+    pub id: String,
+
+    /// Client-assigned alias for the Account. Only provided if the Account
+    /// has an alias set
+    pub alias: Option<String>,
+
+    /// The home currency of the Account
+    pub currency: Currency,
+
+    /// The current balance of the account.
+    #[serde_as(as = "DisplayFromStr")]
+    pub balance: f32,
+
+    /// The net asset value of the Account. Equal to Account balance +
+    /// unrealizedPL.
+    #[serde(rename = "NAV")]
+    #[serde_as(as = "DisplayFromStr")]
+    pub nav: f32,
+
+    /// The total unrealized profit/loss for all Trades currently open in the
+    /// Account.
+    #[serde(rename = "unrealizedPL")]
+    #[serde_as(as = "DisplayFromStr")]
+    pub unrealized_pl: f32,
+
+    /// Margin currently used for the Account.
+    #[serde_as(as = "DisplayFromStr")]
+    pub margin_used: f32,
+
+    /// Margin available for Account currency.
+    #[serde_as(as = "DisplayFromStr")]
+    pub margin_available: f32,
+
+    /// The Account’s margin closeout percentage. When this value is 1.0 or
+    /// above the Account is in a margin closeout situation.
+    #[serde_as(as = "DisplayFromStr")]
+    pub margin_closeout_percent: f32,
+
+    /// The number of Trades currently open in the Account.
+    pub open_trade_count: u32,
+
+    /// The number of Positions currently open in the Account.
+    pub open_position_count: u32,
+
+    /// The number of Orders currently pending in the Account.
+    pub pending_order_count: u32,
+
+    /// Flag indicating that the Account has hedging enabled.
+    pub hedging_enabled: bool,
+}
+
+impl AccountSummary {
+    pub fn mode(&self) -> AccountMode {
+        if self.hedging_enabled {
+            AccountMode::Hedging
+        } else {
+            AccountMode::Netting
+        }
+    }
+}
+
+/// How an account treats opposite trades in the same instrument
+#[derive(Debug, Display, PartialEq, Eq, Clone, Copy)]
+#[display(style = "lowercase")]
+pub enum AccountMode {
+    /// One position per instrument; a sell reduces an open buy
+    Netting,
+    /// Longs and shorts in the same instrument are held side by side
+    Hedging,
+}
+
+impl AccountMode {
+    /// What [`OrderPositionFill::Default`] means for this kind of account
+    pub fn default_position_fill(&self) -> OrderPositionFill {
+        match self {
+            AccountMode::Netting => OrderPositionFill::ReduceFirst,
+            AccountMode::Hedging => OrderPositionFill::OpenOnly,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn summary_deserialize() {
+        let input = r#"{
+            "account": {
+                "id": "101-004-1234567-001",
+                "currency": "USD",
+                "balance": "1000.0000",
+                "NAV": "1012.5000",
+                "unrealizedPL": "12.5000",
+                "marginUsed": "40.0000",
+                "marginAvailable": "972.5000",
+                "marginCloseoutPercent": "0.01975",
+                "openTradeCount": 1,
+                "openPositionCount": 1,
+                "pendingOrderCount": 2,
+                "hedgingEnabled": false,
+                "marginRate": "0.02"
+            },
+            "lastTransactionID": "6356"
+        }"#;
+        let got: AccountSummaryResponse = serde_json::from_str(input).unwrap();
+        assert_eq!(None, got.account.alias);
+        assert_eq!(1012.5, got.account.nav);
+        assert_eq!(12.5, got.account.unrealized_pl);
+        assert_eq!(2, got.account.pending_order_count);
+        assert_eq!("6356", got.last_transaction_id);
+        assert_eq!(AccountMode::Netting, got.account.mode());
+        assert_eq!(
+            OrderPositionFill::ReduceFirst,
+            got.account.mode().default_position_fill()
+        );
+    }
+}
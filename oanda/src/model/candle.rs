@@ -1,8 +1,20 @@
-use chrono::{DateTime, Utc};
+use algorithms::Timeframe;
+use chrono::{DateTime, Duration, Utc};
 use parse_display::{Display, FromStr};
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
+
+use super::price::Price;
+
 mod algorithms_compat;
+mod alignment;
+mod gaps;
+mod spread;
+pub use alignment::{
+    CandleAlignment, DEFAULT_ALIGNMENT_TIMEZONE, DEFAULT_DAILY_ALIGNMENT, DEFAULT_WEEKLY_ALIGNMENT,
+};
+pub use gaps::{candle_gaps, CandleGap};
+pub use spread::{HourlySpreads, SpreadStats};
 
 #[derive(Display, Debug)]
 pub enum CandleType {
@@ -22,7 +34,7 @@ pub enum CandleType {
     All,
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
+#[derive(Debug, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Candle {
     /// The start time of the candlestick
@@ -52,28 +64,28 @@ pub struct Candle {
 }
 
 #[serde_as]
-#[derive(Debug, Deserialize, PartialEq)]
+#[derive(Debug, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct CandlestickData {
     #[serde_as(as = "DisplayFromStr")]
     /// The first (open) price in the time-range represented by the candlestick.
-    pub o: f32,
+    pub o: Price,
 
     #[serde_as(as = "DisplayFromStr")]
     /// The highest price in the time-range represented by the candlestick.
-    pub h: f32,
+    pub h: Price,
 
     #[serde_as(as = "DisplayFromStr")]
     /// The lowest price in the time-range represented by the candlestick.
-    pub l: f32,
+    pub l: Price,
 
     #[serde_as(as = "DisplayFromStr")]
     /// The last (closing) price in the time-range represented by the
     /// candlestick.
-    pub c: f32,
+    pub c: Price,
 }
 
-#[derive(Display, FromStr, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Display, FromStr, Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
 #[display(style = "UPPERCASE")]
 pub enum CandlestickGranularity {
     /// 5 second candlesticks, minute alignment
@@ -120,6 +132,44 @@ pub enum CandlestickGranularity {
     M,
 }
 
+impl CandlestickGranularity {
+    /// How long each candle covers. None for months, which vary
+    pub fn duration(&self) -> Option<Duration> {
+        use CandlestickGranularity::*;
+        let seconds = match self {
+            S5 => 5,
+            S10 => 10,
+            S15 => 15,
+            S30 => 30,
+            M1 => 60,
+            M2 => 2 * 60,
+            M4 => 4 * 60,
+            M5 => 5 * 60,
+            M10 => 10 * 60,
+            M15 => 15 * 60,
+            M30 => 30 * 60,
+            H1 => 60 * 60,
+            H2 => 2 * 60 * 60,
+            H3 => 3 * 60 * 60,
+            H4 => 4 * 60 * 60,
+            H6 => 6 * 60 * 60,
+            H8 => 8 * 60 * 60,
+            H12 => 12 * 60 * 60,
+            D => 24 * 60 * 60,
+            W => 7 * 24 * 60 * 60,
+            M => return None,
+        };
+        Some(Duration::seconds(seconds))
+    }
+
+    /// The candle size for the algorithms crate's warm-up maths. None for
+    /// months
+    pub fn timeframe(&self) -> Option<Timeframe> {
+        self.duration()
+            .and_then(|duration| Timeframe::from_seconds(duration.num_seconds()))
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CandleResponse {
     pub instrument: String,
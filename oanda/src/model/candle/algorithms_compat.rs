@@ -1,23 +1,29 @@
 use super::Candle;
-use algorithms::{Close, High, Low, Open};
+use crate::model::price::to_f32;
+use algorithms::{Close, High, Low, Open, Volume};
 
 impl High for Candle {
     fn high(&self) -> f32 {
-        self.mid.as_ref().unwrap().h
+        to_f32(self.mid.as_ref().unwrap().h)
     }
 }
 impl Low for Candle {
     fn low(&self) -> f32 {
-        self.mid.as_ref().unwrap().l
+        to_f32(self.mid.as_ref().unwrap().l)
     }
 }
 impl Open for Candle {
     fn open(&self) -> f32 {
-        self.mid.as_ref().unwrap().o
+        to_f32(self.mid.as_ref().unwrap().o)
     }
 }
 impl Close for Candle {
     fn close(&self) -> f32 {
-        self.mid.as_ref().unwrap().c
+        to_f32(self.mid.as_ref().unwrap().c)
+    }
+}
+impl Volume for Candle {
+    fn volume(&self) -> f32 {
+        self.volume as f32
     }
 }
@@ -0,0 +1,158 @@
+//! Where daily and weekly candles start, checked together against the
+//! granularity they're used with. OANDA silently ignores alignments that
+//! don't apply, which makes mistakes hard to spot.
+use error_stack::{report, Result, ResultExt};
+
+use super::CandlestickGranularity;
+use crate::{model::instrument::DayOfWeek, Error};
+
+/// The hour daily candles start at when no alignment is given
+pub const DEFAULT_DAILY_ALIGNMENT: u8 = 17;
+/// The timezone of [`DEFAULT_DAILY_ALIGNMENT`]
+pub const DEFAULT_ALIGNMENT_TIMEZONE: &str = "America/New_York";
+/// The day weekly candles start on when no alignment is given
+pub const DEFAULT_WEEKLY_ALIGNMENT: DayOfWeek = DayOfWeek::Friday;
+
+/// The `dailyAlignment`, `alignmentTimezone` and `weeklyAlignment` candle
+/// parameters. Anything left unset uses OANDA's default
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CandleAlignment {
+    pub daily_alignment: Option<u8>,
+    pub alignment_timezone: Option<String>,
+    pub weekly_alignment: Option<DayOfWeek>,
+}
+
+impl CandleAlignment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Days start at `hour` (0 to 23) in `timezone`, eg. "UTC" or "Europe/London"
+    pub fn daily(mut self, hour: u8, timezone: impl Into<String>) -> Self {
+        self.daily_alignment = Some(hour);
+        self.alignment_timezone = Some(timezone.into());
+        self
+    }
+
+    /// Weeks start on `day`
+    pub fn weekly(mut self, day: DayOfWeek) -> Self {
+        self.weekly_alignment = Some(day);
+        self
+    }
+
+    /// The hour days start at, after defaults
+    pub fn effective_daily_alignment(&self) -> u8 {
+        self.daily_alignment.unwrap_or(DEFAULT_DAILY_ALIGNMENT)
+    }
+
+    /// The timezone of [`Self::effective_daily_alignment`], after defaults
+    pub fn effective_alignment_timezone(&self) -> &str {
+        self.alignment_timezone
+            .as_deref()
+            .unwrap_or(DEFAULT_ALIGNMENT_TIMEZONE)
+    }
+
+    /// The day weeks start on, after defaults
+    pub fn effective_weekly_alignment(&self) -> DayOfWeek {
+        self.weekly_alignment.unwrap_or(DEFAULT_WEEKLY_ALIGNMENT)
+    }
+
+    /// Checks the alignment makes sense for candles of `granularity`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::CandleAlignment`] if the hour is over 23, the
+    /// timezone is empty, a daily alignment is given for candles shorter
+    /// than 2 hours, or a weekly alignment is given for anything but weekly
+    /// candles
+    pub fn validate(&self, granularity: CandlestickGranularity) -> Result<(), Error> {
+        let reason = if self.daily_alignment.is_some_and(|hour| hour > 23) {
+            "the daily alignment must be an hour from 0 to 23"
+        } else if self.alignment_timezone.as_deref() == Some("") {
+            "the alignment timezone is empty"
+        } else if (self.daily_alignment.is_some() || self.alignment_timezone.is_some())
+            && !granularity.has_daily_alignment()
+        {
+            "a daily alignment only applies to candles of 2 hours or more"
+        } else if self.weekly_alignment.is_some() && !granularity.has_weekly_alignment() {
+            "a weekly alignment only applies to weekly candles"
+        } else {
+            return Ok(());
+        };
+        Err(report!(Error::CandleAlignment(reason)))
+            .attach_printable(format!("{self:?} with {granularity} candles"))
+    }
+}
+
+impl CandlestickGranularity {
+    /// Whether the candles start at the daily alignment hour
+    pub fn has_daily_alignment(&self) -> bool {
+        use CandlestickGranularity::*;
+        matches!(self, H2 | H3 | H4 | H6 | H8 | H12 | D | W | M)
+    }
+
+    /// Whether the candles start on the weekly alignment day
+    pub fn has_weekly_alignment(&self) -> bool {
+        *self == CandlestickGranularity::W
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn defaults() {
+        let alignment = CandleAlignment::new();
+        assert_eq!(17, alignment.effective_daily_alignment());
+        assert_eq!("America/New_York", alignment.effective_alignment_timezone());
+        assert_eq!(DayOfWeek::Friday, alignment.effective_weekly_alignment());
+        // Nothing set is fine for anything
+        assert!(alignment.validate(CandlestickGranularity::S5).is_ok());
+    }
+
+    #[test]
+    fn valid() {
+        let alignment = CandleAlignment::new()
+            .daily(0, "UTC")
+            .weekly(DayOfWeek::Monday);
+        assert!(alignment.validate(CandlestickGranularity::W).is_ok());
+        let daily = CandleAlignment::new().daily(0, "UTC");
+        assert!(daily.validate(CandlestickGranularity::H4).is_ok());
+        assert!(daily.validate(CandlestickGranularity::D).is_ok());
+    }
+
+    #[test]
+    fn invalid() {
+        let invalid = [
+            (
+                CandleAlignment::new().daily(24, "UTC"),
+                CandlestickGranularity::D,
+            ),
+            (
+                CandleAlignment::new().daily(0, ""),
+                CandlestickGranularity::D,
+            ),
+            (
+                CandleAlignment::new().daily(0, "UTC"),
+                CandlestickGranularity::H1,
+            ),
+            (
+                CandleAlignment::new().weekly(DayOfWeek::Monday),
+                CandlestickGranularity::D,
+            ),
+            (
+                CandleAlignment::new().weekly(DayOfWeek::Monday),
+                CandlestickGranularity::M15,
+            ),
+        ];
+        for (alignment, granularity) in invalid {
+            let err = alignment.validate(granularity).unwrap_err();
+            assert!(
+                matches!(err.current_context(), Error::CandleAlignment(_)),
+                "{alignment:?} {granularity}"
+            );
+        }
+    }
+}
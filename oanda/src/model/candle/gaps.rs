@@ -0,0 +1,96 @@
+//! Finds candles missing from a run of candles. A stretch without candles
+//! only counts as a gap if the forex market was open for some of it; the
+//! weekend close isn't a gap.
+use chrono::{DateTime, Utc};
+
+use super::{Candle, CandlestickGranularity};
+use crate::model::market_hours::is_forex_market_open;
+
+/// Candles missing between two candles we have
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CandleGap {
+    /// The start time of the candle before the gap
+    pub after: DateTime<Utc>,
+    /// The start time of the candle after the gap
+    pub before: DateTime<Utc>,
+    /// How many candles should have started in between while the market
+    /// was open
+    pub missing: usize,
+}
+
+/// The gaps in `candles`, which should be in time order. Nothing can be
+/// said about monthly candles, which vary in length, so they never have any
+pub fn candle_gaps(candles: &[Candle], granularity: CandlestickGranularity) -> Vec<CandleGap> {
+    let Some(step) = granularity.duration() else {
+        return Vec::new();
+    };
+    candles
+        .windows(2)
+        .filter_map(|pair| {
+            let (after, before) = (pair[0].time, pair[1].time);
+            let missing = std::iter::successors(Some(after + step), |time| Some(*time + step))
+                .take_while(|time| *time < before)
+                .filter(|time| is_forex_market_open(*time))
+                .count();
+            (missing > 0).then_some(CandleGap {
+                after,
+                before,
+                missing,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use pretty_assertions::assert_eq;
+
+    fn candle(time: DateTime<Utc>) -> Candle {
+        Candle {
+            time,
+            bid: None,
+            ask: None,
+            mid: None,
+            volume: 1,
+            complete: true,
+        }
+    }
+
+    #[test]
+    fn gaps() {
+        // 2023-03-08 is a Wednesday
+        let start = Utc.with_ymd_and_hms(2023, 3, 8, 12, 0, 0).unwrap();
+        let minutes = |m| start + Duration::minutes(m);
+        let candles: Vec<Candle> = [0, 15, 60, 75]
+            .into_iter()
+            .map(minutes)
+            .map(candle)
+            .collect();
+        assert_eq!(
+            vec![CandleGap {
+                after: minutes(15),
+                before: minutes(60),
+                missing: 2
+            }],
+            candle_gaps(&candles, CandlestickGranularity::M15)
+        );
+        assert!(candle_gaps(&candles, CandlestickGranularity::M).is_empty());
+    }
+
+    #[test]
+    fn weekend_isnt_a_gap() {
+        // The last hour before Friday's close, then the first after Sunday's open
+        let friday = Utc.with_ymd_and_hms(2023, 3, 10, 20, 0, 0).unwrap();
+        let sunday = Utc.with_ymd_and_hms(2023, 3, 12, 22, 0, 0).unwrap();
+        let candles = [candle(friday), candle(sunday)];
+        assert!(candle_gaps(&candles, CandlestickGranularity::H1).is_empty());
+        // Losing Sunday's first candle is though
+        let candles = [candle(friday), candle(sunday + Duration::hours(1))];
+        assert_eq!(
+            1,
+            candle_gaps(&candles, CandlestickGranularity::H1)[0].missing
+        );
+    }
+}
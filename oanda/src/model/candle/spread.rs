@@ -0,0 +1,152 @@
+//! Spread statistics by hour of the day, worked out from bid and ask
+//! candles. The spread widens a lot around the rollover and when the
+//! big markets are shut, so these tell us which hours are worth trading.
+use std::collections::BTreeMap;
+
+use chrono::Timelike;
+
+use super::Candle;
+use crate::model::price::to_f32;
+
+/// How wide the spread was in one hour of the day (UTC)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpreadStats {
+    /// 0 to 23, in UTC
+    pub hour: u32,
+    /// The number of candles sampled
+    pub samples: usize,
+    pub mean: f32,
+    pub median: f32,
+    pub max: f32,
+}
+
+/// The spread at each hour of the day that had any candles with both a
+/// bid and an ask
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct HourlySpreads {
+    hours: BTreeMap<u32, SpreadStats>,
+}
+
+impl HourlySpreads {
+    /// Samples the spread at the close of each candle. Candles without
+    /// both bid and ask prices are skipped, so ask for both with
+    /// [`crate::model::instrument::PricingComponent`]
+    pub fn from_candles<'a>(candles: impl IntoIterator<Item = &'a Candle>) -> Self {
+        let mut by_hour: BTreeMap<u32, Vec<f32>> = BTreeMap::new();
+        for candle in candles {
+            if let Some((bid, ask)) = candle.bid.as_ref().zip(candle.ask.as_ref()) {
+                by_hour
+                    .entry(candle.time.hour())
+                    .or_default()
+                    .push(to_f32(ask.c) - to_f32(bid.c));
+            }
+        }
+        let hours = by_hour
+            .into_iter()
+            .map(|(hour, mut spreads)| {
+                spreads.sort_by(f32::total_cmp);
+                let samples = spreads.len();
+                let middle = samples / 2;
+                let median = if samples % 2 == 0 {
+                    (spreads[middle - 1] + spreads[middle]) / 2.0
+                } else {
+                    spreads[middle]
+                };
+                let stats = SpreadStats {
+                    hour,
+                    samples,
+                    mean: spreads.iter().sum::<f32>() / samples as f32,
+                    median,
+                    max: spreads[samples - 1],
+                };
+                (hour, stats)
+            })
+            .collect();
+        Self { hours }
+    }
+
+    /// The stats for `hour` (UTC), if there were any candles in it
+    pub fn hour(&self, hour: u32) -> Option<&SpreadStats> {
+        self.hours.get(&hour)
+    }
+
+    /// Every hour with candles, from 0 to 23
+    pub fn iter(&self) -> impl Iterator<Item = &SpreadStats> {
+        self.hours.values()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hours.is_empty()
+    }
+
+    /// The hours (UTC) whose median spread is at most `max_spread`, for
+    /// picking when to trade
+    pub fn hours_below(&self, max_spread: f32) -> Vec<u32> {
+        self.iter()
+            .filter(|stats| stats.median <= max_spread)
+            .map(|stats| stats.hour)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::{candle::CandlestickData, price::from_f32};
+    use chrono::{TimeZone, Utc};
+    use pretty_assertions::assert_eq;
+
+    fn prices(close: f32) -> Option<CandlestickData> {
+        let close = from_f32(close);
+        Some(CandlestickData {
+            o: close,
+            h: close,
+            l: close,
+            c: close,
+        })
+    }
+
+    fn candle(hour: u32, minute: u32, spread: f32) -> Candle {
+        Candle {
+            time: Utc.with_ymd_and_hms(2023, 5, 2, hour, minute, 0).unwrap(),
+            bid: prices(1.1),
+            ask: prices(1.1 + spread),
+            mid: None,
+            volume: 1,
+            complete: true,
+        }
+    }
+
+    #[test]
+    fn by_hour() {
+        let mut mid_only = candle(9, 0, 0.0);
+        mid_only.bid = None;
+        let candles = [
+            candle(8, 0, 0.0001),
+            candle(8, 15, 0.0003),
+            candle(8, 30, 0.0002),
+            candle(21, 0, 0.0010),
+            candle(21, 15, 0.0020),
+            mid_only,
+        ];
+        let spreads = HourlySpreads::from_candles(&candles);
+        let eight = spreads.hour(8).unwrap();
+        assert_eq!(3, eight.samples);
+        assert!((eight.median - 0.0002).abs() < 0.00001);
+        assert!((eight.mean - 0.0002).abs() < 0.00001);
+        assert!((eight.max - 0.0003).abs() < 0.00001);
+        let nine_pm = spreads.hour(21).unwrap();
+        assert!((nine_pm.median - 0.0015).abs() < 0.00001);
+        // Candles without a bid and ask don't count
+        assert_eq!(None, spreads.hour(9));
+        assert_eq!(vec![8], spreads.hours_below(0.0005));
+        assert_eq!(vec![8, 21], spreads.hours_below(0.01));
+    }
+
+    #[test]
+    fn empty() {
+        let spreads = HourlySpreads::from_candles(&[]);
+        assert!(spreads.is_empty());
+        assert!(spreads.hours_below(1.0).is_empty());
+    }
+}
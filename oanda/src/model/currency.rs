@@ -0,0 +1,191 @@
+//! Currencies, and converting amounts into the account's home currency.
+//! See <https://developer.oanda.com/rest-live-v20/primitives-df/#Currency>
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
+
+use crate::Error;
+
+/// An ISO 4217 currency code, like USD
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Currency(String);
+
+impl Currency {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The base and quote currencies of a currency pair instrument, eg.
+    /// EUR_JPY gives (EUR, JPY). None for instruments that aren't currency
+    /// pairs, like US30_USD
+    pub fn split_instrument(instrument: &str) -> Option<(Currency, Currency)> {
+        let (base, quote) = instrument.split_once('_')?;
+        Some((base.parse().ok()?, quote.parse().ok()?))
+    }
+}
+
+impl FromStr for Currency {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() == 3 && s.chars().all(|c| c.is_ascii_uppercase()) {
+            Ok(Currency(s.to_string()))
+        } else {
+            Err(Error::InvalidCurrency(s.to_string()))
+        }
+    }
+}
+
+impl TryFrom<String> for Currency {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Currency> for String {
+    fn from(currency: Currency) -> Self {
+        currency.0
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// How to turn an amount in `currency` into the account's home currency.
+/// The pricing endpoint sends these when asked with `includeHomeConversions`
+#[serde_as]
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HomeConversions {
+    /// The currency to be converted into the home currency.
+    pub currency: Currency,
+
+    /// The factor used to convert any gains for an Account in the specified
+    /// currency into the Account’s home currency.
+    #[serde_as(as = "DisplayFromStr")]
+    pub account_gain: f32,
+
+    /// The factor used to convert any losses for an Account in the specified
+    /// currency into the Account’s home currency.
+    #[serde_as(as = "DisplayFromStr")]
+    pub account_loss: f32,
+
+    /// The factor used to convert a Position or Trade Value in the specified
+    /// currency into the Account’s home currency.
+    #[serde_as(as = "DisplayFromStr")]
+    pub position_value: f32,
+}
+
+/// A conversion factor; multiply by it to convert
+#[serde_as]
+#[derive(Debug, Deserialize, PartialEq, Clone, Copy)]
+pub struct ConversionFactor {
+    #[serde_as(as = "DisplayFromStr")]
+    pub factor: f32,
+}
+
+/// The factors to convert an instrument's quote and base currencies into
+/// the account's home currency. Gains and losses convert at slightly
+/// different rates.
+#[derive(Debug, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct HomeConversionFactors {
+    /// The ConversionFactor in effect for the Account for converting any
+    /// gains realized in Instrument quote units into units of the Account’s
+    /// home currency.
+    pub gain_quote_home: ConversionFactor,
+
+    /// The ConversionFactor in effect for the Account for converting any
+    /// losses realized in Instrument quote units into units of the Account’s
+    /// home currency.
+    pub loss_quote_home: ConversionFactor,
+
+    /// The ConversionFactor in effect for the Account for converting any
+    /// gains realized in Instrument base units into units of the Account’s
+    /// home currency.
+    pub gain_base_home: ConversionFactor,
+
+    /// The ConversionFactor in effect for the Account for converting any
+    /// losses realized in Instrument base units into units of the Account’s
+    /// home currency.
+    pub loss_base_home: ConversionFactor,
+}
+
+impl HomeConversionFactors {
+    /// Factors for an instrument, given the conversions of its base and quote currencies
+    pub fn new(base: &HomeConversions, quote: &HomeConversions) -> Self {
+        let factor = |factor| ConversionFactor { factor };
+        Self {
+            gain_quote_home: factor(quote.account_gain),
+            loss_quote_home: factor(quote.account_loss),
+            gain_base_home: factor(base.account_gain),
+            loss_base_home: factor(base.account_loss),
+        }
+    }
+
+    /// Converts a profit (positive) or loss (negative) in the quote currency
+    /// into the home currency
+    pub fn quote_to_home(&self, amount: f32) -> f32 {
+        convert(amount, self.gain_quote_home, self.loss_quote_home)
+    }
+
+    /// Converts a profit (positive) or loss (negative) in the base currency
+    /// into the home currency
+    pub fn base_to_home(&self, amount: f32) -> f32 {
+        convert(amount, self.gain_base_home, self.loss_base_home)
+    }
+
+    /// What a one pip move is worth in the home currency when holding
+    /// `units`, given the instrument's
+    /// [`pip_location`](crate::model::Instrument::pip_location). Always positive
+    pub fn pip_value(&self, units: f32, pip_location: i32) -> f32 {
+        self.quote_to_home(units.abs() * 10f32.powi(pip_location))
+    }
+}
+
+fn convert(amount: f32, gain: ConversionFactor, loss: ConversionFactor) -> f32 {
+    if amount >= 0.0 {
+        amount * gain.factor
+    } else {
+        amount * loss.factor
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_currency() {
+        assert_eq!("USD", "USD".parse::<Currency>().unwrap().as_str());
+        for invalid in ["usd", "US", "US30", ""] {
+            assert!(invalid.parse::<Currency>().is_err(), "{invalid}");
+        }
+        let (base, quote) = Currency::split_instrument("EUR_JPY").unwrap();
+        assert_eq!(("EUR", "JPY"), (base.as_str(), quote.as_str()));
+        assert_eq!(None, Currency::split_instrument("US30_USD"));
+    }
+
+    #[test]
+    fn cross_pip_value() {
+        // A USD account trading EUR_JPY
+        let input = r#"[
+            {"currency": "EUR", "accountGain": "1.07", "accountLoss": "1.08", "positionValue": "1.075"},
+            {"currency": "JPY", "accountGain": "0.0073", "accountLoss": "0.0074", "positionValue": "0.00735"}
+        ]"#;
+        let conversions: Vec<HomeConversions> = serde_json::from_str(input).unwrap();
+        let factors = HomeConversionFactors::new(&conversions[0], &conversions[1]);
+        // 10,000 units, so a pip (0.01 JPY) is worth 100 JPY
+        assert!((factors.pip_value(-10_000.0, -2) - 0.73).abs() < 0.0001);
+        assert!((factors.quote_to_home(-1000.0) - -7.4).abs() < 0.0001);
+        assert!((factors.base_to_home(100.0) - 107.0).abs() < 0.001);
+    }
+}
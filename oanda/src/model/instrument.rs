@@ -4,6 +4,14 @@ use serde::{Deserialize, Serialize, Serializer};
 
 use serde_with::{serde_as, DisplayFromStr};
 
+mod filter;
+mod guardrails;
+mod sizing;
+mod stop_loss_plan;
+pub use filter::InstrumentFilter;
+pub use guardrails::OrderViolation;
+pub use stop_loss_plan::StopLossPlan;
+
 #[derive(Debug, Deserialize)]
 pub struct Instruments {
     pub instruments: Vec<Instrument>,
@@ -101,7 +109,7 @@ pub struct Instrument {
 
 /// The type of an instrument
 /// [See docs](https://developer.oanda.com/rest-live-v20/primitives-df/#InstrumentType)
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, PartialEq, Eq, Clone, Copy)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum InstrumentType {
     /// Represents a Currency instrument type
@@ -193,7 +201,7 @@ pub struct FinancingDayOfWeek {
     pub days_charged: i32,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone, Copy)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum DayOfWeek {
     Sunday,
@@ -0,0 +1,122 @@
+//! Picks instruments out of an account's instrument list, so config can say
+//! "all the currency pairs tagged MAJOR" instead of naming each one.
+use serde::Deserialize;
+
+use super::{Instrument, InstrumentType};
+
+/// Matches instruments of a type and/or with all of some tags. An empty
+/// filter matches everything.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct InstrumentFilter {
+    /// Only instruments of this type
+    pub instrument_type: Option<InstrumentType>,
+    /// Only instruments with a tag of each of these names, of any tag type.
+    /// Compared ignoring case
+    pub tags: Vec<String>,
+}
+
+impl InstrumentFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn instrument_type(mut self, instrument_type: InstrumentType) -> Self {
+        self.instrument_type = Some(instrument_type);
+        self
+    }
+
+    pub fn tag(mut self, name: impl Into<String>) -> Self {
+        self.tags.push(name.into());
+        self
+    }
+
+    pub fn matches(&self, instrument: &Instrument) -> bool {
+        (self.instrument_type.is_none() || self.instrument_type == Some(instrument.instrument_type))
+            && self.tags.iter().all(|tag| instrument.has_tag(tag))
+    }
+
+    /// The names of the matching instruments, eg. to resolve config into
+    /// something to trade
+    pub fn names<'a>(&self, instruments: impl IntoIterator<Item = &'a Instrument>) -> Vec<String> {
+        instruments
+            .into_iter()
+            .filter(|instrument| self.matches(instrument))
+            .map(|instrument| instrument.name.clone())
+            .collect()
+    }
+}
+
+impl Instrument {
+    /// Whether the instrument has a tag called `name`, ignoring case
+    pub fn has_tag(&self, name: &str) -> bool {
+        self.tags
+            .iter()
+            .any(|tag| tag.name.eq_ignore_ascii_case(name))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn instrument(name: &str, instrument_type: &str, tags: &[&str]) -> Instrument {
+        let tags: Vec<_> = tags
+            .iter()
+            .map(|tag| format!(r#"{{ "type": "ASSET_CLASS", "name": "{tag}" }}"#))
+            .collect();
+        let input = format!(
+            r#"{{
+                "name": "{name}",
+                "type": "{instrument_type}",
+                "displayName": "{name}",
+                "pipLocation": -4,
+                "displayPrecision": 5,
+                "tradeUnitsPrecision": 0,
+                "minimumTradeSize": "1",
+                "maximumTrailingStopDistance": "1.00000",
+                "minimumTrailingStopDistance": "0.00050",
+                "maximumPositionSize": "0",
+                "maximumOrderUnits": "100000000",
+                "marginRate": "0.0333",
+                "commission": {{ "commission": "0", "unitsTraded": "1", "minimumCommission": "0" }},
+                "guaranteedStopLossOrderMode": "DISABLED",
+                "financing": {{ "longRate": "-0.0512", "shortRate": "0.0258", "financingDaysOfWeek": [] }},
+                "tags": [{}]
+            }}"#,
+            tags.join(",")
+        );
+        serde_json::from_str(&input).unwrap()
+    }
+
+    #[test]
+    fn filter() {
+        let instruments = [
+            instrument("EUR_USD", "CURRENCY", &["CURRENCY", "MAJOR"]),
+            instrument("EUR_NOK", "CURRENCY", &["CURRENCY"]),
+            instrument("XAU_USD", "METAL", &["METAL"]),
+            instrument("US30_USD", "CFD", &["INDEX", "MAJOR"]),
+        ];
+        assert_eq!(4, InstrumentFilter::new().names(&instruments).len());
+        let majors = InstrumentFilter::new()
+            .instrument_type(InstrumentType::Currency)
+            .tag("major");
+        assert_eq!(vec!["EUR_USD"], majors.names(&instruments));
+        let metals = InstrumentFilter::new().instrument_type(InstrumentType::Metal);
+        assert_eq!(vec!["XAU_USD"], metals.names(&instruments));
+    }
+
+    #[test]
+    fn from_config() {
+        let got: InstrumentFilter =
+            serde_json::from_str(r#"{ "instrument_type": "CURRENCY", "tags": ["MAJOR"] }"#)
+                .unwrap();
+        assert_eq!(
+            InstrumentFilter::new()
+                .instrument_type(InstrumentType::Currency)
+                .tag("MAJOR"),
+            got
+        );
+    }
+}
@@ -0,0 +1,221 @@
+//! Checks an order against the instrument's limits before it's sent, so we
+//! get a clear error up front instead of a reject from the broker.
+use chrono::{DateTime, Utc};
+use error_stack::{report, Result};
+
+use super::{GuaranteedStopLossOrderModeForInstrument, Instrument};
+use crate::Error;
+
+/// Why an order would be rejected
+#[derive(thiserror::Error, Debug, PartialEq, Clone)]
+pub enum OrderViolation {
+    #[error("{units} units is less than the minimum trade size of {minimum}")]
+    BelowMinimumTradeSize { units: f64, minimum: f32 },
+    #[error("{units} units is more than the maximum order size of {maximum}")]
+    AboveMaximumOrderUnits { units: f64, maximum: u32 },
+    #[error("a position of {position} units is more than the maximum position size of {maximum}")]
+    AboveMaximumPositionSize { position: f64, maximum: u32 },
+    #[error("{units} units has more than {precision} decimal places")]
+    UnitsPrecision { units: f64, precision: i32 },
+    #[error("guaranteed stop loss orders are disabled for this instrument")]
+    GuaranteedStopDisabled,
+    #[error("a guaranteed stop {distance} away is closer than the minimum of {minimum}")]
+    GuaranteedStopTooClose { distance: f32, minimum: f32 },
+    #[error("a trailing stop {distance} away is outside the allowed {minimum} to {maximum}")]
+    TrailingStopDistance {
+        distance: f32,
+        minimum: f32,
+        maximum: f32,
+    },
+    #[error("the order is for {order} but the instrument is {instrument}")]
+    InstrumentMismatch { order: String, instrument: String },
+    #[error("a stop loss at {stop} is on the wrong side of the entry at {entry}")]
+    StopLossWrongSide { stop: f32, entry: f32 },
+    #[error("a take profit at {take_profit} is on the wrong side of the entry at {entry}")]
+    TakeProfitWrongSide { take_profit: f32, entry: f32 },
+    #[error("a GTD order needs a GTD time")]
+    MissingGtdTime,
+    #[error("the GTD time {gtd_time} has already passed")]
+    GtdTimeInPast { gtd_time: DateTime<Utc> },
+}
+
+impl Instrument {
+    /// Checks an order for `units` (negative for a short) would be accepted,
+    /// given we already hold `position_units` of this instrument
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::OrderViolation`] if the order is too big or too small,
+    /// has too many decimal places, or would make the position too big
+    pub fn check_order_units(&self, units: f64, position_units: f64) -> Result<(), Error> {
+        let size = units.abs();
+        let violation = if size < f64::from(self.minimum_trade_size) {
+            Some(OrderViolation::BelowMinimumTradeSize {
+                units,
+                minimum: self.minimum_trade_size,
+            })
+        } else if size > f64::from(self.maximum_order_units) {
+            Some(OrderViolation::AboveMaximumOrderUnits {
+                units,
+                maximum: self.maximum_order_units,
+            })
+        // A maximum position size of 0 means there's no limit
+        } else if self.maximum_position_size > 0
+            && (position_units + units).abs() > f64::from(self.maximum_position_size)
+        {
+            Some(OrderViolation::AboveMaximumPositionSize {
+                position: position_units + units,
+                maximum: self.maximum_position_size,
+            })
+        } else if !has_precision(units, self.trade_units_precision) {
+            Some(OrderViolation::UnitsPrecision {
+                units,
+                precision: self.trade_units_precision,
+            })
+        } else {
+            None
+        };
+        violation.map_or(Ok(()), |violation| {
+            Err(report!(Error::OrderViolation(violation))
+                .attach_printable(format!("Instrument: {}", self.name)))
+        })
+    }
+
+    /// Checks a guaranteed stop loss `distance` (in price units) from the
+    /// entry would be accepted
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::OrderViolation`] if guaranteed stops are disabled or
+    /// the stop is too close
+    pub fn check_guaranteed_stop_distance(&self, distance: f32) -> Result<(), Error> {
+        let violation = match (
+            &self.guaranteed_stop_loss_order_mode,
+            self.minimum_guaranteed_stop_loss_distance,
+        ) {
+            (GuaranteedStopLossOrderModeForInstrument::Disabled, _) => {
+                Some(OrderViolation::GuaranteedStopDisabled)
+            }
+            (_, Some(minimum)) if distance < minimum => {
+                Some(OrderViolation::GuaranteedStopTooClose { distance, minimum })
+            }
+            _ => None,
+        };
+        violation.map_or(Ok(()), |violation| {
+            Err(report!(Error::OrderViolation(violation))
+                .attach_printable(format!("Instrument: {}", self.name)))
+        })
+    }
+
+    /// Checks a trailing stop loss `distance` (in price units) would be accepted
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::OrderViolation`] if the distance is outside the
+    /// instrument's trailing stop limits
+    pub fn check_trailing_stop_distance(&self, distance: f32) -> Result<(), Error> {
+        let (minimum, maximum) = (
+            self.minimum_trailing_stop_distance,
+            self.maximum_trailing_stop_distance,
+        );
+        if (minimum..=maximum).contains(&distance) {
+            Ok(())
+        } else {
+            Err(report!(Error::OrderViolation(
+                OrderViolation::TrailingStopDistance {
+                    distance,
+                    minimum,
+                    maximum
+                }
+            ))
+            .attach_printable(format!("Instrument: {}", self.name)))
+        }
+    }
+}
+
+/// True if `units` has no more than `precision` decimal places
+fn has_precision(units: f64, precision: i32) -> bool {
+    let scaled = units * 10f64.powi(precision);
+    // Leave a little room for rounding
+    (scaled - scaled.round()).abs() < 0.001
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const EUR_USD: &str = r#"{
+        "name": "EUR_USD",
+        "type": "CURRENCY",
+        "displayName": "EUR/USD",
+        "pipLocation": -4,
+        "displayPrecision": 5,
+        "tradeUnitsPrecision": 0,
+        "minimumTradeSize": "1",
+        "maximumTrailingStopDistance": "1.00000",
+        "minimumGuaranteedStopLossDistance": "0.0010",
+        "minimumTrailingStopDistance": "0.00050",
+        "maximumPositionSize": "10000000",
+        "maximumOrderUnits": "100000000",
+        "marginRate": "0.0333",
+        "commission": { "commission": "0", "unitsTraded": "1", "minimumCommission": "0" },
+        "guaranteedStopLossOrderMode": "ALLOWED",
+        "guaranteedStopLossOrderExecutionPremium": "0.00005",
+        "financing": { "longRate": "-0.0512", "shortRate": "0.0258", "financingDaysOfWeek": [] },
+        "tags": [{ "type": "ASSET_CLASS", "name": "CURRENCY" }]
+    }"#;
+
+    fn violation(result: Result<(), Error>) -> OrderViolation {
+        match result.unwrap_err().current_context() {
+            Error::OrderViolation(violation) => violation.clone(),
+            other => panic!("Expected an order violation, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn units() {
+        let instrument: Instrument = serde_json::from_str(EUR_USD).unwrap();
+        assert!(instrument.check_order_units(-1000.0, 0.0).is_ok());
+        assert_eq!(
+            OrderViolation::BelowMinimumTradeSize {
+                units: 0.0,
+                minimum: 1.0
+            },
+            violation(instrument.check_order_units(0.0, 0.0))
+        );
+        assert_eq!(
+            OrderViolation::UnitsPrecision {
+                units: 10.5,
+                precision: 0
+            },
+            violation(instrument.check_order_units(10.5, 0.0))
+        );
+        // Adding to a big position goes over the limit, but reducing it is fine
+        assert_eq!(
+            OrderViolation::AboveMaximumPositionSize {
+                position: 10_500_000.0,
+                maximum: 10_000_000
+            },
+            violation(instrument.check_order_units(1_000_000.0, 9_500_000.0))
+        );
+        assert!(instrument
+            .check_order_units(-1_000_000.0, 9_500_000.0)
+            .is_ok());
+    }
+
+    #[test]
+    fn stop_distances() {
+        let instrument: Instrument = serde_json::from_str(EUR_USD).unwrap();
+        assert!(instrument.check_guaranteed_stop_distance(0.002).is_ok());
+        assert_eq!(
+            OrderViolation::GuaranteedStopTooClose {
+                distance: 0.0005,
+                minimum: 0.001
+            },
+            violation(instrument.check_guaranteed_stop_distance(0.0005))
+        );
+        assert!(instrument.check_trailing_stop_distance(0.001).is_ok());
+        assert!(instrument.check_trailing_stop_distance(0.0001).is_err());
+    }
+}
@@ -0,0 +1,138 @@
+//! Turns an amount of money in the account's home currency into a number of
+//! units of an instrument, so callers can size trades as "risk $100" or
+//! "$10,000 notional" instead of working out the units by hand, and
+//! works out the margin a number of units ties up.
+use super::Instrument;
+use crate::model::currency::HomeConversionFactors;
+
+impl Instrument {
+    /// The units to hold so the position is worth `notional` in the home
+    /// currency at `price`. Uses the loss conversion so the position is
+    /// never bigger than asked for. Always positive; the caller picks the
+    /// direction
+    pub fn units_for_notional(
+        &self,
+        notional: f32,
+        price: f32,
+        factors: &HomeConversionFactors,
+    ) -> f32 {
+        let unit_value = price * factors.loss_quote_home.factor;
+        self.round_units(notional.abs() / unit_value)
+    }
+
+    /// The units to hold so that being stopped out `stop_distance` (in price
+    /// units) away loses `risk` in the home currency. Always positive; the
+    /// caller picks the direction
+    pub fn units_for_risk(
+        &self,
+        risk: f32,
+        stop_distance: f32,
+        factors: &HomeConversionFactors,
+    ) -> f32 {
+        let unit_loss = stop_distance.abs() * factors.loss_quote_home.factor;
+        self.round_units(risk.abs() / unit_loss)
+    }
+
+    /// The margin in the home currency that holding `units` at `price` needs,
+    /// at the instrument's margin rate. Uses the loss conversion so it's
+    /// never less than OANDA will ask for
+    pub fn margin_for(&self, units: f32, price: f32, factors: &HomeConversionFactors) -> f32 {
+        units.abs() * price * factors.loss_quote_home.factor * self.margin_rate
+    }
+
+    /// Rounds `units` towards zero to the instrument's
+    /// [`trade_units_precision`](Self::trade_units_precision), so sizing
+    /// never goes over the amount asked for. Non finite amounts (eg. from a
+    /// zero stop distance) come back as 0
+    pub fn round_units(&self, units: f32) -> f32 {
+        if !units.is_finite() {
+            return 0.0;
+        }
+        let scale = 10f32.powi(self.trade_units_precision);
+        // Leave a little room for f32 rounding so 99.99999 counts as 100
+        (units * scale + 0.001_f32.copysign(units)).trunc() / scale
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::currency::HomeConversions;
+    use pretty_assertions::assert_eq;
+
+    fn instrument(name: &str, precision: i32) -> Instrument {
+        let input = format!(
+            r#"{{
+                "name": "{name}",
+                "type": "CURRENCY",
+                "displayName": "{name}",
+                "pipLocation": -2,
+                "displayPrecision": 3,
+                "tradeUnitsPrecision": {precision},
+                "minimumTradeSize": "1",
+                "maximumTrailingStopDistance": "100.000",
+                "minimumTrailingStopDistance": "0.050",
+                "maximumPositionSize": "0",
+                "maximumOrderUnits": "100000000",
+                "marginRate": "0.05",
+                "commission": {{ "commission": "0", "unitsTraded": "1", "minimumCommission": "0" }},
+                "guaranteedStopLossOrderMode": "DISABLED",
+                "financing": {{ "longRate": "0", "shortRate": "0", "financingDaysOfWeek": [] }},
+                "tags": []
+            }}"#
+        );
+        serde_json::from_str(&input).unwrap()
+    }
+
+    /// A USD account trading EUR_JPY
+    fn factors() -> HomeConversionFactors {
+        let input = r#"[
+            {"currency": "EUR", "accountGain": "1.07", "accountLoss": "1.08", "positionValue": "1.075"},
+            {"currency": "JPY", "accountGain": "0.0073", "accountLoss": "0.0074", "positionValue": "0.00735"}
+        ]"#;
+        let conversions: Vec<HomeConversions> = serde_json::from_str(input).unwrap();
+        HomeConversionFactors::new(&conversions[0], &conversions[1])
+    }
+
+    #[test]
+    fn notional() {
+        let eur_jpy = instrument("EUR_JPY", 0);
+        // Each unit is worth 160 JPY, or 1.184 USD
+        assert_eq!(
+            8445.0,
+            eur_jpy.units_for_notional(10_000.0, 160.0, &factors())
+        );
+        assert_eq!(
+            8445.0,
+            eur_jpy.units_for_notional(-10_000.0, 160.0, &factors())
+        );
+    }
+
+    #[test]
+    fn risk() {
+        let eur_jpy = instrument("EUR_JPY", 0);
+        // A 50 pip stop loses 0.5 JPY, or 0.0037 USD, per unit
+        assert_eq!(27027.0, eur_jpy.units_for_risk(100.0, 0.5, &factors()));
+        assert_eq!(0.0, eur_jpy.units_for_risk(100.0, 0.0, &factors()));
+        let fractional = instrument("EUR_JPY", 2);
+        assert_eq!(27027.02, fractional.units_for_risk(100.0, 0.5, &factors()));
+    }
+
+    #[test]
+    fn margin() {
+        let eur_jpy = instrument("EUR_JPY", 0);
+        // 10,000 units at 160 is 11,840 USD, 5% of which is 592
+        let margin = eur_jpy.margin_for(10_000.0, 160.0, &factors());
+        assert!((592.0 - margin).abs() < 0.01, "{margin}");
+        assert_eq!(margin, eur_jpy.margin_for(-10_000.0, 160.0, &factors()));
+    }
+
+    #[test]
+    fn rounding() {
+        let instrument = instrument("EUR_JPY", 1);
+        assert_eq!(10.5, instrument.round_units(10.59));
+        assert_eq!(-10.5, instrument.round_units(-10.59));
+        assert_eq!(100.0, instrument.round_units(99.99999));
+        assert_eq!(0.0, instrument.round_units(f32::NAN));
+    }
+}
@@ -0,0 +1,195 @@
+//! Picks between a normal and a guaranteed stop loss for a new trade,
+//! following the account's guaranteed stop loss mode for the instrument,
+//! and works out what the guarantee would cost.
+use error_stack::{report, Result};
+
+use super::{GuaranteedStopLossOrderModeForInstrument, Instrument, OrderViolation};
+use crate::{
+    model::{
+        order::Order,
+        price::to_f32,
+        transaction::{SLTrigger, StopLoss},
+    },
+    Error,
+};
+
+/// The stop loss to attach to a new trade
+#[derive(Debug, Clone, PartialEq)]
+pub struct StopLossPlan {
+    pub stop_loss: StopLoss,
+    /// Whether it has to go in as a guaranteed stop loss
+    pub guaranteed: bool,
+    /// What's charged, in the quote currency, if the guaranteed stop is
+    /// triggered. 0 for a normal stop loss
+    pub premium: f32,
+}
+
+impl StopLossPlan {
+    /// Puts the stop loss in the right on fill field of `order`
+    pub fn apply(self, order: &mut Order) {
+        if self.guaranteed {
+            order.stop_loss_on_fill = None;
+            order.guaranteed_stop_loss_on_fill = Some(self.stop_loss);
+        } else {
+            order.stop_loss_on_fill = Some(self.stop_loss);
+            order.guaranteed_stop_loss_on_fill = None;
+        }
+    }
+}
+
+impl Instrument {
+    /// Plans `stop_loss` for a trade of `units` entering at `entry`. It's
+    /// guaranteed if the account requires it for this instrument, or if we
+    /// `want_guaranteed` and it's allowed
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::OrderViolation`] if we want a guaranteed stop and
+    /// they're disabled, or it's guaranteed and too close to the entry
+    pub fn plan_stop_loss(
+        &self,
+        stop_loss: StopLoss,
+        entry: f32,
+        units: f32,
+        want_guaranteed: bool,
+    ) -> Result<StopLossPlan, Error> {
+        use GuaranteedStopLossOrderModeForInstrument::*;
+        let guaranteed = match self.guaranteed_stop_loss_order_mode {
+            Required => true,
+            Allowed => want_guaranteed,
+            Disabled if want_guaranteed => {
+                return Err(report!(Error::OrderViolation(
+                    OrderViolation::GuaranteedStopDisabled
+                ))
+                .attach_printable(format!("Instrument: {}", self.name)))
+            }
+            Disabled => false,
+        };
+        if !guaranteed {
+            return Ok(StopLossPlan {
+                stop_loss,
+                guaranteed,
+                premium: 0.0,
+            });
+        }
+        let distance = match stop_loss.trigger {
+            SLTrigger::Distance(distance) => distance,
+            SLTrigger::Price(price) => (entry - to_f32(price)).abs(),
+        };
+        self.check_guaranteed_stop_distance(distance)?;
+        let premium = self
+            .guaranteed_stop_loss_order_execution_premium
+            .unwrap_or_default()
+            * units.abs();
+        Ok(StopLossPlan {
+            stop_loss,
+            guaranteed,
+            premium,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::price::from_f32;
+    use pretty_assertions::assert_eq;
+
+    fn instrument(mode: &str) -> Instrument {
+        let input = format!(
+            r#"{{
+                "name": "EUR_USD",
+                "type": "CURRENCY",
+                "displayName": "EUR/USD",
+                "pipLocation": -4,
+                "displayPrecision": 5,
+                "tradeUnitsPrecision": 0,
+                "minimumTradeSize": "1",
+                "maximumTrailingStopDistance": "1.00000",
+                "minimumGuaranteedStopLossDistance": "0.0010",
+                "minimumTrailingStopDistance": "0.00050",
+                "maximumPositionSize": "0",
+                "maximumOrderUnits": "100000000",
+                "marginRate": "0.0333",
+                "commission": {{ "commission": "0", "unitsTraded": "1", "minimumCommission": "0" }},
+                "guaranteedStopLossOrderMode": "{mode}",
+                "guaranteedStopLossOrderExecutionPremium": "0.00005",
+                "financing": {{ "longRate": "-0.0512", "shortRate": "0.0258", "financingDaysOfWeek": [] }},
+                "tags": []
+            }}"#
+        );
+        serde_json::from_str(&input).unwrap()
+    }
+
+    fn stop_loss(price: f32) -> StopLoss {
+        StopLoss::builder()
+            .trigger(SLTrigger::Price(from_f32(price)))
+            .build()
+    }
+
+    fn violation(result: Result<StopLossPlan, Error>) -> OrderViolation {
+        match result.unwrap_err().current_context() {
+            Error::OrderViolation(violation) => violation.clone(),
+            other => panic!("Expected an order violation, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn required() {
+        let plan = instrument("REQUIRED")
+            .plan_stop_loss(stop_loss(1.0950), 1.1000, 10_000.0, false)
+            .unwrap();
+        assert!(plan.guaranteed);
+        assert!((plan.premium - 0.5).abs() < 0.0001);
+        // Too close to be guaranteed
+        assert_eq!(
+            OrderViolation::GuaranteedStopTooClose {
+                distance: 0.0005,
+                minimum: 0.001
+            },
+            violation(
+                instrument("REQUIRED").plan_stop_loss(
+                    StopLoss::builder()
+                        .trigger(SLTrigger::Distance(0.0005))
+                        .build(),
+                    1.1,
+                    -10_000.0,
+                    false
+                )
+            )
+        );
+    }
+
+    #[test]
+    fn allowed() {
+        let instrument = instrument("ALLOWED");
+        let plan = instrument
+            .plan_stop_loss(stop_loss(1.0950), 1.1000, 10_000.0, false)
+            .unwrap();
+        assert_eq!(
+            StopLossPlan {
+                stop_loss: stop_loss(1.0950),
+                guaranteed: false,
+                premium: 0.0
+            },
+            plan
+        );
+        let plan = instrument
+            .plan_stop_loss(stop_loss(1.0950), 1.1000, 10_000.0, true)
+            .unwrap();
+        assert!(plan.guaranteed);
+    }
+
+    #[test]
+    fn disabled() {
+        let instrument = instrument("DISABLED");
+        let plan = instrument
+            .plan_stop_loss(stop_loss(1.0950), 1.1000, 10_000.0, false)
+            .unwrap();
+        assert!(!plan.guaranteed);
+        assert_eq!(
+            OrderViolation::GuaranteedStopDisabled,
+            violation(instrument.plan_stop_loss(stop_loss(1.0950), 1.1000, 10_000.0, true))
+        );
+    }
+}
@@ -0,0 +1,43 @@
+//! The weekly forex trading schedule, for when we want to know if the
+//! market is open without asking the broker.
+//!
+//! Forex trades from 17:00 New York time on Sunday until 17:00 New York
+//! time on Friday. That's 21:00 or 22:00 UTC depending on daylight saving,
+//! so without a timezone database we take the safe side: closed from 21:00
+//! UTC Friday until 22:00 UTC Sunday.
+use chrono::{DateTime, Datelike, Timelike, Utc, Weekday};
+
+/// The UTC hour on Friday from which we call the market closed
+pub const FRIDAY_CLOSE_HOUR: u32 = 21;
+/// The UTC hour on Sunday from which we call the market open
+pub const SUNDAY_OPEN_HOUR: u32 = 22;
+
+/// True if `time` falls inside the weekly forex trading hours. It doesn't
+/// know about holidays; use the pricing `tradeable` flag for that.
+pub fn is_forex_market_open(time: DateTime<Utc>) -> bool {
+    match time.weekday() {
+        Weekday::Sat => false,
+        Weekday::Fri => time.hour() < FRIDAY_CLOSE_HOUR,
+        Weekday::Sun => time.hour() >= SUNDAY_OPEN_HOUR,
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn weekly_schedule() {
+        // 2023-03-10 is a Friday
+        let time = |day, hour| Utc.with_ymd_and_hms(2023, 3, day, hour, 0, 0).unwrap();
+        assert!(is_forex_market_open(time(8, 12)));
+        assert!(is_forex_market_open(time(10, 20)));
+        assert!(!is_forex_market_open(time(10, 21)));
+        assert!(!is_forex_market_open(time(11, 12)));
+        assert!(!is_forex_market_open(time(12, 21)));
+        assert!(is_forex_market_open(time(12, 22)));
+        assert!(is_forex_market_open(time(13, 0)));
+    }
+}
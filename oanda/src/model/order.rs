@@ -1,10 +1,17 @@
 use crate::model::trade::{ClientExtensions, TimeInForce};
 use crate::model::transaction::StopLoss;
+use crate::model::units::Units;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
 
-use super::{trade::MarketOrderTimeInForce, transaction::TakeProfitDetails};
+use super::{
+    price::Price,
+    trade::MarketOrderTimeInForce,
+    transaction::{TakeProfitDetails, Transaction},
+};
+
+mod validate;
 
 /// Order structure
 #[serde_as]
@@ -21,14 +28,14 @@ pub struct Order {
 
     /// The quantity requested to be filled by the Market Order. A positive
     /// number of units results in a long Order, and a negative number of units
-    /// results in a short Order.
+    /// results in a short Order. See [`Units::long`] and [`Units::short`].
     #[serde_as(as = "DisplayFromStr")]
-    pub units: f32,
+    pub units: Units,
 
     /// The worst price that the client is willing to have the Market Order
     /// filled at.
     #[serde_as(as = "Option<DisplayFromStr>")]
-    pub price_bound: Option<f32>,
+    pub price_bound: Option<Price>,
 
     /// Specification of how Positions in the Account are modified when the Order
     /// is filled.
@@ -36,6 +43,7 @@ pub struct Order {
 
     /// The client extensions to add to the Order. Do not set, modify, or delete
     /// clientExtensions if your account is associated with MT4.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub client_extensions: Option<ClientExtensions>,
 
     /// TakeProfitDetails specifies the details of a Take Profit Order to be
@@ -68,6 +76,7 @@ pub struct Order {
     /// Client Extensions to add to the Trade created when the Order is filled
     /// (if such a Trade is created). Do not set, modify, or delete
     /// tradeClientExtensions if your account is associated with MT4.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub trade_client_extensions: Option<ClientExtensions>,
 }
 
@@ -92,7 +101,7 @@ pub struct LimitOrder {
     /// only be filled by a market price that is equal to or better than this
     /// price.
     #[serde_as(as = "DisplayFromStr")]
-    price: f32,
+    price: Price,
 
     /// The date/time when the Limit Order will be cancelled if its timeInForce
     /// is “GTD”.
@@ -140,3 +149,45 @@ pub enum OrderPositionFill {
     #[default]
     Default,
 }
+
+/// What OANDA did to cancel an order
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelOrderResponse {
+    /// The Transaction that cancelled the Order
+    pub order_cancel_transaction: Transaction,
+
+    /// The IDs of all Transactions that were created while satisfying the request.
+    #[serde(default, rename = "relatedTransactionIDs")]
+    pub related_transaction_ids: Vec<String>,
+
+    /// The ID of the most recent Transaction created for the Account
+    #[serde(rename = "lastTransactionID")]
+    pub last_transaction_id: String,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn cancel_order_response() {
+        let input = r#"{
+            "orderCancelTransaction": {
+                "type": "ORDER_CANCEL",
+                "id": "6360",
+                "time": "2023-05-05T13:30:00.000000000Z",
+                "userID": 1234,
+                "accountID": "101-001-1234-001",
+                "batchID": "6360",
+                "requestID": "60423"
+            },
+            "relatedTransactionIDs": ["6360"],
+            "lastTransactionID": "6360"
+        }"#;
+        let got: CancelOrderResponse = serde_json::from_str(input).unwrap();
+        assert_eq!("6360", got.order_cancel_transaction.id);
+        assert_eq!(vec!["6360".to_string()], got.related_transaction_ids);
+    }
+}
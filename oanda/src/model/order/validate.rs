@@ -0,0 +1,230 @@
+//! Makes the checks OANDA would make on a new order locally, and gives back
+//! the body we'd send, so orders can be built and tested without a network.
+use chrono::{DateTime, Utc};
+use error_stack::{report, Result};
+use serde::Serialize;
+
+use super::Order;
+use crate::{
+    model::{
+        instrument::{Instrument, OrderViolation},
+        price::to_f32,
+        trade::TimeInForce,
+        transaction::{SLTrigger, StopLoss},
+    },
+    Error,
+};
+
+/// What's posted to the orders endpoint
+#[derive(Serialize)]
+struct OrderBody<'a> {
+    order: &'a Order,
+}
+
+impl Order {
+    /// Checks the order would be accepted for `instrument`, filling at about
+    /// `entry` with `position_units` already held, and returns the JSON body
+    /// that would be sent. `now` is what GTD times are checked against
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::OrderViolation`] for the first check that fails: the
+    /// instrument, the units (see [`Instrument::check_order_units`]), a stop
+    /// loss or take profit on the wrong side of the entry, a guaranteed stop
+    /// too close to it, or a take profit GTD time that's missing or passed
+    pub fn validate(
+        &self,
+        instrument: &Instrument,
+        entry: f32,
+        position_units: f64,
+        now: DateTime<Utc>,
+    ) -> Result<serde_json::Value, Error> {
+        let violation = |violation| {
+            Err(report!(Error::OrderViolation(violation))
+                .attach_printable(format!("Instrument: {}", instrument.name)))
+        };
+        if self.instrument != instrument.name {
+            return violation(OrderViolation::InstrumentMismatch {
+                order: self.instrument.clone(),
+                instrument: instrument.name.clone(),
+            });
+        }
+        instrument.check_order_units(self.units.value(), position_units)?;
+        let long = self.units.is_long();
+        for stop_loss in [&self.stop_loss_on_fill, &self.guaranteed_stop_loss_on_fill]
+            .into_iter()
+            .flatten()
+        {
+            if let SLTrigger::Price(stop) = stop_loss.trigger {
+                let stop = to_f32(stop);
+                if (long && stop >= entry) || (!long && stop <= entry) {
+                    return violation(OrderViolation::StopLossWrongSide { stop, entry });
+                }
+            }
+        }
+        if let Some(stop_loss) = &self.guaranteed_stop_loss_on_fill {
+            instrument.check_guaranteed_stop_distance(stop_distance(stop_loss, entry))?;
+        }
+        if let Some(take_profit) = &self.take_profit_on_fill {
+            let price = to_f32(take_profit.price);
+            if (long && price <= entry) || (!long && price >= entry) {
+                return violation(OrderViolation::TakeProfitWrongSide {
+                    take_profit: price,
+                    entry,
+                });
+            }
+            match (take_profit.time_in_force, take_profit.gtd_time) {
+                (TimeInForce::Gtd, None) => return violation(OrderViolation::MissingGtdTime),
+                (TimeInForce::Gtd, Some(gtd_time)) if gtd_time <= now => {
+                    return violation(OrderViolation::GtdTimeInPast { gtd_time })
+                }
+                _ => (),
+            }
+        }
+        serde_json::to_value(OrderBody { order: self }).map_err(|err| {
+            report!(Error::JsonConversion).attach_printable(format!("Order body: {err}"))
+        })
+    }
+}
+
+/// How far the stop is from `entry`, in price units
+fn stop_distance(stop_loss: &StopLoss, entry: f32) -> f32 {
+    match stop_loss.trigger {
+        SLTrigger::Distance(distance) => distance,
+        SLTrigger::Price(price) => (entry - to_f32(price)).abs(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::{
+        order::{OrderPositionFill, OrderType},
+        price::from_f32,
+        transaction::TakeProfitDetails,
+        Units,
+    };
+    use chrono::Duration;
+    use pretty_assertions::assert_eq;
+
+    const EUR_USD: &str = r#"{
+        "name": "EUR_USD",
+        "type": "CURRENCY",
+        "displayName": "EUR/USD",
+        "pipLocation": -4,
+        "displayPrecision": 5,
+        "tradeUnitsPrecision": 0,
+        "minimumTradeSize": "1",
+        "maximumTrailingStopDistance": "1.00000",
+        "minimumGuaranteedStopLossDistance": "0.0010",
+        "minimumTrailingStopDistance": "0.00050",
+        "maximumPositionSize": "0",
+        "maximumOrderUnits": "100000000",
+        "marginRate": "0.0333",
+        "commission": { "commission": "0", "unitsTraded": "1", "minimumCommission": "0" },
+        "guaranteedStopLossOrderMode": "ALLOWED",
+        "guaranteedStopLossOrderExecutionPremium": "0.00005",
+        "financing": { "longRate": "-0.0512", "shortRate": "0.0258", "financingDaysOfWeek": [] },
+        "tags": []
+    }"#;
+
+    fn order(units: Units) -> Order {
+        Order {
+            order_type: OrderType::Market,
+            instrument: "EUR_USD".to_string(),
+            units,
+            price_bound: None,
+            position_fill: OrderPositionFill::Default,
+            client_extensions: None,
+            take_profit_on_fill: None,
+            stop_loss_on_fill: Some(
+                StopLoss::builder()
+                    .trigger(SLTrigger::Price(from_f32(if units.is_long() {
+                        1.09
+                    } else {
+                        1.11
+                    })))
+                    .build(),
+            ),
+            guaranteed_stop_loss_on_fill: None,
+            trade_client_extensions: None,
+        }
+    }
+
+    fn take_profit(price: f32, gtd_time: Option<DateTime<Utc>>) -> TakeProfitDetails {
+        TakeProfitDetails {
+            price: from_f32(price),
+            time_in_force: TimeInForce::Gtd,
+            gtd_time,
+            client_extensions: None,
+        }
+    }
+
+    fn violation(result: Result<serde_json::Value, Error>) -> OrderViolation {
+        match result.unwrap_err().current_context() {
+            Error::OrderViolation(violation) => violation.clone(),
+            other => panic!("Expected an order violation, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn body() {
+        let instrument: Instrument = serde_json::from_str(EUR_USD).unwrap();
+        let body = order(Units::short(1000.0))
+            .validate(&instrument, 1.1, 0.0, Utc::now())
+            .unwrap();
+        assert_eq!("MARKET", body["order"]["type"]);
+        assert_eq!("EUR_USD", body["order"]["instrument"]);
+        assert_eq!("-1000", body["order"]["units"]);
+        assert_eq!("DEFAULT", body["order"]["positionFill"]);
+    }
+
+    #[test]
+    fn violations() {
+        let instrument: Instrument = serde_json::from_str(EUR_USD).unwrap();
+        let now = Utc::now();
+        let mut wrong_side = order(Units::long(1000.0));
+        wrong_side.stop_loss_on_fill = order(Units::short(1000.0)).stop_loss_on_fill;
+        assert_eq!(
+            OrderViolation::StopLossWrongSide {
+                stop: 1.11,
+                entry: 1.1
+            },
+            violation(wrong_side.validate(&instrument, 1.1, 0.0, now))
+        );
+        let mut too_close = order(Units::long(1000.0));
+        too_close.guaranteed_stop_loss_on_fill = Some(
+            StopLoss::builder()
+                .trigger(SLTrigger::Distance(0.0005))
+                .build(),
+        );
+        assert_eq!(
+            OrderViolation::GuaranteedStopTooClose {
+                distance: 0.0005,
+                minimum: 0.001
+            },
+            violation(too_close.validate(&instrument, 1.1, 0.0, now))
+        );
+        let mut gtd = order(Units::long(1000.0));
+        gtd.take_profit_on_fill = Some(take_profit(1.12, None));
+        assert_eq!(
+            OrderViolation::MissingGtdTime,
+            violation(gtd.validate(&instrument, 1.1, 0.0, now))
+        );
+        let gtd_time = now - Duration::minutes(1);
+        gtd.take_profit_on_fill = Some(take_profit(1.12, Some(gtd_time)));
+        assert_eq!(
+            OrderViolation::GtdTimeInPast { gtd_time },
+            violation(gtd.validate(&instrument, 1.1, 0.0, now))
+        );
+        gtd.take_profit_on_fill = Some(take_profit(1.12, Some(now + Duration::hours(1))));
+        assert!(gtd.validate(&instrument, 1.1, 0.0, now).is_ok());
+        assert_eq!(
+            OrderViolation::UnitsPrecision {
+                units: 10.5,
+                precision: 0
+            },
+            violation(order(Units::long(10.5)).validate(&instrument, 1.1, 0.0, now))
+        );
+    }
+}
@@ -0,0 +1,287 @@
+//! Positions; everything held in one instrument, long and short.
+//! See <https://developer.oanda.com/rest-live-v20/position-df/>
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
+
+use super::{currency::Currency, price::Price, transaction::Transaction, AccountSummary};
+
+/// See <https://developer.oanda.com/rest-live-v20/position-ep/>
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionsResponse {
+    pub positions: Vec<Position>,
+    /// The ID of the most recent Transaction created for the Account
+    #[serde(rename = "lastTransactionID")]
+    pub last_transaction_id: String,
+}
+
+/// The specification of a Position within an Account.
+#[serde_as]
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Position {
+    /// The Position’s Instrument.
+    pub instrument: String,
+
+    /// Profit/loss realized by the Position over the lifetime of the Account.
+    #[serde_as(as = "DisplayFromStr")]
+    pub pl: f32,
+
+    /// The unrealized profit/loss of all open Trades that contribute to this Position.
+    #[serde_as(as = "DisplayFromStr")]
+    #[serde(rename = "unrealizedPL")]
+    pub unrealized_pl: f32,
+
+    /// The details of the long side of the Position.
+    pub long: PositionSide,
+
+    /// The details of the short side of the Position.
+    pub short: PositionSide,
+}
+
+/// The representation of a Position for a single direction (long or short).
+#[serde_as]
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionSide {
+    /// Number of units in the position (negative value indicates short
+    /// position, positive indicates long position).
+    #[serde_as(as = "DisplayFromStr")]
+    pub units: f32,
+
+    /// Volume-weighted average of the underlying Trade open prices for the Position.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub average_price: Option<Price>,
+
+    /// List of the open Trade IDs which contribute to the open Position.
+    #[serde(default, rename = "tradeIDs")]
+    pub trade_ids: Vec<String>,
+
+    /// Profit/loss realized by the PositionSide over the lifetime of the Account.
+    #[serde_as(as = "DisplayFromStr")]
+    pub pl: f32,
+
+    /// The unrealized profit/loss of all open Trades that contribute to this PositionSide.
+    #[serde_as(as = "DisplayFromStr")]
+    #[serde(rename = "unrealizedPL")]
+    pub unrealized_pl: f32,
+}
+
+impl PositionSide {
+    pub fn is_open(&self) -> bool {
+        self.units != 0.0
+    }
+}
+
+/// An open position with its unrealized P&L measured against the account
+#[derive(Debug, PartialEq, Clone)]
+pub struct PositionPl {
+    pub position: Position,
+    /// The unrealized P&L in the account's home currency
+    pub unrealized_pl: f32,
+    /// The unrealized P&L as a percent of the account's NAV
+    pub percent_of_nav: f32,
+}
+
+/// Every open position's unrealized P&L, and the NAV it's measured against
+#[derive(Debug, PartialEq, Clone)]
+pub struct OpenPositionsPl {
+    /// The account's home currency, which all the amounts are in
+    pub currency: Currency,
+    pub nav: f32,
+    pub positions: Vec<PositionPl>,
+}
+
+impl OpenPositionsPl {
+    /// Joins the open positions with the account summary. OANDA already
+    /// reports position P&L in the home currency, so nothing needs converting
+    pub fn new(summary: &AccountSummary, positions: Vec<Position>) -> Self {
+        let nav = summary.nav;
+        let positions = positions
+            .into_iter()
+            .map(|position| PositionPl {
+                unrealized_pl: position.unrealized_pl,
+                percent_of_nav: percent_of(position.unrealized_pl, nav),
+                position,
+            })
+            .collect();
+        Self {
+            currency: summary.currency.clone(),
+            nav,
+            positions,
+        }
+    }
+
+    /// The unrealized P&L of all the positions together, in the home currency
+    pub fn total_unrealized_pl(&self) -> f32 {
+        self.positions
+            .iter()
+            .map(|position| position.unrealized_pl)
+            .sum()
+    }
+
+    /// The unrealized P&L of all the positions together, as a percent of NAV
+    pub fn total_percent_of_nav(&self) -> f32 {
+        percent_of(self.total_unrealized_pl(), self.nav)
+    }
+}
+
+/// `amount` as a percent of `nav`; 0 if there's no NAV to measure against
+fn percent_of(amount: f32, nav: f32) -> f32 {
+    if nav == 0.0 {
+        0.0
+    } else {
+        amount / nav * 100.0
+    }
+}
+
+/// Which sides of a position to close. Each side is either "ALL" or "NONE";
+/// we don't do partial closes
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClosePositionRequest {
+    pub long_units: &'static str,
+    pub short_units: &'static str,
+}
+
+impl ClosePositionRequest {
+    /// Closes whichever sides of `position` are open
+    pub fn all(position: &Position) -> Self {
+        let units = |side: &PositionSide| if side.is_open() { "ALL" } else { "NONE" };
+        Self {
+            long_units: units(&position.long),
+            short_units: units(&position.short),
+        }
+    }
+}
+
+/// What OANDA did to close a position
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ClosePositionResponse {
+    /// The MarketOrderTransaction created to close the long Position.
+    pub long_order_create_transaction: Option<Transaction>,
+
+    /// OrderFill Transaction that closes the long Position
+    pub long_order_fill_transaction: Option<Transaction>,
+
+    /// OrderCancel Transaction that cancels the MarketOrder created to close the long Position
+    pub long_order_cancel_transaction: Option<Transaction>,
+
+    /// The MarketOrderTransaction created to close the short Position.
+    pub short_order_create_transaction: Option<Transaction>,
+
+    /// OrderFill Transaction that closes the short Position
+    pub short_order_fill_transaction: Option<Transaction>,
+
+    /// OrderCancel Transaction that cancels the MarketOrder created to close the short Position
+    pub short_order_cancel_transaction: Option<Transaction>,
+
+    /// The IDs of all Transactions that were created while satisfying the request.
+    #[serde(default, rename = "relatedTransactionIDs")]
+    pub related_transaction_ids: Vec<String>,
+
+    /// The ID of the most recent Transaction created for the Account
+    #[serde(rename = "lastTransactionID")]
+    pub last_transaction_id: String,
+}
+
+impl ClosePositionResponse {
+    /// True if OANDA cancelled the market order for either side instead of filling it
+    pub fn was_cancelled(&self) -> bool {
+        self.long_order_cancel_transaction.is_some()
+            || self.short_order_cancel_transaction.is_some()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::price::to_f32;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn close_open_sides() {
+        let input = r#"{
+            "positions": [
+                {
+                    "instrument": "EUR_USD",
+                    "pl": "-12.5",
+                    "unrealizedPL": "3.25",
+                    "long": {
+                        "units": "1000",
+                        "averagePrice": "1.06920",
+                        "tradeIDs": ["6349"],
+                        "pl": "-12.5",
+                        "unrealizedPL": "3.25"
+                    },
+                    "short": {
+                        "units": "0",
+                        "pl": "0.0",
+                        "unrealizedPL": "0.0"
+                    }
+                }
+            ],
+            "lastTransactionID": "6356"
+        }"#;
+        let got: PositionsResponse = serde_json::from_str(input).unwrap();
+        let position = &got.positions[0];
+        assert_eq!(Some(1.0692), position.long.average_price.map(to_f32));
+        assert_eq!(vec!["6349".to_string()], position.long.trade_ids);
+        assert!(position.short.trade_ids.is_empty());
+        assert_eq!(
+            ClosePositionRequest {
+                long_units: "ALL",
+                short_units: "NONE",
+            },
+            ClosePositionRequest::all(position)
+        );
+    }
+
+    #[test]
+    fn pl_against_nav() {
+        let summary = r#"{
+            "id": "101-004-1234567-001",
+            "currency": "USD",
+            "balance": "1000.0000",
+            "NAV": "1000.0000",
+            "unrealizedPL": "0.0000",
+            "marginUsed": "40.0000",
+            "marginAvailable": "960.0000",
+            "marginCloseoutPercent": "0.02",
+            "openTradeCount": 2,
+            "openPositionCount": 2,
+            "pendingOrderCount": 0,
+            "hedgingEnabled": false,
+            "marginRate": "0.02"
+        }"#;
+        let summary: AccountSummary = serde_json::from_str(summary).unwrap();
+        let position = |instrument: &str, unrealized_pl: &str| {
+            let input = format!(
+                r#"{{
+                    "instrument": "{instrument}",
+                    "pl": "0",
+                    "unrealizedPL": "{unrealized_pl}",
+                    "long": {{ "units": "1000", "pl": "0", "unrealizedPL": "{unrealized_pl}" }},
+                    "short": {{ "units": "0", "pl": "0", "unrealizedPL": "0" }}
+                }}"#
+            );
+            serde_json::from_str::<Position>(&input).unwrap()
+        };
+        let got = OpenPositionsPl::new(
+            &summary,
+            vec![position("EUR_USD", "25.0"), position("USD_JPY", "-10.0")],
+        );
+        assert_eq!("USD", got.currency.as_str());
+        assert_eq!(
+            vec![("EUR_USD", 2.5), ("USD_JPY", -1.0)],
+            got.positions
+                .iter()
+                .map(|pl| (pl.position.instrument.as_str(), pl.percent_of_nav))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(15.0, got.total_unrealized_pl());
+        assert_eq!(1.5, got.total_percent_of_nav());
+    }
+}
@@ -0,0 +1,65 @@
+//! The type OANDA's string encoded prices are parsed into. f32 by default,
+//! which can't hold every 5 decimal place FX price exactly; turn on the
+//! `f64-prices` feature for f64, or `decimal-prices` for
+//! [`rust_decimal::Decimal`]. If both are on, Decimal wins.
+//!
+//! The algorithms crate works in f32, so use [`to_f32`] and [`from_f32`]
+//! rather than `as` so code builds whichever type is picked.
+
+#[cfg(not(any(feature = "f64-prices", feature = "decimal-prices")))]
+pub type Price = f32;
+
+#[cfg(all(feature = "f64-prices", not(feature = "decimal-prices")))]
+pub type Price = f64;
+
+#[cfg(feature = "decimal-prices")]
+pub type Price = rust_decimal::Decimal;
+
+/// `price` as an f32, rounding it if need be
+#[cfg(not(any(feature = "f64-prices", feature = "decimal-prices")))]
+pub fn to_f32(price: Price) -> f32 {
+    price
+}
+
+/// `price` as an f32, rounding it if need be
+#[cfg(all(feature = "f64-prices", not(feature = "decimal-prices")))]
+pub fn to_f32(price: Price) -> f32 {
+    price as f32
+}
+
+/// `price` as an f32, rounding it if need be. NaN if it's out of range
+#[cfg(feature = "decimal-prices")]
+pub fn to_f32(price: Price) -> f32 {
+    rust_decimal::prelude::ToPrimitive::to_f32(&price).unwrap_or(f32::NAN)
+}
+
+/// An f32 worked out by us, eg. a mid price, as a [`Price`]
+#[cfg(not(any(feature = "f64-prices", feature = "decimal-prices")))]
+pub fn from_f32(value: f32) -> Price {
+    value
+}
+
+/// An f32 worked out by us, eg. a mid price, as a [`Price`]
+#[cfg(all(feature = "f64-prices", not(feature = "decimal-prices")))]
+pub fn from_f32(value: f32) -> Price {
+    value as f64
+}
+
+/// An f32 worked out by us, eg. a mid price, as a [`Price`]. 0 if it's
+/// NaN or infinite
+#[cfg(feature = "decimal-prices")]
+pub fn from_f32(value: f32) -> Price {
+    rust_decimal::prelude::FromPrimitive::from_f32(value).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        assert!((to_f32(from_f32(1.08765)) - 1.08765).abs() < 0.000001);
+        let parsed: Price = "1.08765".parse().unwrap();
+        assert!((to_f32(parsed) - 1.08765).abs() < 0.000001);
+    }
+}
@@ -0,0 +1,259 @@
+//! Prices as they come from the pricing endpoints and the pricing stream.
+//! See <https://developer.oanda.com/rest-live-v20/pricing-df/>
+use algorithms::{distance_in_pips, TradeDirection};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_with::{serde_as, DisplayFromStr};
+
+use super::{
+    currency::HomeConversions,
+    price::{to_f32, Price},
+};
+
+/// See <https://developer.oanda.com/rest-live-v20/pricing-ep/>
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PricesResponse {
+    pub prices: Vec<ClientPrice>,
+    /// Only sent when asked for with `includeHomeConversions`
+    #[serde(default)]
+    pub home_conversions: Vec<HomeConversions>,
+}
+
+/// One line of the pricing stream. Heartbeats are sent every 5 seconds so
+/// we can tell the connection is still alive
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PricingStreamMessage {
+    Price(ClientPrice),
+    Heartbeat { time: DateTime<Utc> },
+}
+
+/// The specification of an Account-specific Price.
+#[serde_as]
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientPrice {
+    /// The Price’s Instrument.
+    pub instrument: String,
+
+    /// The date/time when the Price was created
+    pub time: DateTime<Utc>,
+
+    /// Flag indicating if the Price is tradeable or not
+    pub tradeable: bool,
+
+    /// The list of prices and liquidity available on the Instrument’s bid side.
+    /// It is possible for this list to be empty if there is no bid liquidity
+    /// currently available for the Instrument in the Account.
+    pub bids: Vec<PriceBucket>,
+
+    /// The list of prices and liquidity available on the Instrument’s ask side.
+    /// It is possible for this list to be empty if there is no ask liquidity
+    /// currently available for the Instrument in the Account.
+    pub asks: Vec<PriceBucket>,
+
+    /// The closeout bid Price. This Price is used when a bid is required to
+    /// closeout a Position (margin closeout or manual) yet there is no bid
+    /// liquidity. The closeout bid is never used to open a new position.
+    #[serde_as(as = "DisplayFromStr")]
+    pub closeout_bid: Price,
+
+    /// The closeout ask Price. This Price is used when a ask is required to
+    /// closeout a Position (margin closeout or manual) yet there is no ask
+    /// liquidity. The closeout ask is never used to open a new position.
+    #[serde_as(as = "DisplayFromStr")]
+    pub closeout_ask: Price,
+}
+
+/// A Price Bucket represents a price available for an amount of liquidity
+#[serde_as]
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceBucket {
+    /// The Price offered by the PriceBucket
+    #[serde_as(as = "DisplayFromStr")]
+    pub price: Price,
+
+    /// The amount of liquidity offered by the PriceBucket, in units
+    pub liquidity: u64,
+}
+
+impl ClientPrice {
+    /// The highest price someone will buy from us at. None if there's no bid liquidity
+    pub fn best_bid(&self) -> Option<f32> {
+        self.bids
+            .iter()
+            .map(|bucket| to_f32(bucket.price))
+            .reduce(f32::max)
+    }
+
+    /// The lowest price someone will sell to us at. None if there's no ask liquidity
+    pub fn best_ask(&self) -> Option<f32> {
+        self.asks
+            .iter()
+            .map(|bucket| to_f32(bucket.price))
+            .reduce(f32::min)
+    }
+
+    /// Half way between the best bid and the best ask
+    pub fn mid(&self) -> Option<f32> {
+        self.best_bid()
+            .zip(self.best_ask())
+            .map(|(bid, ask)| (bid + ask) / 2.0)
+    }
+
+    /// The best ask minus the best bid, in price units
+    pub fn spread(&self) -> Option<f32> {
+        self.best_bid()
+            .zip(self.best_ask())
+            .map(|(bid, ask)| ask - bid)
+    }
+
+    /// The spread in pips, given the instrument's `pip_location`
+    /// (see [`Instrument::pip_location`](crate::model::Instrument::pip_location))
+    pub fn spread_in_pips(&self, pip_location: i32) -> Option<f32> {
+        self.best_bid()
+            .zip(self.best_ask())
+            .map(|(bid, ask)| distance_in_pips(bid, ask, pip_location))
+    }
+
+    /// The buckets we'd fill against when opening a trade in `direction`,
+    /// best price first. A long buys from the asks, a short sells to the bids
+    pub fn book(&self, direction: TradeDirection) -> Vec<&PriceBucket> {
+        let mut book: Vec<_> = match direction {
+            TradeDirection::Long => self.asks.iter().collect(),
+            TradeDirection::Short => self.bids.iter().collect(),
+        };
+        book.sort_by(|a, b| match direction {
+            TradeDirection::Long => to_f32(a.price).total_cmp(&to_f32(b.price)),
+            TradeDirection::Short => to_f32(b.price).total_cmp(&to_f32(a.price)),
+        });
+        book
+    }
+
+    /// The total units available to trade in `direction` within `pips` of the
+    /// best price
+    pub fn liquidity_within(&self, direction: TradeDirection, pips: f32, pip_location: i32) -> u64 {
+        let book = self.book(direction);
+        let Some(best) = book.first().map(|bucket| to_f32(bucket.price)) else {
+            return 0;
+        };
+        // Prices are f32s so leave a little room for rounding
+        let limit = pips + 0.001;
+        book.into_iter()
+            .filter(|bucket| distance_in_pips(to_f32(bucket.price), best, pip_location) <= limit)
+            .map(|bucket| bucket.liquidity)
+            .sum()
+    }
+
+    /// The worst price we'd have to accept to fill `units` in `direction`,
+    /// walking down the book from the best price. Useful as an order's price
+    /// bound. None if there isn't enough liquidity to fill `units`
+    pub fn price_for_units(&self, direction: TradeDirection, units: u64) -> Option<f32> {
+        let mut filled = 0;
+        self.book(direction).into_iter().find_map(|bucket| {
+            filled += bucket.liquidity;
+            (filled >= units).then_some(to_f32(bucket.price))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const PRICE: &str = r#"{
+        "type": "PRICE",
+        "instrument": "EUR_USD",
+        "time": "2023-03-06T08:00:00.000000000Z",
+        "status": "tradeable",
+        "tradeable": true,
+        "bids": [
+            { "price": "1.06890", "liquidity": 1000000 },
+            { "price": "1.06900", "liquidity": 500000 }
+        ],
+        "asks": [
+            { "price": "1.06920", "liquidity": 500000 },
+            { "price": "1.06930", "liquidity": 1000000 }
+        ],
+        "closeoutBid": "1.06880",
+        "closeoutAsk": "1.06940"
+    }"#;
+
+    #[test]
+    fn deserialize() {
+        let price: ClientPrice = serde_json::from_str(PRICE).unwrap();
+        assert_eq!("EUR_USD", price.instrument);
+        assert!(price.tradeable);
+        assert_eq!(2, price.bids.len());
+        assert_eq!(1.0692, to_f32(price.asks[0].price));
+        assert_eq!(500000, price.asks[0].liquidity);
+        assert_eq!(1.0688, to_f32(price.closeout_bid));
+    }
+
+    #[test]
+    fn stream_messages() {
+        let price: PricingStreamMessage = serde_json::from_str(PRICE).unwrap();
+        assert!(matches!(price, PricingStreamMessage::Price(_)));
+        let heartbeat = r#"{"type":"HEARTBEAT","time":"2023-03-06T08:00:05.000000000Z"}"#;
+        let heartbeat: PricingStreamMessage = serde_json::from_str(heartbeat).unwrap();
+        assert!(matches!(heartbeat, PricingStreamMessage::Heartbeat { .. }));
+    }
+
+    #[test]
+    fn derived_prices() {
+        let price: ClientPrice = serde_json::from_str(PRICE).unwrap();
+        assert_eq!(Some(1.069), price.best_bid());
+        assert_eq!(Some(1.0692), price.best_ask());
+        assert!((price.mid().unwrap() - 1.0691).abs() < 0.00001);
+        assert!((price.spread().unwrap() - 0.0002).abs() < 0.00001);
+        assert!((price.spread_in_pips(-4).unwrap() - 2.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn no_liquidity() {
+        let mut price: ClientPrice = serde_json::from_str(PRICE).unwrap();
+        price.asks.clear();
+        assert_eq!(Some(1.069), price.best_bid());
+        assert_eq!(None, price.best_ask());
+        assert_eq!(None, price.mid());
+        assert_eq!(None, price.spread_in_pips(-4));
+    }
+
+    #[test]
+    fn liquidity_within() {
+        let price: ClientPrice = serde_json::from_str(PRICE).unwrap();
+        assert_eq!(
+            500000,
+            price.liquidity_within(TradeDirection::Long, 0.5, -4)
+        );
+        assert_eq!(
+            1500000,
+            price.liquidity_within(TradeDirection::Long, 1.0, -4)
+        );
+        assert_eq!(
+            1500000,
+            price.liquidity_within(TradeDirection::Short, 1.5, -4)
+        );
+    }
+
+    #[test]
+    fn price_for_units() {
+        let price: ClientPrice = serde_json::from_str(PRICE).unwrap();
+        assert_eq!(
+            Some(1.0692),
+            price.price_for_units(TradeDirection::Long, 500000)
+        );
+        assert_eq!(
+            Some(1.0693),
+            price.price_for_units(TradeDirection::Long, 500001)
+        );
+        assert_eq!(
+            Some(1.069),
+            price.price_for_units(TradeDirection::Short, 10000)
+        );
+        assert_eq!(None, price.price_for_units(TradeDirection::Short, 2000000));
+    }
+}
@@ -1,9 +1,12 @@
-use super::order::OrderType;
+use super::{order::OrderType, price::Price, transaction::Transaction};
 use chrono::DateTime;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
 use std::collections::HashMap;
-use typed_builder::TypedBuilder;
+
+use crate::Error;
+use error_stack::{report, Result, ResultExt};
 
 /// TradeState represents the state of a trade.
 ///
@@ -21,17 +24,117 @@ pub enum TradeState {
     CloseWhenTradeable,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, TypedBuilder)]
+/// Our own id, tag and comment attached to an Order or Trade. Each part is
+/// optional; OANDA only sends back the ones that were set.
+///
+/// Use [`ClientExtensions::builder`] to make one; it checks the values are
+/// ones OANDA will accept.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(default)]
 pub struct ClientExtensions {
     /// The Client ID of the Order/Trade.
-    #[builder(setter(into))]
-    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
     /// A tag associated with the Order/Trade.
-    #[builder(setter(into))]
-    pub tag: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
     /// A comment associated with the Order/Trade.
-    #[builder(setter(into))]
-    pub comment: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+/// OANDA rejects client ids, tags and comments longer than this
+pub const MAX_CLIENT_EXTENSION_LENGTH: usize = 128;
+
+impl ClientExtensions {
+    pub fn builder() -> ClientExtensionsBuilder {
+        ClientExtensionsBuilder::default()
+    }
+}
+
+/// Builds [`ClientExtensions`], validating them in [`Self::build`]
+#[derive(Debug, Default)]
+pub struct ClientExtensionsBuilder {
+    extensions: ClientExtensions,
+}
+
+impl ClientExtensionsBuilder {
+    /// The Client ID of the Order/Trade. It can't start with `@`, because
+    /// OANDA uses that to tell client ids from its own ids
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.extensions.id = Some(id.into());
+        self
+    }
+
+    /// A tag associated with the Order/Trade.
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.extensions.tag = Some(tag.into());
+        self
+    }
+
+    /// A comment associated with the Order/Trade.
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.extensions.comment = Some(comment.into());
+        self
+    }
+
+    /// Checks the extensions and returns them
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ClientExtensions`] if any part is empty, longer than
+    /// [`MAX_CLIENT_EXTENSION_LENGTH`] characters or has control characters
+    /// in it, or if the id starts with `@`
+    pub fn build(self) -> Result<ClientExtensions, Error> {
+        let ClientExtensions { id, tag, comment } = &self.extensions;
+        for (field, value) in [("id", id), ("tag", tag), ("comment", comment)] {
+            let Some(value) = value else { continue };
+            let reason = if value.is_empty() {
+                "is empty"
+            } else if value.chars().count() > MAX_CLIENT_EXTENSION_LENGTH {
+                "is too long"
+            } else if value.chars().any(char::is_control) {
+                "has control characters in it"
+            } else {
+                continue;
+            };
+            return Err(report!(Error::ClientExtensions { field, reason }))
+                .attach_printable(format!("{field}: {value:?}"));
+        }
+        if id.as_ref().is_some_and(|id| id.starts_with('@')) {
+            return Err(report!(Error::ClientExtensions {
+                field: "id",
+                reason: "starts with @",
+            }))
+            .attach_printable(format!("id: {id:?}"));
+        }
+        Ok(self.extensions)
+    }
+}
+
+/// Which trade an endpoint acts on. Either OANDA's trade id, or the client
+/// id we gave the trade in its [`ClientExtensions`], which OANDA wants
+/// prefixed with `@`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum TradeSpecifier {
+    Id(String),
+    ClientId(String),
+}
+
+impl TradeSpecifier {
+    /// The trade whose [`ClientExtensions::id`] is `client_id`
+    pub fn client_id(client_id: impl Into<String>) -> Self {
+        TradeSpecifier::ClientId(client_id.into())
+    }
+}
+
+impl std::fmt::Display for TradeSpecifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TradeSpecifier::Id(id) => f.write_str(id),
+            TradeSpecifier::ClientId(client_id) => write!(f, "@{client_id}"),
+        }
+    }
 }
 
 /// Specification of which price component should be used when determining
@@ -87,6 +190,7 @@ pub enum OrderState {
 /// A TakeProfitOrder is an order that is linked to an open Trade and created with a price threshold.
 /// The Order will be filled (closing the Trade) by the first price that is equal to or better than the threshold.
 /// A TakeProfitOrder cannot be used to open a new Position.
+#[serde_as]
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TakeProfitOrder {
@@ -109,7 +213,8 @@ pub struct TakeProfitOrder {
     /// The price threshold specified for the TakeProfit Order. The associated
     /// Trade will be closed by a market price that is equal to or better than
     /// this threshold.
-    pub price: String,
+    #[serde_as(as = "DisplayFromStr")]
+    pub price: Price,
     /// The time-in-force requested for the TakeProfit Order. Restricted to
     /// “GTC”, “GFD” and “GTD” for TakeProfit Orders.
     pub time_in_force: TimeInForce,
@@ -164,6 +269,7 @@ pub struct Trades {
 }
 
 /// Represents a Trade with all its associated data.
+#[serde_as]
 #[derive(Debug, Deserialize)]
 pub struct Trade {
     /// The Trade's identifier, unique within the Trade's Account.
@@ -171,7 +277,8 @@ pub struct Trade {
     /// The Trade's Instrument.
     pub instrument: String,
     /// The execution price of the Trade.
-    pub price: String,
+    #[serde_as(as = "DisplayFromStr")]
+    pub price: Price,
     /// The date/time when the Trade was opened.
     pub open_time: DateTime<Utc>,
     /// The current state of the Trade.
@@ -189,8 +296,9 @@ pub struct Trade {
     /// Margin currently used by the Trade.
     pub margin_used: f32,
     /// The average closing price of the Trade. Only present if the Trade has been closed or reduced at least once.
+    #[serde_as(as = "Option<DisplayFromStr>")]
     #[serde(default)]
-    pub average_close_price: Option<String>,
+    pub average_close_price: Option<Price>,
     /// The IDs of the Transactions that have closed portions of this Trade.
     pub closing_transaction_ids: Vec<String>,
     /// The financing paid/collected for this Trade.
@@ -260,3 +368,145 @@ pub struct TradesResponse {
     #[serde(rename = "lastTransactionID")]
     pub last_transaction_id: String,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct TradeResponse {
+    pub trade: Trade,
+    #[serde(rename = "lastTransactionID")]
+    pub last_transaction_id: String,
+}
+
+/// Closes all of a trade at market price
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct CloseTradeRequest {
+    pub units: &'static str,
+}
+
+impl CloseTradeRequest {
+    /// Closes the whole trade
+    pub fn all() -> Self {
+        Self { units: "ALL" }
+    }
+}
+
+/// What OANDA did to close a trade
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CloseTradeResponse {
+    /// The MarketOrder Transaction created to close the Trade.
+    pub order_create_transaction: Option<Transaction>,
+
+    /// The OrderFill Transaction that fills the Trade-closing MarketOrder
+    /// and closes the Trade.
+    pub order_fill_transaction: Option<Transaction>,
+
+    /// The OrderCancel Transaction that immediately cancelled the
+    /// Trade-closing MarketOrder.
+    pub order_cancel_transaction: Option<Transaction>,
+
+    /// The IDs of all Transactions that were created while satisfying the request.
+    #[serde(default, rename = "relatedTransactionIDs")]
+    pub related_transaction_ids: Vec<String>,
+
+    /// The ID of the most recent Transaction created for the Account
+    #[serde(rename = "lastTransactionID")]
+    pub last_transaction_id: String,
+}
+
+impl CloseTradeResponse {
+    /// True if OANDA cancelled the closing market order instead of filling it
+    pub fn was_cancelled(&self) -> bool {
+        self.order_cancel_transaction.is_some()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn close_trade_response() {
+        let input = r#"{
+            "orderCreateTransaction": {
+                "type": "MARKET_ORDER",
+                "id": "6357",
+                "time": "2023-05-05T13:30:00.000000000Z",
+                "userID": 1234,
+                "accountID": "101-001-1234-001",
+                "batchID": "6357",
+                "requestID": "60422"
+            },
+            "orderCancelTransaction": {
+                "type": "ORDER_CANCEL",
+                "id": "6358",
+                "time": "2023-05-05T13:30:00.000000000Z",
+                "userID": 1234,
+                "accountID": "101-001-1234-001",
+                "batchID": "6357",
+                "requestID": "60422"
+            },
+            "relatedTransactionIDs": ["6357", "6358"],
+            "lastTransactionID": "6358"
+        }"#;
+        let got: CloseTradeResponse = serde_json::from_str(input).unwrap();
+        assert!(got.was_cancelled());
+        assert_eq!(None, got.order_fill_transaction);
+        assert_eq!("6358", got.last_transaction_id);
+        assert_eq!(
+            r#"{"units":"ALL"}"#,
+            serde_json::to_string(&CloseTradeRequest::all()).unwrap()
+        );
+    }
+
+    #[test]
+    fn client_extensions_builder() {
+        let got = ClientExtensions::builder()
+            .id("robot-1")
+            .tag("breakout")
+            .build()
+            .unwrap();
+        let expected = ClientExtensions {
+            id: Some("robot-1".to_string()),
+            tag: Some("breakout".to_string()),
+            comment: None,
+        };
+        assert_eq!(expected, got);
+        // Unset parts are left out
+        assert_eq!(
+            r#"{"id":"robot-1","tag":"breakout"}"#,
+            serde_json::to_string(&got).unwrap()
+        );
+    }
+
+    #[test]
+    fn client_extensions_invalid() {
+        let long = "x".repeat(MAX_CLIENT_EXTENSION_LENGTH + 1);
+        let invalid = [
+            ClientExtensions::builder().id(""),
+            ClientExtensions::builder().tag(long),
+            ClientExtensions::builder().comment("two\nlines"),
+            ClientExtensions::builder().id("@robot-1"),
+        ];
+        for builder in invalid {
+            let err = builder.build().unwrap_err();
+            assert!(matches!(
+                err.current_context(),
+                Error::ClientExtensions { .. }
+            ));
+        }
+    }
+
+    #[test]
+    fn trade_specifier() {
+        assert_eq!("6349", TradeSpecifier::Id("6349".to_string()).to_string());
+        assert_eq!("@robot-1", TradeSpecifier::client_id("robot-1").to_string());
+    }
+
+    #[test]
+    fn client_extensions_partial() {
+        let got: ClientExtensions = serde_json::from_str(r#"{"id": "robot-1"}"#).unwrap();
+        assert_eq!(Some("robot-1".to_string()), got.id);
+        assert_eq!(None, got.tag);
+    }
+}
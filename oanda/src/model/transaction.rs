@@ -1,7 +1,16 @@
+mod financing;
+mod history;
+mod order_fill;
 mod stop_loss;
-use super::trade::TimeInForce;
+use super::{price::Price, trade::TimeInForce};
 use crate::model::trade::ClientExtensions;
 use chrono::{DateTime, Utc};
+pub use financing::{
+    AccountFinancingMode, DailyFinancing, DividendAdjustment, OpenTradeDividendAdjustment,
+    OpenTradeFinancing, PositionFinancing,
+};
+pub use history::{Transaction, TransactionKind, TransactionPagesResponse, TransactionsResponse};
+pub use order_fill::{OrderFill, TradeOpen};
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
 pub use stop_loss::{SLTrigger, StopLoss, TrailingStopLoss};
@@ -12,7 +21,7 @@ pub struct TakeProfitDetails {
     /// The price that the Take Profit Order will be triggered at. Only one of
     /// the price and distance fields may be specified.
     #[serde_as(as = "DisplayFromStr")]
-    pub price: f32,
+    pub price: Price,
 
     /// The time in force for the created Take Profit Order. This may only be
     /// GTC, GTD or GFD.
@@ -23,5 +32,6 @@ pub struct TakeProfitDetails {
     pub gtd_time: Option<DateTime<Utc>>,
 
     /// The Client Extensions to add to the Take Profit Order when created.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub client_extensions: Option<ClientExtensions>,
 }
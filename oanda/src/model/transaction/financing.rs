@@ -0,0 +1,132 @@
+//! Transactions that change the balance without a trade being opened or
+//! closed: overnight financing and dividend adjustments.
+//! See <https://developer.oanda.com/rest-live-v20/transaction-df/>
+use serde::Deserialize;
+use serde_with::{serde_as, DisplayFromStr};
+
+/// Financing charged or paid for the open positions in the account
+#[serde_as]
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyFinancing {
+    /// The amount of financing paid/collected for the Account, in the
+    /// account's home currency.
+    #[serde_as(as = "DisplayFromStr")]
+    pub financing: f32,
+
+    /// The Account’s balance after daily financing.
+    #[serde_as(as = "DisplayFromStr")]
+    pub account_balance: f32,
+
+    /// The account financing mode at the time of the daily financing.
+    pub account_financing_mode: Option<AccountFinancingMode>,
+
+    /// The financing paid/collected for each Position in the Account.
+    #[serde(default)]
+    pub position_financings: Vec<PositionFinancing>,
+}
+
+/// The financing paid/collected for one instrument's Position
+#[serde_as]
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionFinancing {
+    /// The instrument of the Position that financing is being paid/collected for.
+    pub instrument: String,
+
+    /// The amount of financing paid/collected for the Position, in the
+    /// account's home currency.
+    #[serde_as(as = "DisplayFromStr")]
+    pub financing: f32,
+
+    /// The financing paid/collected for each open Trade within the Position.
+    #[serde(default)]
+    pub open_trade_financings: Vec<OpenTradeFinancing>,
+}
+
+/// The financing paid/collected for one open Trade
+#[serde_as]
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenTradeFinancing {
+    /// The ID of the Trade that financing is being paid/collected for.
+    #[serde(rename = "tradeID")]
+    pub trade_id: String,
+
+    /// The amount of financing paid/collected for the Trade, in the
+    /// account's home currency.
+    #[serde_as(as = "DisplayFromStr")]
+    pub financing: f32,
+
+    /// The financing rate in effect for the instrument used to calculate
+    /// the amount of financing paid/collected for the Trade.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub financing_rate: Option<f32>,
+}
+
+/// How financing is calculated for an account
+#[derive(Debug, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AccountFinancingMode {
+    /// No financing is paid/charged for open Trades in the Account
+    NoFinancing,
+    /// Second-by-second financing is paid/charged for open Trades in the
+    /// Account, both daily and when the the Trade is closed
+    SecondBySecond,
+    /// A full day’s worth of financing is paid/charged for open Trades in the
+    /// Account daily at 5pm New York time
+    Daily,
+    /// A full day’s worth of financing is paid/charged for open Trades in the
+    /// Account daily at the instrument's configured time
+    DailyInstrument,
+}
+
+/// A dividend paid or charged on an open CFD position
+#[serde_as]
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DividendAdjustment {
+    /// The name of the instrument for the dividendAdjustment transaction
+    pub instrument: String,
+
+    /// The total dividend adjustment amount paid or collected, in the
+    /// account's home currency.
+    #[serde_as(as = "DisplayFromStr")]
+    pub dividend_adjustment: f32,
+
+    /// The total dividend adjustment amount paid or collected, in the
+    /// instrument's quote currency.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub quote_dividend_adjustment: Option<f32>,
+
+    /// The Account balance after applying the DividendAdjustment Transaction
+    #[serde_as(as = "DisplayFromStr")]
+    pub account_balance: f32,
+
+    /// The dividend adjustment paid or collected for each open Trade
+    #[serde(default)]
+    pub open_trade_dividend_adjustments: Vec<OpenTradeDividendAdjustment>,
+}
+
+/// The dividend adjustment paid or collected for one open Trade
+#[serde_as]
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenTradeDividendAdjustment {
+    /// The ID of the Trade for which the dividend adjustment is to be paid or collected.
+    #[serde(rename = "tradeID")]
+    pub trade_id: String,
+
+    /// The dividend adjustment amount to pay or collect for the Trade, in
+    /// the account's home currency.
+    #[serde_as(as = "DisplayFromStr")]
+    pub dividend_adjustment: f32,
+
+    /// The dividend adjustment amount to pay or collect for the Trade, in
+    /// the instrument's quote currency.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub quote_dividend_adjustment: Option<f32>,
+}
@@ -0,0 +1,241 @@
+//! The account's transaction history.
+//! See <https://developer.oanda.com/rest-live-v20/transaction-ep/>
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use super::{
+    financing::{DailyFinancing, DividendAdjustment},
+    order_fill::OrderFill,
+};
+
+/// The first response when listing transactions; the transactions
+/// themselves are behind the `pages` URLs
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionPagesResponse {
+    /// The starting time provided in the request.
+    pub from: DateTime<Utc>,
+
+    /// The ending time provided in the request.
+    pub to: DateTime<Utc>,
+
+    /// The pageSize provided in the request
+    pub page_size: usize,
+
+    /// The number of Transactions that are contained in the pages returned
+    pub count: usize,
+
+    /// The list of URLs that represent idrange queries providing the data for
+    /// each page in the query results
+    pub pages: Vec<String>,
+
+    /// The ID of the most recent Transaction created for the Account
+    #[serde(rename = "lastTransactionID")]
+    pub last_transaction_id: String,
+}
+
+/// One page of transactions
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionsResponse {
+    pub transactions: Vec<Transaction>,
+
+    /// The ID of the most recent Transaction created for the Account
+    #[serde(rename = "lastTransactionID")]
+    pub last_transaction_id: String,
+}
+
+/// The fields every transaction has, plus the type specific details
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Transaction {
+    /// The Transaction’s Identifier.
+    pub id: String,
+
+    /// The date/time when the Transaction was created.
+    pub time: DateTime<Utc>,
+
+    /// The ID of the user that initiated the creation of the Transaction.
+    #[serde(rename = "userID")]
+    pub user_id: u64,
+
+    /// The ID of the Account the Transaction was created for.
+    #[serde(rename = "accountID")]
+    pub account_id: String,
+
+    /// The ID of the “batch” that the Transaction belongs to. Transactions in
+    /// the same batch are applied to the Account simultaneously.
+    #[serde(rename = "batchID")]
+    pub batch_id: String,
+
+    /// The Request ID of the request which generated the transaction.
+    #[serde(rename = "requestID")]
+    pub request_id: Option<String>,
+
+    #[serde(flatten)]
+    pub kind: TransactionKind,
+}
+
+/// What happened, keyed on the transaction's `type` field
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TransactionKind {
+    DailyFinancing(DailyFinancing),
+    DividendAdjustment(DividendAdjustment),
+    OrderFill(OrderFill),
+    /// A transaction type we don't model yet
+    #[serde(other)]
+    Other,
+}
+
+impl TransactionKind {
+    /// Money that came in or went out of the account without a trade being
+    /// closed, in the account's home currency. None for other transactions
+    pub fn non_trade_pl(&self) -> Option<f32> {
+        match self {
+            TransactionKind::DailyFinancing(financing) => Some(financing.financing),
+            TransactionKind::DividendAdjustment(dividend) => Some(dividend.dividend_adjustment),
+            TransactionKind::OrderFill(_) | TransactionKind::Other => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::financing::{AccountFinancingMode, OpenTradeFinancing};
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn order_fill() {
+        let input = r#"{
+            "type": "ORDER_FILL",
+            "orderID": "6356",
+            "instrument": "EUR_USD",
+            "units": "1000",
+            "tradeOpened": {
+                "tradeID": "6357",
+                "units": "1000",
+                "clientExtensions": { "id": "baseline-EUR_USD-20230505T133000", "tag": "baseline" }
+            },
+            "id": "6357",
+            "userID": 1234567,
+            "accountID": "101-011-1234567-001",
+            "batchID": "6356",
+            "time": "2023-05-05T13:30:01.000000000Z"
+        }"#;
+        let got: Transaction = serde_json::from_str(input).unwrap();
+        assert_eq!(None, got.kind.non_trade_pl());
+        let TransactionKind::OrderFill(fill) = got.kind else {
+            panic!("Expected an order fill: {:?}", got.kind);
+        };
+        assert_eq!(("6356", 1000.0), (fill.order_id.as_str(), fill.units));
+        let extensions = fill.client_extensions().unwrap();
+        assert_eq!(Some("baseline"), extensions.tag.as_deref());
+    }
+
+    #[test]
+    fn deserialize_page() {
+        let input = r#"{
+            "transactions": [
+                {
+                    "type": "CLIENT_CONFIGURE",
+                    "marginRate": "0.02",
+                    "id": "2",
+                    "userID": 1234567,
+                    "accountID": "101-011-1234567-001",
+                    "batchID": "2",
+                    "requestID": "1789",
+                    "time": "2023-03-06T08:00:00.000000000Z"
+                }
+            ],
+            "lastTransactionID": "6356"
+        }"#;
+        let got: TransactionsResponse = serde_json::from_str(input).unwrap();
+        assert_eq!("6356", got.last_transaction_id);
+        let transaction = &got.transactions[0];
+        assert_eq!("2", transaction.id);
+        assert_eq!(Some("1789".to_string()), transaction.request_id);
+        assert_eq!(TransactionKind::Other, transaction.kind);
+        assert_eq!(None, transaction.kind.non_trade_pl());
+    }
+
+    #[test]
+    fn daily_financing() {
+        let input = r#"{
+            "type": "DAILY_FINANCING",
+            "financing": "-0.5071",
+            "accountBalance": "99871.2463",
+            "accountFinancingMode": "DAILY",
+            "positionFinancings": [
+                {
+                    "instrument": "EUR_USD",
+                    "financing": "-0.5071",
+                    "openTradeFinancings": [
+                        {
+                            "tradeID": "6349",
+                            "financing": "-0.5071",
+                            "financingRate": "-0.0184"
+                        }
+                    ]
+                }
+            ],
+            "id": "6357",
+            "userID": 1234567,
+            "accountID": "101-011-1234567-001",
+            "batchID": "6357",
+            "time": "2023-03-06T21:00:00.000000000Z"
+        }"#;
+        let got: Transaction = serde_json::from_str(input).unwrap();
+        assert_eq!(None, got.request_id);
+        assert_eq!(Some(-0.5071), got.kind.non_trade_pl());
+        let TransactionKind::DailyFinancing(financing) = got.kind else {
+            panic!("Expected daily financing: {:?}", got.kind);
+        };
+        assert_eq!(
+            Some(AccountFinancingMode::Daily),
+            financing.account_financing_mode
+        );
+        let position = &financing.position_financings[0];
+        assert_eq!("EUR_USD", position.instrument);
+        assert_eq!(
+            OpenTradeFinancing {
+                trade_id: "6349".to_string(),
+                financing: -0.5071,
+                financing_rate: Some(-0.0184),
+            },
+            position.open_trade_financings[0]
+        );
+    }
+
+    #[test]
+    fn dividend_adjustment() {
+        let input = r#"{
+            "type": "DIVIDEND_ADJUSTMENT",
+            "instrument": "US30_USD",
+            "dividendAdjustment": "1.2500",
+            "quoteDividendAdjustment": "1.2500",
+            "accountBalance": "99872.5",
+            "openTradeDividendAdjustments": [
+                {
+                    "tradeID": "6350",
+                    "dividendAdjustment": "1.2500",
+                    "quoteDividendAdjustment": "1.2500"
+                }
+            ],
+            "id": "6358",
+            "userID": 1234567,
+            "accountID": "101-011-1234567-001",
+            "batchID": "6358",
+            "time": "2023-03-07T21:00:00.000000000Z"
+        }"#;
+        let got: Transaction = serde_json::from_str(input).unwrap();
+        assert_eq!(Some(1.25), got.kind.non_trade_pl());
+        let TransactionKind::DividendAdjustment(dividend) = got.kind else {
+            panic!("Expected a dividend adjustment: {:?}", got.kind);
+        };
+        assert_eq!("US30_USD", dividend.instrument);
+        assert_eq!(99872.5, dividend.account_balance);
+        assert_eq!("6350", dividend.open_trade_dividend_adjustments[0].trade_id);
+    }
+}
@@ -0,0 +1,53 @@
+//! An order being filled, and the trade it opened if any. The trade carries
+//! the client extensions the order asked for, which is how a fill is matched
+//! back to whatever placed the order.
+//! See <https://developer.oanda.com/rest-live-v20/transaction-df/#OrderFillTransaction>
+use serde::Deserialize;
+use serde_with::{serde_as, DisplayFromStr};
+
+use crate::model::trade::ClientExtensions;
+
+#[serde_as]
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderFill {
+    /// The ID of the Order filled.
+    #[serde(rename = "orderID")]
+    pub order_id: String,
+
+    /// The name of the filled Order’s instrument.
+    pub instrument: String,
+
+    /// The number of units filled by the OrderFill.
+    #[serde_as(as = "DisplayFromStr")]
+    pub units: f32,
+
+    /// The Trade that was opened when the Order was filled (only provided if
+    /// filling the Order resulted in a new Trade).
+    pub trade_opened: Option<TradeOpen>,
+}
+
+impl OrderFill {
+    /// The client extensions of the trade the fill opened, if it opened one
+    /// and they were set
+    pub fn client_extensions(&self) -> Option<&ClientExtensions> {
+        self.trade_opened.as_ref()?.client_extensions.as_ref()
+    }
+}
+
+/// A Trade opened by an order fill
+#[serde_as]
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TradeOpen {
+    /// The ID of the Trade that was opened
+    #[serde(rename = "tradeID")]
+    pub trade_id: String,
+
+    /// The number of units opened by the Trade
+    #[serde_as(as = "DisplayFromStr")]
+    pub units: f32,
+
+    /// The client extensions for the newly opened Trade
+    pub client_extensions: Option<ClientExtensions>,
+}
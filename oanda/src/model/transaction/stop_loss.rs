@@ -7,7 +7,7 @@ pub use self::rust::{SLTrigger, StopLoss, TimeInForce, TrailingStopLoss};
 
 // Builder / rust side
 mod rust {
-    use crate::model::trade::ClientExtensions;
+    use crate::model::{price::Price, trade::ClientExtensions};
     use chrono::{DateTime, Utc};
     use serde::{Deserialize, Serialize};
     use serde_with::serde_as;
@@ -29,7 +29,7 @@ mod rust {
     pub enum SLTrigger {
         /// The price that the Stop Loss Order will be triggered at. Only one of
         /// the price and distance fields may be specified.
-        Price(f32),
+        Price(Price),
         /// Specifies the distance (in price units) from the Trade’s open price to
         /// use as the Stop Loss Order price. Only one of the distance and price
         /// fields may be specified.
@@ -188,7 +188,7 @@ impl TryFrom<oanda::StopLoss> for rust::TrailingStopLoss {
 
 // Oanda / json side
 mod oanda {
-    use crate::model::trade::ClientExtensions;
+    use crate::model::{price::Price, trade::ClientExtensions};
     use chrono::{DateTime, Utc};
     use serde::{Deserialize, Serialize};
     use serde_with::{serde_as, DisplayFromStr};
@@ -217,7 +217,7 @@ mod oanda {
         /// The price that the Stop Loss Order will be triggered at. Only one of
         /// the price and distance fields may be specified.
         #[serde_as(as = "Option<DisplayFromStr>")]
-        pub price: Option<f32>,
+        pub price: Option<Price>,
 
         /// Specifies the distance (in price units) from the Trade’s open price to
         /// use as the Stop Loss Order price. Only one of the distance and price
@@ -234,13 +234,14 @@ mod oanda {
         pub time_in_force: TimeInForce,
 
         /// The Client Extensions to add to the Stop Loss Order when created.
+        #[serde(skip_serializing_if = "Option::is_none")]
         pub client_extensions: Option<ClientExtensions>,
     }
 
     impl StopLoss {
         /// The price that the Stop Loss Order will be triggered at. Only one of
         /// the price and distance fields may be specified.
-        pub fn get_price(&self) -> Option<f32> {
+        pub fn get_price(&self) -> Option<Price> {
             self.price
         }
 
@@ -280,7 +281,9 @@ mod oanda {
 
 #[cfg(test)]
 mod test {
-    use crate::model::{trade::ClientExtensions, transaction::stop_loss::rust::SLTrigger};
+    use crate::model::{
+        price::from_f32, trade::ClientExtensions, transaction::stop_loss::rust::SLTrigger,
+    };
     use chrono::TimeZone;
 
     use super::{oanda, rust};
@@ -290,12 +293,12 @@ mod test {
     #[test]
     fn stop_loss_builder() {
         let got = rust::StopLoss::builder()
-            .trigger(SLTrigger::Price(1.4))
+            .trigger(SLTrigger::Price(from_f32(1.4)))
             .time_in_force(rust::TimeInForce::Gtc)
             .build();
         let got: oanda::StopLoss = got.into();
         let expected = oanda::StopLoss {
-            price: Some(1.4),
+            price: Some(from_f32(1.4)),
             time_in_force: oanda::TimeInForce::Gtc,
             ..Default::default()
         };
@@ -309,7 +312,8 @@ mod test {
             .id("trader")
             .tag("Joe")
             .comment("open")
-            .build();
+            .build()
+            .unwrap();
 
         let got = rust::StopLoss::builder()
             .trigger(SLTrigger::Distance(99.9))
@@ -332,7 +336,7 @@ mod test {
         let input = r#"{ "timeInForce": "GTC", "price": "1.7000" }"#;
         let got: rust::StopLoss = serde_json::from_str(&input).unwrap();
         let expected = rust::StopLoss {
-            trigger: SLTrigger::Price(1.7),
+            trigger: SLTrigger::Price("1.7".parse().unwrap()),
             time_in_force: rust::TimeInForce::Gtc,
             client_extensions: None,
         };
@@ -0,0 +1,99 @@
+//! The number of units in an order. OANDA tells buys from sells by the sign
+//! of the units, so a stray minus flips the trade; build them with
+//! [`Units::long`] or [`Units::short`] instead of negating numbers by hand.
+use std::{fmt, str::FromStr};
+
+use crate::{model::instrument::Instrument, Error};
+
+/// A signed number of units: positive is long, negative is short. An f64,
+/// as an f32 can't hold every whole number of units past 2^24
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Units(f64);
+
+impl Units {
+    /// Buy `units`. The sign of `units` is ignored
+    pub fn long(units: f64) -> Self {
+        Self(units.abs())
+    }
+
+    /// Sell `units`. The sign of `units` is ignored
+    pub fn short(units: f64) -> Self {
+        Self(-units.abs())
+    }
+
+    /// The signed value OANDA expects
+    pub fn value(&self) -> f64 {
+        self.0
+    }
+
+    /// How many units, whichever way they go
+    pub fn size(&self) -> f64 {
+        self.0.abs()
+    }
+
+    pub fn is_long(&self) -> bool {
+        self.0 > 0.0
+    }
+
+    pub fn is_short(&self) -> bool {
+        self.0 < 0.0
+    }
+
+    /// Rounded to `precision` decimal places, eg. an instrument's
+    /// `trade_units_precision`
+    pub fn rounded(self, precision: i32) -> Self {
+        let scale = 10f64.powi(precision);
+        Self((self.0 * scale).round() / scale)
+    }
+
+    /// Rounded to as many decimal places as `instrument` can trade
+    pub fn for_instrument(self, instrument: &Instrument) -> Self {
+        self.rounded(instrument.trade_units_precision)
+    }
+}
+
+impl fmt::Display for Units {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // f64's Display never uses an exponent, which OANDA won't take
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Units {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse()
+            .ok()
+            .filter(|units: &f64| units.is_finite())
+            .map(Units)
+            .ok_or_else(|| Error::InvalidUnits(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn signs() {
+        assert_eq!(Units::long(100.0), Units::long(-100.0));
+        assert_eq!(-100.0, Units::short(100.0).value());
+        assert_eq!(-100.0, Units::short(-100.0).value());
+        assert!(Units::long(1.0).is_long() && Units::short(1.0).is_short());
+        assert_eq!(100.0, Units::short(100.0).size());
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!("1000", Units::long(1000.0).to_string());
+        assert_eq!("-2500", Units::short(2500.0).to_string());
+        assert_eq!("10.5", Units::long(10.54).rounded(1).to_string());
+        assert_eq!("-3", Units::short(2.6).rounded(0).to_string());
+        assert_eq!(Units::short(12.5), "-12.5".parse().unwrap());
+        assert!("lots".parse::<Units>().is_err());
+        // Past 2^24, where an f32 would round to an even number
+        assert_eq!("16777217", Units::long(16_777_217.0).rounded(0).to_string());
+    }
+}
@@ -0,0 +1,106 @@
+//! Fills in candles missing from what OANDA sent us, so the ATR and levels
+//! aren't worked out over holes in the history. Gaps are found with
+//! [`candle_gaps`], which doesn't count the weekend close, and asked for
+//! again with ranged requests.
+//!
+//! OANDA leaves out candles nobody traded in, so some gaps can't be filled.
+//! If they're still there after a few tries we raise an alert and carry on.
+use error_stack::{Result, ResultExt};
+use oanda::{
+    client::instrument::Instrument,
+    model::{
+        candle::{candle_gaps, CandlestickGranularity as Granularity},
+        Candle,
+    },
+};
+use tracing::{info, warn};
+
+use crate::error::Error;
+
+/// How many times to ask for the missing candles before giving up on them
+const ATTEMPTS: usize = 3;
+
+/// `candles` with any gaps filled in, in time order
+pub async fn backfill(
+    instrument: &Instrument<'_>,
+    granularity: Granularity,
+    mut candles: Vec<Candle>,
+) -> Result<Vec<Candle>, Error> {
+    for attempt in 1..=ATTEMPTS {
+        let gaps = candle_gaps(&candles, granularity);
+        if gaps.is_empty() {
+            return Ok(candles);
+        }
+        let missing: usize = gaps.iter().map(|gap| gap.missing).sum();
+        info!(
+            attempt,
+            gaps = gaps.len(),
+            missing,
+            "Backfilling missing candles"
+        );
+        for gap in gaps {
+            let more = instrument
+                .candles()
+                .granularity(granularity)
+                .from(gap.after)
+                .to(gap.before)
+                .include_first(false)
+                .build()
+                .send()
+                .await
+                .change_context(Error::new("Couldn't backfill the missing candles"))
+                .attach_printable_lazy(|| format!("Gap: {gap:?}"))?
+                .candles;
+            candles = merge(candles, more);
+        }
+    }
+    let gaps = candle_gaps(&candles, granularity);
+    if !gaps.is_empty() {
+        warn!(
+            alert = "incomplete_candles",
+            attempts = ATTEMPTS,
+            ?gaps,
+            "OANDA keeps sending candles with gaps in them"
+        );
+    }
+    Ok(candles)
+}
+
+/// `candles` and `more` together in time order, without duplicates
+fn merge(mut candles: Vec<Candle>, more: Vec<Candle>) -> Vec<Candle> {
+    candles.extend(more);
+    candles.sort_by_key(|candle| candle.time);
+    candles.dedup_by_key(|candle| candle.time);
+    candles
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+
+    fn candle(time: DateTime<Utc>) -> Candle {
+        Candle {
+            time,
+            bid: None,
+            ask: None,
+            mid: None,
+            volume: 1,
+            complete: true,
+        }
+    }
+
+    #[test]
+    fn merged_in_order() {
+        let start = Utc.with_ymd_and_hms(2023, 3, 8, 12, 0, 0).unwrap();
+        let candles = |minutes: &[i64]| -> Vec<Candle> {
+            minutes
+                .iter()
+                .map(|&m| candle(start + Duration::minutes(m)))
+                .collect()
+        };
+        let merged = merge(candles(&[0, 15, 60]), candles(&[15, 30, 45]));
+        assert_eq!(candles(&[0, 15, 30, 45, 60]), merged);
+        assert!(candle_gaps(&merged, Granularity::M15).is_empty());
+    }
+}
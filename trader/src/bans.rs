@@ -0,0 +1,112 @@
+//! Instruments someone has banned us from trading for a while, with the
+//! `ban` command. Kept in a file so every run sees them until they expire.
+use std::{collections::BTreeMap, fs, io::ErrorKind, path::Path};
+
+use chrono::{DateTime, Utc};
+use error_stack::{IntoReport, Result, ResultExt};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ban {
+    /// We can trade the instrument again after this
+    pub until: DateTime<Utc>,
+    /// Who banned it
+    pub operator: String,
+}
+
+/// The bans by instrument
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Bans(BTreeMap<String, Ban>);
+
+impl Bans {
+    /// The bans saved at `path`; none if there's no file
+    pub fn load(path: &Path) -> Result<Bans, Error> {
+        let input = match fs::read_to_string(path) {
+            Ok(input) => input,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Bans::default()),
+            Err(err) => {
+                return Err(err)
+                    .into_report()
+                    .change_context(Error::new("Couldn't read the bans"))
+                    .attach_printable_lazy(|| format!("Path: {}", path.display()))
+            }
+        };
+        toml::from_str(&input)
+            .into_report()
+            .change_context(Error::new("Couldn't parse the bans"))
+            .attach_printable_lazy(|| format!("Path: {}", path.display()))
+    }
+
+    /// Saves the bans to `path`
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let output = toml::to_string(self)
+            .into_report()
+            .change_context(Error::new("Couldn't serialize the bans"))?;
+        fs::write(path, output)
+            .into_report()
+            .change_context(Error::new("Couldn't save the bans"))
+            .attach_printable_lazy(|| format!("Path: {}", path.display()))
+    }
+
+    /// Bans `instrument` until `until`, replacing any ban it already has.
+    /// Bans that are over by `now` are dropped
+    pub fn ban(
+        &mut self,
+        instrument: &str,
+        until: DateTime<Utc>,
+        operator: &str,
+        now: DateTime<Utc>,
+    ) {
+        self.0.retain(|_, ban| ban.until > now);
+        self.0.insert(
+            instrument.to_string(),
+            Ban {
+                until,
+                operator: operator.to_string(),
+            },
+        );
+    }
+
+    /// The ban on `instrument`, if it's still on at `now`
+    pub fn get(&self, instrument: &str, now: DateTime<Utc>) -> Option<&Ban> {
+        self.0.get(instrument).filter(|ban| ban.until > now)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn expiry() {
+        let now = Utc.with_ymd_and_hms(2023, 5, 5, 13, 30, 0).unwrap();
+        let mut bans = Bans::default();
+        bans.ban("EUR_USD", now + Duration::hours(12), "alice", now);
+        assert_eq!(
+            Some("alice"),
+            bans.get("EUR_USD", now).map(|ban| ban.operator.as_str())
+        );
+        assert_eq!(None, bans.get("GBP_USD", now));
+        let later = now + Duration::hours(12);
+        assert_eq!(None, bans.get("EUR_USD", later));
+        // Banning something else clears out the old ban
+        bans.ban("GBP_USD", later + Duration::hours(1), "bob", later);
+        assert_eq!(1, bans.0.len());
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir().join(format!("trader-bans-{}.toml", std::process::id()));
+        assert_eq!(Bans::default(), Bans::load(&path).unwrap());
+        let now = Utc.with_ymd_and_hms(2023, 5, 5, 13, 30, 0).unwrap();
+        let mut bans = Bans::default();
+        bans.ban("EUR_USD", now + Duration::hours(12), "alice", now);
+        bans.save(&path).unwrap();
+        assert_eq!(bans, Bans::load(&path).unwrap());
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,84 @@
+//! How big a renko brick is. Changing this changes every level we find, so
+//! the mode in use is logged with each run to keep experiments reproducible.
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// How to size renko bricks. In config it's one of:
+///
+/// ```toml
+/// brick_size = { atr_multiple = 1.5 }
+/// brick_size = { fixed_pips = 10 }
+/// brick_size = { percent_of_price = 0.1 }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BrickSize {
+    /// A multiple of the configured ATR
+    AtrMultiple(f32),
+    /// A fixed number of pips
+    FixedPips(f32),
+    /// A percentage of the latest close, eg. 0.1 for 0.1%
+    PercentOfPrice(f32),
+}
+
+impl Default for BrickSize {
+    /// One ATR
+    fn default() -> Self {
+        BrickSize::AtrMultiple(1.0)
+    }
+}
+
+impl BrickSize {
+    /// The brick size in price units, given the ATR, the latest close and
+    /// the instrument's pip location
+    pub fn size(&self, atr: f32, price: f32, pip_location: i32) -> f32 {
+        match *self {
+            BrickSize::AtrMultiple(multiple) => atr * multiple,
+            BrickSize::FixedPips(pips) => pips * 10f32.powi(pip_location),
+            BrickSize::PercentOfPrice(percent) => price * percent / 100.0,
+        }
+    }
+
+    /// The number the mode is configured with
+    pub fn value(&self) -> f32 {
+        match *self {
+            BrickSize::AtrMultiple(value)
+            | BrickSize::FixedPips(value)
+            | BrickSize::PercentOfPrice(value) => value,
+        }
+    }
+}
+
+impl fmt::Display for BrickSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BrickSize::AtrMultiple(multiple) => write!(f, "{multiple} x ATR"),
+            BrickSize::FixedPips(pips) => write!(f, "{pips} pips"),
+            BrickSize::PercentOfPrice(percent) => write!(f, "{percent}% of price"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sizes() {
+        let atr = 0.0012;
+        let price = 1.1;
+        assert!((BrickSize::AtrMultiple(1.5).size(atr, price, -4) - 0.0018).abs() < 1e-7);
+        assert!((BrickSize::FixedPips(10.0).size(atr, price, -4) - 0.001).abs() < 1e-7);
+        assert!((BrickSize::FixedPips(10.0).size(atr, 150.0, -2) - 0.1).abs() < 1e-6);
+        assert!((BrickSize::PercentOfPrice(0.1).size(atr, price, -4) - 0.0011).abs() < 1e-7);
+        assert_eq!(atr, BrickSize::default().size(atr, price, -4));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!("1.5 x ATR", BrickSize::AtrMultiple(1.5).to_string());
+        assert_eq!("10 pips", BrickSize::FixedPips(10.0).to_string());
+        assert_eq!("0.1% of price", BrickSize::PercentOfPrice(0.1).to_string());
+    }
+}
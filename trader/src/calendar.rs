@@ -0,0 +1,138 @@
+//! Market holidays, which the weekly schedule doesn't know about. On a
+//! holiday there won't be any new candles, so we don't trade; on a day that
+//! closes early we stop entering trades as close to the early close as we
+//! would to a rollover. Dates are trading days, see [`TradingDay::date`], and
+//! early closes are in the trading day's timezone, eg.
+//!
+//! ```toml
+//! [[holidays]]
+//! date = "2023-12-25"
+//!
+//! [[holidays]]
+//! date = "2023-12-24"
+//! closes_at = "13:00:00"
+//! ```
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone, Utc};
+use error_stack::{report, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{config::ConfigKey, error::Error, trading_day::TradingDay};
+
+/// A day the market is shut, or closes early
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Holiday {
+    pub date: NaiveDate,
+    /// When the market closes, local time. Shut all day if it's not set
+    pub closes_at: Option<NaiveTime>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Calendar(Vec<Holiday>);
+
+impl Calendar {
+    /// Checks no day is listed twice, which would leave which one applies
+    /// up to the order they're in
+    pub fn validate(&self) -> Result<(), Error> {
+        for (index, holiday) in self.0.iter().enumerate() {
+            if self.0[..index]
+                .iter()
+                .any(|other| other.date == holiday.date)
+            {
+                return Err(report!(Error::new(format!(
+                    "{} is in the holidays more than once",
+                    holiday.date
+                )))
+                .attach(ConfigKey::new("holidays")));
+            }
+        }
+        Ok(())
+    }
+
+    /// The holiday on `date`, if it is one
+    pub fn holiday(&self, date: NaiveDate) -> Option<&Holiday> {
+        self.0.iter().find(|holiday| holiday.date == date)
+    }
+
+    /// When the trading day `time` is in closes early, if it does
+    pub fn early_close(&self, time: DateTime<Utc>, day: &TradingDay) -> Option<DateTime<Utc>> {
+        let holiday = self.holiday(day.date(time))?;
+        let closes_at = holiday.date.and_time(holiday.closes_at?);
+        let close = day.timezone.from_local_datetime(&closes_at).earliest()?;
+        Some(close.with_timezone(&Utc))
+    }
+
+    /// Whether the market is shut for a holiday at `time`: all day, or
+    /// after an early close
+    pub fn is_closed(&self, time: DateTime<Utc>, day: &TradingDay) -> bool {
+        match self.holiday(day.date(time)) {
+            Some(Holiday {
+                closes_at: None, ..
+            }) => true,
+            Some(_) => self
+                .early_close(time, day)
+                .is_some_and(|close| time >= close),
+            None => false,
+        }
+    }
+
+    /// Whether `time` is within the trading day's blackout minutes of an
+    /// early close
+    pub fn is_near_close(&self, time: DateTime<Utc>, day: &TradingDay) -> bool {
+        let blackout = Duration::minutes(day.blackout_minutes.into());
+        self.early_close(time, day)
+            .is_some_and(|close| close - time <= blackout)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn utc(month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2023, month, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    fn calendar() -> Calendar {
+        let date = |day| NaiveDate::from_ymd_opt(2023, 12, day).unwrap();
+        Calendar(vec![
+            Holiday {
+                date: date(25),
+                closes_at: None,
+            },
+            Holiday {
+                date: date(22),
+                closes_at: NaiveTime::from_hms_opt(13, 0, 0),
+            },
+        ])
+    }
+
+    #[test]
+    fn holidays() {
+        let day = TradingDay::default();
+        let calendar = calendar();
+        // Christmas day's trading day runs from 22:00 UTC on the 24th
+        assert!(!calendar.is_closed(utc(12, 24, 21, 0), &day));
+        assert!(calendar.is_closed(utc(12, 24, 23, 0), &day));
+        assert!(calendar.is_closed(utc(12, 25, 12, 0), &day));
+        assert!(!calendar.is_closed(utc(12, 26, 12, 0), &day));
+    }
+
+    #[test]
+    fn early_close() {
+        let day = TradingDay::default();
+        let calendar = calendar();
+        // 13:00 in New York is 18:00 UTC in December
+        assert_eq!(
+            Some(utc(12, 22, 18, 0)),
+            calendar.early_close(utc(12, 22, 12, 0), &day)
+        );
+        assert!(!calendar.is_closed(utc(12, 22, 17, 0), &day));
+        assert!(!calendar.is_near_close(utc(12, 22, 17, 0), &day));
+        assert!(calendar.is_near_close(utc(12, 22, 17, 50), &day));
+        assert!(calendar.is_closed(utc(12, 22, 18, 0), &day));
+        assert_eq!(None, calendar.early_close(utc(12, 21, 12, 0), &day));
+    }
+}
@@ -0,0 +1,164 @@
+//! Break glass commands for when someone needs to step in by hand, given on
+//! the command line instead of a normal run, eg.
+//!
+//! ```text
+//! trader close-trade 6349
+//! trader cancel-order @robot-1
+//! trader ban EUR_USD 12
+//! trader reanalyse EUR_USD
+//! trader reoptimize EUR_USD
+//! trader init /opt/trader
+//! ```
+//!
+//! Whoever runs one is logged with it, from `TRADER_OPERATOR` or else `USER`.
+use std::{env, fmt, path::PathBuf};
+
+use error_stack::{bail, Result};
+use oanda::model::trade::TradeSpecifier;
+
+use crate::error::Error;
+
+const USAGE: &str = "Usage: trader [close-trade <trade> | cancel-order <order> | ban <instrument> <hours> | reanalyse <instrument> | reoptimize <instrument> | init [dir]]";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Close all of a trade at market price
+    CloseTrade(TradeSpecifier),
+    /// Cancel a pending order, by id or `@` and our client id
+    CancelOrder(String),
+    /// Don't trade an instrument for a while
+    Ban { instrument: String, hours: u32 },
+    /// Forget an instrument's saved levels and trade it now, so the levels
+    /// are found again
+    Reanalyse(String),
+    /// Grid search the strategy settings on an instrument's recent candles
+    /// now, whenever it last ran. See [`reoptimize`](crate::reoptimize)
+    Reoptimize(String),
+    /// Write a starter config and systemd units into a directory. See
+    /// [`init`](crate::init)
+    Init(PathBuf),
+}
+
+impl Command {
+    /// The command in `args`, which don't include the program name. None
+    /// if there aren't any, for a normal run
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Option<Command>, Error> {
+        let args: Vec<String> = args.into_iter().collect();
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let command = match args.as_slice() {
+            [] => return Ok(None),
+            ["close-trade", trade] => Command::CloseTrade(match trade.strip_prefix('@') {
+                Some(client_id) => TradeSpecifier::client_id(client_id),
+                None => TradeSpecifier::Id(trade.to_string()),
+            }),
+            ["cancel-order", order] => Command::CancelOrder(order.to_string()),
+            ["ban", instrument, hours] => {
+                let Ok(hours) = hours.parse() else {
+                    bail!(Error::new(format!(
+                        "The hours to ban {instrument} for must be a whole number. Got {hours}"
+                    )))
+                };
+                Command::Ban {
+                    instrument: instrument.to_string(),
+                    hours,
+                }
+            }
+            ["reanalyse", instrument] => Command::Reanalyse(instrument.to_string()),
+            ["reoptimize", instrument] => Command::Reoptimize(instrument.to_string()),
+            ["init"] => Command::Init(PathBuf::from(".")),
+            ["init", dir] => Command::Init(PathBuf::from(dir)),
+            _ => bail!(Error::new(USAGE)),
+        };
+        Ok(Some(command))
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Command::CloseTrade(trade) => write!(f, "close-trade {trade}"),
+            Command::CancelOrder(order) => write!(f, "cancel-order {order}"),
+            Command::Ban { instrument, hours } => write!(f, "ban {instrument} {hours}"),
+            Command::Reanalyse(instrument) => write!(f, "reanalyse {instrument}"),
+            Command::Reoptimize(instrument) => write!(f, "reoptimize {instrument}"),
+            Command::Init(dir) => write!(f, "init {}", dir.display()),
+        }
+    }
+}
+
+/// Who is running the command
+pub fn operator() -> Result<String, Error> {
+    match env::var("TRADER_OPERATOR").or_else(|_| env::var("USER")) {
+        Ok(operator) if !operator.is_empty() => Ok(operator),
+        _ => bail!(Error::new(
+            "Set TRADER_OPERATOR to who is running the command"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<Command>, Error> {
+        Command::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn commands() {
+        assert_eq!(None, parse(&[]).unwrap());
+        assert_eq!(
+            Some(Command::CloseTrade(TradeSpecifier::Id("6349".to_string()))),
+            parse(&["close-trade", "6349"]).unwrap()
+        );
+        assert_eq!(
+            Some(Command::CloseTrade(TradeSpecifier::client_id("robot-1"))),
+            parse(&["close-trade", "@robot-1"]).unwrap()
+        );
+        assert_eq!(
+            Some(Command::CancelOrder("@robot-2".to_string())),
+            parse(&["cancel-order", "@robot-2"]).unwrap()
+        );
+        let ban = parse(&["ban", "EUR_USD", "12"]).unwrap().unwrap();
+        assert_eq!(
+            Command::Ban {
+                instrument: "EUR_USD".to_string(),
+                hours: 12
+            },
+            ban
+        );
+        assert_eq!("ban EUR_USD 12", ban.to_string());
+        assert_eq!(
+            Some(Command::Reanalyse("EUR_USD".to_string())),
+            parse(&["reanalyse", "EUR_USD"]).unwrap()
+        );
+        assert_eq!(
+            Some(Command::Reoptimize("EUR_USD".to_string())),
+            parse(&["reoptimize", "EUR_USD"]).unwrap()
+        );
+        assert_eq!(
+            Some(Command::Init(PathBuf::from("."))),
+            parse(&["init"]).unwrap()
+        );
+        assert_eq!(
+            Some(Command::Init(PathBuf::from("/opt/trader"))),
+            parse(&["init", "/opt/trader"]).unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        for args in [
+            &["close-trade"][..],
+            &["ban", "EUR_USD"],
+            &["ban", "EUR_USD", "-1"],
+            &["ban", "EUR_USD", "soon"],
+            &["reanalyse", "EUR_USD", "GBP_USD"],
+            &["reoptimize"],
+            &["init", "here", "there"],
+            &["sell"],
+        ] {
+            assert!(parse(args).is_err(), "{args:?}");
+        }
+    }
+}
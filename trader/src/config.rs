@@ -0,0 +1,519 @@
+//! Settings for a run, read from a TOML file. The path comes from the
+//! `TRADER_CONFIG` environment variable; without it everything is defaulted.
+//! Everything is checked when it's read, so a bad setting stops the trader
+//! before it starts, with the line it's on.
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+use error_stack::{report, IntoReport, Report, Result, ResultExt};
+use oanda::model::candle::CandlestickGranularity as Granularity;
+use serde::{Deserialize, Serialize};
+
+use crate::{brick_size::BrickSize, calendar::Calendar, error::Error, trading_day::TradingDay};
+
+mod comparison;
+mod drawdown;
+mod history_limit;
+mod instrument;
+mod reoptimize;
+pub use comparison::{Comparison, Strategy, Variant};
+pub use drawdown::{AlertLevel, DrawdownAlerts};
+pub use history_limit::HistoryLimit;
+pub use instrument::{InstrumentOverrides, InstrumentSettings};
+pub use reoptimize::Reoptimize;
+
+/// Pivots need a candle either side of the middle one
+const PIVOT_WINDOW: RangeInclusive<usize> = 3..=101;
+/// Enough for the default ATR period, up to the most OANDA sends at once
+const CANDLE_COUNT: RangeInclusive<u16> = 15..=5000;
+const ENTRY_ATR_MULTIPLE: RangeInclusive<f32> = 0.1..=10.0;
+const MAX_MARGIN_USAGE: RangeInclusive<f32> = 0.01..=1.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// How big the renko bricks we find support and resistance on are
+    pub brick_size: BrickSize,
+    /// Where support and resistance levels are kept between runs
+    pub levels_dir: PathBuf,
+    /// Where instruments banned with the `ban` command are kept
+    pub bans_file: PathBuf,
+    /// Where the instruments OANDA has halted are kept, so a halt is only
+    /// alerted on once and we notice when it's over
+    pub halts_file: PathBuf,
+    /// How many renko bricks each pivot is looked for in
+    pub pivot_window: usize,
+    /// We buy when the price is above resistance by less than this many ATRs
+    pub entry_atr_multiple: f32,
+    /// How many candles to download at a time
+    pub candle_count: u16,
+    /// How far back to look for support and resistance before giving up
+    pub max_history: HistoryLimit,
+    /// Signals scoring less than this, from 0 to 1, aren't traded. See
+    /// [`SignalScore`](crate::signal_score::SignalScore)
+    pub min_signal_score: f32,
+    /// Breakouts are only traded while the ADX of the candles is at least
+    /// this, from 0 to 100, so we stay out of markets that aren't trending.
+    /// Any ADX will do if it's not set
+    pub min_adx: Option<f32>,
+    /// Breakouts are only traded while the Hurst exponent of the candles is
+    /// at least this, from 0 to 1, so we stay out of markets that keep
+    /// reverting to the mean. Any exponent will do if it's not set
+    pub min_hurst: Option<f32>,
+    /// The most of the NAV, from 0 to 1, that can be used as margin once a
+    /// new position is open. See [`MarginCheck`](crate::margin::MarginCheck)
+    pub max_margin_usage: f32,
+    /// When to raise the alarm as the account falls from its peak
+    pub drawdown: DrawdownAlerts,
+    /// The candles we find the ATR and levels in, unless overridden
+    pub granularity: Granularity,
+    /// How many candles the ATR is averaged over, unless overridden
+    pub atr_period: usize,
+    /// The percent of the balance to risk on each trade, unless overridden
+    pub risk: f32,
+    /// When each trading day ends
+    pub trading_day: TradingDay,
+    /// The days the market is shut or closes early
+    pub holidays: Calendar,
+    /// Settings for particular instruments, by name
+    pub instruments: BTreeMap<String, InstrumentOverrides>,
+    /// Where to save a chart and the analysis of every signal, taken or
+    /// not. Nothing is saved if it's not set
+    pub debug_dump_dir: Option<PathBuf>,
+    /// A second strategy to run next to this one. See [`Comparison`]
+    pub comparison: Option<Comparison>,
+    /// A regular grid search for better strategy settings. See [`Reoptimize`]
+    pub reoptimize: Option<Reoptimize>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            brick_size: BrickSize::default(),
+            levels_dir: PathBuf::from("levels"),
+            bans_file: PathBuf::from("bans.toml"),
+            halts_file: PathBuf::from("halts.toml"),
+            pivot_window: 5,
+            entry_atr_multiple: 1.0,
+            candle_count: 200,
+            max_history: HistoryLimit::default(),
+            min_signal_score: 0.5,
+            min_adx: None,
+            min_hurst: None,
+            max_margin_usage: 0.5,
+            drawdown: DrawdownAlerts::default(),
+            granularity: Granularity::M15,
+            atr_period: 14,
+            risk: 1.0,
+            trading_day: TradingDay::default(),
+            holidays: Calendar::default(),
+            instruments: BTreeMap::new(),
+            debug_dump_dir: None,
+            comparison: None,
+            reoptimize: None,
+        }
+    }
+}
+
+impl Config {
+    /// Reads the file named by `TRADER_CONFIG`, or the defaults if it isn't set
+    pub fn load() -> Result<Config, Error> {
+        match Config::path() {
+            Some(path) => Config::from_file(path),
+            None => Ok(Config::default()),
+        }
+    }
+
+    /// The file named by `TRADER_CONFIG`, if it's set
+    pub fn path() -> Option<PathBuf> {
+        env::var_os("TRADER_CONFIG").map(PathBuf::from)
+    }
+
+    /// The settings for `instrument`; the global ones with its overrides applied
+    pub fn instrument(&self, instrument: &str) -> InstrumentSettings {
+        let defaults = self.instrument_defaults();
+        match self.instruments.get(instrument) {
+            Some(overrides) => overrides.apply(defaults),
+            None => defaults,
+        }
+    }
+
+    /// The strategies to evaluate: this one, and the candidate if there's
+    /// a comparison. Only one of them is live
+    pub fn strategies(&self) -> Vec<Strategy> {
+        let baseline = Config {
+            comparison: None,
+            ..self.clone()
+        };
+        let Some(comparison) = &self.comparison else {
+            return vec![Strategy {
+                variant: Variant::Baseline,
+                live: true,
+                config: baseline,
+            }];
+        };
+        let candidate = comparison.candidate.apply(&baseline);
+        vec![
+            Strategy {
+                variant: Variant::Baseline,
+                live: comparison.live == Variant::Baseline,
+                config: baseline,
+            },
+            Strategy {
+                variant: Variant::Candidate,
+                live: comparison.live == Variant::Candidate,
+                config: candidate,
+            },
+        ]
+    }
+
+    /// A short hash of every setting, to tell which config a trade was
+    /// opened with. It's FNV-1a of the settings as JSON, so it's the same
+    /// from build to build and doesn't change with how they print to a log
+    pub fn hash(&self) -> Result<String, Error> {
+        let json = serde_json::to_vec(self)
+            .into_report()
+            .change_context(Error::new("Couldn't serialize the config to hash it"))?;
+        let hash = json
+            .into_iter()
+            .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+            });
+        Ok(format!("{hash:016x}"))
+    }
+
+    fn instrument_defaults(&self) -> InstrumentSettings {
+        InstrumentSettings {
+            granularity: self.granularity,
+            atr_period: self.atr_period,
+            risk: self.risk,
+        }
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Config, Error> {
+        let path = path.as_ref();
+        let input = fs::read_to_string(path)
+            .into_report()
+            .change_context(Error::new("Couldn't read the config file"))
+            .attach_printable_lazy(|| format!("Path: {}", path.display()))?;
+        Config::parse(&input).attach_printable_lazy(|| format!("Path: {}", path.display()))
+    }
+
+    pub fn parse(input: &str) -> Result<Config, Error> {
+        let config: Config = toml::from_str(input)
+            .into_report()
+            .change_context(Error::new("Couldn't parse the config"))?;
+        config.validate().map_err(|report| {
+            let line = report
+                .downcast_ref::<ConfigKey>()
+                .and_then(|key| key.line_in(input));
+            match line {
+                Some((number, line)) => report.attach_printable(format!("Line {number}: {line}")),
+                None => report,
+            }
+        })?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        let value = self.brick_size.value();
+        if !value.is_finite() || value <= 0.0 {
+            return Err(report!(Error::new(format!(
+                "The brick size must be more than zero. Got {}",
+                self.brick_size
+            )))
+            .attach(ConfigKey::new("brick_size")));
+        }
+        check_range("pivot_window", self.pivot_window, PIVOT_WINDOW)?;
+        check_range(
+            "entry_atr_multiple",
+            self.entry_atr_multiple,
+            ENTRY_ATR_MULTIPLE,
+        )?;
+        check_range("candle_count", self.candle_count, CANDLE_COUNT)?;
+        if self.max_history.is_empty() {
+            return Err(report!(Error::new("max_history must allow some candles"))
+                .attach(ConfigKey::new("max_history")));
+        }
+        check_range("min_signal_score", self.min_signal_score, 0.0..=1.0)?;
+        if let Some(min_adx) = self.min_adx {
+            check_range("min_adx", min_adx, 0.0..=100.0)?;
+        }
+        if let Some(min_hurst) = self.min_hurst {
+            check_range("min_hurst", min_hurst, 0.0..=1.0)?;
+        }
+        check_range("max_margin_usage", self.max_margin_usage, MAX_MARGIN_USAGE)?;
+        self.drawdown.validate()?;
+        self.trading_day.validate()?;
+        self.holidays.validate()?;
+        let defaults = self.instrument_defaults();
+        defaults.validate(self.candle_count)?;
+        for (instrument, overrides) in &self.instruments {
+            overrides.validate(instrument, defaults, self.candle_count)?;
+        }
+        if let Some(comparison) = &self.comparison {
+            comparison
+                .candidate
+                .apply(self)
+                .validate()
+                .map_err(|report| ConfigKey::within(report, "comparison.candidate"))
+                .attach_printable("In the comparison candidate")?;
+        }
+        if let Some(reoptimize) = &self.reoptimize {
+            reoptimize.validate()?;
+        }
+        Ok(())
+    }
+}
+
+/// The setting a validation error is about, as its dotted path in the
+/// file, eg. `trading_day.rollover_hour`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ConfigKey(String);
+
+impl ConfigKey {
+    pub fn new(key: impl ToString) -> Self {
+        Self(key.to_string())
+    }
+
+    /// Moves the key on `report`, if there is one, under `table`
+    pub fn within(report: Report<Error>, table: &str) -> Report<Error> {
+        match report.downcast_ref::<ConfigKey>() {
+            Some(key) => {
+                let key = ConfigKey(format!("{table}.{}", key.0));
+                report.attach(key)
+            }
+            None => report,
+        }
+    }
+
+    /// The number and text of the line the setting is on in `input`. None
+    /// if it's not set in a `key = value` line under its table
+    fn line_in<'a>(&self, input: &'a str) -> Option<(usize, &'a str)> {
+        let (table, name) = self.0.rsplit_once('.').unwrap_or(("", &self.0));
+        let mut current = "";
+        for (index, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.starts_with('[') {
+                current = line.trim_matches(|c| c == '[' || c == ']').trim();
+            } else if current == table
+                && line
+                    .strip_prefix(name)
+                    .is_some_and(|rest| rest.trim_start().starts_with('='))
+            {
+                return Some((index + 1, line));
+            }
+        }
+        None
+    }
+}
+
+pub(crate) fn check_range<T>(name: &str, value: T, range: RangeInclusive<T>) -> Result<(), Error>
+where
+    T: PartialOrd + std::fmt::Display,
+{
+    if !range.contains(&value) {
+        return Err(report!(Error::new(format!(
+            "{name} must be from {} to {}. Got {value}",
+            range.start(),
+            range.end()
+        )))
+        .attach(ConfigKey::new(name)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(Config::default(), Config::parse("").unwrap());
+        let config = Config::parse("brick_size = { fixed_pips = 10 }").unwrap();
+        assert_eq!(BrickSize::FixedPips(10.0), config.brick_size);
+        let config = Config::parse("[brick_size]\npercent_of_price = 0.1").unwrap();
+        assert_eq!(BrickSize::PercentOfPrice(0.1), config.brick_size);
+        let config = Config::parse(r#"levels_dir = "/var/lib/trader""#).unwrap();
+        assert_eq!(Path::new("/var/lib/trader"), config.levels_dir);
+        assert_eq!(Path::new("bans.toml"), config.bans_file);
+        assert_eq!(Path::new("halts.toml"), config.halts_file);
+        assert_eq!(None, config.debug_dump_dir);
+        let config = Config::parse(r#"debug_dump_dir = "dumps""#).unwrap();
+        assert_eq!(Some(PathBuf::from("dumps")), config.debug_dump_dir);
+        let config = Config::parse("pivot_window = 7\ncandle_count = 500").unwrap();
+        assert_eq!((7, 500), (config.pivot_window, config.candle_count));
+        let config =
+            Config::parse("max_margin_usage = 0.25\nmin_adx = 25\nmin_hurst = 0.4").unwrap();
+        assert_eq!(Some(25.0), config.min_adx);
+        assert_eq!(Some(0.4), config.min_hurst);
+        assert_eq!(0.25, config.max_margin_usage);
+        let config = Config::parse("max_history = { days = 30 }").unwrap();
+        assert_eq!(HistoryLimit::Days(30), config.max_history);
+        let config = Config::parse("[trading_day]\ntimezone = \"Europe/London\"").unwrap();
+        assert_eq!(chrono_tz::Europe::London, config.trading_day.timezone);
+        assert_eq!(17, config.trading_day.rollover_hour);
+        let input = "[[holidays]]\ndate = \"2023-12-25\"\n[[holidays]]\ndate = \"2023-12-22\"\ncloses_at = \"13:00:00\"";
+        let holidays = Config::parse(input).unwrap().holidays;
+        let drawdown = Config::parse("[drawdown]\nwarning = 4.0\nchart_dir = \"charts\"")
+            .unwrap()
+            .drawdown;
+        assert_eq!(
+            (2.0, 4.0, 10.0),
+            (drawdown.info, drawdown.warning, drawdown.page)
+        );
+        assert_eq!(Some(PathBuf::from("charts")), drawdown.chart_dir);
+        let date = |day| chrono::NaiveDate::from_ymd_opt(2023, 12, day).unwrap();
+        assert_eq!(None, holidays.holiday(date(25)).unwrap().closes_at);
+        assert!(holidays.holiday(date(22)).unwrap().closes_at.is_some());
+        assert_eq!(None, config.reoptimize);
+        let input = "[reoptimize]\npivot_window = [3, 7]\nbrick_size = [{ fixed_pips = 10 }]";
+        let reoptimize = Config::parse(input).unwrap().reoptimize.unwrap();
+        assert_eq!(vec![3, 7], reoptimize.pivot_window);
+        assert_eq!(vec![BrickSize::FixedPips(10.0)], reoptimize.brick_size);
+        assert!(reoptimize.entry_atr_multiple.is_empty());
+        assert_eq!((7, false), (reoptimize.every_days, reoptimize.apply));
+    }
+
+    #[test]
+    fn instrument_overrides() {
+        let input = r#"
+            granularity = "M30"
+            risk = 2.0
+
+            [instruments.XAU_USD]
+            granularity = "H1"
+            atr_period = 20
+        "#;
+        let config = Config::parse(input).unwrap();
+        let expected = InstrumentSettings {
+            granularity: Granularity::H1,
+            atr_period: 20,
+            risk: 2.0,
+        };
+        assert_eq!(expected, config.instrument("XAU_USD"));
+        let expected = InstrumentSettings {
+            granularity: Granularity::M30,
+            atr_period: 14,
+            risk: 2.0,
+        };
+        assert_eq!(expected, config.instrument("EUR_USD"));
+    }
+
+    #[test]
+    fn comparison() {
+        assert_eq!(1, Config::default().strategies().len());
+        let input = r#"
+            pivot_window = 5
+            debug_dump_dir = "dumps"
+
+            [comparison]
+            live = "candidate"
+
+            [comparison.candidate]
+            pivot_window = 7
+        "#;
+        let strategies = Config::parse(input).unwrap().strategies();
+        let [baseline, candidate] = strategies.as_slice() else {
+            panic!("Expected two strategies, got {strategies:?}");
+        };
+        assert_eq!(
+            (Variant::Baseline, false),
+            (baseline.variant, baseline.live)
+        );
+        assert_eq!(
+            (Variant::Candidate, true),
+            (candidate.variant, candidate.live)
+        );
+        assert_eq!(5, baseline.config.pivot_window);
+        assert_eq!(7, candidate.config.pivot_window);
+        assert_eq!(Path::new("levels"), baseline.config.levels_dir);
+        assert_eq!(Path::new("levels/candidate"), candidate.config.levels_dir);
+        assert_eq!(
+            Some(PathBuf::from("dumps/candidate")),
+            candidate.config.debug_dump_dir
+        );
+        assert_eq!(None, candidate.config.comparison);
+        assert_ne!(
+            baseline.config.hash().unwrap(),
+            candidate.config.hash().unwrap()
+        );
+        assert_eq!(16, baseline.config.hash().unwrap().len());
+    }
+
+    #[test]
+    fn invalid() {
+        for input in [
+            "brick_size = { atr_multiple = 0 }",
+            "brick_size = { fixed_pips = -5 }",
+            "brick_size = { renko = 1 }",
+            "brick = { atr_multiple = 1 }",
+            "pivot_window = 1",
+            "entry_atr_multiple = 0.0",
+            "entry_atr_multiple = nan",
+            "candle_count = 10",
+            "candle_count = 5001",
+            "min_signal_score = 1.5",
+            "max_margin_usage = 0.0",
+            "min_adx = 101",
+            "min_hurst = 1.5",
+            "max_margin_usage = 1.5",
+            "max_history = { days = 0 }",
+            "max_history = { weeks = 2 }",
+            "atr_period = 300",
+            "[instruments.XAU_USD]\natr_period = 1",
+            "[instruments.XAU_USD]\nperiod = 20",
+            "[trading_day]\nrollover_hour = 24",
+            "[drawdown]\ninfo = 6.0",
+            "[drawdown]\npage = 0",
+            "[[holidays]]\ndate = \"2023-12-25\"\nopens_at = \"09:00:00\"",
+            "[trading_day]\ntimezone = \"Mars/Olympus_Mons\"",
+            "[comparison.candidate]\npivot_window = 1",
+            "[comparison]\nlive = \"both\"\n[comparison.candidate]",
+            "[[holidays]]\ndate = \"2023-12-25\"\n[[holidays]]\ndate = \"2023-12-25\"",
+            "risk = 101",
+            "[reoptimize]\nevery_days = 0",
+            "[reoptimize]\npivot_window = [5, 1]",
+            "[reoptimize]\nentry_atr_multiple = [20.0]",
+            "[reoptimize]\nbrick_size = [{ atr_multiple = 0 }]",
+            "[reoptimize]\ncandles = 50",
+            "[reoptimize]\nevery = 7",
+        ] {
+            assert!(Config::parse(input).is_err(), "{input}");
+        }
+    }
+
+    /// The error for a bad setting says which line it's on
+    fn error(input: &str) -> String {
+        format!("{:?}", Config::parse(input).unwrap_err())
+    }
+
+    #[test]
+    fn errors_point_at_the_line() {
+        let input = "risk = 2.0\n\n[instruments.XAU_USD]\nrisk = 50.0\n";
+        assert!(
+            error(input).contains("Line 4: risk = 50.0"),
+            "{}",
+            error(input)
+        );
+        let input = "pivot_window = 5\n[trading_day]\nrollover_hour = 25\n";
+        assert!(error(input).contains("Line 3: rollover_hour = 25"));
+        let input = "[drawdown]\npage = 0\n";
+        assert!(error(input).contains("Line 2: page = 0"));
+        let input = "candle_count = 20\natr_period = 30";
+        assert!(error(input).contains("Line 2: atr_period = 30"));
+        let input = "[comparison.candidate]\npivot_window = 1";
+        assert!(error(input).contains("Line 2: pivot_window = 1"));
+        let input = "max_history = { days = 0 }";
+        assert!(error(input).contains("Line 1: max_history"));
+        let input = "[reoptimize]\napply = true\npivot_window = [1]";
+        assert!(error(input).contains("Line 3: pivot_window = [1]"));
+        // TOML's own errors say where they are
+        assert!(error("granularity = \"M7\"").contains("line 1"));
+        assert!(error("\n[trading_dey]").contains("line 2"));
+    }
+}
@@ -0,0 +1,96 @@
+//! Runs a second strategy next to the configured one on the same data, so a
+//! parameter change can be tried in production without trading on it. Only
+//! one of the two sends orders; the other paper trades. Both are journaled
+//! with which variant they are, eg.
+//!
+//! ```toml
+//! pivot_window = 5
+//!
+//! [comparison]
+//! live = "baseline"
+//!
+//! [comparison.candidate]
+//! pivot_window = 7
+//! ```
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::Config;
+use crate::brick_size::BrickSize;
+
+/// Where the candidate's files go, under the baseline's directories
+const CANDIDATE_DIR: &str = "candidate";
+
+/// One side of an A/B comparison
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Variant {
+    /// The strategy configured at the top level
+    #[default]
+    Baseline,
+    /// The baseline with the comparison's overrides applied
+    Candidate,
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Variant::Baseline => "baseline",
+            Variant::Candidate => "candidate",
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Comparison {
+    /// The variant that sends orders. The other one paper trades
+    #[serde(default)]
+    pub live: Variant,
+    /// How the candidate differs from the baseline
+    pub candidate: StrategyOverrides,
+}
+
+/// Strategy settings that replace the baseline's. Anything left out is
+/// inherited
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StrategyOverrides {
+    pub brick_size: Option<BrickSize>,
+    pub pivot_window: Option<usize>,
+    pub entry_atr_multiple: Option<f32>,
+    pub min_signal_score: Option<f32>,
+}
+
+impl StrategyOverrides {
+    /// `baseline` with these overrides applied. The candidate keeps its
+    /// levels and debug dumps in a `candidate` directory under the
+    /// baseline's, so the two don't mix
+    pub fn apply(&self, baseline: &Config) -> Config {
+        Config {
+            brick_size: self.brick_size.unwrap_or(baseline.brick_size),
+            pivot_window: self.pivot_window.unwrap_or(baseline.pivot_window),
+            entry_atr_multiple: self
+                .entry_atr_multiple
+                .unwrap_or(baseline.entry_atr_multiple),
+            min_signal_score: self.min_signal_score.unwrap_or(baseline.min_signal_score),
+            levels_dir: baseline.levels_dir.join(CANDIDATE_DIR),
+            debug_dump_dir: baseline
+                .debug_dump_dir
+                .as_ref()
+                .map(|dir| dir.join(CANDIDATE_DIR)),
+            comparison: None,
+            ..baseline.clone()
+        }
+    }
+}
+
+/// One of the strategies a run evaluates
+#[derive(Debug, Clone, PartialEq)]
+pub struct Strategy {
+    pub variant: Variant,
+    /// Whether it sends orders, rather than paper trading
+    pub live: bool,
+    pub config: Config,
+}
@@ -0,0 +1,97 @@
+//! How far the account can fall from its peak before we raise the alarm,
+//! and how loudly. Each threshold is a percent of the peak NAV, eg.
+//!
+//! ```toml
+//! [drawdown]
+//! info = 2.0
+//! warning = 5.0
+//! page = 10.0
+//! chart_dir = "charts"
+//! ```
+use std::path::PathBuf;
+
+use error_stack::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use super::check_range;
+use crate::error::Error;
+
+/// How loud an alert is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertLevel {
+    Info,
+    Warning,
+    /// Someone needs to look now
+    Page,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DrawdownAlerts {
+    /// The drawdown, in percent, that's logged as information
+    pub info: f32,
+    /// The drawdown that's logged as a warning
+    pub warning: f32,
+    /// The drawdown that pages someone
+    pub page: f32,
+    /// Where the peak and recent NAVs are kept between runs
+    pub history_file: PathBuf,
+    /// Where to save a chart of the equity curve with each alert. None are
+    /// saved if it's not set
+    pub chart_dir: Option<PathBuf>,
+}
+
+impl Default for DrawdownAlerts {
+    fn default() -> Self {
+        Self {
+            info: 2.0,
+            warning: 5.0,
+            page: 10.0,
+            history_file: PathBuf::from("equity.toml"),
+            chart_dir: None,
+        }
+    }
+}
+
+impl DrawdownAlerts {
+    /// The loudest alert a drawdown of `percent` calls for, if any
+    pub fn level(&self, percent: f32) -> Option<AlertLevel> {
+        [
+            (self.page, AlertLevel::Page),
+            (self.warning, AlertLevel::Warning),
+            (self.info, AlertLevel::Info),
+        ]
+        .into_iter()
+        .find(|(threshold, _)| percent >= *threshold)
+        .map(|(_, level)| level)
+    }
+
+    pub(super) fn validate(&self) -> Result<(), Error> {
+        check_range("drawdown.info", self.info, 0.1..=100.0)?;
+        check_range("drawdown.warning", self.warning, 0.1..=100.0)?;
+        check_range("drawdown.page", self.page, 0.1..=100.0)?;
+        if !(self.info < self.warning && self.warning < self.page) {
+            bail!(Error::new(format!(
+                "The drawdown thresholds must go up from info to warning to page. Got {}, {} and {}",
+                self.info, self.warning, self.page
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn levels() {
+        let alerts = DrawdownAlerts::default();
+        assert_eq!(None, alerts.level(1.9));
+        assert_eq!(Some(AlertLevel::Info), alerts.level(2.0));
+        assert_eq!(Some(AlertLevel::Warning), alerts.level(7.5));
+        assert_eq!(Some(AlertLevel::Page), alerts.level(25.0));
+        assert!(AlertLevel::Info < AlertLevel::Page);
+    }
+}
@@ -0,0 +1,66 @@
+//! How far back we'll go looking for support and resistance before giving
+//! up. In config it's one of:
+//!
+//! ```toml
+//! max_history = { candles = 2000 }
+//! max_history = { days = 30 }
+//! ```
+use std::fmt;
+
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryLimit {
+    /// At most this many candles
+    Candles(usize),
+    /// Candles going back at most this many days from the latest
+    Days(u32),
+}
+
+impl Default for HistoryLimit {
+    /// Ten downloads of the default 200 candles
+    fn default() -> Self {
+        HistoryLimit::Candles(2000)
+    }
+}
+
+impl HistoryLimit {
+    /// Whether having `count` candles covering `span` uses up the limit
+    pub fn is_reached(&self, count: usize, span: Duration) -> bool {
+        match *self {
+            HistoryLimit::Candles(max) => count >= max,
+            HistoryLimit::Days(days) => span >= Duration::days(days.into()),
+        }
+    }
+
+    /// Whether the limit allows any candles at all
+    pub(super) fn is_empty(&self) -> bool {
+        matches!(self, HistoryLimit::Candles(0) | HistoryLimit::Days(0))
+    }
+}
+
+impl fmt::Display for HistoryLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HistoryLimit::Candles(candles) => write!(f, "{candles} candles"),
+            HistoryLimit::Days(days) => write!(f, "{days} days"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reached() {
+        let candles = HistoryLimit::Candles(400);
+        assert!(!candles.is_reached(200, Duration::days(100)));
+        assert!(candles.is_reached(400, Duration::hours(1)));
+        let days = HistoryLimit::Days(7);
+        assert!(!days.is_reached(10_000, Duration::days(6)));
+        assert!(days.is_reached(10, Duration::days(7)));
+    }
+}
@@ -0,0 +1,124 @@
+//! Settings that can be different for each instrument. The global ones are
+//! used unless an instrument overrides them, eg. slower settings for gold:
+//!
+//! ```toml
+//! granularity = "M15"
+//! atr_period = 14
+//!
+//! [instruments.XAU_USD]
+//! granularity = "H1"
+//! atr_period = 20
+//! ```
+use std::ops::RangeInclusive;
+
+use error_stack::{report, Result, ResultExt};
+use oanda::model::candle::CandlestickGranularity as Granularity;
+use serde::{Deserialize, Serialize};
+
+use super::{check_range, ConfigKey};
+use crate::error::Error;
+
+const ATR_PERIOD: RangeInclusive<usize> = 2..=100;
+const RISK: RangeInclusive<f32> = 0.01..=10.0;
+
+/// The settings for one instrument, after any overrides
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InstrumentSettings {
+    /// The candles we find the ATR and levels in
+    pub granularity: Granularity,
+    /// How many candles the ATR is averaged over
+    pub atr_period: usize,
+    /// The percent of the balance to risk on each trade
+    pub risk: f32,
+}
+
+impl InstrumentSettings {
+    /// Checks the settings are in bounds, and that downloading
+    /// `candle_count` candles is enough for the ATR
+    pub(super) fn validate(&self, candle_count: u16) -> Result<(), Error> {
+        check_range("atr_period", self.atr_period, ATR_PERIOD)?;
+        check_range("risk", self.risk, RISK)?;
+        if self.atr_period >= candle_count.into() {
+            return Err(report!(Error::new(format!(
+                "candle_count ({candle_count}) must be more than atr_period ({})",
+                self.atr_period
+            )))
+            .attach(ConfigKey::new("atr_period")));
+        }
+        Ok(())
+    }
+}
+
+/// Settings for one instrument that replace the global ones. Anything left
+/// out is inherited
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InstrumentOverrides {
+    pub granularity: Option<Granularity>,
+    pub atr_period: Option<usize>,
+    pub risk: Option<f32>,
+}
+
+impl InstrumentOverrides {
+    /// `defaults` with these overrides applied
+    pub fn apply(&self, defaults: InstrumentSettings) -> InstrumentSettings {
+        InstrumentSettings {
+            granularity: self.granularity.unwrap_or(defaults.granularity),
+            atr_period: self.atr_period.unwrap_or(defaults.atr_period),
+            risk: self.risk.unwrap_or(defaults.risk),
+        }
+    }
+
+    pub(super) fn validate(
+        &self,
+        instrument: &str,
+        defaults: InstrumentSettings,
+        candle_count: u16,
+    ) -> Result<(), Error> {
+        self.apply(defaults)
+            .validate(candle_count)
+            .map_err(|report| ConfigKey::within(report, &format!("instruments.{instrument}")))
+            .attach_printable_lazy(|| format!("In the overrides for {instrument}"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn defaults() -> InstrumentSettings {
+        InstrumentSettings {
+            granularity: Granularity::M15,
+            atr_period: 14,
+            risk: 1.0,
+        }
+    }
+
+    #[test]
+    fn inherits_what_isnt_overridden() {
+        assert_eq!(defaults(), InstrumentOverrides::default().apply(defaults()));
+        let overrides = InstrumentOverrides {
+            granularity: Some(Granularity::H1),
+            atr_period: None,
+            risk: Some(0.5),
+        };
+        let expected = InstrumentSettings {
+            granularity: Granularity::H1,
+            atr_period: 14,
+            risk: 0.5,
+        };
+        assert_eq!(expected, overrides.apply(defaults()));
+    }
+
+    #[test]
+    fn validate() {
+        assert!(defaults().validate(200).is_ok());
+        // Not enough candles for the ATR
+        assert!(defaults().validate(14).is_err());
+        let overrides = InstrumentOverrides {
+            risk: Some(50.0),
+            ..Default::default()
+        };
+        assert!(overrides.validate("XAU_USD", defaults(), 200).is_err());
+    }
+}
@@ -0,0 +1,96 @@
+//! A regular grid search over the strategy settings on recent history, so
+//! they keep up with the market. See [`reoptimize`](crate::reoptimize). Each
+//! list is the values to try for that setting; leave one out to keep the
+//! current value, eg.
+//!
+//! ```toml
+//! [reoptimize]
+//! every_days = 7
+//! pivot_window = [3, 5, 7]
+//! entry_atr_multiple = [0.5, 1.0, 1.5]
+//! brick_size = [{ atr_multiple = 1.0 }, { atr_multiple = 1.5 }]
+//! ```
+//!
+//! The values are held to the same ranges as the settings themselves, so
+//! an applied winner is always a config the trader will run with.
+use std::path::PathBuf;
+
+use error_stack::{report, Result};
+use serde::{Deserialize, Serialize};
+
+use super::{check_range, ConfigKey, ENTRY_ATR_MULTIPLE, PIVOT_WINDOW};
+use crate::{brick_size::BrickSize, error::Error};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Reoptimize {
+    /// How many days apart the runs are
+    pub every_days: u16,
+    /// How many of the latest candles to backtest on
+    pub candles: u16,
+    /// The renko brick sizes to try
+    pub brick_size: Vec<BrickSize>,
+    /// The pivot windows to try
+    pub pivot_window: Vec<usize>,
+    /// The entry ATR multiples to try
+    pub entry_atr_multiple: Vec<f32>,
+    /// Settings that made fewer trades than this in the backtest can't win
+    pub min_trades: usize,
+    /// Backtested trades take profit this many R from the entry
+    pub target_r: f32,
+    /// Write the winner into the config file, so the next run trades with
+    /// it. Otherwise a copy of the config with the winner in it is written to
+    /// `proposal_file` for someone to look at. Either way only the strategy
+    /// settings change; the comments and layout are kept
+    pub apply: bool,
+    /// Where the proposed config goes when it's not applied
+    pub proposal_file: PathBuf,
+    /// When the job last ran, kept between runs
+    pub last_run_file: PathBuf,
+}
+
+impl Default for Reoptimize {
+    fn default() -> Self {
+        Self {
+            every_days: 7,
+            candles: 2000,
+            brick_size: Vec::new(),
+            pivot_window: Vec::new(),
+            entry_atr_multiple: Vec::new(),
+            min_trades: 10,
+            target_r: 2.0,
+            apply: false,
+            proposal_file: PathBuf::from("proposed.toml"),
+            last_run_file: PathBuf::from("reoptimized.toml"),
+        }
+    }
+}
+
+impl Reoptimize {
+    pub(super) fn validate(&self) -> Result<(), Error> {
+        check_range("reoptimize.every_days", self.every_days, 1..=365)?;
+        check_range("reoptimize.candles", self.candles, 100..=5000)?;
+        check_range("reoptimize.min_trades", self.min_trades, 1..=1000)?;
+        check_range("reoptimize.target_r", self.target_r, 0.1..=20.0)?;
+        for brick_size in &self.brick_size {
+            let value = brick_size.value();
+            if !value.is_finite() || value <= 0.0 {
+                return Err(report!(Error::new(format!(
+                    "The brick sizes to try must be more than zero. Got {brick_size}"
+                )))
+                .attach(ConfigKey::new("reoptimize.brick_size")));
+            }
+        }
+        for &pivot_window in &self.pivot_window {
+            check_range("reoptimize.pivot_window", pivot_window, PIVOT_WINDOW)?;
+        }
+        for &multiple in &self.entry_atr_multiple {
+            check_range(
+                "reoptimize.entry_atr_multiple",
+                multiple,
+                ENTRY_ATR_MULTIPLE,
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+//! Saves a chart and the analysis behind every signal we evaluate, taken or
+//! skipped, so setups can be eyeballed later; handy when tuning the pivot
+//! window. Off unless `debug_dump_dir` is set in the config.
+//!
+//! Each trading day gets its own directory, with an SVG and a JSON file per
+//! signal named after the time, instrument, strategy variant and whether it
+//! was taken, or why not.
+use std::{fs, path::Path};
+
+use algorithms::{AnalysisSnapshot, Close, High, Low, Open};
+use chrono::{DateTime, NaiveDate, Utc};
+use error_stack::{IntoReport, Result, ResultExt};
+use svg::{
+    node::element::{Circle, Line, Rectangle},
+    Document,
+};
+
+use crate::{config::Variant, error::Error};
+
+const BRICK_WIDTH: f64 = 10.0;
+const HEIGHT: f64 = 400.0;
+/// Room around the chart so the pivot markers aren't cut off
+const MARGIN: f64 = 10.0;
+
+/// What happened to a signal
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decision<'a> {
+    pub instrument: &'a str,
+    /// Which side of an A/B comparison judged it
+    pub variant: Variant,
+    pub time: DateTime<Utc>,
+    /// The trading day the signal came on
+    pub day: NaiveDate,
+    /// Why it wasn't taken, eg. `no_trend`. None if it was
+    pub skipped: Option<&'static str>,
+    /// The levels the signal was judged against
+    pub support: f32,
+    pub resistance: f32,
+}
+
+impl Decision<'_> {
+    /// The file name, without an extension
+    fn name(&self) -> String {
+        let outcome = match self.skipped {
+            Some(reason) => format!("skipped-{reason}"),
+            None => "taken".to_string(),
+        };
+        format!(
+            "{}-{}-{}-{outcome}",
+            self.time.format("%H%M%S"),
+            self.instrument,
+            self.variant
+        )
+    }
+}
+
+/// Writes the chart and snapshot for `decision` under `dir`
+pub fn dump(dir: &Path, decision: &Decision, snapshot: &AnalysisSnapshot) -> Result<(), Error> {
+    let dir = dir.join(decision.day.to_string());
+    let path = dir.join(decision.name());
+    let json = snapshot
+        .to_json()
+        .into_report()
+        .change_context(Error::new("Couldn't serialize the analysis snapshot"))?;
+    let document = chart(snapshot, decision.support, decision.resistance);
+    fs::create_dir_all(&dir)
+        .and_then(|_| fs::write(path.with_extension("json"), json))
+        .and_then(|_| svg::save(path.with_extension("svg"), &document))
+        .into_report()
+        .change_context(Error::new("Couldn't write the debug dump"))
+        .attach_printable_lazy(|| format!("Path: {}", path.display()))
+}
+
+/// The renko bricks with their pivots marked, and lines at `support` and
+/// `resistance`. Higher prices are further up
+pub fn chart(snapshot: &AnalysisSnapshot, support: f32, resistance: f32) -> Document {
+    let bricks = &snapshot.bricks;
+    let width = bricks.len() as f64 * BRICK_WIDTH + MARGIN * 2.0;
+    let document = Document::new()
+        .set("width", width)
+        .set("height", HEIGHT)
+        .set("viewBox", (0, 0, width, HEIGHT));
+    let (top, bottom) = bricks
+        .iter()
+        .fold((support, resistance), |(top, bottom), brick| {
+            (top.max(brick.high()), bottom.min(brick.low()))
+        });
+    let scale = (HEIGHT - MARGIN * 2.0) / ((top - bottom) as f64).max(f64::EPSILON);
+    let y = |price: f32| MARGIN + (top - price) as f64 * scale;
+    let x = |index: usize| MARGIN + index as f64 * BRICK_WIDTH;
+    let level = |price: f32, color: &str| {
+        Line::new()
+            .set("x1", 0)
+            .set("y1", y(price))
+            .set("x2", width)
+            .set("y2", y(price))
+            .set("stroke", color)
+            .set("stroke-dasharray", 4)
+    };
+    let document = document
+        .add(level(support, "green"))
+        .add(level(resistance, "red"));
+    let document = bricks
+        .iter()
+        .enumerate()
+        .fold(document, |document, (i, brick)| {
+            let color = if brick.close() > brick.open() {
+                "green"
+            } else {
+                "red"
+            };
+            document.add(
+                Rectangle::new()
+                    .set("x", x(i))
+                    .set("y", y(brick.high()))
+                    .set("width", BRICK_WIDTH)
+                    .set("height", (y(brick.low()) - y(brick.high())).max(1.0))
+                    .set("fill", color)
+                    .set("stroke", "black")
+                    .set("stroke-width", 0.5),
+            )
+        });
+    snapshot
+        .pivots
+        .iter()
+        .enumerate()
+        .flat_map(|(i, pivot)| {
+            let high = pivot.high().map(|price| (i, price, "blue"));
+            let low = pivot.low().map(|price| (i, price, "orange"));
+            high.into_iter().chain(low)
+        })
+        .fold(document, |document, (i, price, color)| {
+            document.add(
+                Circle::new()
+                    .set("cx", x(i) + BRICK_WIDTH / 2.0)
+                    .set("cy", y(price))
+                    .set("r", 3)
+                    .set("fill", color),
+            )
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use algorithms::{Pivot, RenkoReversal, SnapshotCandle};
+    use chrono::TimeZone;
+
+    fn snapshot() -> AnalysisSnapshot {
+        let candles: Vec<SnapshotCandle> = [1.0, 2.0, 3.0, 2.0, 1.0, 2.0, 3.0, 4.0]
+            .into_iter()
+            .map(|close| SnapshotCandle {
+                open: close,
+                high: close,
+                low: close,
+                close,
+            })
+            .collect();
+        AnalysisSnapshot::capture(&candles, 0.5, RenkoReversal::EveryBrick, 3).unwrap()
+    }
+
+    #[test]
+    fn chart_has_every_brick() {
+        let mut snapshot = snapshot();
+        snapshot.pivots[1] = Pivot::High(3.0);
+        snapshot.pivots[2] = Pivot::HighLow {
+            high: 3.0,
+            low: 1.0,
+        };
+        let chart = chart(&snapshot, 1.0, 3.0).to_string();
+        assert_eq!(snapshot.bricks.len(), chart.matches("<rect").count());
+        assert_eq!(2, chart.matches("<line").count());
+        assert_eq!(3, chart.matches("<circle").count());
+    }
+
+    #[test]
+    fn dump_per_day() {
+        let dir = std::env::temp_dir().join(format!("trader-dump-{}", std::process::id()));
+        let decision = Decision {
+            instrument: "EUR_USD",
+            variant: Variant::Candidate,
+            time: Utc.with_ymd_and_hms(2023, 5, 5, 13, 30, 0).unwrap(),
+            day: NaiveDate::from_ymd_opt(2023, 5, 5).unwrap(),
+            skipped: Some("no_trend"),
+            support: 1.0,
+            resistance: 3.0,
+        };
+        let snapshot = snapshot();
+        dump(&dir, &decision, &snapshot).unwrap();
+        let path = dir
+            .join("2023-05-05")
+            .join("133000-EUR_USD-candidate-skipped-no_trend");
+        let json = fs::read_to_string(path.with_extension("json")).unwrap();
+        assert_eq!(snapshot, AnalysisSnapshot::from_json(&json).unwrap());
+        assert!(path.with_extension("svg").exists());
+        let taken = Decision {
+            variant: Variant::Baseline,
+            skipped: None,
+            ..decision
+        };
+        assert_eq!("133000-EUR_USD-baseline-taken", taken.name());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
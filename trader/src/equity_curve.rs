@@ -0,0 +1,212 @@
+//! The account's NAV over recent runs and the highest it's been, kept in a
+//! file so the drawdown can be measured from one run to the next. Alerts
+//! escalate: each level is raised once as the drawdown gets deeper, and
+//! they start over once it's back under the lowest threshold.
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use error_stack::{IntoReport, Result, ResultExt};
+use serde::{Deserialize, Serialize};
+use svg::{node::element::Polyline, Document};
+
+use crate::{
+    config::{AlertLevel, DrawdownAlerts},
+    error::Error,
+};
+
+/// How many NAVs to keep for the chart
+const MAX_POINTS: usize = 1000;
+/// How many of the latest NAVs go with an alert
+const EXCERPT_POINTS: usize = 10;
+const WIDTH: f64 = 800.0;
+const HEIGHT: f64 = 300.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EquityPoint {
+    pub time: DateTime<Utc>,
+    pub nav: f32,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EquityCurve {
+    /// The highest NAV seen
+    pub peak: f32,
+    /// The loudest alert raised since the drawdown last recovered
+    pub alerted: Option<AlertLevel>,
+    /// The recent NAVs, oldest first
+    pub points: Vec<EquityPoint>,
+}
+
+impl EquityCurve {
+    /// The curve saved at `path`; an empty one if there's no file
+    pub fn load(path: &Path) -> Result<EquityCurve, Error> {
+        let input = match fs::read_to_string(path) {
+            Ok(input) => input,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(EquityCurve::default()),
+            Err(err) => {
+                return Err(err)
+                    .into_report()
+                    .change_context(Error::new("Couldn't read the equity history"))
+                    .attach_printable_lazy(|| format!("Path: {}", path.display()))
+            }
+        };
+        toml::from_str(&input)
+            .into_report()
+            .change_context(Error::new("Couldn't parse the equity history"))
+            .attach_printable_lazy(|| format!("Path: {}", path.display()))
+    }
+
+    /// Saves the curve to `path`
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let output = toml::to_string(self)
+            .into_report()
+            .change_context(Error::new("Couldn't serialize the equity history"))?;
+        fs::write(path, output)
+            .into_report()
+            .change_context(Error::new("Couldn't save the equity history"))
+            .attach_printable_lazy(|| format!("Path: {}", path.display()))
+    }
+
+    /// How far the latest NAV is under the peak, in percent
+    pub fn drawdown(&self) -> f32 {
+        match self.points.last() {
+            Some(point) if self.peak > 0.0 => (self.peak - point.nav) / self.peak * 100.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Adds the NAV at `time`. Returns the alert to raise if the drawdown
+    /// has reached a louder level than has been raised already
+    pub fn record(
+        &mut self,
+        time: DateTime<Utc>,
+        nav: f32,
+        alerts: &DrawdownAlerts,
+    ) -> Option<AlertLevel> {
+        self.peak = self.peak.max(nav);
+        self.points.push(EquityPoint { time, nav });
+        let excess = self.points.len().saturating_sub(MAX_POINTS);
+        self.points.drain(..excess);
+        let level = alerts.level(self.drawdown());
+        if level.is_none() {
+            self.alerted = None;
+        }
+        if level > self.alerted {
+            self.alerted = level;
+            level
+        } else {
+            None
+        }
+    }
+
+    /// The latest NAVs with their times, oldest first, so an alert shows
+    /// how the account got there
+    pub fn excerpt(&self) -> String {
+        let start = self.points.len().saturating_sub(EXCERPT_POINTS);
+        self.points[start..]
+            .iter()
+            .map(|point| format!("{} {:.2}", point.time, point.nav))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Saves [`chart`](Self::chart) under `dir`, named for `time`, and
+    /// returns where it went
+    pub fn save_chart(&self, dir: &Path, time: DateTime<Utc>) -> Result<PathBuf, Error> {
+        let path = dir.join(format!("equity-{}.svg", time.format("%Y%m%dT%H%M%S")));
+        fs::create_dir_all(dir)
+            .and_then(|_| svg::save(&path, &self.chart()))
+            .into_report()
+            .change_context(Error::new("Couldn't save the equity chart"))
+            .attach_printable_lazy(|| format!("Path: {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// The NAVs as a line, with the peak at the top
+    pub fn chart(&self) -> Document {
+        let bottom = self
+            .points
+            .iter()
+            .map(|point| point.nav)
+            .fold(self.peak, f32::min);
+        let scale = HEIGHT / ((self.peak - bottom) as f64).max(f64::EPSILON);
+        let step = WIDTH / (self.points.len().max(2) - 1) as f64;
+        let points: Vec<String> = self
+            .points
+            .iter()
+            .enumerate()
+            .map(|(i, point)| {
+                let y = (self.peak - point.nav) as f64 * scale;
+                format!("{:.1},{y:.1}", i as f64 * step)
+            })
+            .collect();
+        Document::new()
+            .set("width", WIDTH)
+            .set("height", HEIGHT)
+            .set("viewBox", (0, 0, WIDTH, HEIGHT))
+            .add(
+                Polyline::new()
+                    .set("points", points.join(" "))
+                    .set("fill", "none")
+                    .set("stroke", "black"),
+            )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn escalates_once_per_level() {
+        let alerts = DrawdownAlerts::default();
+        let start = Utc.with_ymd_and_hms(2023, 5, 5, 13, 30, 0).unwrap();
+        let mut curve = EquityCurve::default();
+        let mut record = |hours, nav| curve.record(start + Duration::hours(hours), nav, &alerts);
+        assert_eq!(None, record(0, 1000.0));
+        assert_eq!(Some(AlertLevel::Info), record(1, 970.0));
+        assert_eq!(None, record(2, 975.0));
+        assert_eq!(Some(AlertLevel::Warning), record(3, 940.0));
+        assert_eq!(Some(AlertLevel::Page), record(4, 880.0));
+        assert_eq!(None, record(5, 870.0));
+        // Back near the peak, so the next fall alerts again
+        assert_eq!(None, record(6, 995.0));
+        assert_eq!(Some(AlertLevel::Info), record(7, 975.0));
+        assert_eq!(1000.0, curve.peak);
+        assert!((curve.drawdown() - 2.5).abs() < 0.0001);
+        let excerpt = curve.excerpt();
+        assert!(
+            excerpt.starts_with("2023-05-05 13:30:00 UTC 1000.00, 2023-05-05 14:30:00 UTC 970.00"),
+            "{excerpt}"
+        );
+        assert!(
+            excerpt.ends_with("2023-05-05 20:30:00 UTC 975.00"),
+            "{excerpt}"
+        );
+        // Only the latest few
+        for hours in 8..20 {
+            curve.record(start + Duration::hours(hours), 990.0, &alerts);
+        }
+        assert_eq!(EXCERPT_POINTS, curve.excerpt().split(", ").count());
+    }
+
+    #[test]
+    fn save_load_and_chart() {
+        let path = std::env::temp_dir().join(format!("trader-equity-{}.toml", std::process::id()));
+        assert_eq!(EquityCurve::default(), EquityCurve::load(&path).unwrap());
+        let mut curve = EquityCurve::default();
+        let time = Utc.with_ymd_and_hms(2023, 5, 5, 13, 30, 0).unwrap();
+        curve.record(time, 1000.0, &DrawdownAlerts::default());
+        curve.record(time, 900.0, &DrawdownAlerts::default());
+        curve.save(&path).unwrap();
+        assert_eq!(curve, EquityCurve::load(&path).unwrap());
+        fs::remove_file(path).unwrap();
+        let chart = curve.chart().to_string();
+        assert!(chart.contains("0.0,0.0 800.0,300.0"), "{chart}");
+    }
+}
@@ -0,0 +1,65 @@
+//! The instruments OANDA has halted, eg. for news. Kept in a file so a
+//! halt is only alerted on once, and the run after it's over can say so.
+use std::{fs, io::ErrorKind, path::Path};
+
+use error_stack::{IntoReport, Result, ResultExt};
+use oanda::client::pricing::HaltTracker;
+
+use crate::error::Error;
+
+/// The halts saved at `path`; none if there's no file
+pub fn load(path: &Path) -> Result<HaltTracker, Error> {
+    let input = match fs::read_to_string(path) {
+        Ok(input) => input,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(HaltTracker::default()),
+        Err(err) => {
+            return Err(err)
+                .into_report()
+                .change_context(Error::new("Couldn't read the halts"))
+                .attach_printable_lazy(|| format!("Path: {}", path.display()))
+        }
+    };
+    toml::from_str(&input)
+        .into_report()
+        .change_context(Error::new("Couldn't parse the halts"))
+        .attach_printable_lazy(|| format!("Path: {}", path.display()))
+}
+
+/// Saves `halts` to `path`
+pub fn save(halts: &HaltTracker, path: &Path) -> Result<(), Error> {
+    let output = toml::to_string(halts)
+        .into_report()
+        .change_context(Error::new("Couldn't serialize the halts"))?;
+    fs::write(path, output)
+        .into_report()
+        .change_context(Error::new("Couldn't save the halts"))
+        .attach_printable_lazy(|| format!("Path: {}", path.display()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use oanda::model::{price::from_f32, pricing::ClientPrice};
+
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir().join(format!("trader-halts-{}.toml", std::process::id()));
+        assert_eq!(HaltTracker::default(), load(&path).unwrap());
+        let mut halts = HaltTracker::default();
+        halts.update(&ClientPrice {
+            instrument: "XAU_USD".to_string(),
+            time: Utc.with_ymd_and_hms(2023, 5, 5, 12, 0, 0).unwrap(),
+            tradeable: false,
+            bids: vec![],
+            asks: vec![],
+            closeout_bid: from_f32(2000.0),
+            closeout_ask: from_f32(2000.5),
+        });
+        save(&halts, &path).unwrap();
+        let loaded = load(&path).unwrap();
+        assert!(loaded.is_halted("XAU_USD"));
+        assert_eq!(halts, loaded);
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,142 @@
+//! Sets a VPS up to run the trader. The starter config and the systemd
+//! units are built into the binary, so deploying is copying it over and
+//! running
+//!
+//! ```text
+//! trader init /opt/trader
+//! ```
+//!
+//! which writes `config.toml`, `trader.service`, `trader.timer` and a
+//! `trader.env` to put the OANDA token in there, pointing at the directory
+//! and at wherever the binary is.
+use std::{
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+use error_stack::{bail, IntoReport, Result, ResultExt};
+
+use crate::error::Error;
+
+const CONFIG: &str = include_str!("../assets/config.toml");
+const SERVICE: &str = include_str!("../assets/trader.service");
+const TIMER: &str = include_str!("../assets/trader.timer");
+const ENV: &str = include_str!("../assets/trader.env");
+
+/// The file the token goes in; only its owner can read it
+pub const ENV_FILE: &str = "trader.env";
+
+/// The files `init` writes, by name
+pub fn files(dir: &Path, exe: &Path) -> [(&'static str, String); 4] {
+    let fill = |template: &str| {
+        template
+            .replace("{dir}", &dir.display().to_string())
+            .replace("{exe}", &exe.display().to_string())
+    };
+    // Quoted and escaped, so any directory name makes valid TOML
+    let toml_path =
+        |name: &str| toml::Value::String(dir.join(name).display().to_string()).to_string();
+    let config = CONFIG
+        .replace("{levels_dir}", &toml_path("levels"))
+        .replace("{bans_file}", &toml_path("bans.toml"))
+        .replace("{halts_file}", &toml_path("halts.toml"));
+    [
+        ("config.toml", config),
+        ("trader.service", fill(SERVICE)),
+        ("trader.timer", fill(TIMER)),
+        (ENV_FILE, ENV.to_string()),
+    ]
+}
+
+/// Writes the starter files into `dir`, making it if need be. Nothing is
+/// overwritten; if any of the files are already there none are written.
+/// Returns the paths written
+pub fn init(dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    fs::create_dir_all(dir)
+        .into_report()
+        .change_context(Error::new("Couldn't make the directory"))
+        .attach_printable_lazy(|| format!("Path: {}", dir.display()))?;
+    // The units need absolute paths
+    let dir = dir
+        .canonicalize()
+        .into_report()
+        .change_context(Error::new("Couldn't find the directory"))
+        .attach_printable_lazy(|| format!("Path: {}", dir.display()))?;
+    let exe = std::env::current_exe()
+        .into_report()
+        .change_context(Error::new("Couldn't find the trader binary"))?;
+    let files = files(&dir, &exe);
+    for (name, _) in &files {
+        let path = dir.join(name);
+        if path.exists() {
+            bail!(Error::new(format!(
+                "{} is already there. Move it out of the way to start again",
+                path.display()
+            )))
+        }
+    }
+    let mut written = Vec::new();
+    for (name, contents) in files {
+        let path = dir.join(name);
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        if name == ENV_FILE {
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        }
+        options
+            .open(&path)
+            .and_then(|mut file| file.write_all(contents.as_bytes()))
+            .map_err(|err| match err.kind() {
+                ErrorKind::AlreadyExists => Error::new("The file appeared while we were writing"),
+                _ => Error::new(format!("Couldn't write the file: {err}")),
+            })
+            .into_report()
+            .attach_printable_lazy(|| format!("Path: {}", path.display()))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn starter_config_parses() {
+        let [(_, config), (_, service), _, _] =
+            files(Path::new("/opt/trader"), Path::new("/opt/trader/trader"));
+        let config = Config::parse(&config).unwrap();
+        assert_eq!(Path::new("/opt/trader/levels"), config.levels_dir);
+        assert!(service.contains("ExecStart=/opt/trader/trader\n"));
+        assert!(service.contains("TRADER_CONFIG=/opt/trader/config.toml\n"));
+        assert!(service.contains("EnvironmentFile=/opt/trader/trader.env\n"));
+    }
+
+    #[test]
+    fn escapes_the_dir() {
+        let dir = Path::new(r#"/opt/"my" trader\"#);
+        let [(_, config), ..] = files(dir, Path::new("/opt/trader/trader"));
+        let config = Config::parse(&config).unwrap();
+        assert_eq!(dir.join("levels"), config.levels_dir);
+        assert_eq!(dir.join("bans.toml"), config.bans_file);
+        assert_eq!(dir.join("halts.toml"), config.halts_file);
+    }
+
+    #[test]
+    fn doesnt_overwrite() {
+        let dir = std::env::temp_dir().join(format!("trader-init-{}", std::process::id()));
+        let written = init(&dir).unwrap();
+        assert_eq!(4, written.len());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let env = fs::metadata(dir.join(ENV_FILE)).unwrap();
+            assert_eq!(0o600, env.permissions().mode() & 0o777);
+        }
+        assert!(init(&dir).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,171 @@
+//! Support and resistance levels saved between runs. Finding them can take
+//! several downloads of 200 candles, so we keep them until the price breaks
+//! through one of them.
+use std::{fs, io::ErrorKind, path::Path};
+
+use algorithms::{
+    pivots, Error as AlgorithmsError, IntoRenkoIterator, IntoSupportAndResistance,
+    IntoSwingStatusIter, RenkoCandle, SupportAndResistance,
+};
+use chrono::{DateTime, Utc};
+use error_stack::{bail, IntoReport, Result, ResultExt};
+use oanda::model::{candle::CandlestickGranularity as Granularity, price::to_f32, Candle};
+use serde::{Deserialize, Serialize};
+
+use crate::{brick_size::BrickSize, error::Error};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Levels {
+    pub support: f32,
+    pub resistance: f32,
+    /// How the renko bricks the levels were found on were sized. Levels
+    /// found with a different mode aren't reused
+    pub brick_size: BrickSize,
+    /// The candles the levels were found in
+    pub granularity: Granularity,
+    /// The start time of the first candle the levels were found from
+    pub from: DateTime<Utc>,
+    /// The start time of the last candle the levels were found from
+    pub to: DateTime<Utc>,
+}
+
+impl Levels {
+    /// Whether any candle after the ones the levels came from closed below
+    /// support or above resistance
+    pub fn is_broken_by(&self, candles: &[Candle]) -> bool {
+        candles
+            .iter()
+            .filter(|candle| candle.time > self.to)
+            .flat_map(|candle| candle.mid.as_ref().map(|mid| to_f32(mid.c)))
+            .any(|close| close < self.support || close > self.resistance)
+    }
+
+    /// The levels saved for `instrument` in `dir`, if any
+    pub fn load(dir: &Path, instrument: &str) -> Result<Option<Levels>, Error> {
+        let path = dir.join(format!("{instrument}.toml"));
+        let input = match fs::read_to_string(&path) {
+            Ok(input) => input,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(err)
+                    .into_report()
+                    .change_context(Error::new("Couldn't read the saved levels"))
+                    .attach_printable_lazy(|| format!("Path: {}", path.display()))
+            }
+        };
+        toml::from_str(&input)
+            .into_report()
+            .change_context(Error::new("Couldn't parse the saved levels"))
+            .attach_printable_lazy(|| format!("Path: {}", path.display()))
+    }
+
+    /// Saves the levels for `instrument` in `dir`, creating it if needed
+    pub fn save(&self, dir: &Path, instrument: &str) -> Result<(), Error> {
+        let path = dir.join(format!("{instrument}.toml"));
+        let output = toml::to_string(self)
+            .into_report()
+            .change_context(Error::new("Couldn't serialize the levels"))?;
+        fs::create_dir_all(dir)
+            .and_then(|_| fs::write(&path, output))
+            .into_report()
+            .change_context(Error::new("Couldn't save the levels"))
+            .attach_printable_lazy(|| format!("Path: {}", path.display()))
+    }
+
+    /// Forgets the levels saved for `instrument` in `dir`, so the next run
+    /// finds new ones. Fine if there aren't any
+    pub fn remove(dir: &Path, instrument: &str) -> Result<(), Error> {
+        let path = dir.join(format!("{instrument}.toml"));
+        match fs::remove_file(&path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err)
+                .into_report()
+                .change_context(Error::new("Couldn't remove the saved levels"))
+                .attach_printable_lazy(|| format!("Path: {}", path.display())),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The support and resistance of the latest swings in the renko bricks of
+/// `closes`, as `(support, resistance)`. None if there aren't enough bricks
+/// to fill a pivot window, or the swings don't give both yet. Backtests call
+/// this for every candle, so it doesn't log the bricks or pivots
+pub fn find(
+    closes: impl IntoIterator<Item = f32>,
+    brick_size: f32,
+    pivot_window: usize,
+) -> Result<Option<(f32, f32)>, Error> {
+    let candles: Vec<RenkoCandle> = closes.into_iter().renko(brick_size).collect();
+    // Run higher high, lower low
+    let support_and_resistance = match pivots(candles.as_slice(), pivot_window) {
+        Ok(pivots) => {
+            let SupportAndResistance {
+                support,
+                resistance,
+            } = pivots.high_low_swing().support_and_resistance();
+            support.zip(resistance)
+        }
+        Err(AlgorithmsError::WindowTooBig { .. }) => None,
+        Err(err) => bail!(Error::new(format!("Couldn't find pivots: {err}"))),
+    };
+    Ok(support_and_resistance)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use oanda::model::{candle::CandlestickData, price::from_f32};
+
+    fn levels() -> Levels {
+        Levels {
+            support: 1.05,
+            resistance: 1.1,
+            brick_size: BrickSize::FixedPips(10.0),
+            granularity: Granularity::M15,
+            from: Utc.with_ymd_and_hms(2023, 5, 1, 0, 0, 0).unwrap(),
+            to: Utc.with_ymd_and_hms(2023, 5, 3, 0, 0, 0).unwrap(),
+        }
+    }
+
+    fn candle(time: DateTime<Utc>, close: f32) -> Candle {
+        let close = from_f32(close);
+        Candle {
+            time,
+            bid: None,
+            ask: None,
+            mid: Some(CandlestickData {
+                o: close,
+                h: close,
+                l: close,
+                c: close,
+            }),
+            volume: 1,
+            complete: true,
+        }
+    }
+
+    #[test]
+    fn broken() {
+        let levels = levels();
+        let later = levels.to + Duration::minutes(15);
+        assert!(!levels.is_broken_by(&[candle(later, 1.07)]));
+        assert!(levels.is_broken_by(&[candle(later, 1.07), candle(later, 1.11)]));
+        assert!(levels.is_broken_by(&[candle(later, 1.04)]));
+        // Candles the levels were found from don't count
+        assert!(!levels.is_broken_by(&[candle(levels.to, 1.2)]));
+    }
+
+    #[test]
+    fn save_and_load() {
+        let dir = std::env::temp_dir().join(format!("trader-levels-{}", std::process::id()));
+        assert_eq!(None, Levels::load(&dir, "EUR_USD").unwrap());
+        let levels = levels();
+        levels.save(&dir, "EUR_USD").unwrap();
+        assert_eq!(Some(levels), Levels::load(&dir, "EUR_USD").unwrap());
+        Levels::remove(&dir, "EUR_USD").unwrap();
+        assert_eq!(None, Levels::load(&dir, "EUR_USD").unwrap());
+        Levels::remove(&dir, "EUR_USD").unwrap();
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,18 +1,52 @@
 use algorithms::{
-    pivots, Atr, IntoRenkoIterator, IntoSupportAndResistance, IntoSwingStatusIter, RenkoCandle,
-    SupportAndResistance,
+    Adx, AnalysisSnapshot, Hurst, IntoAdxIterator, IntoAtrIter, IntoHurstIterator, RenkoReversal,
 };
-use error_stack::{bail, report, Result, ResultExt};
+use chrono::{DateTime, Duration, Utc};
+use error_stack::{bail, report, IntoReport, Result, ResultExt};
 use oanda::{
-    client::instrument::Instrument,
+    client::{instrument::Instrument, pricing::HaltChange},
     host::Host::Dev,
-    model::{candle::CandlestickGranularity as Granularity, instrument::PricingComponent, Candle},
+    model::{
+        candle::CandlestickGranularity as Granularity, instrument::PricingComponent,
+        market_hours::is_forex_market_open, price::to_f32, AccountMode, Candle,
+        Instrument as InstrumentDetails,
+    },
     Client,
 };
-use std::env;
+use std::{env, path::Path};
+mod backfill;
+mod bans;
+mod brick_size;
+mod calendar;
+mod command;
+mod config;
+mod debug_dump;
+mod equity_curve;
 mod error;
+mod halts;
+mod init;
+mod levels;
+mod margin;
+mod reoptimize;
+mod signal_score;
+mod tags;
+mod trading_day;
+use backfill::backfill;
+use bans::Bans;
+use command::Command;
+use config::{AlertLevel, Config, DrawdownAlerts, InstrumentSettings, Reoptimize, Strategy};
+use debug_dump::Decision;
+use equity_curve::EquityCurve;
 use error::Error;
-use tracing::{debug, info, instrument};
+use levels::Levels;
+use margin::MarginCheck;
+use reoptimize::{Backtester, LastRun, Params};
+use signal_score::SignalScore;
+use tags::TradeTags;
+use tracing::{debug, error, info, instrument, warn};
+
+/// We keep one position per instrument, and count on a sell closing a buy
+const SUPPORTED_ACCOUNT_MODE: AccountMode = AccountMode::Netting;
 
 #[tokio::main]
 async fn main() -> Result<(), Error> {
@@ -20,19 +54,97 @@ async fn main() -> Result<(), Error> {
     tracing_subscriber::fmt()
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
         .init();
+    let command = Command::parse(env::args().skip(1))?;
+    // Setting up doesn't need a config, and there may not be one yet
+    if let Some(Command::Init(dir)) = &command {
+        for path in init::init(dir)? {
+            println!("Wrote {}", path.display());
+        }
+        println!(
+            "Fill in OANDA_TOKEN in {}, then install the units as trader.service says",
+            init::ENV_FILE
+        );
+        return Ok(());
+    }
+    let config = Config::load()?;
+    // Everything that affects what we trade, so runs can be reproduced
+    info!(?config, "Effective config");
+    if let Some(command) = command {
+        return run_command(&command, &config).await;
+    }
 
+    // Before trade(), so it's watched on runs that don't get as far as trading
+    let (client, account_id) = connect().await?;
+    check_drawdown(&client, &account_id, &config.drawdown, Utc::now()).await?;
     // Get a list of open trades
-    trade("EUR_USD")
+    let traded = trade("EUR_USD", &config)
         .await
-        .attach_printable_lazy(|| "Instrument: eur_usd")?;
-    Ok(())
+        .attach_printable_lazy(|| "Instrument: eur_usd");
+    // It only needs candles, so a trade run that failed or was skipped doesn't hold it up
+    if let Err(err) = reoptimize_if_due("EUR_USD", &config).await {
+        error!("Couldn't re-optimize the strategy settings: {err:?}");
+    }
+    traded
 }
 
-#[instrument]
-async fn trade(instrument: &str) -> Result<(), Error> {
+#[instrument(skip(config))]
+async fn trade(instrument: &str, config: &Config) -> Result<(), Error> {
     info!("trade start");
-    let token = env::var("OANDA_TOKEN").expect("No OANDA_TOKEN environment variable");
-    let client = Client::new(token, Dev);
+    let settings = config.instrument(instrument);
+    info!(?settings, "Instrument settings");
+    let now = Utc::now();
+    // Over the weekend the last candles are days old; don't analyse them
+    if !is_forex_market_open(now) {
+        info!("The market is closed. Not trading {instrument}");
+        return Ok(());
+    }
+    let bans = Bans::load(&config.bans_file)?;
+    if let Some(ban) = bans.get(instrument, now) {
+        info!(
+            outcome = "skipped",
+            reason = "banned",
+            until = %ban.until,
+            operator = ban.operator,
+            "{instrument} is banned. Not trading it"
+        );
+        return Ok(());
+    }
+    let trading_day = &config.trading_day;
+    info!(day = %trading_day.date(now), started = %trading_day.start(now), "Trading day");
+    if trading_day.is_near_rollover(now) {
+        info!("Too close to the daily rollover. Not trading {instrument}");
+        return Ok(());
+    }
+    // No candles will come on a holiday, and we don't enter just before an early close
+    if config.holidays.is_closed(now, trading_day) {
+        info!("The market is closed for a holiday. Not trading {instrument}");
+        return Ok(());
+    }
+    if config.holidays.is_near_close(now, trading_day) {
+        info!("Too close to the market's early close. Not trading {instrument}");
+        return Ok(());
+    }
+    let (client, account_id) = connect().await?;
+    let mode = client
+        .account(&account_id)
+        .mode()
+        .await
+        .change_context(Error::new("Couldn't get the account mode"))?;
+    if mode != SUPPORTED_ACCOUNT_MODE {
+        bail!(Error::new(format!(
+            "Account {account_id} is a {mode} account. Only {SUPPORTED_ACCOUNT_MODE} accounts are supported"
+        )));
+    }
+    // During a halt every order would be rejected; leave it for the next run
+    if let Some(since) = check_halt(&client, &account_id, instrument, &config.halts_file).await? {
+        info!(
+            outcome = "skipped",
+            reason = "halted",
+            %since,
+            "{instrument} is halted. Not trading it until it's tradeable again"
+        );
+        return Ok(());
+    }
     // Ask for the last candle so we can get the latest bid and ask prices to decide whether to enter the trade or not
     // We're doing it in the background, because I wanted to have the information ready
     // TODO: After consideration, it's probably better and easier to just wait for the last candle at the end
@@ -52,25 +164,60 @@ async fn trade(instrument: &str) -> Result<(), Error> {
                 .change_context(Error::new("Couldn't get the last candle"))
         })
     };
-    // Get 200 historic candles (the maximum the API allows)
+    // Get the historic candles to find the ATR and levels in
     debug!("Getting candles");
     let eur_usd = client.instrument(instrument);
-    let response = eur_usd
+    let mut response = eur_usd
         .candles()
-        .granularity(Granularity::M15)
-        .count(200)
+        .granularity(settings.granularity)
+        .count(config.candle_count.into())
         .build()
         .send()
         .await
         .change_context(Error::new("Couldn't download the candles"))?;
-    // Get the 14 ATR
-    let Some(atr) = response.candles[(response.candles.len() - 14)..]
+    response.candles = backfill(&eur_usd, settings.granularity, response.candles).await?;
+    // The Wilder smoothed ATR as of the latest candle
+    let Some(atr) = response
+        .candles
         .iter()
-        .atr() else { bail!(Error::new("Unable to calculate atr for {instrument}."))};
+        .rolling_atr(settings.atr_period)
+        .last()
+        .flatten()
+    else {
+        bail!(Error::new(format!(
+            "Unable to calculate atr for {instrument}."
+        )))
+    };
     debug!("atr: {atr:#?}");
-
-    let (support, resistance) = support_and_resistance(&eur_usd, response.candles, atr).await?;
-    debug!("support: {support:#?} resistance: {resistance:#?}");
+    let adx = response.candles.iter().adx(Adx::DEFAULT_PERIOD).last();
+    debug!(?adx, "ADX");
+    let hurst = response
+        .candles
+        .iter()
+        .hurst(Hurst::DEFAULT_PERIOD)
+        .last()
+        .flatten();
+    debug!(?hurst, "Hurst exponent");
+    // With an A/B comparison both strategies look at the same candles
+    let strategies = config.strategies();
+    let mut levels = Vec::with_capacity(strategies.len());
+    for strategy in &strategies {
+        let found = find_levels(
+            &client,
+            &account_id,
+            &eur_usd,
+            &response.candles,
+            atr,
+            strategy,
+            &settings,
+        )
+        .await?;
+        levels.push(found);
+    }
+    if levels.iter().all(Option::is_none) {
+        // A normal outcome; the market just has no setup for us right now
+        return Ok(());
+    }
 
     // Now we have our support and resistance, get the last candle with bid and ask prices to see what we're risking
     let Some(last_candle) = last_candle_handle
@@ -81,7 +228,7 @@ async fn trade(instrument: &str) -> Result<(), Error> {
             ))
         })??
         .candles.into_iter().last() else { bail!(Error::new("Asked for the last candle and got noting"))};
-    let Some(gap) = last_candle.bid.as_ref().zip(last_candle.ask.as_ref()).map(|(bid, ask)| ask.c - bid.c) else { return Err(report!(Error::new("last_candle doesn't have bid and ask prices")).attach_printable("last_candle:#?"))};
+    let Some(gap) = last_candle.bid.as_ref().zip(last_candle.ask.as_ref()).map(|(bid, ask)| to_f32(ask.c) - to_f32(bid.c)) else { return Err(report!(Error::new("last_candle doesn't have bid and ask prices")).attach_printable("last_candle:#?"))};
     debug!(
         "Gap is {gap}. ATR is {atr}. Gap is {}% of ATR",
         gap / atr * 100.0
@@ -90,49 +237,590 @@ async fn trade(instrument: &str) -> Result<(), Error> {
     // See if we want to buy or sell
     // If the current price is less than one ATR over support buy
     debug!("last_candle: {last_candle:#?}");
-    let Some(last_buy_price) = last_candle.bid.as_ref().map(|bid| bid.c) else {
+    let Some(last_buy_price) = last_candle.bid.as_ref().map(|bid| to_f32(bid.c)) else {
         return Err(report!(Error::new("The last candle doesn't have a close bid price"))
             .attach_printable(format!("Last candle: {last_candle:#?}")));
     };
-    debug!("last_buy_price: {last_buy_price:#?}\nresistance: {resistance:#?}");
-    if last_buy_price > resistance && last_buy_price < resistance + atr {
-        info!("Buying")
+    debug!("last_buy_price: {last_buy_price:#?}");
+    for (strategy, levels) in strategies.iter().zip(levels) {
+        let Some(Levels {
+            support,
+            resistance,
+            ..
+        }) = levels
+        else {
+            continue;
+        };
+        let (variant, live) = (strategy.variant, strategy.live);
+        let config = &strategy.config;
+        // Why the signal wasn't taken, as logged. Every signal gets dumped,
+        // so the checks don't `continue`
+        let skipped = 'judge: {
+            if last_buy_price <= resistance
+                || last_buy_price >= resistance + atr * config.entry_atr_multiple
+            {
+                break 'judge Some("entry_band");
+            }
+            if let Some(min_adx) = config.min_adx {
+                let trending = adx.is_some_and(|reading| reading.adx >= min_adx);
+                if !trending {
+                    info!(
+                        %variant,
+                        outcome = "skipped",
+                        reason = "no_trend",
+                        ?adx,
+                        min_adx,
+                        "The market isn't trending enough. Not buying"
+                    );
+                    break 'judge Some("no_trend");
+                }
+            }
+            if let Some(min_hurst) = config.min_hurst {
+                if !hurst.is_some_and(|hurst| hurst >= min_hurst) {
+                    info!(
+                        %variant,
+                        outcome = "skipped",
+                        reason = "mean_reverting",
+                        ?hurst,
+                        min_hurst,
+                        "The market keeps reverting to the mean. Not buying"
+                    );
+                    break 'judge Some("mean_reverting");
+                }
+            }
+            let score = SignalScore::long_breakout(&response.candles, resistance, atr, gap);
+            let total = score.total();
+            // Recorded to calibrate the scoring against how the trades turn out
+            info!(%variant, live, ?score, total, "Signal score");
+            if total < config.min_signal_score {
+                info!(
+                    %variant,
+                    outcome = "skipped",
+                    reason = "low_score",
+                    "The signal scored {total}, under the minimum of {}. Not buying",
+                    config.min_signal_score
+                );
+                break 'judge Some("low_score");
+            }
+            let check = margin_check(
+                &client,
+                &account_id,
+                instrument,
+                last_buy_price,
+                support,
+                settings.risk,
+            )
+            .await?;
+            let usage = check.projected_usage();
+            if !check.allows(config.max_margin_usage) {
+                info!(
+                    %variant,
+                    outcome = "skipped",
+                    reason = "margin",
+                    ?check,
+                    usage,
+                    max = config.max_margin_usage,
+                    "Buying would use too much margin. Not buying"
+                );
+                break 'judge Some("margin");
+            }
+            None
+        };
+        if skipped.is_none() {
+            let tags = TradeTags::new(strategy, instrument, now)?;
+            let extensions = tags.client_extensions()?;
+            if live {
+                info!(%variant, risk = settings.risk, ?extensions, "Buying")
+            } else {
+                info!(%variant, risk = settings.risk, ?extensions, "Paper buying")
+            }
+        }
+        if let Some(dir) = &config.debug_dump_dir {
+            let decision = Decision {
+                instrument,
+                variant,
+                time: now,
+                day: trading_day.date(now),
+                skipped,
+                support,
+                resistance,
+            };
+            let dumped = dump_signal(
+                &client,
+                &account_id,
+                &response.candles,
+                atr,
+                config,
+                dir,
+                &decision,
+            )
+            .await;
+            if let Err(err) = dumped {
+                warn!("Couldn't dump the signal for debugging: {err:?}");
+            }
+        }
     }
     // todo!("Sell");
     Ok(())
 }
 
+/// Runs a manual command instead of trading. Who ran it and what happened
+/// are logged. See [`command`]
+#[instrument(skip(config))]
+async fn run_command(command: &Command, config: &Config) -> Result<(), Error> {
+    let operator = command::operator()?;
+    info!(operator, %command, "Manual command");
+    match command {
+        Command::CloseTrade(trade) => {
+            let (client, account_id) = connect().await?;
+            let response = client
+                .trade(&account_id)
+                .close(trade)
+                .await
+                .change_context(Error::new("Couldn't close the trade"))?;
+            info!(
+                operator,
+                cancelled = response.was_cancelled(),
+                transactions = ?response.related_transaction_ids,
+                "Closed {trade}"
+            );
+        }
+        Command::CancelOrder(order) => {
+            let (client, account_id) = connect().await?;
+            let response = client
+                .order(&account_id)
+                .cancel(order)
+                .await
+                .change_context(Error::new("Couldn't cancel the order"))?;
+            info!(
+                operator,
+                transactions = ?response.related_transaction_ids,
+                "Cancelled {order}"
+            );
+        }
+        Command::Ban { instrument, hours } => {
+            let now = Utc::now();
+            let until = now + Duration::hours((*hours).into());
+            let mut bans = Bans::load(&config.bans_file)?;
+            bans.ban(instrument, until, &operator, now);
+            bans.save(&config.bans_file)?;
+            info!(operator, %until, "Banned {instrument}");
+        }
+        Command::Reanalyse(instrument) => {
+            for strategy in config.strategies() {
+                Levels::remove(&strategy.config.levels_dir, instrument)?;
+            }
+            info!(operator, "Forgot the saved levels for {instrument}");
+            trade(instrument, config).await?;
+        }
+        Command::Reoptimize(instrument) => {
+            let Some(settings) = &config.reoptimize else {
+                bail!(Error::new(
+                    "Re-optimization isn't set up. Add a [reoptimize] table to the config"
+                ))
+            };
+            reoptimize(instrument, config, settings).await?;
+        }
+        Command::Init(_) => unreachable!("init is run before the config is loaded"),
+    }
+    Ok(())
+}
+
+/// Runs [`reoptimize`] if it's set up and it's been `every_days` since it
+/// last ran
+async fn reoptimize_if_due(instrument: &str, config: &Config) -> Result<(), Error> {
+    let Some(settings) = &config.reoptimize else {
+        return Ok(());
+    };
+    let last = LastRun::load(settings)?;
+    if !LastRun::is_due(last.as_ref(), settings, Utc::now()) {
+        return Ok(());
+    }
+    reoptimize(instrument, config, settings).await
+}
+
+/// Grid searches the strategy settings on the latest `instrument` candles,
+/// and writes a config with the winner if it beats the current settings.
+/// See [`reoptimize`]
+#[instrument(skip(config, settings))]
+async fn reoptimize(instrument: &str, config: &Config, settings: &Reoptimize) -> Result<(), Error> {
+    let now = Utc::now();
+    let instrument_settings = config.instrument(instrument);
+    let (client, account_id) = connect().await?;
+    let candles = client
+        .instrument(instrument)
+        .candles()
+        .granularity(instrument_settings.granularity)
+        .count(settings.candles.into())
+        .build()
+        .send()
+        .await
+        .change_context(Error::new("Couldn't download the candles to backtest"))?
+        .candles;
+    let pip_location = pip_location(&client, &account_id, instrument).await?;
+    let backtester = Backtester::new(
+        &candles,
+        instrument_settings.atr_period,
+        config.candle_count.into(),
+        pip_location,
+        settings.target_r,
+    );
+    let optimization = reoptimize::grid_search(&backtester, settings, Params::of(config))?;
+    let before = &optimization.before;
+    info!(
+        params = %before.params,
+        stats = ?before.stats,
+        total_r = before.total_r(),
+        candles = candles.len(),
+        "Backtest of the current settings"
+    );
+    match optimization.winner() {
+        Some(winner) => {
+            info!(
+                params = %winner.params,
+                stats = ?winner.stats,
+                total_r = winner.total_r(),
+                "Backtest of the best settings"
+            );
+            let path = reoptimize::write(settings, &winner.params)?;
+            if settings.apply {
+                info!(path = %path.display(), "Applied the re-optimized settings")
+            } else {
+                info!(path = %path.display(), "Proposed the re-optimized settings")
+            }
+        }
+        None => info!(
+            min_trades = settings.min_trades,
+            "Nothing in the grid beat the current settings"
+        ),
+    }
+    LastRun { at: now }.save(settings)
+}
+
+/// A client for the token in `OANDA_TOKEN`, once it's been checked, and the
+/// account to trade in
+async fn connect() -> Result<(Client, String), Error> {
+    let token = env::var("OANDA_TOKEN").expect("No OANDA_TOKEN environment variable");
+    let client = Client::new(token, Dev);
+    client
+        .verify()
+        .await
+        .change_context(Error::new("The OANDA token didn't work"))?;
+    let account_id = account_id(&client).await?;
+    Ok((client, account_id))
+}
+
+/// The account from the `OANDA_ACCOUNT_ID` environment variable, or the
+/// first account the token can see
+async fn account_id(client: &Client) -> Result<String, Error> {
+    if let Ok(account_id) = env::var("OANDA_ACCOUNT_ID") {
+        return Ok(account_id);
+    }
+    let accounts = client
+        .accounts()
+        .list()
+        .await
+        .change_context(Error::new("Couldn't list the accounts"))?;
+    let Some(account) = accounts.into_iter().next() else {
+        bail!(Error::new("No oanda accounts found"))
+    };
+    Ok(account.id)
+}
+
+/// Where the instrument's pips are, eg. -4 for EUR_USD
+async fn pip_location(client: &Client, account_id: &str, instrument: &str) -> Result<i32, Error> {
+    let details = instrument_details(client, account_id, instrument).await?;
+    Ok(details.pip_location)
+}
+
+/// What the account can trade `instrument` in, eg. its margin rate
+async fn instrument_details(
+    client: &Client,
+    account_id: &str,
+    instrument: &str,
+) -> Result<InstrumentDetails, Error> {
+    let instruments = client
+        .accounts()
+        .list_instruments(account_id)
+        .add_instrument(instrument)
+        .send()
+        .await
+        .change_context(Error::new("Couldn't get the instrument details"))?;
+    let details = instruments
+        .into_iter()
+        .find(|details| details.name == instrument);
+    let Some(details) = details else {
+        bail!(Error::new(format!("The account can't trade {instrument}")))
+    };
+    Ok(details)
+}
+
+/// Sizes the position a buy at `price` would open, risking `risk` percent of
+/// the NAV with the stop at `support`, and checks the margin it would need
+async fn margin_check(
+    client: &Client,
+    account_id: &str,
+    instrument: &str,
+    price: f32,
+    support: f32,
+    risk: f32,
+) -> Result<MarginCheck, Error> {
+    let summary = client
+        .accounts()
+        .summary(account_id)
+        .await
+        .change_context(Error::new("Couldn't get the account summary"))?;
+    let details = instrument_details(client, account_id, instrument).await?;
+    let factors = client
+        .pricing(account_id)
+        .home_conversion_factors(instrument)
+        .await
+        .change_context(Error::new("Couldn't get the home conversion factors"))?;
+    let units = details.units_for_risk(summary.nav * risk / 100.0, price - support, &factors);
+    Ok(MarginCheck {
+        nav: summary.nav,
+        margin_used: summary.margin_used,
+        new_margin: details.margin_for(units, price, &factors),
+    })
+}
+
+/// Records the account's NAV and raises an alert if the drawdown from the
+/// peak has got deep enough to call for a louder one than already raised.
+/// The alert comes with the latest NAVs
+async fn check_drawdown(
+    client: &Client,
+    account_id: &str,
+    alerts: &DrawdownAlerts,
+    now: DateTime<Utc>,
+) -> Result<(), Error> {
+    let summary = client
+        .accounts()
+        .summary(account_id)
+        .await
+        .change_context(Error::new("Couldn't get the account summary"))?;
+    let mut curve = EquityCurve::load(&alerts.history_file)?;
+    let level = curve.record(now, summary.nav, alerts);
+    curve.save(&alerts.history_file)?;
+    let Some(level) = level else {
+        return Ok(());
+    };
+    let chart = match &alerts.chart_dir {
+        Some(dir) => Some(curve.save_chart(dir, now)?),
+        None => None,
+    };
+    let (drawdown, peak, nav) = (curve.drawdown(), curve.peak, summary.nav);
+    let chart = chart.as_ref().map(|path| path.display());
+    let recent = curve.excerpt();
+    match level {
+        AlertLevel::Info => {
+            info!(
+                alert = "info",
+                drawdown,
+                peak,
+                nav,
+                chart = ?chart,
+                recent,
+                "The account is {drawdown:.1}% under its peak"
+            )
+        }
+        AlertLevel::Warning => {
+            warn!(
+                alert = "warning",
+                drawdown,
+                peak,
+                nav,
+                chart = ?chart,
+                recent,
+                "The account is {drawdown:.1}% under its peak"
+            )
+        }
+        AlertLevel::Page => {
+            error!(
+                alert = "page",
+                drawdown,
+                peak,
+                nav,
+                chart = ?chart,
+                recent,
+                "The account is {drawdown:.1}% under its peak"
+            )
+        }
+    }
+    Ok(())
+}
+
+/// Feeds the latest price of `instrument` to the halts saved in
+/// `halts_file`, and alerts if it's just been halted or just resumed.
+/// Returns when it stopped trading if it's halted
+async fn check_halt(
+    client: &Client,
+    account_id: &str,
+    instrument: &str,
+    halts_file: &Path,
+) -> Result<Option<DateTime<Utc>>, Error> {
+    let price = client
+        .pricing(account_id)
+        .price(instrument)
+        .await
+        .change_context(Error::new("Couldn't check for a trading halt"))?;
+    let mut halts = halts::load(halts_file)?;
+    let Some(change) = halts.update(&price) else {
+        return Ok(halts.halted_since(instrument));
+    };
+    halts::save(&halts, halts_file)?;
+    match change {
+        HaltChange::Halted { instrument, since } => {
+            warn!(alert = "warning", %since, "OANDA halted trading in {instrument}")
+        }
+        HaltChange::Resumed {
+            instrument,
+            halted_for,
+        } => warn!(
+            alert = "warning",
+            halted_minutes = halted_for.num_minutes(),
+            "{instrument} is trading again"
+        ),
+    }
+    Ok(halts.halted_since(instrument))
+}
+
+/// The saved levels for `strategy` if they're still good, or new ones
+/// found in `candles`, fetching more if needed. None if there's no setup
+#[instrument(skip_all, fields(variant = %strategy.variant))]
+async fn find_levels(
+    client: &Client,
+    account_id: &str,
+    instrument: &Instrument<'_>,
+    candles: &[Candle],
+    atr: f32,
+    strategy: &Strategy,
+    settings: &InstrumentSettings,
+) -> Result<Option<Levels>, Error> {
+    let config = &strategy.config;
+    let name = &instrument.instrument;
+    let saved = Levels::load(&config.levels_dir, name).unwrap_or_else(|err| {
+        warn!("Ignoring the saved levels: {err:?}");
+        None
+    });
+    let levels = match saved {
+        Some(levels)
+            if levels.brick_size == config.brick_size
+                && levels.granularity == settings.granularity
+                && !levels.is_broken_by(candles) =>
+        {
+            info!(from = %levels.from, to = %levels.to, "Reusing the saved levels");
+            levels
+        }
+        _ => {
+            let last_close = candles.last().and_then(|candle| candle.mid.as_ref());
+            let Some(price) = last_close.map(|mid| to_f32(mid.c)) else {
+                bail!(Error::new("The last candle doesn't have a mid price"))
+            };
+            let pip_location = pip_location(client, account_id, name).await?;
+            let brick_size = config.brick_size.size(atr, price, pip_location);
+            // Recorded so a run's levels can be reproduced
+            info!(mode = %config.brick_size, brick_size, atr, price, "Renko brick size");
+            let levels =
+                support_and_resistance(instrument, candles.to_vec(), config, settings, brick_size)
+                    .await?;
+            let Some(levels) = levels else {
+                return Ok(None);
+            };
+            if let Err(err) = levels.save(&config.levels_dir, name) {
+                warn!("Couldn't save the levels for next time: {err:?}");
+            }
+            levels
+        }
+    };
+    debug!(
+        "support: {:#?} resistance: {:#?}",
+        levels.support, levels.resistance
+    );
+    Ok(Some(levels))
+}
+
+/// Redoes the analysis on `candles` and saves it with a chart under `dir`.
+/// See [`debug_dump`]
+async fn dump_signal(
+    client: &Client,
+    account_id: &str,
+    candles: &[Candle],
+    atr: f32,
+    config: &Config,
+    dir: &Path,
+    decision: &Decision<'_>,
+) -> Result<(), Error> {
+    let Some(price) = candles
+        .last()
+        .and_then(|candle| candle.mid.as_ref())
+        .map(|mid| to_f32(mid.c))
+    else {
+        bail!(Error::new("The last candle doesn't have a mid price"))
+    };
+    let pip_location = pip_location(client, account_id, decision.instrument).await?;
+    let brick_size = config.brick_size.size(atr, price, pip_location);
+    let snapshot = AnalysisSnapshot::capture(
+        candles,
+        brick_size,
+        RenkoReversal::default(),
+        config.pivot_window,
+    )
+    .into_report()
+    .change_context(Error::new("Couldn't redo the analysis"))?;
+    debug_dump::dump(dir, decision, &snapshot)
+}
+
 /// Returns support and resistance lines given some candles
 ///
-/// Uses the instrument client to get more candes if more are needed
+/// Uses the instrument client to get more candes if more are needed, up to
+/// `config.max_history`. Returns None if there are none within that
 async fn support_and_resistance(
     instrument: &Instrument<'_>,
     mut normal_candles: Vec<Candle>,
-    atr: f32,
-) -> Result<(f32, f32), Error> {
+    config: &Config,
+    settings: &InstrumentSettings,
+    brick_size: f32,
+) -> Result<Option<Levels>, Error> {
+    let Some(to) = normal_candles.last().map(|candle| candle.time) else {
+        bail!(Error::new("No candles to find support and resistance in"))
+    };
     // We'll keep looping until we get support and resistance lines
     // NOTE: Consider turning the 200 candles thing into a stream
     // NOTE: Maybe we don't want to just throw away the candles ?
     loop {
-        // Turn the candles into renko candles
-        let candles: Vec<RenkoCandle> = normal_candles
+        let closes = normal_candles
             .iter()
-            .flat_map(|candle| candle.mid.as_ref().map(|mid| mid.c))
-            .renko(atr)
-            .collect();
-        debug!("renko: {candles:#?}");
-        // Run higher high, lower low
-        let pivots = pivots(candles.as_slice(), 5);
-        debug!("pivots: {:#?}", pivots.clone().collect::<Vec<_>>());
-        let SupportAndResistance {
-            support,
-            resistance,
-        } = pivots.into_iter().high_low_swing().support_and_resistance();
-        if let Some((support, resistance)) = support.zip(resistance) {
+            .flat_map(|candle| candle.mid.as_ref().map(|mid| to_f32(mid.c)));
+        // None if there aren't enough renko candles to fill a window yet; we'll get more below
+        let support_and_resistance = levels::find(closes, brick_size, config.pivot_window)?;
+        if let Some((support, resistance)) = support_and_resistance {
             // If we have support and resistance lines, let's go
-            break Ok((support, resistance));
+            let Some(from) = normal_candles.first().map(|candle| candle.time) else {
+                bail!(Error::new("Found levels without any candles"))
+            };
+            break Ok(Some(Levels {
+                support,
+                resistance,
+                brick_size: config.brick_size,
+                granularity: settings.granularity,
+                from,
+                to,
+            }));
+        }
+        let span = normal_candles
+            .first()
+            .map_or_else(Duration::zero, |first| to - first.time);
+        if config.max_history.is_reached(normal_candles.len(), span) {
+            info!(
+                outcome = "no_setup",
+                reason = "history_limit",
+                candles = normal_candles.len(),
+                limit = %config.max_history,
+                "No support and resistance within the history limit"
+            );
+            break Ok(None);
         }
-        // If we don't have support and resistance lines, go back and get another 200 candles
+        // If we don't have support and resistance lines, go back and get more candles
         debug!(
             "Getting more candles. Currently have {}",
             normal_candles.len()
@@ -142,15 +830,25 @@ async fn support_and_resistance(
         let end_time = first_candle.time;
         let mut new_candles = instrument
             .candles()
+            .granularity(settings.granularity)
             .to(end_time)
-            .count(200)
+            .count(config.candle_count.into())
             .build()
             .send()
             .await
             .change_context(Error::new("Couldn't download subsequent candles"))?
             .candles;
+        if new_candles.is_empty() {
+            info!(
+                outcome = "no_setup",
+                reason = "no_more_history",
+                candles = normal_candles.len(),
+                "No support and resistance in all the history there is"
+            );
+            break Ok(None);
+        }
         debug_assert_ne!(new_candles.last(), normal_candles.first(), "You shouldn't have a duplicate candle in there, delete the last candle from what you receive. Maybe try .include_first(false)");
         new_candles.extend(normal_candles);
-        normal_candles = new_candles;
+        normal_candles = backfill(instrument, settings.granularity, new_candles).await?;
     }
 }
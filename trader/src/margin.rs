@@ -0,0 +1,52 @@
+//! Keeps the account's margin usage down before we add to it. A new position
+//! is only entered if the margin already used plus what the position would
+//! need stays under `max_margin_usage` of the NAV.
+//!
+//! The new margin is added on top even when it would net against a position
+//! we already hold, so the check errs on the side of not trading.
+
+/// What a new position would do to the account's margin
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MarginCheck {
+    /// The account's net asset value in the home currency
+    pub nav: f32,
+    /// The margin the open positions use now
+    pub margin_used: f32,
+    /// The margin the new position would need
+    pub new_margin: f32,
+}
+
+impl MarginCheck {
+    /// The fraction of the NAV that would be used as margin once the new
+    /// position is open. Infinite if the NAV is gone
+    pub fn projected_usage(&self) -> f32 {
+        if self.nav <= 0.0 {
+            return f32::INFINITY;
+        }
+        (self.margin_used + self.new_margin) / self.nav
+    }
+
+    /// Whether the new position keeps margin usage at or under `max_usage`
+    pub fn allows(&self, max_usage: f32) -> bool {
+        self.projected_usage() <= max_usage
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn projected_usage() {
+        let check = MarginCheck {
+            nav: 1000.0,
+            margin_used: 200.0,
+            new_margin: 100.0,
+        };
+        assert_eq!(0.3, check.projected_usage());
+        assert!(check.allows(0.3));
+        assert!(!check.allows(0.25));
+        let broke = MarginCheck { nav: 0.0, ..check };
+        assert!(!broke.allows(1.0));
+    }
+}
@@ -0,0 +1,592 @@
+//! Re-runs the grid search for the strategy settings on recent history. The
+//! current settings and every combination in the [`Reoptimize`] lists are
+//! backtested on the same candles, and the one that made the most R over at
+//! least `min_trades` trades wins, if it beat the current settings.
+//!
+//! The backtest is simpler than a live run. It buys at the close of a candle
+//! that's above resistance by less than `entry_atr_multiple` ATRs, with the
+//! stop at support and a take profit `target_r` R away, one trade at a time.
+//! The levels are found in the last `candle_count` closes, without looking
+//! further back, and the signal score, ADX, Hurst and margin checks are left
+//! out.
+use std::{fmt, fs, io::ErrorKind, path::PathBuf};
+
+use algorithms::{
+    Close, ExcursionTracker, High, IntoAtrIter, Low, SignalOutcome, SignalStats, TradeDirection,
+};
+use chrono::{DateTime, Duration, Utc};
+use error_stack::{bail, IntoReport, Result, ResultExt};
+use oanda::model::Candle;
+use serde::{Deserialize, Serialize};
+use toml_edit::{DocumentMut, InlineTable, Item, TableLike, Value};
+
+use crate::{
+    brick_size::BrickSize,
+    config::{Config, Reoptimize},
+    error::Error,
+    levels,
+};
+
+/// The strategy settings the grid search tries
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Params {
+    pub brick_size: BrickSize,
+    pub pivot_window: usize,
+    pub entry_atr_multiple: f32,
+}
+
+impl Params {
+    /// The settings `config` trades with
+    pub fn of(config: &Config) -> Self {
+        Self {
+            brick_size: config.brick_size,
+            pivot_window: config.pivot_window,
+            entry_atr_multiple: config.entry_atr_multiple,
+        }
+    }
+}
+
+impl fmt::Display for Params {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "brick size {}, pivot window {}, entry ATR multiple {}",
+            self.brick_size, self.pivot_window, self.entry_atr_multiple
+        )
+    }
+}
+
+/// Every combination of the values in `settings`, with `current`'s value
+/// for any setting that has none listed
+pub fn grid(settings: &Reoptimize, current: Params) -> Vec<Params> {
+    fn or_current<T: Copy>(values: &[T], current: T) -> Vec<T> {
+        if values.is_empty() {
+            vec![current]
+        } else {
+            values.to_vec()
+        }
+    }
+    let mut grid = Vec::new();
+    for brick_size in or_current(&settings.brick_size, current.brick_size) {
+        for pivot_window in or_current(&settings.pivot_window, current.pivot_window) {
+            for entry_atr_multiple in
+                or_current(&settings.entry_atr_multiple, current.entry_atr_multiple)
+            {
+                grid.push(Params {
+                    brick_size,
+                    pivot_window,
+                    entry_atr_multiple,
+                });
+            }
+        }
+    }
+    grid
+}
+
+/// Candles to backtest on, with what every backtest needs worked out once
+pub struct Backtester<'a> {
+    candles: &'a [Candle],
+    closes: Vec<f32>,
+    /// The ATR as of each candle
+    atrs: Vec<Option<f32>>,
+    /// How many closes the levels are looked for in
+    window: usize,
+    pip_location: i32,
+    target_r: f32,
+}
+
+impl<'a> Backtester<'a> {
+    /// `candles` need mid prices. `window` is how many candles back the
+    /// levels are looked for in, like a live run's `candle_count`
+    pub fn new(
+        candles: &'a [Candle],
+        atr_period: usize,
+        window: usize,
+        pip_location: i32,
+        target_r: f32,
+    ) -> Self {
+        Self {
+            candles,
+            closes: candles.iter().map(|candle| candle.close()).collect(),
+            // The same Wilder smoothed ATR as a live run. There isn't one for
+            // the first few candles
+            atrs: candles.iter().rolling_atr(atr_period).collect(),
+            window,
+            pip_location,
+            target_r,
+        }
+    }
+
+    /// The trades `params` would have made. One still open at the end is
+    /// closed at the last close
+    pub fn run(&self, params: &Params) -> Result<Vec<SignalOutcome<()>>, Error> {
+        let mut outcomes = Vec::new();
+        // The open trade, its stop and its target
+        let mut open: Option<(ExcursionTracker, f32, f32)> = None;
+        for (index, candle) in self.candles.iter().enumerate() {
+            if let Some((tracker, stop, target)) = &mut open {
+                tracker.update(candle);
+                // If a candle reaches both we can't tell which came first; assume the worst
+                let exit = if candle.low() <= *stop {
+                    Some(*stop)
+                } else if candle.high() >= *target {
+                    Some(*target)
+                } else {
+                    None
+                };
+                if let Some(exit) = exit {
+                    outcomes.push(tracker.outcome((), exit));
+                    open = None;
+                }
+                continue;
+            }
+            let Some(atr) = self.atrs[index] else {
+                continue;
+            };
+            let close = self.closes[index];
+            let start = (index + 1).saturating_sub(self.window);
+            let brick_size = params.brick_size.size(atr, close, self.pip_location);
+            let closes = self.closes[start..=index].iter().copied();
+            let Some((support, resistance)) =
+                levels::find(closes, brick_size, params.pivot_window)?
+            else {
+                continue;
+            };
+            if close <= resistance
+                || close >= resistance + atr * params.entry_atr_multiple
+                || support >= close
+            {
+                continue;
+            }
+            let tracker = ExcursionTracker::new(TradeDirection::Long, close, support);
+            let target = close + (close - support) * self.target_r;
+            open = Some((tracker, support, target));
+        }
+        if let Some(((tracker, ..), last)) = open.zip(self.closes.last()) {
+            outcomes.push(tracker.outcome((), *last));
+        }
+        Ok(outcomes)
+    }
+
+    /// How `params` did
+    pub fn score(&self, params: Params) -> Result<Score, Error> {
+        let outcomes = self.run(&params)?;
+        Ok(Score {
+            params,
+            stats: SignalStats::new(&outcomes),
+        })
+    }
+}
+
+/// How a set of settings did in the backtest
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Score {
+    pub params: Params,
+    /// None if it made no trades
+    pub stats: Option<SignalStats>,
+}
+
+impl Score {
+    pub fn trades(&self) -> usize {
+        self.stats.map_or(0, |stats| stats.count)
+    }
+
+    /// The R made over all the trades
+    pub fn total_r(&self) -> f32 {
+        self.stats
+            .map_or(0.0, |stats| stats.expectancy * stats.count as f32)
+    }
+}
+
+/// The result of a grid search
+#[derive(Debug, Clone, PartialEq)]
+pub struct Optimization {
+    /// How the current settings did
+    pub before: Score,
+    /// The best in the grid with at least `min_trades` trades, if any made that many
+    pub best: Option<Score>,
+}
+
+impl Optimization {
+    /// The best settings, if they made more R than the current ones
+    pub fn winner(&self) -> Option<&Score> {
+        self.best
+            .as_ref()
+            .filter(|best| best.params != self.before.params)
+            .filter(|best| best.total_r() > self.before.total_r())
+    }
+}
+
+/// Backtests `current` and the whole grid in `settings`
+pub fn grid_search(
+    backtester: &Backtester,
+    settings: &Reoptimize,
+    current: Params,
+) -> Result<Optimization, Error> {
+    let before = backtester.score(current)?;
+    let mut best: Option<Score> = None;
+    for params in grid(settings, current) {
+        let score = backtester.score(params)?;
+        if score.trades() < settings.min_trades {
+            continue;
+        }
+        if !best.is_some_and(|best| best.total_r() >= score.total_r()) {
+            best = Some(score);
+        }
+    }
+    Ok(Optimization { before, best })
+}
+
+/// `config`, the text of a config file, with `params` in place of its
+/// strategy settings. Everything else, comments and all, is left as it was
+pub fn with_params(config: &str, params: &Params) -> Result<String, Error> {
+    let mut document: DocumentMut = config
+        .parse()
+        .into_report()
+        .change_context(Error::new("Couldn't parse the config"))?;
+    // The brick size is an enum, so its table has the one key naming the mode
+    let mode = toml::Value::try_from(params.brick_size)
+        .ok()
+        .and_then(|brick_size| brick_size.as_table()?.keys().next().cloned());
+    let Some(mode) = mode else {
+        bail!(Error::new(format!(
+            "Couldn't serialize the brick size {}",
+            params.brick_size
+        )))
+    };
+    let size = float(params.brick_size.value());
+    match document
+        .get_mut("brick_size")
+        .and_then(Item::as_table_like_mut)
+    {
+        // Kept as a [brick_size] table or an inline one, whichever it was
+        Some(brick_size) => {
+            if !brick_size.contains_key(&mode) {
+                brick_size.clear();
+            }
+            set(brick_size, &mode, size);
+        }
+        None => {
+            let brick_size = InlineTable::from_iter([(mode, size)]);
+            set(document.as_table_mut(), "brick_size", brick_size.into());
+        }
+    }
+    let table = document.as_table_mut();
+    set(table, "pivot_window", (params.pivot_window as i64).into());
+    set(
+        table,
+        "entry_atr_multiple",
+        float(params.entry_atr_multiple),
+    );
+    let output = document.to_string();
+    // Never write a config the trader won't start with
+    Config::parse(&output).attach_printable("The re-optimized config doesn't validate")?;
+    Ok(output)
+}
+
+/// Sets `key` in `table` to `value`, keeping the comments around the old one
+fn set(table: &mut dyn TableLike, key: &str, mut value: Value) {
+    match table.get_mut(key) {
+        Some(item) => {
+            if let Some(old) = item.as_value() {
+                *value.decor_mut() = old.decor().clone();
+            }
+            *item = Item::Value(value);
+        }
+        None => {
+            table.insert(key, Item::Value(value));
+        }
+    }
+}
+
+/// `value` as a TOML float, written the way the f32 prints. Widened to f64
+/// an f32 like 1.1 would come out as 1.100000023841858
+fn float(value: f32) -> Value {
+    let value: f64 = value.to_string().parse().unwrap_or(value.into());
+    value.into()
+}
+
+/// Writes the config at `TRADER_CONFIG` with `params` in place of its
+/// strategy settings. It's written back over the config if `settings.apply`
+/// is set, or to `settings.proposal_file` if not. Returns the path written
+pub fn write(settings: &Reoptimize, params: &Params) -> Result<PathBuf, Error> {
+    let Some(config_path) = Config::path() else {
+        bail!(Error::new(
+            "There's no config file to re-optimize. Set TRADER_CONFIG"
+        ))
+    };
+    let config = fs::read_to_string(&config_path)
+        .into_report()
+        .change_context(Error::new("Couldn't read the config file"))
+        .attach_printable_lazy(|| format!("Path: {}", config_path.display()))?;
+    let output = with_params(&config, params)?;
+    let path = if settings.apply {
+        config_path
+    } else {
+        settings.proposal_file.clone()
+    };
+    fs::write(&path, output)
+        .into_report()
+        .change_context(Error::new("Couldn't write the re-optimized config"))
+        .attach_printable_lazy(|| format!("Path: {}", path.display()))?;
+    Ok(path)
+}
+
+/// When the job last ran, kept between runs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LastRun {
+    pub at: DateTime<Utc>,
+}
+
+impl LastRun {
+    /// The last run saved in `settings.last_run_file`; None if it's never run
+    pub fn load(settings: &Reoptimize) -> Result<Option<LastRun>, Error> {
+        let path = &settings.last_run_file;
+        let input = match fs::read_to_string(path) {
+            Ok(input) => input,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(err)
+                    .into_report()
+                    .change_context(Error::new("Couldn't read the last re-optimization"))
+                    .attach_printable_lazy(|| format!("Path: {}", path.display()))
+            }
+        };
+        toml::from_str(&input)
+            .into_report()
+            .change_context(Error::new("Couldn't parse the last re-optimization"))
+            .attach_printable_lazy(|| format!("Path: {}", path.display()))
+    }
+
+    /// Saves the run to `settings.last_run_file`
+    pub fn save(&self, settings: &Reoptimize) -> Result<(), Error> {
+        let path = &settings.last_run_file;
+        let output = toml::to_string(self)
+            .into_report()
+            .change_context(Error::new("Couldn't serialize the last re-optimization"))?;
+        fs::write(path, output)
+            .into_report()
+            .change_context(Error::new("Couldn't save the last re-optimization"))
+            .attach_printable_lazy(|| format!("Path: {}", path.display()))
+    }
+
+    /// Whether it's time to run again
+    pub fn is_due(last: Option<&LastRun>, settings: &Reoptimize, now: DateTime<Utc>) -> bool {
+        match last {
+            Some(last) => now - last.at >= Duration::days(settings.every_days.into()),
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+    use oanda::model::{candle::CandlestickData, price::from_f32};
+
+    fn candle(index: usize, close: f32) -> Candle {
+        Candle {
+            time: Utc.with_ymd_and_hms(2023, 5, 1, 0, 0, 0).unwrap()
+                + Duration::minutes(15 * index as i64),
+            bid: None,
+            ask: None,
+            mid: Some(CandlestickData {
+                o: from_f32(close),
+                h: from_f32(close + 0.5),
+                l: from_f32(close - 0.5),
+                c: from_f32(close),
+            }),
+            volume: 1,
+            complete: true,
+        }
+    }
+
+    /// Swings between 100 and 110 a few times, then breaks out up to 140
+    fn breakout() -> Vec<Candle> {
+        let mut closes = Vec::new();
+        for _ in 0..4 {
+            closes.extend((0..10).map(|step| 100.0 + step as f32));
+            closes.extend((0..10).map(|step| 110.0 - step as f32));
+        }
+        closes.extend((0..40).map(|step| 100.0 + step as f32));
+        closes
+            .into_iter()
+            .enumerate()
+            .map(|(index, close)| candle(index, close))
+            .collect()
+    }
+
+    fn current() -> Params {
+        Params {
+            brick_size: BrickSize::FixedPips(2.0),
+            pivot_window: 3,
+            entry_atr_multiple: 1.0,
+        }
+    }
+
+    #[test]
+    fn grids() {
+        let current = current();
+        assert_eq!(vec![current], grid(&Reoptimize::default(), current));
+        let settings = Reoptimize {
+            pivot_window: vec![3, 5, 7],
+            entry_atr_multiple: vec![0.5, 1.5],
+            ..Reoptimize::default()
+        };
+        let grid = grid(&settings, current);
+        assert_eq!(6, grid.len());
+        assert!(grid
+            .iter()
+            .all(|params| params.brick_size == current.brick_size));
+        assert_eq!(7, grid[5].pivot_window);
+        assert_eq!(1.5, grid[5].entry_atr_multiple);
+    }
+
+    #[test]
+    fn backtest() {
+        let candles = breakout();
+        // FixedPips(2.0) with a pip location of 0 is 2.0 bricks
+        let backtester = Backtester::new(&candles, 14, 200, 0, 2.0);
+        let outcomes = backtester.run(&current()).unwrap();
+        assert!(!outcomes.is_empty());
+        // It's one long rally, so the first trade makes money
+        assert!(outcomes[0].r > 0.0, "{outcomes:?}");
+        // Nothing to break out of in a flat market
+        let flat: Vec<Candle> = (0..100).map(|index| candle(index, 100.0)).collect();
+        let backtester = Backtester::new(&flat, 14, 200, 0, 2.0);
+        assert!(backtester.run(&current()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn search() {
+        let candles = breakout();
+        let backtester = Backtester::new(&candles, 14, 200, 0, 2.0);
+        let settings = Reoptimize {
+            entry_atr_multiple: vec![0.1, 1.0, 10.0],
+            min_trades: 1,
+            ..Reoptimize::default()
+        };
+        let optimization = grid_search(&backtester, &settings, current()).unwrap();
+        let best = optimization.best.unwrap();
+        assert!(best.total_r() >= optimization.before.total_r());
+        assert!(best.trades() >= 1);
+        // Nothing wins if nothing trades enough
+        let settings = Reoptimize {
+            min_trades: 1000,
+            ..settings
+        };
+        let optimization = grid_search(&backtester, &settings, current()).unwrap();
+        assert_eq!(None, optimization.winner());
+    }
+
+    #[test]
+    fn winner_must_beat_the_current_settings() {
+        let score = |params, r| Score {
+            params,
+            stats: SignalStats::new(&[SignalOutcome {
+                setup: (),
+                r,
+                mfe: 2.0,
+                mae: 1.0,
+            }]),
+        };
+        let better = Params {
+            pivot_window: 7,
+            ..current()
+        };
+        let optimization = Optimization {
+            before: score(current(), 1.0),
+            best: Some(score(better, 2.0)),
+        };
+        assert_eq!(
+            Some(7),
+            optimization.winner().map(|w| w.params.pivot_window)
+        );
+        let optimization = Optimization {
+            best: Some(score(better, 0.5)),
+            ..optimization
+        };
+        assert_eq!(None, optimization.winner());
+    }
+
+    #[test]
+    fn writes_the_params() {
+        let input = r#"# Risk a bit more
+risk = 2.0
+pivot_window = 5 # Bricks either side
+
+[brick_size]
+# The fixed size in pips
+fixed_pips = 10
+
+[trading_day]
+rollover_hour = 16
+"#;
+        let params = Params {
+            brick_size: BrickSize::FixedPips(12.5),
+            pivot_window: 7,
+            entry_atr_multiple: 0.3,
+        };
+        let expected = r#"# Risk a bit more
+risk = 2.0
+pivot_window = 7 # Bricks either side
+entry_atr_multiple = 0.3
+
+[brick_size]
+# The fixed size in pips
+fixed_pips = 12.5
+
+[trading_day]
+rollover_hour = 16
+"#;
+        assert_eq!(expected, with_params(input, &params).unwrap());
+        // A new brick size mode replaces the old one
+        let params = Params {
+            brick_size: BrickSize::AtrMultiple(1.1),
+            ..params
+        };
+        let output = with_params(input, &params).unwrap();
+        assert!(
+            output.contains("[brick_size]\natr_multiple = 1.1\n"),
+            "{output}"
+        );
+        let config = Config::parse(&output).unwrap();
+        assert_eq!(params, Params::of(&config));
+        assert_eq!(16, config.trading_day.rollover_hour);
+        // With nothing set they're all added
+        let output = with_params("", &params).unwrap();
+        assert!(
+            output.contains("brick_size = { atr_multiple = 1.1 }"),
+            "{output}"
+        );
+        assert_eq!(params, Params::of(&Config::parse(&output).unwrap()));
+    }
+
+    #[test]
+    fn last_run() {
+        let path = std::env::temp_dir().join(format!("trader-reoptimized-{}", std::process::id()));
+        let settings = Reoptimize {
+            last_run_file: path.clone(),
+            ..Reoptimize::default()
+        };
+        let now = Utc.with_ymd_and_hms(2023, 5, 1, 0, 0, 0).unwrap();
+        assert_eq!(None, LastRun::load(&settings).unwrap());
+        assert!(LastRun::is_due(None, &settings, now));
+        let last = LastRun { at: now };
+        last.save(&settings).unwrap();
+        let loaded = LastRun::load(&settings).unwrap();
+        assert_eq!(Some(&last), loaded.as_ref());
+        assert!(!LastRun::is_due(
+            loaded.as_ref(),
+            &settings,
+            now + Duration::days(6)
+        ));
+        assert!(LastRun::is_due(
+            loaded.as_ref(),
+            &settings,
+            now + Duration::days(7)
+        ));
+        fs::remove_file(path).unwrap();
+    }
+}
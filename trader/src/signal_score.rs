@@ -0,0 +1,186 @@
+//! Scores a breakout signal by how many things agree with it, so weak
+//! setups can be skipped. Each component scores from 0 (against the trade)
+//! to 1 (for it), and they're all logged so the weighting can be tuned
+//! against how trades turn out.
+use algorithms::{Atr, Close, High, IntoLinearRegression, Low};
+
+/// How many candles the trend, RSI and level touches are measured over
+const LOOKBACK: usize = 14;
+/// The squeeze compares the ATR of the last [`LOOKBACK`] candles to the ATR
+/// of this many
+const SQUEEZE_LOOKBACK: usize = LOOKBACK * 4;
+
+/// The components of a long breakout signal's score
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SignalScore {
+    /// How steeply price has been rising
+    pub trend: f32,
+    /// Momentum without being overbought
+    pub rsi: f32,
+    /// How much volatility contracted before the breakout
+    pub squeeze: f32,
+    /// How many times price tested resistance before breaking it
+    pub level_strength: f32,
+    /// How small the spread is next to the ATR
+    pub spread: f32,
+}
+
+impl SignalScore {
+    /// Scores a buy as price breaks `resistance`. `candles` are the ones the
+    /// ATR was worked out from, oldest first
+    pub fn long_breakout<C>(candles: &[C], resistance: f32, atr: f32, spread: f32) -> Self
+    where
+        C: High + Low + Close,
+    {
+        Self {
+            trend: trend(candles, atr),
+            rsi: rsi(candles).map_or(0.0, rsi_score),
+            squeeze: squeeze(candles),
+            level_strength: level_strength(candles, resistance, atr),
+            spread: (1.0 - spread / atr * 5.0).clamp(0.0, 1.0),
+        }
+    }
+
+    /// The average of the components, from 0 to 1
+    pub fn total(&self) -> f32 {
+        (self.trend + self.rsi + self.squeeze + self.level_strength + self.spread) / 5.0
+    }
+}
+
+fn last<C>(candles: &[C], count: usize) -> &[C] {
+    &candles[candles.len().saturating_sub(count)..]
+}
+
+/// 0.5 when flat, reaching 1 when price has risen an ATR over the lookback
+fn trend<C: Close>(candles: &[C], atr: f32) -> f32 {
+    let closes = last(candles, LOOKBACK).iter().map(Close::close);
+    let Some(line) = closes.linear_regression(LOOKBACK, 2.0).last() else {
+        return 0.5;
+    };
+    (0.5 + line.slope * LOOKBACK as f32 / atr / 2.0).clamp(0.0, 1.0)
+}
+
+/// The relative strength index of the last [`LOOKBACK`] closes
+fn rsi<C: Close>(candles: &[C]) -> Option<f32> {
+    let closes: Vec<f32> = last(candles, LOOKBACK + 1)
+        .iter()
+        .map(Close::close)
+        .collect();
+    if closes.len() < 2 {
+        return None;
+    }
+    let (gains, losses) = closes.windows(2).map(|pair| pair[1] - pair[0]).fold(
+        (0.0, 0.0),
+        |(gains, losses), change| {
+            if change > 0.0 {
+                (gains + change, losses)
+            } else {
+                (gains, losses - change)
+            }
+        },
+    );
+    if gains + losses == 0.0 {
+        return Some(50.0);
+    }
+    Some(100.0 * gains / (gains + losses))
+}
+
+/// Rises from an RSI of 30 to 60, and falls off again past 70 as the
+/// market gets overbought
+fn rsi_score(rsi: f32) -> f32 {
+    if rsi <= 70.0 {
+        ((rsi - 30.0) / 30.0).clamp(0.0, 1.0)
+    } else {
+        ((100.0 - rsi) / 30.0).clamp(0.0, 1.0)
+    }
+}
+
+/// 1 when the recent ATR is half (or less) of the longer term one
+fn squeeze<C: High + Low + Close>(candles: &[C]) -> f32 {
+    let recent = last(candles, LOOKBACK).iter().atr();
+    let longer = last(candles, SQUEEZE_LOOKBACK).iter().atr();
+    match recent.zip(longer) {
+        Some((recent, longer)) if longer > 0.0 => (2.0 - 2.0 * recent / longer).clamp(0.0, 1.0),
+        _ => 0.0,
+    }
+}
+
+/// How many candles came within a quarter ATR of resistance without closing
+/// above it. Three or more scores 1
+fn level_strength<C: High + Close>(candles: &[C], resistance: f32, atr: f32) -> f32 {
+    let touches = last(candles, SQUEEZE_LOOKBACK)
+        .iter()
+        .filter(|candle| candle.high() >= resistance - atr / 4.0 && candle.close() <= resistance)
+        .count();
+    (touches as f32 / 3.0).min(1.0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Candle {
+        high: f32,
+        low: f32,
+        close: f32,
+    }
+
+    impl High for Candle {
+        fn high(&self) -> f32 {
+            self.high
+        }
+    }
+
+    impl Low for Candle {
+        fn low(&self) -> f32 {
+            self.low
+        }
+    }
+
+    impl Close for Candle {
+        fn close(&self) -> f32 {
+            self.close
+        }
+    }
+
+    fn candle(close: f32, range: f32) -> Candle {
+        Candle {
+            high: close + range / 2.0,
+            low: close - range / 2.0,
+            close,
+        }
+    }
+
+    #[test]
+    fn rsi_scores() {
+        let rising: Vec<_> = (0..20).map(|i| candle(i as f32, 1.0)).collect();
+        assert_eq!(Some(100.0), rsi(&rising));
+        // Overbought
+        assert_eq!(0.0, rsi_score(100.0));
+        assert_eq!(1.0, rsi_score(65.0));
+        assert_eq!(0.0, rsi_score(25.0));
+        let flat: Vec<_> = (0..20).map(|_| candle(1.0, 1.0)).collect();
+        assert_eq!(Some(50.0), rsi(&flat));
+    }
+
+    #[test]
+    fn squeeze_and_touches() {
+        // Wide candles, then narrow ones testing 10.5 without closing above it
+        let mut candles: Vec<_> = (0..42).map(|_| candle(10.0, 2.0)).collect();
+        candles.extend((0..14).map(|_| candle(10.2, 0.5)));
+        assert_eq!(1.0, squeeze(&candles));
+        assert_eq!(1.0, level_strength(&candles, 10.5, 1.0));
+        let score = SignalScore::long_breakout(&candles, 10.5, 1.0, 0.05);
+        assert!((score.spread - 0.75).abs() < 0.0001);
+        assert_eq!(0.5, score.trend);
+    }
+
+    #[test]
+    fn trend_follows_slope() {
+        // Up 1 ATR over the lookback
+        let rising: Vec<_> = (0..14).map(|i| candle(i as f32 / 13.0, 0.1)).collect();
+        assert!(trend(&rising, 1.0) > 0.95);
+        let falling: Vec<_> = rising.iter().rev().map(|c| candle(c.close, 0.1)).collect();
+        assert!(trend(&falling, 1.0) < 0.05);
+    }
+}
@@ -0,0 +1,131 @@
+//! What we attach to each order, and so each trade, as OANDA client
+//! extensions, so a trade can be traced back to the signal and settings
+//! that opened it:
+//!
+//! - the id is the signal id, eg. `baseline-EUR_USD-20230505T133000`
+//! - the tag is the strategy, eg. `baseline`
+//! - the comment is the hash of the strategy's config, eg. `config=1f0e...`
+//!
+//! The trade's fill carries them back, so [`TradeTags::fill`] can find how a
+//! signal was filled in the transaction history.
+use chrono::{DateTime, Utc};
+use error_stack::{Result, ResultExt};
+use oanda::model::{
+    trade::ClientExtensions,
+    transaction::{OrderFill, Transaction, TransactionKind},
+};
+
+use crate::{config::Strategy, error::Error};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TradeTags {
+    /// The strategy that took the signal
+    pub strategy: String,
+    /// Unique to the signal: the strategy, instrument and time
+    pub signal_id: String,
+    /// See [`Config::hash`](crate::config::Config::hash)
+    pub config_hash: String,
+}
+
+impl TradeTags {
+    /// The tags for a signal on `instrument` at `time`, taken by `strategy`
+    pub fn new(strategy: &Strategy, instrument: &str, time: DateTime<Utc>) -> Result<Self, Error> {
+        let name = strategy.variant.to_string();
+        Ok(Self {
+            signal_id: format!("{name}-{instrument}-{}", time.format("%Y%m%dT%H%M%S")),
+            strategy: name,
+            config_hash: strategy.config.hash()?,
+        })
+    }
+
+    /// The tags as OANDA client extensions, for the order and the trade it opens
+    pub fn client_extensions(&self) -> Result<ClientExtensions, Error> {
+        ClientExtensions::builder()
+            .id(&self.signal_id)
+            .tag(&self.strategy)
+            .comment(format!("config={}", self.config_hash))
+            .build()
+            .change_context(Error::new("Couldn't tag the order"))
+    }
+
+    /// The fill among `transactions` that opened this signal's trade, found
+    /// by the signal id in its client extensions. None if it isn't there
+    pub fn fill<'a>(
+        &self,
+        transactions: impl IntoIterator<Item = &'a Transaction>,
+    ) -> Option<&'a OrderFill> {
+        let signal_id = Some(self.signal_id.as_str());
+        transactions
+            .into_iter()
+            .find_map(|transaction| match &transaction.kind {
+                TransactionKind::OrderFill(fill)
+                    if fill
+                        .client_extensions()
+                        .and_then(|extensions| extensions.id.as_deref())
+                        == signal_id =>
+                {
+                    Some(fill)
+                }
+                _ => None,
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::Config;
+    use chrono::TimeZone;
+    use oanda::model::transaction::TransactionsResponse;
+
+    #[test]
+    fn tags() {
+        let strategies = Config::default().strategies();
+        let time = Utc.with_ymd_and_hms(2023, 5, 5, 13, 30, 0).unwrap();
+        let tags = TradeTags::new(&strategies[0], "EUR_USD", time).unwrap();
+        assert_eq!("baseline-EUR_USD-20230505T133000", tags.signal_id);
+        let extensions = tags.client_extensions().unwrap();
+        assert_eq!(Some("baseline"), extensions.tag.as_deref());
+        assert_eq!(
+            Some(format!("config={}", Config::default().hash().unwrap())),
+            extensions.comment
+        );
+    }
+
+    #[test]
+    fn finds_the_fill() {
+        let strategies = Config::default().strategies();
+        let time = Utc.with_ymd_and_hms(2023, 5, 5, 13, 30, 0).unwrap();
+        let tags = TradeTags::new(&strategies[0], "EUR_USD", time).unwrap();
+        let fill = |id: &str, signal_id: &str| {
+            format!(
+                r#"{{
+                    "type": "ORDER_FILL",
+                    "orderID": "{id}",
+                    "instrument": "EUR_USD",
+                    "units": "1000",
+                    "tradeOpened": {{
+                        "tradeID": "{id}",
+                        "units": "1000",
+                        "clientExtensions": {{ "id": "{signal_id}", "tag": "baseline" }}
+                    }},
+                    "id": "{id}",
+                    "userID": 1234567,
+                    "accountID": "101-011-1234567-001",
+                    "batchID": "{id}",
+                    "time": "2023-05-05T13:30:01.000000000Z"
+                }}"#
+            )
+        };
+        let page = format!(
+            r#"{{"transactions": [{}, {}], "lastTransactionID": "7"}}"#,
+            fill("6", "baseline-EUR_USD-20230505T121500"),
+            fill("7", "baseline-EUR_USD-20230505T133000"),
+        );
+        let page: TransactionsResponse = serde_json::from_str(&page).unwrap();
+        let found = tags.fill(&page.transactions).unwrap();
+        assert_eq!("7", found.order_id);
+        let later = TradeTags::new(&strategies[0], "EUR_USD", time + chrono::Duration::hours(1));
+        assert_eq!(None, later.unwrap().fill(&page.transactions));
+    }
+}
@@ -0,0 +1,132 @@
+//! The trading day, which ends at the daily rollover (17:00 New York time by
+//! default) rather than at UTC midnight. OANDA takes its financing snapshot
+//! at the rollover and spreads widen around it, so we don't enter then.
+//! Anything counted per day should use these boundaries.
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use error_stack::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::check_range, error::Error};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TradingDay {
+    /// The timezone `rollover_hour` is in
+    pub timezone: Tz,
+    /// The hour trading days end at
+    pub rollover_hour: u32,
+    /// How many minutes either side of the rollover we don't enter trades
+    pub blackout_minutes: u32,
+}
+
+impl Default for TradingDay {
+    fn default() -> Self {
+        Self {
+            timezone: chrono_tz::America::New_York,
+            rollover_hour: 17,
+            blackout_minutes: 15,
+        }
+    }
+}
+
+impl TradingDay {
+    /// The trading day `time` is in, named by the date it ends on like the
+    /// forex market does. So 18:00 New York time on Monday is in Tuesday
+    pub fn date(&self, time: DateTime<Utc>) -> NaiveDate {
+        let local = time.with_timezone(&self.timezone);
+        let date = local.date_naive();
+        if self.rollover_hour > 0 && local.hour() >= self.rollover_hour {
+            date.succ_opt().unwrap_or(date)
+        } else {
+            date
+        }
+    }
+
+    /// When the trading day `time` is in started
+    pub fn start(&self, time: DateTime<Utc>) -> DateTime<Utc> {
+        let date = time.with_timezone(&self.timezone).date_naive();
+        let rollover = self.rollover_on(date);
+        if rollover <= time {
+            rollover
+        } else {
+            self.rollover_on(date.pred_opt().unwrap_or(date))
+        }
+    }
+
+    /// When the trading day `time` is in ends
+    pub fn end(&self, time: DateTime<Utc>) -> DateTime<Utc> {
+        let start = self.start(time).with_timezone(&self.timezone).date_naive();
+        self.rollover_on(start.succ_opt().unwrap_or(start))
+    }
+
+    /// Whether `time` is within [`Self::blackout_minutes`] of a rollover
+    pub fn is_near_rollover(&self, time: DateTime<Utc>) -> bool {
+        let blackout = Duration::minutes(self.blackout_minutes.into());
+        time - self.start(time) < blackout || self.end(time) - time <= blackout
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        check_range("trading_day.rollover_hour", self.rollover_hour, 0..=23)?;
+        check_range(
+            "trading_day.blackout_minutes",
+            self.blackout_minutes,
+            0..=120,
+        )
+    }
+
+    /// The rollover at the start of `date`'s evening, local time
+    fn rollover_on(&self, date: NaiveDate) -> DateTime<Utc> {
+        let naive = date
+            .and_hms_opt(self.rollover_hour, 0, 0)
+            .unwrap_or_default();
+        let local = self
+            .timezone
+            .from_local_datetime(&naive)
+            .earliest()
+            // The hour was skipped by daylight saving; it starts an hour later
+            .or_else(|| {
+                self.timezone
+                    .from_local_datetime(&(naive + Duration::hours(1)))
+                    .earliest()
+            });
+        match local {
+            Some(local) => local.with_timezone(&Utc),
+            None => Utc.from_utc_datetime(&naive),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn utc(month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2023, month, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn follows_daylight_saving() {
+        let day = TradingDay::default();
+        // In January New York is UTC-5, so the rollover is 22:00 UTC
+        assert_eq!(utc(1, 9, 22, 0), day.start(utc(1, 10, 12, 0)));
+        assert_eq!(utc(1, 10, 22, 0), day.end(utc(1, 10, 12, 0)));
+        // In July it's UTC-4, so 21:00 UTC
+        assert_eq!(utc(7, 10, 21, 0), day.start(utc(7, 10, 21, 30)));
+        let date = |month, day| NaiveDate::from_ymd_opt(2023, month, day).unwrap();
+        assert_eq!(date(7, 11), day.date(utc(7, 10, 21, 30)));
+        assert_eq!(date(7, 10), day.date(utc(7, 10, 20, 30)));
+        // Midnight UTC isn't a boundary
+        assert_eq!(day.date(utc(1, 9, 23, 0)), day.date(utc(1, 10, 1, 0)));
+    }
+
+    #[test]
+    fn blackout() {
+        let day = TradingDay::default();
+        assert!(day.is_near_rollover(utc(1, 10, 21, 50)));
+        assert!(day.is_near_rollover(utc(1, 10, 22, 10)));
+        assert!(!day.is_near_rollover(utc(1, 10, 21, 40)));
+        assert!(!day.is_near_rollover(utc(1, 10, 22, 20)));
+    }
+}